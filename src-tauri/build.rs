@@ -1,3 +1,33 @@
 fn main() {
+    #[cfg(feature = "gog")]
+    build_galaxy_shim();
+
     tauri_build::build()
 }
+
+/// Compile the C interface to the GOG Galaxy SDK and link the SDK library.
+/// The SDK isn't redistributable through crates.io, so its folder comes from `GALAXY_SDK_DIR`.
+#[cfg(feature = "gog")]
+fn build_galaxy_shim() {
+    use std::path::PathBuf;
+
+    println!("cargo:rerun-if-env-changed=GALAXY_SDK_DIR");
+    println!("cargo:rerun-if-changed=gog/galaxy_shim.cpp");
+    let sdk = PathBuf::from(
+        std::env::var("GALAXY_SDK_DIR").expect("the `gog` feature needs GALAXY_SDK_DIR set to the Galaxy SDK folder"),
+    );
+
+    cc::Build::new()
+        .cpp(true)
+        .file("gog/galaxy_shim.cpp")
+        .include(sdk.join("Include"))
+        .compile("galaxy_shim");
+
+    println!("cargo:rustc-link-search=native={}", sdk.join("Libraries").display());
+    let library = if cfg!(all(windows, target_pointer_width = "64")) {
+        "Galaxy64"
+    } else {
+        "Galaxy"
+    };
+    println!("cargo:rustc-link-lib=dylib={}", library);
+}
@@ -0,0 +1,38 @@
+// Encrypted assets come from the launcher's server already decrypted, so the engine's
+// decrypter (MV's Decrypter, MZ's Utils) passes files without the RPG Maker header through.
+(function () {
+  "use strict";
+
+  function isEncrypted(buffer) {
+    if (!(buffer instanceof ArrayBuffer) || buffer.byteLength < 4) {
+      return false;
+    }
+    var bytes = new Uint8Array(buffer, 0, 4);
+    // "RPGM"
+    return bytes[0] === 0x52 && bytes[1] === 0x50 && bytes[2] === 0x47 && bytes[3] === 0x4d;
+  }
+
+  function passThrough(owner, name) {
+    var decrypt = owner[name];
+    if (typeof decrypt !== "function" || decrypt.__launcherPassThrough) {
+      return;
+    }
+    owner[name] = function (buffer) {
+      return isEncrypted(buffer) ? decrypt.apply(this, arguments) : buffer;
+    };
+    owner[name].__launcherPassThrough = true;
+  }
+
+  // The engine's scripts have all run by the time it asks for an asset, and the response is
+  // only decrypted after that
+  var open = XMLHttpRequest.prototype.open;
+  XMLHttpRequest.prototype.open = function () {
+    if (typeof Decrypter !== "undefined") {
+      passThrough(Decrypter, "decryptArrayBuffer");
+    }
+    if (typeof Utils !== "undefined") {
+      passThrough(Utils, "decryptArrayBuffer");
+    }
+    return open.apply(this, arguments);
+  };
+})();
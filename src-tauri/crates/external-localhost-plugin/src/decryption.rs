@@ -0,0 +1,59 @@
+//! Decryption of RPG Maker's encrypted assets.
+//!
+//! Deployed games can encrypt their images (`.rpgmvp`, `.png_`) and audio (`.rpgmvo`,
+//! `.rpgmvm`, `.rpgmvw`, `.ogg_`, `.m4a_`) with the key in `data/System.json`. Encrypted
+//! files start with a fixed 16 byte header, followed by the original file with its first 16
+//! bytes XORed with the key. The server answers with the original file, and [`SCRIPT`] makes
+//! the engine's own decrypter pass it through as it is.
+
+use serde::Deserialize;
+
+const HEADER: [u8; 16] = [
+    0x52, 0x50, 0x47, 0x4d, 0x56, 0x00, 0x00, 0x00, 0x00, 0x03, 0x01, 0x00, 0x00, 0x00, 0x00, 0x00,
+];
+const ENCRYPTED_EXTENSIONS: [&str; 7] = ["rpgmvp", "rpgmvo", "rpgmvm", "rpgmvw", "png_", "ogg_", "m4a_"];
+
+/// Injected into every page when assets are decrypted.
+pub(crate) const SCRIPT: &str = include_str!("decryption.js");
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct SystemJson {
+    encryption_key: Option<String>,
+}
+
+/// The key in the content of `System.json`, if it has a valid one.
+pub(crate) fn parse_key(system_json: &[u8]) -> Option<[u8; 16]> {
+    // Some editors save System.json with a BOM
+    let system: SystemJson = serde_json::from_slice(system_json.strip_prefix(b"\xef\xbb\xbf").unwrap_or(system_json))
+        .map_err(|e| log::warn!("Invalid System.json: {}", e))
+        .ok()?;
+    let key = system.encryption_key?;
+    if key.len() != 32 || !key.is_ascii() {
+        return None;
+    }
+    let mut bytes = [0; 16];
+    for (i, byte) in bytes.iter_mut().enumerate() {
+        *byte = u8::from_str_radix(&key[i * 2..i * 2 + 2], 16).ok()?;
+    }
+    Some(bytes)
+}
+
+/// Whether `path` has one of the encrypted asset extensions.
+pub(crate) fn is_encrypted_path(path: &str) -> bool {
+    path.rsplit_once('.')
+        .is_some_and(|(_, extension)| ENCRYPTED_EXTENSIONS.iter().any(|e| extension.eq_ignore_ascii_case(e)))
+}
+
+/// The original file of an encrypted asset. Data without the header, like an asset a
+/// packager left unencrypted, is returned as it is.
+pub(crate) fn decrypt(mut data: Vec<u8>, key: &[u8; 16]) -> Vec<u8> {
+    if !data.starts_with(&HEADER) {
+        return data;
+    }
+    data.drain(..HEADER.len());
+    for (byte, key) in data.iter_mut().zip(key) {
+        *byte ^= key;
+    }
+    data
+}
@@ -0,0 +1,300 @@
+//! Built-in diagnostics for the localhost server.
+//!
+//! Keeps the last requests served and the JS errors reported by the injected boot probe,
+//! and renders them as a page at `/__launcher/diagnostics`, so a black screen can be
+//! turned into something a player can screenshot and send. The capabilities the probe finds
+//! in the webview, compared with NW.js, are rendered at `/__launcher/parity`.
+
+use std::collections::VecDeque;
+use std::sync::{Arc, Condvar, Mutex};
+use std::time::{Duration, Instant};
+
+use axum::body::Body;
+use axum::http::{header, HeaderValue, Response as HttpResponse, StatusCode};
+use serde::{Deserialize, Serialize};
+
+/// Path prefix reserved for the launcher's own routes.
+pub const ROUTE_PREFIX: &str = "/__launcher/";
+/// Largest report the boot probe can post; the parity report is the biggest by far.
+pub(crate) const MAX_REPORT_SIZE: usize = 1024 * 1024;
+
+/// How many requests are kept for the diagnostics page.
+const MAX_REQUESTS: usize = 200;
+/// How many script errors are kept for the diagnostics page.
+const MAX_ERRORS: usize = 100;
+
+struct RequestRecord {
+    elapsed: f64,
+    method: String,
+    url: String,
+    status: u16,
+}
+
+struct ScriptError {
+    elapsed: f64,
+    message: String,
+    source: String,
+}
+
+/// How an NW.js capability is provided in the webview.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Support {
+    /// The webview has it, as NW.js does.
+    Native,
+    /// The launcher stands in for it.
+    Shimmed,
+    /// Neither the webview nor the launcher provide it.
+    Missing,
+}
+
+/// One capability checked by the parity probe.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Capability {
+    pub name: String,
+    pub support: Support,
+    pub detail: String,
+}
+
+struct State {
+    started_at: Instant,
+    address: String,
+    game_started: bool,
+    first_paint: bool,
+    request_count: u64,
+    requests: VecDeque<RequestRecord>,
+    errors: VecDeque<ScriptError>,
+    parity: Vec<Capability>,
+}
+
+#[derive(Deserialize)]
+#[serde(tag = "type", rename_all = "camelCase")]
+enum Report {
+    Painted,
+    Started,
+    Error { message: String, source: String },
+    Parity { capabilities: Vec<Capability> },
+}
+
+struct Shared {
+    state: Mutex<State>,
+    painted: Condvar,
+}
+
+/// Server status shared between the server thread and the app, managed as app state.
+#[derive(Clone)]
+pub struct Diagnostics(Arc<Shared>);
+
+impl Diagnostics {
+    pub(crate) fn new(address: String) -> Self {
+        Self(Arc::new(Shared {
+            state: Mutex::new(State {
+                started_at: Instant::now(),
+                address,
+                game_started: false,
+                first_paint: false,
+                request_count: 0,
+                requests: VecDeque::new(),
+                errors: VecDeque::new(),
+                parity: Vec::new(),
+            }),
+            painted: Condvar::new(),
+        }))
+    }
+
+    /// Whether the boot probe reported that the game reached its first scene.
+    pub fn game_started(&self) -> bool {
+        self.0.state.lock().unwrap().game_started
+    }
+
+    /// Block until the game page reports its first paint, or the timeout expires.
+    /// Returns whether the page painted.
+    pub fn wait_for_first_paint(&self, timeout: Duration) -> bool {
+        let state = self.0.state.lock().unwrap();
+        let (state, _) = self
+            .0
+            .painted
+            .wait_timeout_while(state, timeout, |state| !state.first_paint)
+            .unwrap();
+        state.first_paint
+    }
+
+    /// The capabilities reported by the parity probe. Empty until the game page loaded.
+    pub fn parity(&self) -> Vec<Capability> {
+        self.0.state.lock().unwrap().parity.clone()
+    }
+
+    pub(crate) fn record(&self, method: &str, url: &str, status: u16) {
+        log::debug!("{} {} {}", status, method, url);
+        let mut state = self.0.state.lock().unwrap();
+        let elapsed = state.started_at.elapsed().as_secs_f64();
+        state.request_count += 1;
+        if state.requests.len() == MAX_REQUESTS {
+            state.requests.pop_front();
+        }
+        state.requests.push_back(RequestRecord {
+            elapsed,
+            method: method.to_string(),
+            url: url.to_string(),
+            status,
+        });
+    }
+
+    /// Handle a report posted by the boot probe. Returns `false` if it couldn't be parsed.
+    fn handle_report(&self, body: &str) -> bool {
+        let Ok(report) = serde_json::from_str::<Report>(body) else {
+            return false;
+        };
+
+        let mut state = self.0.state.lock().unwrap();
+        match report {
+            Report::Painted => {
+                state.first_paint = true;
+                self.0.painted.notify_all();
+            }
+            Report::Started => {
+                state.game_started = true;
+                state.first_paint = true;
+                self.0.painted.notify_all();
+            }
+            Report::Error { message, source } => {
+                log::warn!("Game script error: {} ({})", message, source);
+                let elapsed = state.started_at.elapsed().as_secs_f64();
+                if state.errors.len() == MAX_ERRORS {
+                    state.errors.pop_front();
+                }
+                state.errors.push_back(ScriptError {
+                    elapsed,
+                    message,
+                    source,
+                });
+            }
+            Report::Parity { capabilities } => {
+                for capability in capabilities.iter().filter(|c| c.support == Support::Missing) {
+                    log::info!("Missing NW.js capability: {} ({})", capability.name, capability.detail);
+                }
+                state.parity = capabilities;
+            }
+        }
+        true
+    }
+
+    /// Answer a request under [`ROUTE_PREFIX`] with the given body, returning the status too.
+    pub(crate) fn respond(&self, path: &str, body: &[u8]) -> (u16, HttpResponse<Body>) {
+        let (status, content_type, body) = match &path[ROUTE_PREFIX.len()..] {
+            "report" => {
+                if self.handle_report(&String::from_utf8_lossy(body)) {
+                    (204, "text/plain", String::new())
+                } else {
+                    (400, "text/plain", "Bad Request".to_string())
+                }
+            }
+            "ping" => (204, "text/plain", String::new()),
+            "diagnostics" => (200, "text/html; charset=utf-8", self.render_page()),
+            "parity" => (200, "text/html; charset=utf-8", self.render_parity_page()),
+            _ => (404, "text/plain", "Not Found".to_string()),
+        };
+
+        let mut response = HttpResponse::new(Body::from(body));
+        *response.status_mut() = StatusCode::from_u16(status).unwrap_or(StatusCode::OK);
+        response
+            .headers_mut()
+            .insert(header::CONTENT_TYPE, HeaderValue::from_static(content_type));
+        (status, response)
+    }
+
+    fn render_page(&self) -> String {
+        let state = self.0.state.lock().unwrap();
+        let mut html = page_head("Launcher diagnostics");
+
+        html.push_str("<h1>The game did not start</h1>");
+        html.push_str("<p>The launcher did not receive a start signal from the game. The information below can help find out why; please include it when reporting the problem.</p>");
+
+        html.push_str("<h2>Server</h2><table>");
+        html.push_str(&format!("<tr><td>Address</td><td>{}</td></tr>", escape(&state.address)));
+        html.push_str(&format!(
+            "<tr><td>Uptime</td><td>{:.1}s</td></tr>",
+            state.started_at.elapsed().as_secs_f64()
+        ));
+        html.push_str(&format!("<tr><td>Requests served</td><td>{}</td></tr>", state.request_count));
+        html.push_str(&format!("<tr><td>Game started</td><td>{}</td></tr>", state.game_started));
+        html.push_str("</table>");
+
+        html.push_str(&format!("<h2>Script errors ({})</h2>", state.errors.len()));
+        if state.errors.is_empty() {
+            html.push_str("<p>No script errors were reported.</p>");
+        } else {
+            html.push_str("<table><tr><th>Time</th><th>Message</th><th>Source</th></tr>");
+            for error in &state.errors {
+                html.push_str(&format!(
+                    "<tr><td>{:.2}s</td><td class=\"bad\">{}</td><td>{}</td></tr>",
+                    error.elapsed,
+                    escape(&error.message),
+                    escape(&error.source)
+                ));
+            }
+            html.push_str("</table>");
+        }
+
+        html.push_str("<h2>Last requests</h2><table><tr><th>Time</th><th>Method</th><th>Status</th><th>URL</th></tr>");
+        for request in state.requests.iter().rev() {
+            let class = if request.status >= 400 { " class=\"bad\"" } else { "" };
+            html.push_str(&format!(
+                "<tr><td>{:.2}s</td><td>{}</td><td{}>{}</td><td>{}</td></tr>",
+                request.elapsed,
+                escape(&request.method),
+                class,
+                request.status,
+                escape(&request.url)
+            ));
+        }
+        html.push_str("</table></body></html>");
+        html
+    }
+
+    fn render_parity_page(&self) -> String {
+        let state = self.0.state.lock().unwrap();
+        let mut html = page_head("NW.js parity report");
+        html.push_str("<h1>NW.js parity report</h1>");
+        html.push_str("<p>What this game's page can use compared with NW.js, where RPG Maker games normally run. Shimmed capabilities are provided by the launcher; missing ones may need a plugin change.</p>");
+        if state.parity.is_empty() {
+            html.push_str("<p>The game page has not reported yet. Reload this page once the game is running.</p>");
+        } else {
+            html.push_str("<table><tr><th>Capability</th><th>Support</th><th>Details</th></tr>");
+            for capability in &state.parity {
+                let (class, label) = match capability.support {
+                    Support::Native => ("", "Native"),
+                    Support::Shimmed => ("", "Shimmed"),
+                    Support::Missing => (" class=\"bad\"", "Missing"),
+                };
+                html.push_str(&format!(
+                    "<tr><td>{}</td><td{}>{}</td><td>{}</td></tr>",
+                    escape(&capability.name),
+                    class,
+                    label,
+                    escape(&capability.detail)
+                ));
+            }
+            html.push_str("</table>");
+        }
+        html.push_str("</body></html>");
+        html
+    }
+}
+
+fn page_head(title: &str) -> String {
+    format!(
+        "<!doctype html><html><head><meta charset=\"utf-8\"><title>{}</title>\
+         <style>body{{font-family:sans-serif;background:#1e1e1e;color:#ddd;margin:2em}}\
+         table{{border-collapse:collapse;width:100%;font-size:13px}}td,th{{padding:2px 8px;text-align:left}}\
+         tr:nth-child(even){{background:#2a2a2a}}.bad{{color:#f66}}h2{{margin-top:1.5em}}</style></head><body>",
+        title
+    )
+}
+
+fn escape(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
@@ -0,0 +1,825 @@
+// Copyright 2019-2023 Tauri Programme within The Commons Conservancy
+// SPDX-License-Identifier: Apache-2.0
+// SPDX-License-Identifier: MIT
+
+//! Expose external folder assets through a localhost server for RPG Maker games.
+//!
+//! **Note: This plugin brings considerable security risks and you should only use it if you know what you are doing.**
+//!
+//! The server runs on Tauri's async runtime from the plugin's setup, with keep-alive
+//! connections and [several files loading at a time](Builder::threads), and serves the
+//! [external folder](Builder::external_folder). A request is looked up in order in:
+//!
+//! 1. the [`Mounts`], latest first: folders layered on top at runtime, like mods or DLC;
+//! 2. the external folder;
+//! 3. the [archive](Builder::archive) callback, for packed files;
+//! 4. the same steps again for each of the [aliases](Builder::aliases) of the path.
+//!
+//! Encrypted RPG Maker assets can be [decrypted](Builder::decrypt_assets) first. Found
+//! files go through the [transforms](Builder::transform), then HTML pages get the
+//! [injected scripts](Builder::inject_script) right after `<head>`, before any of the page's
+//! own scripts, unless they are [left out](Builder::skip_injection). Range requests are
+//! supported, and [`Builder::on_request`] can add headers. Responses carry an `ETag` and,
+//! for loose files, a `Last-Modified` date, so the webview revalidates its cache with
+//! conditional requests and gets a bodiless 304 for files that haven't changed. Loose files
+//! over [`STREAM_THRESHOLD`] bytes, like movies, are streamed from disk as they are instead
+//! of being read into memory.
+//!
+//! Paths under `/__launcher/` are the plugin's own: the boot probe posts reports to
+//! `/__launcher/report`, and [`Diagnostics`] renders them at `/__launcher/diagnostics`
+//! and `/__launcher/parity`. `/__launcher/fs/` gives synchronous, read-only access to the
+//! files for the launcher's `fs` helpers, and [`GameFiles`] the same lookups to the app.
+//!
+//! ```ignore
+//! let server = external_localhost_plugin::Builder::new(8080)
+//!     .external_folder("Game_Contents")
+//!     .inject_script("console.log('injected')");
+//! tauri::Builder::default()
+//!     .plugin(server.build())
+//!     .run(tauri::generate_context!())
+//!     .unwrap();
+//! ```
+
+mod decryption;
+mod diagnostics;
+mod sync_fs;
+
+pub use diagnostics::{Capability, Diagnostics};
+pub use sync_fs::{FileStat, GameFile, GameFiles};
+
+use std::collections::hash_map::DefaultHasher;
+use std::collections::{BTreeMap, HashMap};
+use std::hash::{Hash, Hasher};
+use std::path::{Path, PathBuf};
+use std::fs;
+use std::io::{Read, Seek, SeekFrom, Write};
+use std::net::{TcpStream, ToSocketAddrs};
+use std::sync::atomic::AtomicBool;
+use std::sync::{Arc, Mutex, OnceLock, RwLock};
+use std::time::{Duration, SystemTime};
+
+use axum::body::Body;
+use axum::extract::State;
+use axum::http::{
+    header, HeaderMap, HeaderName, HeaderValue, Request as HttpRequest, Response as HttpResponse, StatusCode,
+};
+use axum::Router;
+use percent_encoding::percent_decode_str;
+use tauri::{
+    plugin::{Builder as PluginBuilder, TauriPlugin},
+    Manager, Runtime,
+};
+use tauri::async_runtime::JoinHandle;
+use tokio::io::AsyncReadExt;
+use tokio::net::TcpListener;
+use tokio::sync::Semaphore;
+use tokio_util::io::ReaderStream;
+
+/// Loose files larger than this are streamed from disk and skip the transforms.
+pub const STREAM_THRESHOLD: u64 = 8 * 1024 * 1024;
+/// Files loaded at the same time unless [`Builder::threads`] says otherwise: enough for a
+/// map transfer loading tilesets, audio and data together.
+pub const DEFAULT_THREADS: usize = 4;
+
+/// The request a [`Builder::on_request`] callback is answering.
+pub struct Request {
+    url: String,
+}
+
+impl Request {
+    pub fn url(&self) -> &str {
+        &self.url
+    }
+}
+
+/// The response a [`Builder::on_request`] callback can add headers to.
+pub struct Response {
+    headers: HashMap<String, String>,
+}
+
+impl Response {
+    pub fn add_header<H: Into<String>, V: Into<String>>(&mut self, header: H, value: V) {
+        self.headers.insert(header.into(), value.into());
+    }
+}
+
+/// Folders layered on top of the external folder at runtime, managed as app state.
+///
+/// Files found in a mount take precedence over the game's own files, and later mounts
+/// win over earlier ones.
+#[derive(Clone, Default)]
+pub struct Mounts(Arc<RwLock<Vec<PathBuf>>>);
+
+impl Mounts {
+    /// Layer `folder` on top of the others. Mounting a folder twice does nothing.
+    pub fn mount<P: AsRef<Path>>(&self, folder: P) {
+        let folder = folder.as_ref().to_path_buf();
+        let mut mounts = self.0.write().unwrap();
+        if !mounts.contains(&folder) {
+            mounts.push(folder);
+        }
+    }
+
+    pub fn unmount<P: AsRef<Path>>(&self, folder: P) {
+        self.0.write().unwrap().retain(|f| f != folder.as_ref());
+    }
+
+    fn resolve(&self, relative: &str) -> Option<PathBuf> {
+        self.0
+            .read()
+            .unwrap()
+            .iter()
+            .rev()
+            .map(|folder| folder.join(relative))
+            .find(|path| path.is_file())
+    }
+}
+
+/// Scripts injected into every HTML page served, managed as app state so the app can add
+/// more after the plugin is built (e.g. scripts that depend on files found at startup).
+#[derive(Clone, Default)]
+pub struct InjectedScripts(Arc<RwLock<Vec<String>>>);
+
+impl InjectedScripts {
+    /// Inject `script` into the pages served from now on, after the scripts already added.
+    pub fn push<S: Into<String>>(&self, script: S) {
+        self.0.write().unwrap().push(script.into());
+    }
+
+    fn snapshot(&self) -> Vec<String> {
+        self.0.read().unwrap().clone()
+    }
+}
+
+/// The server task, managed as app state so the app can check it and start it again, e.g.
+/// after the system resumes from sleep, which can leave the listening socket dead.
+#[derive(Clone)]
+pub struct ServerHandle {
+    address: String,
+    serve: Arc<dyn Fn() -> JoinHandle<()> + Send + Sync>,
+    task: Arc<Mutex<Option<JoinHandle<()>>>>,
+}
+
+impl ServerHandle {
+    /// Start the server, stopping the previous one first so its socket frees the port.
+    fn spawn(&self) {
+        let mut task = self.task.lock().unwrap();
+        if let Some(previous) = task.take() {
+            previous.abort();
+        }
+        *task = Some((self.serve)());
+    }
+
+    /// Whether the server answers a request within a second.
+    pub fn is_responding(&self) -> bool {
+        let Some(address) = self.address.to_socket_addrs().ok().and_then(|mut addresses| addresses.next()) else {
+            return false;
+        };
+        let Ok(mut stream) = TcpStream::connect_timeout(&address, Duration::from_secs(1)) else {
+            return false;
+        };
+        let _ = stream.set_read_timeout(Some(Duration::from_secs(1)));
+        let request = format!(
+            "GET {}ping HTTP/1.1\r\nHost: {}\r\nConnection: close\r\n\r\n",
+            diagnostics::ROUTE_PREFIX,
+            self.address
+        );
+        let mut status = [0u8; 12];
+        stream.write_all(request.as_bytes()).is_ok()
+            && stream.read_exact(&mut status).is_ok()
+            && status.starts_with(b"HTTP/1.1 2")
+    }
+
+    /// Start the server again if it stopped answering. Returns whether it was restarted.
+    pub fn ensure_running(&self) -> bool {
+        if self.is_responding() {
+            return false;
+        }
+        log::warn!("Server on {} is not responding, starting it again", self.address);
+        self.spawn();
+        true
+    }
+}
+
+type OnRequest = Option<Box<dyn Fn(&Request, &mut Response) + Send + Sync>>;
+type Aliases = Option<Box<dyn Fn(&str) -> Vec<String> + Send + Sync>>;
+type Archive = Option<Box<dyn Fn(&str) -> Option<Vec<u8>> + Send + Sync>>;
+type Transform = Box<dyn Fn(&str, Vec<u8>) -> Vec<u8> + Send + Sync>;
+type SkipInjection = Option<Box<dyn Fn(&str) -> bool + Send + Sync>>;
+
+/// Configures the server. Every option is optional except the port.
+pub struct Builder {
+    port: u16,
+    host: Option<String>,
+    on_request: OnRequest,
+    aliases: Aliases,
+    archive: Archive,
+    transforms: Vec<Transform>,
+    external_folder: Option<PathBuf>,
+    injected_scripts: Vec<String>,
+    skip_injection: SkipInjection,
+    threads: usize,
+    decrypt_assets: bool,
+}
+
+impl Builder {
+    /// A server on `port`, which the caller picks (e.g. with `portpicker`).
+    pub fn new(port: u16) -> Self {
+        Self {
+            port,
+            host: None,
+            on_request: None,
+            aliases: None,
+            archive: None,
+            transforms: Vec::new(),
+            external_folder: None,
+            injected_scripts: Vec::new(),
+            skip_injection: None,
+            threads: DEFAULT_THREADS,
+            decrypt_assets: false,
+        }
+    }
+
+    /// Number of files loaded at the same time, each on a blocking thread, while other requests
+    /// wait their turn. Defaults to [`DEFAULT_THREADS`].
+    pub fn threads(mut self, threads: usize) -> Self {
+        self.threads = threads.max(1);
+        self
+    }
+
+    /// Change the host the plugin binds to. Defaults to `localhost`.
+    pub fn host<H: Into<String>>(mut self, host: H) -> Self {
+        self.host = Some(host.into());
+        self
+    }
+
+    /// Set the external folder to serve files from (e.g., "Game_Contents").
+    pub fn external_folder<P: AsRef<Path>>(mut self, folder: P) -> Self {
+        self.external_folder = Some(folder.as_ref().to_path_buf());
+        self
+    }
+
+    /// Inject a script at the top of every HTML page served, before any of the game's own scripts.
+    pub fn inject_script<S: Into<String>>(mut self, script: S) -> Self {
+        self.injected_scripts.push(script.into());
+        self
+    }
+
+    /// Pages served without the injected scripts, given their path relative to the external
+    /// folder, like a manual shipped with the game.
+    pub fn skip_injection<F: Fn(&str) -> bool + Send + Sync + 'static>(mut self, f: F) -> Self {
+        self.skip_injection.replace(Box::new(f));
+        self
+    }
+
+    /// Called for every file served, before it is sent.
+    pub fn on_request<F: Fn(&Request, &mut Response) + Send + Sync + 'static>(
+        mut self,
+        f: F,
+    ) -> Self {
+        self.on_request.replace(Box::new(f));
+        self
+    }
+
+    /// Other paths to try, in order, when a requested file doesn't exist.
+    pub fn aliases<F: Fn(&str) -> Vec<String> + Send + Sync + 'static>(mut self, f: F) -> Self {
+        self.aliases.replace(Box::new(f));
+        self
+    }
+
+    /// Where to look for files missing from the external folder, like packed archives.
+    pub fn archive<F: Fn(&str) -> Option<Vec<u8>> + Send + Sync + 'static>(mut self, f: F) -> Self {
+        self.archive.replace(Box::new(f));
+        self
+    }
+
+    /// Rewrite files as they are served, given their path relative to the external folder.
+    /// Transforms run in the order they were added, on every file except the loose ones
+    /// over [`STREAM_THRESHOLD`] bytes.
+    pub fn transform<F: Fn(&str, Vec<u8>) -> Vec<u8> + Send + Sync + 'static>(mut self, f: F) -> Self {
+        self.transforms.push(Box::new(f));
+        self
+    }
+
+    /// Serve RPG Maker's encrypted images and audio (`.rpgmvp`, `.rpgmvo`, `.rpgmvm`,
+    /// `.rpgmvw`, `.png_`, `.ogg_`, `.m4a_`) decrypted with the key in `data/System.json`,
+    /// whatever their size, and make the engine's decrypter accept them as they are.
+    pub fn decrypt_assets(mut self) -> Self {
+        self.decrypt_assets = true;
+        self
+    }
+
+    /// The plugin, which starts the server when the app is set up. The [`Mounts`],
+    /// [`InjectedScripts`], [`Diagnostics`] and [`GameFiles`] are managed as app state from
+    /// then on.
+    pub fn build<R: Runtime>(mut self) -> TauriPlugin<R> {
+        let port = self.port;
+        let host = self.host.unwrap_or("localhost".to_string());
+        let on_request = self.on_request.take();
+        let aliases = self.aliases.take();
+        let archive = self.archive.take();
+        let transforms = std::mem::take(&mut self.transforms);
+        let external_folder = self.external_folder;
+        let mut injected_scripts = self.injected_scripts;
+        let decrypt_assets = self.decrypt_assets;
+        if decrypt_assets {
+            injected_scripts.insert(0, decryption::SCRIPT.to_string());
+        }
+        let skip_injection = self.skip_injection.take();
+        let threads = self.threads;
+
+        PluginBuilder::new("external-localhost")
+            .setup(move |app, _api| {
+                let server_address = format!("{host}:{port}");
+                let mounts = Mounts::default();
+                app.manage(mounts.clone());
+                let diagnostics = Diagnostics::new(server_address.clone());
+                app.manage(diagnostics.clone());
+                let injected_scripts = InjectedScripts(Arc::new(RwLock::new(injected_scripts)));
+                app.manage(injected_scripts.clone());
+
+                let context = Arc::new(Context {
+                    mounts,
+                    diagnostics,
+                    injected_scripts,
+                    on_request,
+                    aliases,
+                    archive,
+                    transforms,
+                    external_folder,
+                    skip_injection,
+                    loads: Semaphore::new(threads),
+                    decrypt_assets,
+                    key: OnceLock::new(),
+                    mapped_folders: RwLock::new(BTreeMap::new()),
+                    fs_routes: AtomicBool::new(true),
+                });
+                app.manage(GameFiles(context.clone()));
+
+                let address = server_address.clone();
+                let serve: Arc<dyn Fn() -> JoinHandle<()> + Send + Sync> = Arc::new(move || {
+                    let context = context.clone();
+                    let server_address = server_address.clone();
+                    tauri::async_runtime::spawn(async move {
+                        let listener = match TcpListener::bind(&server_address).await {
+                            Ok(listener) => listener,
+                            Err(e) => {
+                                log::error!("Failed to create server: {}", e);
+                                return;
+                            }
+                        };
+                        let router = Router::new().fallback(handle_request).with_state(context);
+                        if let Err(e) = axum::serve(listener, router).await {
+                            log::error!("Server on {} failed: {}", server_address, e);
+                        }
+                        log::warn!("Server on {} stopped", server_address);
+                    })
+                });
+                let handle = ServerHandle {
+                    address,
+                    serve,
+                    task: Default::default(),
+                };
+                handle.spawn();
+                app.manage(handle);
+                Ok(())
+            })
+            .build()
+    }
+}
+
+/// What the server needs to answer requests, shared by every connection.
+struct Context {
+    mounts: Mounts,
+    diagnostics: Diagnostics,
+    injected_scripts: InjectedScripts,
+    on_request: OnRequest,
+    aliases: Aliases,
+    archive: Archive,
+    transforms: Vec<Transform>,
+    external_folder: Option<PathBuf>,
+    skip_injection: SkipInjection,
+    /// Files being loaded at the same time, each on a blocking thread.
+    loads: Semaphore,
+    decrypt_assets: bool,
+    /// The asset key from `data/System.json`, read on the first encrypted request.
+    key: OnceLock<Option<[u8; 16]>>,
+    /// Folders the `fs` routes find under a path prefix, see [`GameFiles::map_folder`].
+    mapped_folders: RwLock<BTreeMap<String, PathBuf>>,
+    /// Whether pages may use the `fs` routes, see [`GameFiles::allow_routes`].
+    fs_routes: AtomicBool,
+}
+
+impl Context {
+    /// The external folder, or `Game_Contents` next to the executable when there is none.
+    fn root(&self) -> PathBuf {
+        if let Some(external_folder) = &self.external_folder {
+            return external_folder.clone();
+        }
+        let current_dir = std::env::current_exe()
+            .ok()
+            .and_then(|exe| exe.parent().map(|p| p.to_path_buf()))
+            .unwrap_or_else(|| std::env::current_dir().unwrap_or_default());
+        current_dir.join("Game_Contents")
+    }
+
+    /// Find `relative` in the mounts, the external folder and the archive, in that order.
+    fn load(&self, relative: &str) -> Option<Found> {
+        let loose = match self.mounts.resolve(relative) {
+            Some(mounted) => load_external_file(&mounted),
+            None => load_external_file(&self.root().join(relative)),
+        };
+        // Loose files win over packed ones
+        loose.or_else(|| {
+            let content = self.archive.as_ref()?(relative)?;
+            Some(Found {
+                content: Content::Data(content),
+                mime_type: get_mime_type(Path::new(relative)),
+                modified: None,
+            })
+        })
+    }
+
+    /// The key to decrypt `path` with, if it is an encrypted asset to decrypt.
+    fn decryption_key(&self, path: &str) -> Option<[u8; 16]> {
+        if !self.decrypt_assets || !decryption::is_encrypted_path(path) {
+            return None;
+        }
+        *self.key.get_or_init(|| {
+            let key = match self.load("data/System.json") {
+                Some(Found {
+                    content: Content::Data(system_json),
+                    ..
+                }) => decryption::parse_key(&system_json),
+                _ => None,
+            };
+            if key.is_none() {
+                log::warn!("data/System.json has no valid encryption key, encrypted assets are served as they are");
+            }
+            key
+        })
+    }
+
+    /// Answer a request for a file. Blocks on the filesystem and the app's callbacks.
+    fn serve_file(&self, path: &str, requested_url: &str, headers: &HeaderMap) -> (u16, HttpResponse<Body>) {
+        // Handle root path and remove leading slash, defaulting to index.html
+        let file_path = path.strip_prefix('/').unwrap_or(path);
+        let final_path = if file_path.is_empty() {
+            "index.html"
+        } else {
+            file_path
+        };
+
+        let file_content = self.load(final_path).or_else(|| {
+            let aliases = self.aliases.as_ref()?;
+            aliases(final_path).iter().find_map(|alias| self.load(alias))
+        });
+        let Some(Found {
+            content,
+            mime_type,
+            modified,
+        }) = file_content
+        else {
+            return (404, text_response(404, "Not Found"));
+        };
+
+        let content = match (content, self.decryption_key(final_path)) {
+            (Content::Data(data), Some(key)) => Content::Data(decryption::decrypt(data, &key)),
+            (Content::File(mut file, length), Some(key)) => {
+                let mut data = Vec::with_capacity(length);
+                if let Err(e) = file.read_to_end(&mut data) {
+                    log::warn!("Failed to read {}: {}", final_path, e);
+                    return (500, text_response(500, "Internal Server Error"));
+                }
+                Content::Data(decryption::decrypt(data, &key))
+            }
+            (content, None) => content,
+        };
+        let content = match content {
+            Content::Data(mut data) => {
+                for transform in &self.transforms {
+                    data = transform(final_path, data);
+                }
+                let skipped = self.skip_injection.as_ref().is_some_and(|skip| skip(final_path));
+                if mime_type == "text/html" && !skipped {
+                    let scripts = self.injected_scripts.snapshot();
+                    if !scripts.is_empty() {
+                        data = inject_scripts(&data, &scripts);
+                    }
+                }
+                Content::Data(data)
+            }
+            file => file,
+        };
+
+        let request = Request {
+            url: requested_url.to_string(),
+        };
+        let mut response = Response {
+            headers: Default::default(),
+        };
+
+        response.add_header("Content-Type", &mime_type);
+
+        // Add CORS headers for better compatibility
+        response.add_header("Access-Control-Allow-Origin", "*");
+        response.add_header("Access-Control-Allow-Methods", "GET, POST, OPTIONS");
+        response.add_header("Access-Control-Allow-Headers", "Content-Type");
+
+        // Add cache headers for better performance (especially for audio files). Everything
+        // else is revalidated on every load, which costs a 304 when it hasn't changed
+        if mime_type.starts_with("audio/") || mime_type.starts_with("image/") {
+            response.add_header("Cache-Control", "public, max-age=31536000");
+        } else {
+            response.add_header("Cache-Control", "no-cache");
+        }
+        let etag = entity_tag(&content, modified);
+        if let Some(etag) = &etag {
+            response.add_header("ETag", etag);
+        }
+        // HTTP dates have no fractions of a second, and would never match an mtime with them
+        let modified = modified.map(|time| {
+            let seconds = time.duration_since(SystemTime::UNIX_EPOCH).unwrap_or_default().as_secs();
+            SystemTime::UNIX_EPOCH + Duration::from_secs(seconds)
+        });
+        if let Some(modified) = modified {
+            response.add_header("Last-Modified", httpdate::fmt_http_date(modified));
+        }
+
+        if let Some(on_request) = &self.on_request {
+            on_request(&request, &mut response);
+        }
+
+        if is_not_modified(headers, etag.as_deref(), modified) {
+            let mut http_response = HttpResponse::new(Body::empty());
+            *http_response.status_mut() = StatusCode::NOT_MODIFIED;
+            insert_headers(http_response.headers_mut(), response.headers);
+            return (304, http_response);
+        }
+
+        // Media elements and Effekseer's loader may ask for byte ranges
+        response.add_header("Accept-Ranges", "bytes");
+        let total = content.len();
+        let range = headers.get(header::RANGE).and_then(|value| value.to_str().ok());
+        let (status, start, end) = match range.map(|r| parse_range(r, total)) {
+            Some(Some((start, end))) => {
+                response.add_header("Content-Range", format!("bytes {}-{}/{}", start, end, total));
+                (206, start, end + 1)
+            }
+            Some(None) => {
+                response.add_header("Content-Range", format!("bytes */{}", total));
+                (416, 0, 0)
+            }
+            None => (200, 0, total),
+        };
+        let body = match content.body(start, end) {
+            Ok(body) => body,
+            Err(e) => {
+                log::warn!("Failed to read {}: {}", final_path, e);
+                return (500, text_response(500, "Internal Server Error"));
+            }
+        };
+
+        let mut http_response = HttpResponse::new(body);
+        *http_response.status_mut() = StatusCode::from_u16(status).unwrap_or(StatusCode::OK);
+        let headers = http_response.headers_mut();
+        insert_headers(headers, response.headers);
+        // Streamed bodies would otherwise go out chunked, and WebKit won't seek media in a
+        // 206 response without a length
+        headers.insert(header::CONTENT_LENGTH, HeaderValue::from(end - start));
+        (status, http_response)
+    }
+}
+
+/// Answer any request: the launcher's own routes, or a file.
+async fn handle_request(State(context): State<Arc<Context>>, req: HttpRequest<Body>) -> HttpResponse<Body> {
+    let requested_url = req.uri().to_string();
+    let method = req.method().to_string();
+    // Decode percent-encoded URLs (critical for RPG Maker compatibility)
+    let path = percent_decode_str(req.uri().path()).decode_utf8_lossy().to_string();
+
+    // Launcher routes (boot probe reports, diagnostics page)
+    if path.starts_with(diagnostics::ROUTE_PREFIX) && !path.starts_with(sync_fs::PREFIX) {
+        let body = axum::body::to_bytes(req.into_body(), diagnostics::MAX_REPORT_SIZE)
+            .await
+            .unwrap_or_default();
+        let (status, response) = context.diagnostics.respond(&path, &body);
+        context.diagnostics.record(&method, &requested_url, status);
+        return response;
+    }
+
+    let headers = req.headers().clone();
+    let query = req.uri().query().map(str::to_string);
+    let _permit = context.loads.acquire().await;
+    let loader = context.clone();
+    let url = requested_url.clone();
+    let served = tokio::task::spawn_blocking(move || match path.strip_prefix(sync_fs::PREFIX) {
+        Some(route) => loader.serve_fs(route, query.as_deref()),
+        None => loader.serve_file(&path, &url, &headers),
+    });
+    let (status, response) = match served.await {
+        Ok(served) => served,
+        Err(e) => {
+            log::error!("Failed to serve {}: {}", requested_url, e);
+            (500, text_response(500, "Internal Server Error"))
+        }
+    };
+    context.diagnostics.record(&method, &requested_url, status);
+    response
+}
+
+fn insert_headers(headers: &mut HeaderMap, added: HashMap<String, String>) {
+    for (header, value) in added {
+        if let (Ok(name), Ok(value)) = (HeaderName::try_from(header), HeaderValue::try_from(value)) {
+            headers.insert(name, value);
+        }
+    }
+}
+
+/// A strong validator for `content`: a hash of the bytes in memory, which covers the
+/// transforms and injected scripts, or the length and modification time of a file
+/// streamed from disk.
+fn entity_tag(content: &Content, modified: Option<SystemTime>) -> Option<String> {
+    let mut hasher = DefaultHasher::new();
+    match content {
+        Content::Data(data) => data.hash(&mut hasher),
+        Content::File(_, length) => (length, modified?).hash(&mut hasher),
+    }
+    Some(format!("\"{:016x}\"", hasher.finish()))
+}
+
+/// Whether the request's cached copy is still good. `If-None-Match` wins over
+/// `If-Modified-Since` when both are sent.
+fn is_not_modified(headers: &HeaderMap, etag: Option<&str>, modified: Option<SystemTime>) -> bool {
+    if let Some(if_none_match) = headers.get(header::IF_NONE_MATCH).and_then(|value| value.to_str().ok()) {
+        return etag.is_some_and(|etag| {
+            if_none_match
+                .split(',')
+                .map(|tag| tag.trim().trim_start_matches("W/"))
+                .any(|tag| tag == "*" || tag == etag)
+        });
+    }
+    let if_modified_since = headers
+        .get(header::IF_MODIFIED_SINCE)
+        .and_then(|value| value.to_str().ok())
+        .and_then(|value| httpdate::parse_http_date(value).ok());
+    matches!((modified, if_modified_since), (Some(modified), Some(since)) if modified <= since)
+}
+
+fn text_response(status: u16, text: &'static str) -> HttpResponse<Body> {
+    let mut response = HttpResponse::new(Body::from(text));
+    *response.status_mut() = StatusCode::from_u16(status).unwrap_or(StatusCode::INTERNAL_SERVER_ERROR);
+    response
+        .headers_mut()
+        .insert(header::CONTENT_TYPE, HeaderValue::from_static("text/plain"));
+    response
+}
+
+/// A file found for a request.
+struct Found {
+    content: Content,
+    mime_type: String,
+    /// When the file was last changed, for loose files.
+    modified: Option<SystemTime>,
+}
+
+/// A file to serve: in memory, or open on disk with its length when it's too large for that.
+enum Content {
+    Data(Vec<u8>),
+    File(fs::File, usize),
+}
+
+impl Content {
+    fn len(&self) -> usize {
+        match self {
+            Content::Data(data) => data.len(),
+            Content::File(_, length) => *length,
+        }
+    }
+
+    /// A response body with the bytes from `start` up to, but not including, `end`.
+    fn body(self, start: usize, end: usize) -> std::io::Result<Body> {
+        match self {
+            Content::Data(mut data) => {
+                data.truncate(end);
+                data.drain(..start.min(data.len()));
+                Ok(Body::from(data))
+            }
+            Content::File(mut file, _) => {
+                file.seek(SeekFrom::Start(start as u64))?;
+                let file = tokio::fs::File::from_std(file).take(end.saturating_sub(start) as u64);
+                Ok(Body::from_stream(ReaderStream::new(file)))
+            }
+        }
+    }
+}
+
+/// Load a file from the external filesystem. Files over [`STREAM_THRESHOLD`] are only opened.
+fn load_external_file(file_path: &Path) -> Option<Found> {
+    if !file_path.exists() || !file_path.is_file() {
+        return None;
+    }
+
+    let mut file = fs::File::open(file_path).ok()?;
+    let metadata = file.metadata().ok()?;
+    let length = metadata.len();
+    let content = if length > STREAM_THRESHOLD {
+        Content::File(file, length as usize)
+    } else {
+        let mut content = Vec::new();
+        file.read_to_end(&mut content).ok()?;
+        Content::Data(content)
+    };
+    Some(Found {
+        content,
+        mime_type: get_mime_type(file_path),
+        modified: metadata.modified().ok(),
+    })
+}
+
+/// Parse a single-range `Range: bytes=...` header against a body of `total` bytes into an
+/// inclusive range. `None` means the range can't be satisfied.
+fn parse_range(header: &str, total: usize) -> Option<(usize, usize)> {
+    let spec = header.trim().strip_prefix("bytes=")?;
+    // Multiple ranges are rare enough that the first one will do
+    let spec = spec.split(',').next()?.trim();
+    let (start, end) = spec.split_once('-')?;
+    let (start, end) = match (start.trim(), end.trim()) {
+        ("", suffix) => {
+            let length: usize = suffix.parse().ok()?;
+            (total.checked_sub(length.min(total))?, total.checked_sub(1)?)
+        }
+        (start, "") => (start.parse().ok()?, total.checked_sub(1)?),
+        (start, end) => (start.parse().ok()?, end.parse::<usize>().ok()?.min(total.checked_sub(1)?)),
+    };
+    (start <= end && start < total).then_some((start, end))
+}
+
+/// Insert the given scripts right after the `<head>` tag of an HTML document
+fn inject_scripts(html: &[u8], scripts: &[String]) -> Vec<u8> {
+    let html = String::from_utf8_lossy(html);
+    let tags: String = scripts
+        .iter()
+        .map(|script| format!("<script>{}</script>", script.replace("</", "<\\/")))
+        .collect();
+
+    let lower = html.to_ascii_lowercase();
+    let insert_at = lower
+        .find("<head")
+        .and_then(|start| lower[start..].find('>').map(|end| start + end + 1))
+        .or_else(|| lower.find("<script"))
+        .unwrap_or(0);
+
+    let mut result = String::with_capacity(html.len() + tags.len());
+    result.push_str(&html[..insert_at]);
+    result.push_str(&tags);
+    result.push_str(&html[insert_at..]);
+    result.into_bytes()
+}
+
+/// Get MIME type based on file extension
+fn get_mime_type(file_path: &Path) -> String {
+    let extension = file_path
+        .extension()
+        .and_then(|ext| ext.to_str())
+        .unwrap_or("")
+        .to_lowercase();
+
+    match extension.as_str() {
+        "html" | "htm" => "text/html",
+        "css" => "text/css",
+        "js" => "application/javascript",
+        "json" => "application/json",
+        "png" => "image/png",
+        "jpg" | "jpeg" => "image/jpeg",
+        "gif" => "image/gif",
+        "svg" => "image/svg+xml",
+        "ico" => "image/x-icon",
+        "woff" => "font/woff",
+        "woff2" => "font/woff2",
+        "ttf" => "font/ttf",
+        "otf" => "font/otf",
+        "mp3" => "audio/mpeg",
+        "wav" => "audio/wav",
+        "ogg" => "audio/ogg",
+        "m4a" => "audio/mp4",
+        "aac" => "audio/aac",
+        "rpgmvo" => "audio/ogg", // RPG Maker encrypted audio
+        "rpgmvm" => "audio/mp4", // RPG Maker encrypted audio
+        "rpgmvp" => "image/png", // RPG Maker encrypted image
+        "rpgmvw" => "audio/wav", // RPG Maker encrypted audio
+        "png_" => "image/png",   // RPG Maker MZ encrypted image
+        "ogg_" => "audio/ogg",   // RPG Maker MZ encrypted audio
+        "m4a_" => "audio/mp4",   // RPG Maker MZ encrypted audio
+        "mp4" => "video/mp4",
+        "webm" => "video/webm",
+        "ogv" => "video/ogg",
+        "efkefc" | "efkmat" | "efkmodel" | "efk" => "application/octet-stream", // Effekseer effects (MZ)
+        "tga" => "image/x-tga",
+        "dds" => "image/vnd-ms.dds",
+        "csv" => "text/csv",
+        "txt" => "text/plain",
+        "xml" => "application/xml",
+        _ => "application/octet-stream",
+    }.to_string()
+}
\ No newline at end of file
@@ -0,0 +1,305 @@
+//! Read-only file access for the synchronous `fs` helpers of the launcher's polyfill, under
+//! [`PREFIX`].
+//!
+//! Plugins written for NW.js read files with `require("fs")`, often synchronously, which a
+//! webview can only do with synchronous requests. Paths are relative to the external folder
+//! and looked up like the files the game loads, mounts and archive included, but without
+//! aliases, decryption, transforms or injected scripts. Folders from elsewhere can be
+//! [mapped](GameFiles::map_folder) under a path, like the save folder under `save`. The app
+//! can [turn the routes off](GameFiles::allow_routes) for games it doesn't trust with them,
+//! and they answer 403:
+//!
+//! - `read?path=...`: the file as it is, or 404;
+//! - `exists?path=...`: `true` or `false`, for files and folders alike;
+//! - `stat?path=...`: `{"size", "mtimeMs", "isFile", "isDirectory"}`, or 404;
+//! - `readdir?path=...`: the sorted names in a folder, the mounts' merged with the external
+//!   folder's, or 404. Packed files aren't listed.
+//!
+//! [`GameFiles`] does the same lookups for the app's own commands, so the asynchronous helpers
+//! find the same files.
+
+use std::collections::BTreeSet;
+use std::fs;
+use std::path::{Component, Path, PathBuf};
+use std::sync::atomic::Ordering;
+use std::sync::Arc;
+use std::time::UNIX_EPOCH;
+
+use axum::body::Body;
+use axum::http::{header, HeaderValue, Response as HttpResponse};
+use percent_encoding::percent_decode_str;
+use serde::Serialize;
+
+use crate::{load_external_file, text_response, Content, Context};
+
+/// Path prefix of the routes, inside the launcher's own.
+pub(crate) const PREFIX: &str = "/__launcher/fs/";
+
+/// A file or folder as the `fs` helpers see it.
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct FileStat {
+    pub size: u64,
+    /// Last modification time, in milliseconds since the Unix epoch. Missing for packed files.
+    pub mtime_ms: Option<u64>,
+    pub is_file: bool,
+    pub is_directory: bool,
+}
+
+/// A file as [`GameFiles`] finds it.
+pub enum GameFile {
+    /// A file on disk: in a mount, the external folder or a mapped folder.
+    Loose(PathBuf),
+    /// A file from the archive.
+    Packed(Vec<u8>),
+}
+
+/// The files the `fs` routes see, managed as app state so the app's own commands look files
+/// up the same way. Paths are relative to the external folder, with `/` or `\` separators;
+/// ones that would leave it are never found.
+#[derive(Clone)]
+pub struct GameFiles(pub(crate) Arc<Context>);
+
+impl GameFiles {
+    /// Find `folder` under `prefix` (like `save`), in place of whatever the game has there.
+    /// Only the files directly in it are found, not its subfolders or dot files. Mapping a
+    /// prefix again replaces its folder.
+    pub fn map_folder<P: AsRef<Path>>(&self, prefix: &str, folder: P) {
+        let Some(prefix) = relative_path(prefix).filter(|prefix| !prefix.is_empty()) else {
+            log::warn!("Invalid folder prefix: {}", prefix);
+            return;
+        };
+        self.0
+            .mapped_folders
+            .write()
+            .unwrap()
+            .insert(prefix, folder.as_ref().to_path_buf());
+    }
+
+    /// Whether pages may use the routes. They may until this says otherwise.
+    pub fn allow_routes(&self, allowed: bool) {
+        self.0.fs_routes.store(allowed, Ordering::Relaxed);
+    }
+
+    /// The file at `path`, as the `read` route finds it.
+    pub fn find(&self, path: &str) -> Option<GameFile> {
+        self.0.find(&relative_path(path)?)
+    }
+
+    pub fn stat(&self, path: &str) -> Option<FileStat> {
+        self.0.stat(&relative_path(path)?)
+    }
+
+    /// Names in the folder at `path`, sorted.
+    pub fn read_dir(&self, path: &str) -> Option<BTreeSet<String>> {
+        self.0.read_dir(&relative_path(path)?)
+    }
+}
+
+impl Context {
+    /// Answer `route`, the part of the path after [`PREFIX`], for the `path` in `query`.
+    pub(crate) fn serve_fs(&self, route: &str, query: Option<&str>) -> (u16, HttpResponse<Body>) {
+        if !self.fs_routes.load(Ordering::Relaxed) {
+            return (403, text_response(403, "Forbidden"));
+        }
+        let Some(relative) = query.and_then(path_param) else {
+            return (400, text_response(400, "Bad Request"));
+        };
+        match route {
+            "read" => {
+                let content = match self.find(&relative) {
+                    Some(GameFile::Loose(path)) => load_external_file(&path).map(|found| found.content),
+                    Some(GameFile::Packed(data)) => Some(Content::Data(data)),
+                    None => None,
+                };
+                let Some(content) = content else {
+                    return (404, text_response(404, "Not Found"));
+                };
+                let length = content.len();
+                match content.body(0, length) {
+                    Ok(body) => (200, fs_response(body, "application/octet-stream", Some(length))),
+                    Err(e) => {
+                        log::warn!("Failed to read {}: {}", relative, e);
+                        (500, text_response(500, "Internal Server Error"))
+                    }
+                }
+            }
+            "exists" => {
+                let exists = self.stat(&relative).is_some();
+                (
+                    200,
+                    fs_response(Body::from(exists.to_string()), "application/json", None),
+                )
+            }
+            "stat" => match self.stat(&relative).and_then(|stat| serde_json::to_string(&stat).ok()) {
+                Some(stat) => (200, fs_response(Body::from(stat), "application/json", None)),
+                None => (404, text_response(404, "Not Found")),
+            },
+            "readdir" => match self
+                .read_dir(&relative)
+                .and_then(|names| serde_json::to_string(&names).ok())
+            {
+                Some(names) => (200, fs_response(Body::from(names), "application/json", None)),
+                None => (404, text_response(404, "Not Found")),
+            },
+            _ => (404, text_response(404, "Not Found")),
+        }
+    }
+
+    /// Whether `relative` is in a [mapped folder](GameFiles::map_folder), and where, if it is
+    /// the folder itself or a file it shows.
+    fn mapped(&self, relative: &str) -> Option<Option<PathBuf>> {
+        self.mapped_folders
+            .read()
+            .unwrap()
+            .iter()
+            .find_map(|(prefix, folder)| match relative.strip_prefix(prefix.as_str())? {
+                "" => Some(Some(folder.clone())),
+                rest => {
+                    let name = rest.strip_prefix('/')?;
+                    let shown = !name.contains('/') && !name.starts_with('.');
+                    Some(Some(folder.join(name)).filter(|path| shown && path.is_file()))
+                }
+            })
+    }
+
+    /// The file at `relative`: in the mounts, the external folder or the archive, or in the
+    /// mapped folder it is in.
+    fn find(&self, relative: &str) -> Option<GameFile> {
+        if let Some(mapped) = self.mapped(relative) {
+            return mapped.filter(|path| path.is_file()).map(GameFile::Loose);
+        }
+        let loose = self
+            .mounts
+            .resolve(relative)
+            .unwrap_or_else(|| self.root().join(relative));
+        if loose.is_file() {
+            return Some(GameFile::Loose(loose));
+        }
+        self.archive.as_ref()?(relative).map(GameFile::Packed)
+    }
+
+    /// Names in the folder at `relative`, in every mount and the external folder, or in the
+    /// mapped folder it is in.
+    fn read_dir(&self, relative: &str) -> Option<BTreeSet<String>> {
+        let mapped = self.mapped(relative);
+        let is_mapped = mapped.is_some();
+        let folders: Vec<_> = match mapped {
+            Some(path) => vec![path?],
+            None => {
+                let mut folders: Vec<_> = self
+                    .mounts
+                    .0
+                    .read()
+                    .unwrap()
+                    .iter()
+                    .map(|folder| folder.join(relative))
+                    .collect();
+                folders.push(self.root().join(relative));
+                folders
+            }
+        };
+        let mut names = BTreeSet::new();
+        let mut found = false;
+        for entries in folders.iter().filter_map(|folder| fs::read_dir(folder).ok()) {
+            found = true;
+            names.extend(
+                entries
+                    .flatten()
+                    // Mapped folders only show their files
+                    .filter(|entry| {
+                        !is_mapped
+                            || (entry.file_type().is_ok_and(|kind| kind.is_file())
+                                && !entry.file_name().to_string_lossy().starts_with('.'))
+                    })
+                    .map(|entry| entry.file_name().to_string_lossy().into_owned()),
+            );
+        }
+        if !found {
+            return None;
+        }
+        // Mapped folders show up in the folder they are mapped into
+        for prefix in self.mapped_folders.read().unwrap().keys() {
+            let (parent, name) = prefix.rsplit_once('/').unwrap_or(("", prefix.as_str()));
+            if parent == relative {
+                names.insert(name.to_string());
+            }
+        }
+        Some(names)
+    }
+
+    /// The file or folder at `relative`: in the mounts, the external folder or the archive,
+    /// or in the mapped folder it is in.
+    fn stat(&self, relative: &str) -> Option<FileStat> {
+        let mapped = self.mapped(relative);
+        let is_mapped = mapped.is_some();
+        let loose = match mapped {
+            Some(path) => path,
+            None => self
+                .mounts
+                .0
+                .read()
+                .unwrap()
+                .iter()
+                .rev()
+                .map(|folder| folder.join(relative))
+                .find(|path| path.exists())
+                .or_else(|| Some(self.root().join(relative)).filter(|path| path.exists())),
+        };
+        if let Some(metadata) = loose.as_ref().and_then(|path| path.metadata().ok()) {
+            return Some(FileStat {
+                size: metadata.len(),
+                mtime_ms: metadata
+                    .modified()
+                    .ok()
+                    .and_then(|time| time.duration_since(UNIX_EPOCH).ok())
+                    .map(|duration| duration.as_millis() as u64),
+                is_file: metadata.is_file(),
+                is_directory: metadata.is_dir(),
+            });
+        }
+        if is_mapped {
+            return None;
+        }
+        let packed = self.archive.as_ref()?(relative)?;
+        Some(FileStat {
+            size: packed.len() as u64,
+            mtime_ms: None,
+            is_file: true,
+            is_directory: false,
+        })
+    }
+}
+
+/// The `path` parameter of a query string, as a relative path that stays in the folder.
+fn path_param(query: &str) -> Option<String> {
+    let value = query.split('&').find_map(|pair| pair.strip_prefix("path="))?;
+    relative_path(&percent_decode_str(value).decode_utf8().ok()?)
+}
+
+/// `path` with `/` separators and without `.` components, or `None` if it would leave the
+/// folder: through `..`, a root or, on Windows, a drive.
+fn relative_path(path: &str) -> Option<String> {
+    let path = path.replace('\\', "/");
+    let mut parts = Vec::new();
+    for component in Path::new(path.trim_start_matches('/')).components() {
+        match component {
+            Component::Normal(part) => parts.push(part.to_str()?),
+            Component::CurDir => {}
+            _ => return None,
+        }
+    }
+    Some(parts.join("/"))
+}
+
+fn fs_response(body: Body, content_type: &'static str, length: Option<usize>) -> HttpResponse<Body> {
+    let mut response = HttpResponse::new(body);
+    let headers = response.headers_mut();
+    headers.insert(header::CONTENT_TYPE, HeaderValue::from_static(content_type));
+    // Files change while the game runs, e.g. with hot reload
+    headers.insert(header::CACHE_CONTROL, HeaderValue::from_static("no-store"));
+    if let Some(length) = length {
+        headers.insert(header::CONTENT_LENGTH, HeaderValue::from(length));
+    }
+    response
+}
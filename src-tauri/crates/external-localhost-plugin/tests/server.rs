@@ -0,0 +1,547 @@
+//! Serves a temporary game folder through a mock Tauri app and checks the responses.
+
+use std::fs;
+use std::io::{Read, Write};
+use std::net::TcpStream;
+use std::path::{Path, PathBuf};
+use std::thread;
+use std::time::{Duration, Instant};
+
+use external_localhost_plugin::{Builder, GameFile, GameFiles, Mounts, STREAM_THRESHOLD};
+use tauri::test::{mock_builder, mock_context, noop_assets, MockRuntime};
+use tauri::{App, Manager};
+
+struct Served {
+    status: u16,
+    headers: Vec<(String, String)>,
+    body: Vec<u8>,
+}
+
+impl Served {
+    fn header(&self, name: &str) -> Option<&str> {
+        self.headers
+            .iter()
+            .find(|(header, _)| header.eq_ignore_ascii_case(name))
+            .map(|(_, value)| value.as_str())
+    }
+
+    fn text(&self) -> String {
+        String::from_utf8_lossy(&self.body).into_owned()
+    }
+}
+
+/// A fresh folder under the system temp folder, with the given files.
+fn game_folder(name: &str, files: &[(&str, &str)]) -> PathBuf {
+    let dir = std::env::temp_dir().join(format!("external-localhost-plugin-{}-{}", std::process::id(), name));
+    let _ = fs::remove_dir_all(&dir);
+    for (path, content) in files {
+        let path = dir.join(path);
+        fs::create_dir_all(path.parent().unwrap()).unwrap();
+        fs::write(path, content).unwrap();
+    }
+    dir
+}
+
+/// Build a mock app with the plugin on a free port, which starts the server.
+fn serve(builder: impl FnOnce(Builder) -> Builder) -> (App<MockRuntime>, u16) {
+    let port = portpicker::pick_unused_port().expect("no free port");
+    let plugin = builder(Builder::new(port).host("127.0.0.1")).build();
+    let app = mock_builder()
+        .plugin(plugin)
+        .build(mock_context(noop_assets()))
+        .expect("failed to build the app");
+    (app, port)
+}
+
+fn get(port: u16, path: &str, headers: &[(&str, &str)]) -> Served {
+    // The server starts on its own thread, so give it a moment
+    let mut stream = (0..50)
+        .find_map(|_| {
+            TcpStream::connect(("127.0.0.1", port)).ok().or_else(|| {
+                thread::sleep(Duration::from_millis(100));
+                None
+            })
+        })
+        .expect("the server did not start");
+
+    let mut request = format!(
+        "GET {} HTTP/1.1\r\nHost: 127.0.0.1:{}\r\nConnection: close\r\n",
+        path, port
+    );
+    for (name, value) in headers {
+        request.push_str(&format!("{}: {}\r\n", name, value));
+    }
+    request.push_str("\r\n");
+    stream.write_all(request.as_bytes()).unwrap();
+
+    let mut response = Vec::new();
+    stream.read_to_end(&mut response).unwrap();
+    let split = response
+        .windows(4)
+        .position(|w| w == b"\r\n\r\n")
+        .expect("no header end");
+    let head = String::from_utf8_lossy(&response[..split]).into_owned();
+    let mut lines = head.lines();
+    let status = lines.next().unwrap().split(' ').nth(1).unwrap().parse().unwrap();
+    let headers = lines
+        .filter_map(|line| line.split_once(':'))
+        .map(|(name, value)| (name.trim().to_string(), value.trim().to_string()))
+        .collect();
+    let mut body = response[split + 4..].to_vec();
+    // Bodies without a length come chunked
+    if head.to_ascii_lowercase().contains("transfer-encoding: chunked") {
+        body = dechunk(&body);
+    }
+    Served { status, headers, body }
+}
+
+fn dechunk(mut data: &[u8]) -> Vec<u8> {
+    let mut body = Vec::new();
+    loop {
+        let line_end = data.windows(2).position(|w| w == b"\r\n").unwrap();
+        let size = usize::from_str_radix(std::str::from_utf8(&data[..line_end]).unwrap().trim(), 16).unwrap();
+        if size == 0 {
+            return body;
+        }
+        body.extend_from_slice(&data[line_end + 2..line_end + 2 + size]);
+        data = &data[line_end + 2 + size + 2..];
+    }
+}
+
+fn cleanup(dir: &Path) {
+    let _ = fs::remove_dir_all(dir);
+}
+
+#[test]
+fn serves_the_external_folder_and_injects_scripts() {
+    let dir = game_folder(
+        "inject",
+        &[
+            (
+                "index.html",
+                "<html><head><title>Game</title></head><body></body></html>",
+            ),
+            ("data/System.json", "{\"gameTitle\":\"Test\"}"),
+        ],
+    );
+    let (_app, port) = serve(|server| server.external_folder(&dir).inject_script("window.injected = 1;"));
+
+    let page = get(port, "/", &[]);
+    assert_eq!(page.status, 200);
+    assert_eq!(page.header("Content-Type"), Some("text/html"));
+    assert!(page
+        .text()
+        .starts_with("<html><head><script>window.injected = 1;</script><title>"));
+
+    let data = get(port, "/data/System.json", &[]);
+    assert_eq!(data.status, 200);
+    assert_eq!(data.header("Content-Type"), Some("application/json"));
+    assert_eq!(data.text(), "{\"gameTitle\":\"Test\"}");
+
+    assert_eq!(get(port, "/data/Missing.json", &[]).status, 404);
+    assert_eq!(get(port, "/__launcher/diagnostics", &[]).status, 200);
+    cleanup(&dir);
+}
+
+#[test]
+fn skipped_pages_are_served_without_scripts() {
+    let page = "<html><head></head><body>Manual</body></html>";
+    let dir = game_folder("skip", &[("index.html", page), ("manual/index.html", page)]);
+    let (_app, port) = serve(|server| {
+        server
+            .external_folder(&dir)
+            .inject_script("window.injected = 1;")
+            .skip_injection(|path| path.starts_with("manual/"))
+    });
+
+    assert_ne!(get(port, "/index.html", &[]).text(), page);
+    assert_eq!(get(port, "/manual/index.html", &[]).text(), page);
+    cleanup(&dir);
+}
+
+#[test]
+fn percent_encoded_paths_are_decoded() {
+    let dir = game_folder("encoded", &[("img/faces/Actor 1.png", "face")]);
+    let (_app, port) = serve(|server| server.external_folder(&dir));
+
+    let face = get(port, "/img/faces/Actor%201.png", &[]);
+    assert_eq!(face.status, 200);
+    assert_eq!(face.text(), "face");
+    cleanup(&dir);
+}
+
+#[test]
+fn mounts_take_precedence_and_can_be_removed() {
+    let dir = game_folder("mounts", &[("img/title.png", "base")]);
+    let first = game_folder("mounts-first", &[("img/title.png", "first")]);
+    let second = game_folder("mounts-second", &[("img/title.png", "second")]);
+    let (app, port) = serve(|server| server.external_folder(&dir));
+
+    let mounts = app.state::<Mounts>();
+    mounts.mount(&first);
+    assert_eq!(get(port, "/img/title.png", &[]).text(), "first");
+    mounts.mount(&second);
+    assert_eq!(get(port, "/img/title.png", &[]).text(), "second");
+    mounts.unmount(&second);
+    mounts.unmount(&first);
+    assert_eq!(get(port, "/img/title.png", &[]).text(), "base");
+
+    for dir in [dir, first, second] {
+        cleanup(&dir);
+    }
+}
+
+#[test]
+fn archive_and_aliases_fill_in_missing_files() {
+    let dir = game_folder("archive", &[("loose.txt", "loose"), ("audio/se/Cursor1.ogg", "ogg")]);
+    let (_app, port) = serve(|server| {
+        server
+            .external_folder(&dir)
+            .archive(|path| match path {
+                "packed.txt" => Some(b"packed".to_vec()),
+                "loose.txt" => Some(b"from the archive".to_vec()),
+                _ => None,
+            })
+            .aliases(|path| vec![path.replace(".m4a", ".ogg")])
+    });
+
+    assert_eq!(get(port, "/packed.txt", &[]).text(), "packed");
+    // Loose files win over packed ones
+    assert_eq!(get(port, "/loose.txt", &[]).text(), "loose");
+    assert_eq!(get(port, "/audio/se/Cursor1.m4a", &[]).text(), "ogg");
+    cleanup(&dir);
+}
+
+#[test]
+fn transforms_run_in_order_and_headers_are_added() {
+    let dir = game_folder("transform", &[("js/plugins/Test.js", "a")]);
+    let (_app, port) = serve(|server| {
+        server
+            .external_folder(&dir)
+            .transform(|_, mut content| {
+                content.push(b'b');
+                content
+            })
+            .transform(|path, mut content| {
+                if path == "js/plugins/Test.js" {
+                    content.push(b'c');
+                }
+                content
+            })
+            .on_request(|_, response| response.add_header("X-Test", "yes"))
+    });
+
+    let script = get(port, "/js/plugins/Test.js", &[]);
+    assert_eq!(script.text(), "abc");
+    assert_eq!(script.header("X-Test"), Some("yes"));
+    cleanup(&dir);
+}
+
+#[test]
+fn range_requests_return_partial_content() {
+    let dir = game_folder("range", &[("audio/bgm/Theme.ogg", "0123456789")]);
+    let (_app, port) = serve(|server| server.external_folder(&dir));
+
+    let part = get(port, "/audio/bgm/Theme.ogg", &[("Range", "bytes=2-5")]);
+    assert_eq!(part.status, 206);
+    assert_eq!(part.header("Accept-Ranges"), Some("bytes"));
+    assert_eq!(part.header("Content-Length"), Some("4"));
+    assert_eq!(part.header("Content-Range"), Some("bytes 2-5/10"));
+    assert_eq!(part.text(), "2345");
+
+    let suffix = get(port, "/audio/bgm/Theme.ogg", &[("Range", "bytes=-3")]);
+    assert_eq!(suffix.text(), "789");
+
+    let invalid = get(port, "/audio/bgm/Theme.ogg", &[("Range", "bytes=20-30")]);
+    assert_eq!(invalid.status, 416);
+    cleanup(&dir);
+}
+
+#[test]
+fn mz_effects_and_movies_get_their_types_and_ranges() {
+    let dir = game_folder("effects", &[]);
+    // Effekseer files are binary, starting with their `SKFE` magic
+    let effect: Vec<u8> = b"SKFE".iter().copied().chain((0..=255u8).rev()).collect();
+    let material: Vec<u8> = b"EFKM".iter().copied().chain(0..=255u8).collect();
+    let movie: Vec<u8> = b"OggS".iter().copied().chain((0..64u8).map(|i| i.wrapping_mul(37))).collect();
+    for (path, content) in [
+        ("effects/Heal1.efkefc", &effect),
+        ("effects/Material/Dissolve.efkmat", &material),
+        ("movies/Opening.ogv", &movie),
+    ] {
+        fs::create_dir_all(dir.join(path).parent().unwrap()).unwrap();
+        fs::write(dir.join(path), content).unwrap();
+    }
+    let (_app, port) = serve(|server| server.external_folder(&dir));
+
+    for (path, content, content_type) in [
+        ("/effects/Heal1.efkefc", &effect, "application/octet-stream"),
+        ("/effects/Material/Dissolve.efkmat", &material, "application/octet-stream"),
+        ("/movies/Opening.ogv", &movie, "video/ogg"),
+    ] {
+        let full = get(port, path, &[]);
+        assert_eq!(full.status, 200, "{}", path);
+        assert_eq!(full.header("Content-Type"), Some(content_type), "{}", path);
+        assert_eq!(&full.body, content, "{}", path);
+    }
+
+    let part = get(port, "/effects/Heal1.efkefc", &[("Range", "bytes=4-131")]);
+    assert_eq!(part.status, 206);
+    assert_eq!(part.header("Content-Type"), Some("application/octet-stream"));
+    assert_eq!(part.header("Content-Length"), Some("128"));
+    assert_eq!(
+        part.header("Content-Range"),
+        Some(format!("bytes 4-131/{}", effect.len()).as_str())
+    );
+    assert_eq!(part.body, effect[4..132]);
+
+    let tail = get(port, "/movies/Opening.ogv", &[("Range", "bytes=-16")]);
+    assert_eq!(tail.status, 206);
+    assert_eq!(tail.header("Content-Type"), Some("video/ogg"));
+    assert_eq!(tail.body, movie[movie.len() - 16..]);
+    cleanup(&dir);
+}
+
+#[test]
+fn large_files_are_streamed_as_they_are() {
+    let dir = game_folder("stream", &[]);
+    let length = STREAM_THRESHOLD as usize + 10;
+    let movie: Vec<u8> = (0..length).map(|i| (i % 251) as u8).collect();
+    fs::create_dir_all(dir.join("movies")).unwrap();
+    fs::write(dir.join("movies/Opening.webm"), &movie).unwrap();
+    let (_app, port) = serve(|server| {
+        server.external_folder(&dir).transform(|_, mut content| {
+            content.clear();
+            content
+        })
+    });
+
+    let full = get(port, "/movies/Opening.webm", &[]);
+    assert_eq!(full.status, 200);
+    assert_eq!(full.header("Content-Length"), Some(length.to_string().as_str()));
+    assert_eq!(full.body, movie);
+
+    let tail = get(port, "/movies/Opening.webm", &[("Range", "bytes=-4")]);
+    assert_eq!(tail.status, 206);
+    assert_eq!(tail.header("Content-Length"), Some("4"));
+    assert_eq!(
+        tail.header("Content-Range"),
+        Some(format!("bytes {}-{}/{}", length - 4, length - 1, length).as_str())
+    );
+    assert_eq!(tail.body, movie[length - 4..]);
+    cleanup(&dir);
+}
+
+#[test]
+fn slow_requests_dont_hold_up_others() {
+    let dir = game_folder("threads", &[("fast.txt", "fast")]);
+    let (_app, port) = serve(|server| {
+        server.external_folder(&dir).threads(2).archive(|path| {
+            (path == "slow.txt").then(|| {
+                thread::sleep(Duration::from_secs(2));
+                b"slow".to_vec()
+            })
+        })
+    });
+    // Wait for the server before timing anything
+    get(port, "/fast.txt", &[]);
+
+    let slow = thread::spawn(move || get(port, "/slow.txt", &[]));
+    thread::sleep(Duration::from_millis(200));
+    let started = Instant::now();
+    assert_eq!(get(port, "/fast.txt", &[]).text(), "fast");
+    assert!(started.elapsed() < Duration::from_secs(1));
+    assert_eq!(slow.join().unwrap().text(), "slow");
+    cleanup(&dir);
+}
+
+#[test]
+fn encrypted_assets_are_decrypted() {
+    let key = [
+        0x12u8, 0x34, 0x56, 0x78, 0x9a, 0xbc, 0xde, 0xf0, 0x0f, 0xed, 0xcb, 0xa9, 0x87, 0x65, 0x43, 0x21,
+    ];
+    let hex: String = key.iter().map(|byte| format!("{:02x}", byte)).collect();
+    let system = format!("{{\"hasEncryptedImages\":true,\"encryptionKey\":\"{}\"}}", hex);
+    let page = "<html><head></head><body></body></html>";
+    let dir = game_folder("decrypt", &[("index.html", page), ("data/System.json", &system)]);
+
+    let image: Vec<u8> = b"\x89PNG\r\n\x1a\n".iter().copied().chain(0..100).collect();
+    let mut encrypted = b"RPGMV\0\0\0\0\x03\x01\0\0\0\0\0".to_vec();
+    encrypted.extend(
+        image
+            .iter()
+            .enumerate()
+            .map(|(i, byte)| byte ^ key.get(i).unwrap_or(&0)),
+    );
+    fs::create_dir_all(dir.join("img/pictures")).unwrap();
+    fs::write(dir.join("img/pictures/Title.rpgmvp"), &encrypted).unwrap();
+    fs::write(dir.join("img/pictures/Logo.png_"), &encrypted).unwrap();
+    fs::write(dir.join("img/pictures/Plain.png_"), &image).unwrap();
+    let (_app, port) = serve(|server| server.external_folder(&dir).decrypt_assets());
+
+    let title = get(port, "/img/pictures/Title.rpgmvp", &[]);
+    assert_eq!(title.status, 200);
+    assert_eq!(title.header("Content-Type"), Some("image/png"));
+    assert_eq!(title.body, image);
+    assert_eq!(get(port, "/img/pictures/Logo.png_", &[]).body, image);
+    assert_eq!(get(port, "/img/pictures/Plain.png_", &[]).body, image);
+    assert!(get(port, "/", &[]).text().contains("decryptArrayBuffer"));
+    cleanup(&dir);
+
+    let dir = game_folder("encrypted", &[("data/System.json", &system)]);
+    fs::create_dir_all(dir.join("img/pictures")).unwrap();
+    fs::write(dir.join("img/pictures/Title.rpgmvp"), &encrypted).unwrap();
+    let (_app, port) = serve(|server| server.external_folder(&dir));
+    assert_eq!(get(port, "/img/pictures/Title.rpgmvp", &[]).body, encrypted);
+    cleanup(&dir);
+}
+
+#[test]
+fn unchanged_files_get_not_modified() {
+    let dir = game_folder("conditional", &[("data/Map001.json", "{}")]);
+    let (_app, port) = serve(|server| server.external_folder(&dir));
+
+    let first = get(port, "/data/Map001.json", &[]);
+    assert_eq!(first.status, 200);
+    let etag = first.header("ETag").expect("no ETag").to_string();
+    let modified = first.header("Last-Modified").expect("no Last-Modified").to_string();
+
+    let cached = get(port, "/data/Map001.json", &[("If-None-Match", etag.as_str())]);
+    assert_eq!(cached.status, 304);
+    assert!(cached.body.is_empty());
+    assert_eq!(cached.header("ETag"), Some(etag.as_str()));
+    assert_eq!(
+        get(port, "/data/Map001.json", &[("If-Modified-Since", modified.as_str())]).status,
+        304
+    );
+
+    fs::write(dir.join("data/Map001.json"), "{\"events\":[]}").unwrap();
+    let changed = get(port, "/data/Map001.json", &[("If-None-Match", etag.as_str())]);
+    assert_eq!(changed.status, 200);
+    assert_eq!(changed.text(), "{\"events\":[]}");
+    assert_ne!(changed.header("ETag"), Some(etag.as_str()));
+    cleanup(&dir);
+}
+
+#[test]
+fn fs_routes_read_files_as_they_are() {
+    let dir = game_folder(
+        "fs",
+        &[("data/Extra.json", "{\"a\":1}"), ("index.html", "<html></html>")],
+    );
+    let (_app, port) = serve(|server| {
+        server
+            .external_folder(&dir)
+            .inject_script("window.injected = 1;")
+            .transform(|_, _| b"transformed".to_vec())
+            .archive(|path| (path == "packed/Map.json").then(|| b"packed".to_vec()))
+    });
+
+    let extra = get(port, "/__launcher/fs/read?path=data%2FExtra.json", &[]);
+    assert_eq!(extra.status, 200);
+    assert_eq!(extra.text(), "{\"a\":1}");
+    assert_eq!(
+        get(port, "/__launcher/fs/read?path=index.html", &[]).text(),
+        "<html></html>"
+    );
+    assert_eq!(
+        get(port, "/__launcher/fs/read?path=packed/Map.json", &[]).text(),
+        "packed"
+    );
+    assert_eq!(get(port, "/__launcher/fs/read?path=data/Missing.json", &[]).status, 404);
+
+    assert_eq!(get(port, "/__launcher/fs/exists?path=data", &[]).text(), "true");
+    assert_eq!(get(port, "/__launcher/fs/exists?path=save", &[]).text(), "false");
+    let stat = get(port, "/__launcher/fs/stat?path=data/Extra.json", &[]).text();
+    assert!(stat.contains("\"size\":7"));
+    assert!(stat.contains("\"isFile\":true"));
+    assert_eq!(
+        get(port, "/__launcher/fs/readdir?path=data", &[]).text(),
+        "[\"Extra.json\"]"
+    );
+    assert_eq!(get(port, "/__launcher/fs/readdir?path=save", &[]).status, 404);
+
+    assert_eq!(get(port, "/__launcher/fs/read?path=../fs/index.html", &[]).status, 400);
+    assert_eq!(get(port, "/__launcher/fs/read", &[]).status, 400);
+    cleanup(&dir);
+}
+
+#[test]
+fn mapped_folders_and_game_files_agree_with_fs_routes() {
+    let dir = game_folder("mapped", &[("data/Extra.json", "{\"a\":1}"), ("save/file1.rpgsave", "game's own")]);
+    let saves = game_folder(
+        "mapped-saves",
+        &[
+            ("file1.rpgsave", "slot 1"),
+            ("global.rpgsave", "global"),
+            (".launcher.lock", ""),
+            ("backups/file1.20261016-143000-250.rpgsave", "old slot 1"),
+        ],
+    );
+    let mod_dir = game_folder("mapped-mod", &[("data/Mod.json", "{}")]);
+    let (app, port) = serve(|server| {
+        server
+            .external_folder(&dir)
+            .archive(|path| (path == "data/Packed.json").then(|| b"packed".to_vec()))
+    });
+    app.state::<Mounts>().mount(&mod_dir);
+    let files = app.state::<GameFiles>();
+    files.map_folder("save", &saves);
+
+    assert_eq!(
+        get(port, "/__launcher/fs/read?path=save/file1.rpgsave", &[]).text(),
+        "slot 1"
+    );
+    assert_eq!(
+        get(port, "/__launcher/fs/readdir?path=save", &[]).text(),
+        "[\"file1.rpgsave\",\"global.rpgsave\"]"
+    );
+    assert!(get(port, "/__launcher/fs/readdir?path=", &[]).text().contains("\"save\""));
+    let stat = get(port, "/__launcher/fs/stat?path=save%5Cglobal.rpgsave", &[]).text();
+    assert!(stat.contains("\"size\":6"));
+    // Only the saves themselves, not what else the folder holds
+    assert_eq!(get(port, "/__launcher/fs/read?path=save/.launcher.lock", &[]).status, 404);
+    assert_eq!(get(port, "/__launcher/fs/stat?path=save/backups", &[]).status, 404);
+    assert_eq!(
+        get(port, "/__launcher/fs/read?path=save/backups/file1.20261016-143000-250.rpgsave", &[]).status,
+        404
+    );
+
+    // The app sees what the routes see: mapped folders, mounts and the archive
+    assert_eq!(files.stat("save/global.rpgsave").map(|stat| stat.size), Some(6));
+    assert_eq!(files.stat("save\\file1.rpgsave").map(|stat| stat.size), Some(6));
+    assert!(files.stat("save").is_some_and(|stat| stat.is_directory));
+    assert!(files.stat("data/Mod.json").is_some_and(|stat| stat.is_file));
+    assert_eq!(files.stat("data/Packed.json").map(|stat| stat.mtime_ms), Some(None));
+    assert_eq!(
+        files.read_dir("data").map(|names| names.into_iter().collect::<Vec<_>>()),
+        Some(vec!["Extra.json".to_string(), "Mod.json".to_string()])
+    );
+    assert!(files.stat("../mapped/data/Extra.json").is_none());
+    assert!(files.stat("/data/Extra.json").is_some());
+    assert!(files.stat("save/backups").is_none());
+    assert!(matches!(
+        files.find("save/file1.rpgsave"),
+        Some(GameFile::Loose(path)) if path == saves.join("file1.rpgsave")
+    ));
+    assert!(matches!(
+        files.find("data/Mod.json"),
+        Some(GameFile::Loose(path)) if path.starts_with(&mod_dir)
+    ));
+    assert!(matches!(
+        files.find("data/Packed.json"),
+        Some(GameFile::Packed(data)) if data == b"packed"
+    ));
+    assert!(files.find("data").is_none());
+    assert!(files.find("save/.launcher.lock").is_none());
+
+    // Turning the routes off leaves the app's own lookups alone
+    files.allow_routes(false);
+    assert_eq!(get(port, "/__launcher/fs/read?path=data/Extra.json", &[]).status, 403);
+    assert_eq!(get(port, "/__launcher/fs/readdir?path=save", &[]).status, 403);
+    assert!(files.stat("data/Extra.json").is_some());
+
+    for dir in [dir, saves, mod_dir] {
+        cleanup(&dir);
+    }
+}
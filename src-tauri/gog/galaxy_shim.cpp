@@ -0,0 +1,66 @@
+// C interface over the GOG Galaxy SDK's C++ API, compiled by build.rs with the `gog` feature.
+// Every function returns false when the SDK reports an error.
+
+#include <galaxy/GalaxyApi.h>
+
+namespace {
+
+bool ok() {
+    return galaxy::api::GetError() == nullptr;
+}
+
+} // namespace
+
+extern "C" {
+
+bool galaxy_shim_init(const char* client_id, const char* client_secret) {
+    galaxy::api::Init(galaxy::api::InitOptions(client_id, client_secret));
+    if (!ok()) {
+        return false;
+    }
+    galaxy::api::User()->SignInGalaxy();
+    return ok();
+}
+
+void galaxy_shim_process_data() {
+    galaxy::api::ProcessData();
+}
+
+bool galaxy_shim_signed_in() {
+    return galaxy::api::User()->SignedIn() && ok();
+}
+
+bool galaxy_shim_request_stats() {
+    galaxy::api::Stats()->RequestUserStatsAndAchievements();
+    return ok();
+}
+
+bool galaxy_shim_set_achievement(const char* name) {
+    galaxy::api::Stats()->SetAchievement(name);
+    if (!ok()) {
+        return false;
+    }
+    galaxy::api::Stats()->StoreStatsAndAchievements();
+    return ok();
+}
+
+bool galaxy_shim_clear_achievement(const char* name) {
+    galaxy::api::Stats()->ClearAchievement(name);
+    if (!ok()) {
+        return false;
+    }
+    galaxy::api::Stats()->StoreStatsAndAchievements();
+    return ok();
+}
+
+bool galaxy_shim_is_achieved(const char* name, bool* unlocked) {
+    uint32_t unlock_time = 0;
+    galaxy::api::Stats()->GetAchievement(name, *unlocked, unlock_time);
+    return ok();
+}
+
+void galaxy_shim_shutdown() {
+    galaxy::api::Shutdown();
+}
+
+}
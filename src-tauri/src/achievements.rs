@@ -0,0 +1,159 @@
+//! Launcher-native achievements for builds without Steam.
+//!
+//! Definitions come from `achievements.json` in the game folder; unlocks are stored per
+//! player in the app data folder. Unlocking emits `achievement-unlocked`, which the injected
+//! `achievements.js` shows as a toast, and is mirrored to Steam or GOG Galaxy when available.
+
+use std::collections::HashMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+
+use serde::{Deserialize, Serialize};
+use tauri::{AppHandle, Emitter, Manager, Runtime, State};
+
+pub const ACHIEVEMENTS_FILE_NAME: &str = "achievements.json";
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct Definition {
+    id: String,
+    name: String,
+    #[serde(default)]
+    description: String,
+    /// Image path relative to the game folder.
+    #[serde(default)]
+    icon: Option<String>,
+    /// Hidden achievements don't show their name and description until unlocked.
+    #[serde(default)]
+    hidden: bool,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Achievement {
+    pub id: String,
+    pub name: String,
+    pub description: String,
+    pub icon: Option<String>,
+    pub hidden: bool,
+    /// Unlock time in milliseconds since the Unix epoch, if unlocked.
+    pub unlocked_at: Option<i64>,
+}
+
+pub struct Achievements {
+    definitions: Vec<Definition>,
+    /// Where unlocks are saved.
+    path: PathBuf,
+    unlocked: Mutex<HashMap<String, i64>>,
+}
+
+impl Achievements {
+    pub fn load(game_dir: &Path, path: PathBuf) -> Self {
+        let definitions_path = game_dir.join(ACHIEVEMENTS_FILE_NAME);
+        let definitions = match fs::read_to_string(&definitions_path) {
+            Ok(content) => serde_json::from_str(&content).unwrap_or_else(|e| {
+                log::warn!("Invalid {:?}: {}", definitions_path, e);
+                Vec::new()
+            }),
+            Err(_) => Vec::new(),
+        };
+        let unlocked = fs::read_to_string(&path)
+            .ok()
+            .and_then(|content| serde_json::from_str(&content).ok())
+            .unwrap_or_default();
+
+        Self {
+            definitions,
+            path,
+            unlocked: Mutex::new(unlocked),
+        }
+    }
+
+    fn to_achievement(&self, definition: &Definition, unlocked_at: Option<i64>) -> Achievement {
+        let concealed = definition.hidden && unlocked_at.is_none();
+        Achievement {
+            id: definition.id.clone(),
+            name: if concealed { "Hidden achievement".to_string() } else { definition.name.clone() },
+            description: if concealed { String::new() } else { definition.description.clone() },
+            icon: definition.icon.clone(),
+            hidden: definition.hidden,
+            unlocked_at,
+        }
+    }
+
+    pub fn list(&self) -> Vec<Achievement> {
+        let unlocked = self.unlocked.lock().unwrap();
+        self.definitions
+            .iter()
+            .map(|definition| self.to_achievement(definition, unlocked.get(&definition.id).copied()))
+            .collect()
+    }
+
+    /// Unlock an achievement. Returns it if it wasn't unlocked before.
+    pub fn unlock(&self, id: &str) -> Result<Option<Achievement>, String> {
+        let definition = self
+            .definitions
+            .iter()
+            .find(|definition| definition.id == id)
+            .ok_or_else(|| format!("Unknown achievement: {}", id))?;
+
+        let mut unlocked = self.unlocked.lock().unwrap();
+        if unlocked.contains_key(id) {
+            return Ok(None);
+        }
+        let now = chrono::Utc::now().timestamp_millis();
+        unlocked.insert(id.to_string(), now);
+        self.save(&unlocked)?;
+        Ok(Some(self.to_achievement(definition, Some(now))))
+    }
+
+    /// Lock an achievement again, for testing.
+    pub fn reset(&self, id: &str) -> Result<(), String> {
+        let mut unlocked = self.unlocked.lock().unwrap();
+        if unlocked.remove(id).is_some() {
+            self.save(&unlocked)?;
+        }
+        Ok(())
+    }
+
+    fn save(&self, unlocked: &HashMap<String, i64>) -> Result<(), String> {
+        if let Some(parent) = self.path.parent() {
+            fs::create_dir_all(parent).map_err(|e| e.to_string())?;
+        }
+        let content = serde_json::to_string_pretty(unlocked).map_err(|e| e.to_string())?;
+        fs::write(&self.path, content).map_err(|e| e.to_string())
+    }
+}
+
+#[tauri::command]
+pub fn get_achievements(achievements: State<'_, Achievements>) -> Vec<Achievement> {
+    achievements.list()
+}
+
+/// Unlock an achievement. Returns `false` if it was already unlocked.
+#[tauri::command]
+pub fn unlock_achievement<R: Runtime>(
+    app: AppHandle<R>,
+    achievements: State<'_, Achievements>,
+    id: String,
+) -> Result<bool, String> {
+    let Some(achievement) = achievements.unlock(&id)? else {
+        return Ok(false);
+    };
+
+    log::info!("Achievement unlocked: {}", achievement.id);
+    if let Some(steam) = app.try_state::<crate::steam::Steam>() {
+        let _ = crate::steam::steam_unlock_achievement(steam, id.clone());
+    }
+    if let Some(gog) = app.try_state::<crate::gog::Gog>() {
+        let _ = crate::gog::unlock_achievement(&gog, &id);
+    }
+    app.emit("achievement-unlocked", achievement).map_err(|e| e.to_string())?;
+    Ok(true)
+}
+
+#[tauri::command]
+pub fn reset_achievement(achievements: State<'_, Achievements>, id: String) -> Result<(), String> {
+    achievements.reset(&id)
+}
@@ -0,0 +1,166 @@
+//! Command line handling for the launcher.
+//!
+//! Arguments the launcher doesn't understand belong to the game: they are forwarded as
+//! query parameters on the game URL (where RPG Maker's `Utils.isOptionValid` looks for
+//! `test`, `btest` and `etest`) and exposed through the polyfilled `process.argv`.
+//! The launcher keeps `--game-dir=<path>`, `--watch`, `--safe-mode` and `--self-test=<dir>`
+//! for itself, and the packaging tools `--encrypt-assets`, `--encryption-key=<hex>`,
+//! `--optimize-assets`, `--strip-unused`, `--pack=<file>`, `--unpack=<file>`,
+//! `--generate-manifest` and `--sign-capabilities=<key file>`, which run instead of the game.
+
+use std::path::{Path, PathBuf};
+
+use percent_encoding::{utf8_percent_encode, NON_ALPHANUMERIC};
+
+/// Launcher option selecting the game folder instead of searching for `Game_Contents`.
+pub const GAME_DIR_OPTION: &str = "--game-dir=";
+/// Reload changed map and common event data while the game runs.
+pub const WATCH_OPTION: &str = "--watch";
+/// Encrypt the game's images and audio in place, then exit.
+pub const ENCRYPT_ASSETS_OPTION: &str = "--encrypt-assets";
+/// Key for `--encrypt-assets`, as 32 hex digits. A new one is generated when omitted.
+pub const ENCRYPTION_KEY_OPTION: &str = "--encryption-key=";
+/// Report the images and audio the game never refers to, then exit.
+pub const OPTIMIZE_ASSETS_OPTION: &str = "--optimize-assets";
+/// With `--optimize-assets`, delete the unreferenced files too.
+pub const STRIP_UNUSED_OPTION: &str = "--strip-unused";
+/// Pack the game folder into a `.pak` archive, then exit.
+pub const PACK_OPTION: &str = "--pack=";
+/// Extract a `.pak` archive into the game folder, then exit.
+pub const UNPACK_OPTION: &str = "--unpack=";
+/// Write `manifest.json` with the hash and size of every game file, then exit.
+pub const GENERATE_MANIFEST_OPTION: &str = "--generate-manifest";
+/// Sign the game's `capabilities.json` with the key in the given file, then exit.
+pub const SIGN_CAPABILITIES_OPTION: &str = "--sign-capabilities=";
+/// Run the game without mods and launcher extras.
+pub const SAFE_MODE_OPTION: &str = "--safe-mode";
+/// Check the game's first frame against the reference in the given folder, then exit.
+pub const SELF_TEST_OPTION: &str = "--self-test=";
+
+/// The editor's test launches.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TestMode {
+    /// Playtest from the title screen.
+    Test,
+    /// Battle test with the party from `data/Test_Battlers.json`.
+    BattleTest,
+    /// Event test, running `data/Test_Event.json`.
+    EventTest,
+}
+
+pub struct LaunchArgs {
+    /// Path of the launcher executable, reported as `process.argv[0]`.
+    pub exec_path: String,
+    /// Game folder given with `--game-dir=<path>`, or as a plain argument like NW.js takes it.
+    pub game_dir: Option<PathBuf>,
+    /// Arguments forwarded untouched to the game.
+    pub game_args: Vec<String>,
+    pub watch: bool,
+    pub encrypt_assets: bool,
+    pub encryption_key: Option<String>,
+    pub optimize_assets: bool,
+    pub strip_unused: bool,
+    pub pack: Option<PathBuf>,
+    pub unpack: Option<PathBuf>,
+    pub generate_manifest: bool,
+    pub sign_capabilities: Option<PathBuf>,
+    pub safe_mode: bool,
+    pub self_test: Option<PathBuf>,
+}
+
+impl LaunchArgs {
+    pub fn from_env() -> Self {
+        let mut args = std::env::args();
+        let exec_path = args.next().unwrap_or_default();
+        let mut game_dir = None;
+        let mut game_args = Vec::new();
+        let mut watch = false;
+        let mut encrypt_assets = false;
+        let mut encryption_key = None;
+        let mut optimize_assets = false;
+        let mut strip_unused = false;
+        let mut pack = None;
+        let mut unpack = None;
+        let mut generate_manifest = false;
+        let mut sign_capabilities = None;
+        let mut safe_mode = false;
+        let mut self_test = None;
+
+        for arg in args {
+            match arg.as_str() {
+                // macOS passes a process serial number when launched from Finder
+                a if a.starts_with("-psn_") => {}
+                a if a.starts_with(GAME_DIR_OPTION) => {
+                    game_dir = Some(PathBuf::from(&a[GAME_DIR_OPTION.len()..]));
+                }
+                WATCH_OPTION => watch = true,
+                ENCRYPT_ASSETS_OPTION => encrypt_assets = true,
+                a if a.starts_with(ENCRYPTION_KEY_OPTION) => {
+                    encryption_key = Some(a[ENCRYPTION_KEY_OPTION.len()..].to_string());
+                }
+                OPTIMIZE_ASSETS_OPTION => optimize_assets = true,
+                STRIP_UNUSED_OPTION => strip_unused = true,
+                a if a.starts_with(PACK_OPTION) => pack = Some(PathBuf::from(&a[PACK_OPTION.len()..])),
+                a if a.starts_with(UNPACK_OPTION) => unpack = Some(PathBuf::from(&a[UNPACK_OPTION.len()..])),
+                GENERATE_MANIFEST_OPTION => generate_manifest = true,
+                a if a.starts_with(SIGN_CAPABILITIES_OPTION) => {
+                    sign_capabilities = Some(PathBuf::from(&a[SIGN_CAPABILITIES_OPTION.len()..]));
+                }
+                SAFE_MODE_OPTION => safe_mode = true,
+                a if a.starts_with(SELF_TEST_OPTION) => self_test = Some(PathBuf::from(&a[SELF_TEST_OPTION.len()..])),
+                a if game_dir.is_none() && !a.starts_with('-') && Path::new(a).is_dir() => {
+                    game_dir = Some(PathBuf::from(a));
+                }
+                _ => game_args.push(arg),
+            }
+        }
+
+        Self {
+            exec_path,
+            game_dir,
+            game_args,
+            watch,
+            encrypt_assets,
+            encryption_key,
+            optimize_assets,
+            strip_unused,
+            pack,
+            unpack,
+            generate_manifest,
+            sign_capabilities,
+            safe_mode,
+            self_test,
+        }
+    }
+
+    /// Which test launch this is, if any. The flags are forwarded to the game either way,
+    /// since the engine reads them itself.
+    pub fn test_mode(&self) -> Option<TestMode> {
+        self.game_args.iter().find_map(|arg| match arg.trim_start_matches('-') {
+            "btest" => Some(TestMode::BattleTest),
+            "etest" => Some(TestMode::EventTest),
+            "test" => Some(TestMode::Test),
+            _ => None,
+        })
+    }
+
+    /// Build the query string for the game URL, e.g. `--test --lang=ja` becomes `test&lang=ja`.
+    pub fn query_string(&self) -> String {
+        self.game_args
+            .iter()
+            .map(|arg| {
+                let option = arg.trim_start_matches('-');
+                match option.split_once('=') {
+                    Some((key, value)) => format!(
+                        "{}={}",
+                        utf8_percent_encode(key, NON_ALPHANUMERIC),
+                        utf8_percent_encode(value, NON_ALPHANUMERIC)
+                    ),
+                    None => utf8_percent_encode(option, NON_ALPHANUMERIC).to_string(),
+                }
+            })
+            .filter(|option| !option.is_empty())
+            .collect::<Vec<_>>()
+            .join("&")
+    }
+}
@@ -0,0 +1,158 @@
+//! Loop points of the game's Ogg Vorbis audio.
+//!
+//! RPG Maker loops BGM between the `LOOPSTART` and `LOOPLENGTH` Vorbis comments, which the
+//! engine reads itself from the Ogg files it plays. When it plays something else instead,
+//! like the `.m4a` fallback on WebKit or a converted file, the tags are lost and the music
+//! restarts from the top. The injected `audio_loops.js` fills them in from the Ogg files
+//! through `get_audio_loops`.
+
+use std::collections::BTreeMap;
+use std::fs;
+use std::io::Read;
+use std::path::Path;
+
+use serde::Serialize;
+use tauri::State;
+
+use crate::encryption::{self, EncryptionInfo};
+use crate::game::GameInfo;
+use crate::game_files;
+
+/// Folders with looping audio.
+const LOOP_FOLDERS: [&str; 2] = ["audio/bgm", "audio/bgs"];
+/// How much of a file is read to find the comments. Cover art can make them large.
+const MAX_HEADER_BYTES: u64 = 1024 * 1024;
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct LoopInfo {
+    /// Both in samples, like the tags.
+    pub loop_start: u64,
+    pub loop_length: u64,
+    pub sample_rate: u32,
+}
+
+/// Split the first Ogg pages of `data` into packets, stopping after `count`.
+fn ogg_packets(data: &[u8], count: usize) -> Vec<Vec<u8>> {
+    let mut packets = Vec::new();
+    let mut packet = Vec::new();
+    let mut pos = 0;
+    while packets.len() < count && data.len() >= pos + 27 && &data[pos..pos + 4] == b"OggS" {
+        let segments = data[pos + 26] as usize;
+        let table = pos + 27;
+        let Some(lacing) = data.get(table..table + segments) else {
+            break;
+        };
+        let mut body = table + segments;
+        for &size in lacing {
+            let Some(segment) = data.get(body..body + size as usize) else {
+                return packets;
+            };
+            packet.extend_from_slice(segment);
+            body += size as usize;
+            // A segment shorter than 255 bytes ends the packet
+            if size < 255 {
+                packets.push(std::mem::take(&mut packet));
+                if packets.len() == count {
+                    break;
+                }
+            }
+        }
+        pos = body;
+    }
+    packets
+}
+
+fn read_u32(data: &[u8], pos: usize) -> Option<u32> {
+    Some(u32::from_le_bytes(data.get(pos..pos + 4)?.try_into().ok()?))
+}
+
+/// Read the loop tags of an Ogg Vorbis file.
+pub fn read_loop_info(data: &[u8]) -> Option<LoopInfo> {
+    let packets = ogg_packets(data, 2);
+    let identification = packets.first().filter(|p| p.starts_with(b"\x01vorbis"))?;
+    let comments = packets.get(1).filter(|p| p.starts_with(b"\x03vorbis"))?;
+    let sample_rate = read_u32(identification, 12)?;
+
+    let vendor_length = read_u32(comments, 7)? as usize;
+    let mut pos = 11 + vendor_length;
+    let count = read_u32(comments, pos)?;
+    pos += 4;
+
+    let (mut loop_start, mut loop_length) = (None, None);
+    for _ in 0..count {
+        let length = read_u32(comments, pos)? as usize;
+        let comment = String::from_utf8_lossy(comments.get(pos + 4..pos + 4 + length)?);
+        pos += 4 + length;
+        if let Some((name, value)) = comment.split_once('=') {
+            match name.to_ascii_uppercase().as_str() {
+                "LOOPSTART" => loop_start = value.trim().parse().ok(),
+                "LOOPLENGTH" => loop_length = value.trim().parse().ok(),
+                _ => {}
+            }
+        }
+    }
+
+    Some(LoopInfo {
+        loop_start: loop_start?,
+        loop_length: loop_length.filter(|length| *length > 0)?,
+        sample_rate,
+    })
+}
+
+/// Read the loop tags of an `.ogg`, or `.rpgmvo`/`.ogg_` file in the game folder.
+fn file_loop_info(path: &Path, key: Option<&[u8; 16]>) -> Option<LoopInfo> {
+    let mut data = Vec::new();
+    fs::File::open(path)
+        .ok()?
+        .take(MAX_HEADER_BYTES)
+        .read_to_end(&mut data)
+        .ok()?;
+    if encryption::is_encrypted_path(path) {
+        data = encryption::decrypt(&data, key?).ok()?;
+    }
+    read_loop_info(&data)
+}
+
+fn is_ogg(path: &Path) -> bool {
+    let extension = path
+        .extension()
+        .and_then(|ext| ext.to_str())
+        .unwrap_or_default()
+        .to_lowercase();
+    matches!(extension.as_str(), "ogg" | "rpgmvo" | "ogg_")
+}
+
+/// Loop points of a single file, by its path in the game folder (e.g. `audio/bgm/Theme1.ogg`).
+#[tauri::command]
+pub fn get_audio_loop_info(
+    game: State<'_, GameInfo>,
+    encryption: State<'_, EncryptionInfo>,
+    path: String,
+) -> Result<Option<LoopInfo>, String> {
+    let full_path = game_files::resolve(&game.dir, &path)?;
+    if !is_ogg(&full_path) {
+        return Err(format!("Not an Ogg file: {}", path));
+    }
+    Ok(file_loop_info(&full_path, encryption.key_bytes().as_ref()))
+}
+
+/// Loop points of every looping track, keyed by path without extension (`audio/bgm/Theme1`).
+#[tauri::command]
+pub fn get_audio_loops(game: State<'_, GameInfo>, encryption: State<'_, EncryptionInfo>) -> BTreeMap<String, LoopInfo> {
+    let key = encryption.key_bytes();
+    let mut loops = BTreeMap::new();
+    for folder in LOOP_FOLDERS {
+        for entry in fs::read_dir(game.dir.join(folder)).into_iter().flatten().flatten() {
+            let path = entry.path();
+            if !is_ogg(&path) {
+                continue;
+            }
+            let (Some(stem), Some(info)) = (path.file_stem(), file_loop_info(&path, key.as_ref())) else {
+                continue;
+            };
+            loops.insert(format!("{}/{}", folder, stem.to_string_lossy()), info);
+        }
+    }
+    loops
+}
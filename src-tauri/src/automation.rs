@@ -0,0 +1,342 @@
+//! Automation endpoint, enabled with `automation` in `launcher.json`.
+//!
+//! External test harnesses and accessibility tools drive the game with JSON-RPC 2.0 requests
+//! POSTed to `http://127.0.0.1:<port>/rpc`, carrying the token in an `Authorization: Bearer`
+//! header. The token is generated at startup, or taken from `RPGMAKER_AUTOMATION_TOKEN`, and
+//! written with the port to `<app data>/automation/<game id>.json` for harnesses to find.
+//!
+//! Methods: `ping`, `evaluate` (`{code}`, awaited if it returns a promise), `queryState`,
+//! `loadSave` (`{slot}`), `listSaves` and `screenshot` (a Base64 PNG of the current frame).
+//! Everything that touches the game runs in the page through the injected `automation.js`,
+//! which answers with `automation_result`. Requests are answered concurrently, so a slow
+//! evaluation doesn't hold up the others.
+
+use std::collections::HashMap;
+use std::fs;
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::mpsc::{self, Sender};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use axum::body::Body;
+use axum::http::{header, Method, Request, StatusCode};
+use axum::response::{IntoResponse, Response};
+use axum::Router;
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use tauri::{AppHandle, Manager, Runtime, State};
+
+use crate::config::AutomationConfig;
+use crate::display::main_window;
+use crate::remote;
+use crate::saves;
+
+/// Environment variable with a fixed token, for CI where the harness starts the launcher.
+const TOKEN_VARIABLE: &str = "RPGMAKER_AUTOMATION_TOKEN";
+/// How long the page gets to answer.
+const PAGE_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// JSON-RPC error codes.
+const PARSE_ERROR: i64 = -32700;
+const INVALID_REQUEST: i64 = -32600;
+const METHOD_NOT_FOUND: i64 = -32601;
+const INVALID_PARAMS: i64 = -32602;
+const SERVER_ERROR: i64 = -32000;
+
+/// Summary of the running game, evaluated in the page.
+const QUERY_STATE: &str = r#"(function () {
+  var scene = SceneManager._scene;
+  var state = { scene: scene ? scene.constructor.name : null };
+  if (typeof $gameMap !== "undefined" && $gameMap && $gamePlayer) {
+    state.mapId = $gameMap.mapId();
+    state.player = { x: $gamePlayer.x, y: $gamePlayer.y, direction: $gamePlayer.direction() };
+  }
+  if (typeof $gameParty !== "undefined" && $gameParty) {
+    state.gold = $gameParty.gold();
+    state.party = $gameParty.members().map(function (actor) {
+      return { id: actor.actorId(), name: actor.name(), level: actor.level, hp: actor.hp, mp: actor.mp };
+    });
+  }
+  if (typeof $gameSystem !== "undefined" && $gameSystem) {
+    state.playtimeSecs = $gameSystem.playtime();
+  }
+  state.messageShowing = typeof $gameMessage !== "undefined" && !!$gameMessage && $gameMessage.isBusy();
+  return state;
+})()"#;
+
+/// Load a slot and go to the map, like the load screen does. MZ's `loadGame` returns a promise.
+const LOAD_SAVE: &str = r#"(function (slot) {
+  function loaded(ok) {
+    if (!ok) {
+      throw new Error("Could not load save slot " + slot);
+    }
+    $gameSystem.onAfterLoad();
+    SceneManager.goto(Scene_Map);
+    return true;
+  }
+  var result = DataManager.loadGame(slot);
+  return result && typeof result.then === "function"
+    ? result.then(function () { return loaded(true); })
+    : loaded(result);
+})(__SLOT__)"#;
+
+/// PNG of the current frame as a data URL.
+const SCREENSHOT: &str = r#"(function () {
+  var launcher = window.__RPGMAKER_LAUNCHER__;
+  var canvas = launcher.frameCanvas ? launcher.frameCanvas() : document.querySelector("canvas");
+  if (!canvas) {
+    throw new Error("The game has no canvas yet");
+  }
+  return canvas.toDataURL("image/png");
+})()"#;
+
+#[derive(Deserialize)]
+struct RpcRequest {
+    jsonrpc: Option<String>,
+    #[serde(default)]
+    id: Value,
+    method: String,
+    #[serde(default)]
+    params: Value,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct EndpointFile {
+    port: u16,
+    token: String,
+}
+
+struct RpcError {
+    code: i64,
+    message: String,
+}
+
+impl RpcError {
+    fn new(code: i64, message: impl Into<String>) -> Self {
+        Self {
+            code,
+            message: message.into(),
+        }
+    }
+}
+
+/// What the endpoint's requests are handled with.
+struct Endpoint<R: Runtime> {
+    app: AppHandle<R>,
+    token: String,
+}
+
+/// Evaluations waiting for the page, managed as app state.
+pub struct Automation {
+    next_id: AtomicU64,
+    pending: Mutex<HashMap<u64, Sender<Result<Value, String>>>>,
+    endpoint_file: PathBuf,
+}
+
+impl Automation {
+    /// Start listening. Returns `None` if the port is taken.
+    pub fn start<R: Runtime>(app: &AppHandle<R>, config: &AutomationConfig, endpoint_file: PathBuf) -> Option<Self> {
+        let listener = match remote::bind(("127.0.0.1", config.port)) {
+            Ok(listener) => listener,
+            Err(e) => {
+                log::warn!("Failed to start the automation endpoint on port {}: {}", config.port, e);
+                return None;
+            }
+        };
+
+        let token = std::env::var(TOKEN_VARIABLE)
+            .ok()
+            .filter(|token| !token.is_empty())
+            .unwrap_or_else(remote::generate_token);
+        let endpoint = EndpointFile {
+            port: config.port,
+            token: token.clone(),
+        };
+        let written = endpoint_file.parent().map_or(Ok(()), fs::create_dir_all).and_then(|_| {
+            fs::write(
+                &endpoint_file,
+                serde_json::to_string_pretty(&endpoint).unwrap_or_default(),
+            )
+        });
+        if let Err(e) = written {
+            log::warn!(
+                "Failed to write the automation endpoint file {:?}: {}",
+                endpoint_file,
+                e
+            );
+        }
+        log::info!("Automation endpoint listening on 127.0.0.1:{}", config.port);
+
+        let endpoint = Arc::new(Endpoint {
+            app: app.clone(),
+            token,
+        });
+        remote::serve(
+            listener,
+            Router::new().fallback(handle::<R>).with_state(endpoint),
+            "automation endpoint",
+        );
+
+        Some(Self {
+            next_id: AtomicU64::new(0),
+            pending: Mutex::new(HashMap::new()),
+            endpoint_file,
+        })
+    }
+
+    /// Evaluate `code` in the game page and wait for its value.
+    fn evaluate<R: Runtime>(&self, app: &AppHandle<R>, code: &str) -> Result<Value, String> {
+        let id = self.next_id.fetch_add(1, Ordering::Relaxed);
+        let (sender, receiver) = mpsc::channel();
+        self.pending.lock().unwrap().insert(id, sender);
+
+        let code = serde_json::to_string(code).map_err(|e| e.to_string())?;
+        let script = format!(
+            "window.__RPGMAKER_LAUNCHER__ && window.__RPGMAKER_LAUNCHER__.automationEval({}, {})",
+            id, code
+        );
+        let result = main_window(app)
+            .and_then(|window| window.eval(&script).map_err(|e| e.to_string()))
+            .and_then(|_| {
+                receiver
+                    .recv_timeout(PAGE_TIMEOUT)
+                    .map_err(|_| "The game page did not answer".to_string())?
+            });
+        self.pending.lock().unwrap().remove(&id);
+        result
+    }
+
+    /// Remove the endpoint file, so harnesses don't connect to a launcher that is gone.
+    pub fn stop(&self) {
+        let _ = fs::remove_file(&self.endpoint_file);
+    }
+}
+
+fn bearer_token(request: &Request<Body>) -> Option<&str> {
+    request
+        .headers()
+        .get(header::AUTHORIZATION)?
+        .to_str()
+        .ok()?
+        .strip_prefix("Bearer ")
+}
+
+async fn handle<R: Runtime>(
+    axum::extract::State(endpoint): axum::extract::State<Arc<Endpoint<R>>>,
+    request: Request<Body>,
+) -> Response {
+    let authorized = remote::token_matches(bearer_token(&request), &endpoint.token);
+    let (status, body) = if !authorized {
+        (
+            StatusCode::UNAUTHORIZED,
+            json!({ "error": "Missing or wrong automation token" }),
+        )
+    } else if request.method() != Method::POST || request.uri().path() != "/rpc" {
+        (
+            StatusCode::NOT_FOUND,
+            json!({ "error": "POST JSON-RPC requests to /rpc" }),
+        )
+    } else {
+        let body = axum::body::to_bytes(request.into_body(), remote::MAX_BODY_SIZE)
+            .await
+            .unwrap_or_default();
+        let body = String::from_utf8_lossy(&body).into_owned();
+        // Evaluations wait for the page, so they run off the runtime's workers
+        let app = endpoint.app.clone();
+        let response = tokio::task::spawn_blocking(move || respond(&app, &body))
+            .await
+            .unwrap_or_else(|e| {
+                error_response(Value::Null, RpcError::new(SERVER_ERROR, e.to_string()))
+            });
+        (StatusCode::OK, response)
+    };
+
+    (
+        status,
+        [(header::CONTENT_TYPE, "application/json")],
+        body.to_string(),
+    )
+        .into_response()
+}
+
+fn respond<R: Runtime>(app: &AppHandle<R>, body: &str) -> Value {
+    let request: RpcRequest = match serde_json::from_str(body) {
+        Ok(request) => request,
+        Err(e) => return error_response(Value::Null, RpcError::new(PARSE_ERROR, e.to_string())),
+    };
+    if request.jsonrpc.as_deref() != Some("2.0") {
+        return error_response(
+            request.id,
+            RpcError::new(INVALID_REQUEST, "Expected \"jsonrpc\": \"2.0\""),
+        );
+    }
+    match call(app, &request.method, &request.params) {
+        Ok(result) => json!({ "jsonrpc": "2.0", "id": request.id, "result": result }),
+        Err(error) => error_response(request.id, error),
+    }
+}
+
+fn error_response(id: Value, error: RpcError) -> Value {
+    json!({
+        "jsonrpc": "2.0",
+        "id": id,
+        "error": { "code": error.code, "message": error.message },
+    })
+}
+
+fn call<R: Runtime>(app: &AppHandle<R>, method: &str, params: &Value) -> Result<Value, RpcError> {
+    let automation = app
+        .try_state::<Automation>()
+        .ok_or_else(|| RpcError::new(SERVER_ERROR, "The launcher is still starting"))?;
+    let evaluate = |code: &str| {
+        automation
+            .evaluate(app, code)
+            .map_err(|e| RpcError::new(SERVER_ERROR, e))
+    };
+
+    match method {
+        "ping" => Ok(json!({ "version": app.package_info().version.to_string() })),
+        "evaluate" => {
+            let code = params["code"]
+                .as_str()
+                .ok_or_else(|| RpcError::new(INVALID_PARAMS, "Expected {\"code\": string}"))?;
+            evaluate(code)
+        }
+        "queryState" => evaluate(QUERY_STATE),
+        "loadSave" => {
+            let slot = params["slot"]
+                .as_u64()
+                .ok_or_else(|| RpcError::new(INVALID_PARAMS, "Expected {\"slot\": number}"))?;
+            evaluate(&LOAD_SAVE.replace("__SLOT__", &slot.to_string()))
+        }
+        "listSaves" => saves::list_saves(app.clone())
+            .map(|saves| json!(saves))
+            .map_err(|e| RpcError::new(SERVER_ERROR, e)),
+        "screenshot" => {
+            let url = evaluate(SCREENSHOT)?;
+            let png = url
+                .as_str()
+                .and_then(|url| url.strip_prefix("data:image/png;base64,"))
+                .ok_or_else(|| RpcError::new(SERVER_ERROR, "The page did not return a PNG"))?;
+            Ok(json!({ "png": png }))
+        }
+        _ => Err(RpcError::new(METHOD_NOT_FOUND, format!("Unknown method: {}", method))),
+    }
+}
+
+/// Called by the game page with the outcome of an evaluation.
+#[tauri::command]
+pub fn automation_result(automation: State<'_, Automation>, id: u64, ok: bool, output: Value) {
+    if let Some(sender) = automation.pending.lock().unwrap().remove(&id) {
+        let _ = sender.send(if ok {
+            Ok(output)
+        } else {
+            Err(output
+                .as_str()
+                .map(str::to_string)
+                .unwrap_or_else(|| output.to_string()))
+        });
+    }
+}
@@ -0,0 +1,62 @@
+//! Boss key: a global hotkey that instantly hides and mutes the game, and brings it back
+//! when pressed again. Bound with `hotkeys.bossKey` in `launcher.json`.
+
+use std::sync::atomic::{AtomicBool, Ordering};
+
+use tauri::{AppHandle, Emitter, Manager, Runtime};
+use tauri_plugin_global_shortcut::{GlobalShortcutExt, Shortcut, ShortcutState};
+
+use crate::display;
+
+#[derive(Default)]
+pub struct BossKey {
+    active: AtomicBool,
+}
+
+pub fn plugin<R: Runtime>() -> tauri::plugin::TauriPlugin<R> {
+    tauri_plugin_global_shortcut::Builder::new()
+        .with_handler(|app, _shortcut, event| {
+            if event.state == ShortcutState::Pressed {
+                toggle(app);
+            }
+        })
+        .build()
+}
+
+/// Register the configured combinations. Ones that can't be parsed or are taken by another
+/// application are skipped with a warning.
+pub fn register<R: Runtime>(app: &AppHandle<R>, combos: &[String]) {
+    app.manage(BossKey::default());
+    for combo in combos {
+        match combo.parse::<Shortcut>() {
+            Ok(shortcut) => {
+                if let Err(e) = app.global_shortcut().register(shortcut) {
+                    log::warn!("Failed to register boss key {:?}: {}", combo, e);
+                }
+            }
+            Err(e) => log::warn!("Invalid boss key {:?}: {}", combo, e),
+        }
+    }
+}
+
+fn toggle<R: Runtime>(app: &AppHandle<R>) {
+    let Ok(window) = display::main_window(app) else {
+        return;
+    };
+    let active = !app.state::<BossKey>().active.fetch_xor(true, Ordering::Relaxed);
+
+    let result = if active {
+        window.hide()
+    } else {
+        window
+            .show()
+            .and_then(|_| window.unminimize())
+            .and_then(|_| window.set_focus())
+    };
+    if let Err(e) = result {
+        log::warn!("Boss key failed to toggle the window: {}", e);
+    }
+
+    // The page mutes itself while the boss key is active
+    let _ = app.emit("boss-key", active);
+}
@@ -0,0 +1,172 @@
+//! Importing saves from a game's web version.
+//!
+//! Players who started on a browser build (itch.io and the like) have their progress in the
+//! browser's storage for that site. They export it from the browser console on the game's
+//! page, with `copy(JSON.stringify(localStorage))` for MV, or for MZ, which keeps its saves in
+//! IndexedDB through localforage:
+//!
+//! ```js
+//! var out = {}; localforage.iterate(function (v, k) { out[k] = v; }).then(function () { copy(JSON.stringify(out)); });
+//! ```
+//!
+//! and paste it into a `.json` file. `import_browser_saves` maps the storage keys (`RPG File1`,
+//! `<game id>.file1`, ...) to the files NW.js would have written and checks that each one decodes.
+//! The global info file is merged, so imported slots show up next to the existing ones.
+
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use serde::Serialize;
+use serde_json::Value;
+use tauri::{AppHandle, Runtime};
+use tauri_plugin_dialog::DialogExt;
+
+use crate::save_data::{self, SaveFormat};
+use crate::saves;
+
+#[derive(Debug, Clone, Default, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ImportSummary {
+    /// Save files written.
+    pub imported: Vec<String>,
+    /// Save files left alone because they already exist.
+    pub skipped: Vec<String>,
+    /// Storage keys that looked like saves but didn't decode.
+    pub invalid: Vec<String>,
+}
+
+/// `global`, `config` or `file<n>`, the names NW.js gives the save files.
+fn is_save_name(name: &str) -> bool {
+    name == "global"
+        || name == "config"
+        || name
+            .strip_prefix("file")
+            .is_some_and(|id| !id.is_empty() && id.chars().all(|c| c.is_ascii_digit()))
+}
+
+/// Save file for a web storage key, e.g. `RPG File3` → `file3.rpgsave`, `1234.global` →
+/// `global.rmmzsave`.
+fn file_name(key: &str) -> Option<String> {
+    if let Some(name) = key.strip_prefix("RPG ") {
+        let name = name.to_lowercase();
+        return is_save_name(&name).then(|| format!("{}.{}", name, SaveFormat::Mv.extension()));
+    }
+    // MZ prefixes the save name with the game ID, which isn't needed here
+    let name = key.rsplit('.').next()?;
+    is_save_name(name).then(|| format!("{}.{}", name, SaveFormat::Mz.extension()))
+}
+
+/// Storage entries from an export: `{"key": "value"}`, or `[{"key": ..., "value": ...}]`.
+fn entries(export: &Value) -> Vec<(String, &Value)> {
+    match export {
+        Value::Object(map) => map.iter().map(|(key, value)| (key.clone(), value)).collect(),
+        Value::Array(items) => items
+            .iter()
+            .filter_map(|item| Some((item["key"].as_str()?.to_string(), &item["value"])))
+            .collect(),
+        _ => Vec::new(),
+    }
+}
+
+/// Add the slots of `imported` that aren't in `existing` to it.
+fn merge_global(existing: &mut Value, imported: &Value, slots: &[usize]) {
+    let Some(existing) = existing.as_array_mut() else {
+        return;
+    };
+    for &id in slots {
+        let Some(info) = imported.get(id).filter(|info| info.is_object()) else {
+            continue;
+        };
+        if existing.len() <= id {
+            existing.resize(id + 1, Value::Null);
+        }
+        existing[id] = info.clone();
+    }
+}
+
+fn slot_id(name: &str) -> Option<usize> {
+    name.strip_prefix("file")?.split('.').next()?.parse().ok()
+}
+
+fn import<R: Runtime>(app: &AppHandle<R>, path: &Path, overwrite: bool) -> Result<ImportSummary, String> {
+    let content = fs::read_to_string(path).map_err(|e| e.to_string())?;
+    let export: Value = serde_json::from_str(&content).map_err(|e| format!("Not a storage export: {}", e))?;
+
+    let mut summary = ImportSummary::default();
+    let mut globals = Vec::new();
+    for (key, value) in entries(&export) {
+        let Some(name) = file_name(&key) else {
+            continue;
+        };
+        let format = SaveFormat::from_name(&name).ok_or_else(|| format!("Not a save file: {}", name))?;
+        let Some(data) = value.as_str().filter(|data| save_data::decode(format, data).is_ok()) else {
+            summary.invalid.push(key);
+            continue;
+        };
+        if name.starts_with("global.") {
+            globals.push((name, format, data.to_string()));
+            continue;
+        }
+        if saves::save_path(app, &name)?.is_file() && !overwrite {
+            summary.skipped.push(name);
+            continue;
+        }
+        saves::replace_save(app, name.clone(), data.to_string())?;
+        summary.imported.push(name);
+    }
+
+    // The slot list must cover the imported files, without losing the slots already here
+    for (name, format, data) in globals {
+        let path = saves::save_path(app, &name)?;
+        let data = if path.is_file() && !overwrite {
+            let slots: Vec<usize> = summary
+                .imported
+                .iter()
+                .filter(|file| file.ends_with(format.extension()))
+                .filter_map(|file| slot_id(file))
+                .collect();
+            let mut existing = save_data::read(&path)?;
+            merge_global(&mut existing, &save_data::decode(format, &data)?, &slots);
+            save_data::encode(format, &existing)?
+        } else {
+            data
+        };
+        saves::replace_save(app, name.clone(), data)?;
+        summary.imported.push(name);
+    }
+
+    log::info!(
+        "Imported {} browser save(s) from {:?}, skipped {}, {} invalid",
+        summary.imported.len(),
+        path,
+        summary.skipped.len(),
+        summary.invalid.len()
+    );
+    Ok(summary)
+}
+
+/// Import saves from a browser storage export at `path`, or one picked by the player. Returns
+/// `None` if they cancelled.
+#[tauri::command]
+pub async fn import_browser_saves<R: Runtime>(
+    app: AppHandle<R>,
+    path: Option<PathBuf>,
+    overwrite: bool,
+) -> Result<Option<ImportSummary>, String> {
+    let path = match path {
+        Some(path) => path,
+        None => {
+            let Some(file) = app
+                .dialog()
+                .file()
+                .set_title("Import saves from a browser export")
+                .add_filter("Storage export", &["json", "txt"])
+                .blocking_pick_file()
+            else {
+                return Ok(None);
+            };
+            file.into_path().map_err(|e| e.to_string())?
+        }
+    };
+    import(&app, &path, overwrite).map(Some)
+}
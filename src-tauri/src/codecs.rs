@@ -0,0 +1,200 @@
+//! Media codec checks, from inside the webview.
+//!
+//! The preflight checks can only guess from the system which formats the webview decodes.
+//! The injected probe asks the page itself (`canPlayType`) and reports with `report_codecs`.
+//! When MV games can't play Ogg Vorbis, the engine asks for `.m4a` files instead; if the game
+//! doesn't ship them and `ffmpeg` is installed, they are transcoded in the background into a
+//! cache folder mounted on the localhost server. Otherwise, and for WebM movies, a dialog
+//! explains which system codecs to install.
+
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process::{Command, Stdio};
+
+use serde::Deserialize;
+use tauri::{AppHandle, Manager, Runtime};
+use tauri_plugin_dialog::{DialogExt, MessageDialogKind};
+
+use crate::config::LauncherConfig;
+use crate::encryption::{self, EncryptionInfo};
+use crate::game::GameInfo;
+use external_localhost_plugin::Mounts;
+
+/// Folders transcoded first, so the title music is ready soonest.
+const AUDIO_FOLDERS: [&str; 4] = ["audio/bgm", "audio/bgs", "audio/me", "audio/se"];
+
+/// What the page reported it can decode.
+#[derive(Debug, Clone, Copy, Deserialize)]
+pub struct CodecSupport {
+    pub ogg: bool,
+    pub webm: bool,
+    pub aac: bool,
+}
+
+/// Ogg files, plain or encrypted, that have no `.m4a` counterpart.
+fn ogg_without_m4a(game_dir: &Path) -> Vec<PathBuf> {
+    AUDIO_FOLDERS
+        .iter()
+        .flat_map(|folder| encryption::files_in(&game_dir.join(folder)))
+        .filter(|path| {
+            let extension = path.extension().and_then(|ext| ext.to_str()).unwrap_or_default();
+            let counterpart = match extension.to_lowercase().as_str() {
+                "ogg" => "m4a",
+                "rpgmvo" => "rpgmvm",
+                _ => return false,
+            };
+            !path.with_extension(counterpart).is_file()
+        })
+        .collect()
+}
+
+fn has_movies_without_mp4(game_dir: &Path) -> bool {
+    encryption::files_in(&game_dir.join("movies")).iter().any(|path| {
+        path.extension().is_some_and(|ext| ext.eq_ignore_ascii_case("webm")) && !path.with_extension("mp4").is_file()
+    })
+}
+
+fn ffmpeg_available() -> bool {
+    Command::new("ffmpeg")
+        .arg("-version")
+        .stdout(Stdio::null())
+        .stderr(Stdio::null())
+        .status()
+        .is_ok_and(|status| status.success())
+}
+
+/// Transcode one Ogg file to AAC in `target`, going through a temporary file since ffmpeg
+/// can't write MP4 to a pipe.
+fn transcode(source: &Path, target: &Path, key: Option<&[u8; 16]>) -> Result<(), String> {
+    let mut data = fs::read(source).map_err(|e| format!("{:?}: {}", source, e))?;
+    if let Some(key) = key {
+        data = encryption::decrypt(&data, key)?;
+    }
+    if let Some(parent) = target.parent() {
+        fs::create_dir_all(parent).map_err(|e| e.to_string())?;
+    }
+    let input = target.with_extension("src.ogg");
+    let output = target.with_extension("part.m4a");
+    fs::write(&input, &data).map_err(|e| e.to_string())?;
+    let status = Command::new("ffmpeg")
+        .args(["-y", "-loglevel", "error", "-i"])
+        .arg(&input)
+        .args(["-vn", "-c:a", "aac", "-b:a", "160k", "-map_metadata", "0"])
+        .arg(&output)
+        .stdin(Stdio::null())
+        .status();
+    let _ = fs::remove_file(&input);
+    match status {
+        Ok(status) if status.success() => {}
+        Ok(status) => return Err(format!("ffmpeg exited with {}", status)),
+        Err(e) => return Err(e.to_string()),
+    }
+
+    let result = match key {
+        Some(key) => fs::read(&output)
+            .map_err(|e| e.to_string())
+            .and_then(|m4a| fs::write(target, encryption::encrypt(&m4a, key)).map_err(|e| e.to_string())),
+        None => fs::rename(&output, target).map_err(|e| e.to_string()),
+    };
+    let _ = fs::remove_file(&output);
+    result
+}
+
+/// Transcode the missing `.m4a` files into `cache_dir` on a background thread. Files done
+/// in an earlier session are kept, and the folder is mounted right away so each file is
+/// served as soon as it is ready.
+fn start_transcoding<R: Runtime>(app: &AppHandle<R>, sources: Vec<PathBuf>, cache_dir: PathBuf) {
+    let game_dir = app.state::<GameInfo>().dir.clone();
+    let key = app.state::<EncryptionInfo>().key_bytes();
+    app.state::<Mounts>().mount(&cache_dir);
+
+    std::thread::spawn(move || {
+        let mut done = 0;
+        for source in &sources {
+            let Ok(relative) = source.strip_prefix(&game_dir) else {
+                continue;
+            };
+            let encrypted = source.extension().is_some_and(|ext| ext.eq_ignore_ascii_case("rpgmvo"));
+            let target = cache_dir
+                .join(relative)
+                .with_extension(if encrypted { "rpgmvm" } else { "m4a" });
+            if target.is_file() {
+                continue;
+            }
+            match transcode(source, &target, if encrypted { key.as_ref() } else { None }) {
+                Ok(()) => done += 1,
+                Err(e) => log::warn!("Failed to transcode {:?}: {}", relative, e),
+            }
+        }
+        log::info!("Transcoded {} of {} Ogg files to AAC", done, sources.len());
+    });
+}
+
+fn codec_guidance() -> &'static str {
+    if cfg!(target_os = "linux") {
+        "Install the GStreamer base, good and libav plugin sets \
+         (Debian/Ubuntu: gstreamer1.0-plugins-base gstreamer1.0-plugins-good gstreamer1.0-libav, \
+         Fedora: gstreamer1-plugins-base gstreamer1-plugins-good gstreamer1-libav, \
+         Arch: gst-plugins-base gst-plugins-good gst-libav), then restart the game."
+    } else if cfg!(target_os = "macos") {
+        "Update macOS: Ogg and WebM playback comes with recent versions of Safari's engine."
+    } else {
+        "Install the Media Feature Pack for your edition of Windows (Settings > Apps > \
+         Optional features), and update the Microsoft Edge WebView2 Runtime."
+    }
+}
+
+/// Act on the formats the game page can decode: transcode audio if possible, else explain
+/// what to install. Returns whether anything is missing.
+#[tauri::command]
+pub async fn report_codecs<R: Runtime>(app: AppHandle<R>, support: CodecSupport) -> Result<bool, String> {
+    log::info!(
+        "Webview codecs: ogg {}, webm {}, aac {}",
+        support.ogg,
+        support.webm,
+        support.aac
+    );
+    let game = app.state::<GameInfo>();
+    // MZ decodes Ogg itself with its WebAssembly decoder, so only MV needs the fallback
+    let mz = game.dir.join("js").join("rmmz_core.js").is_file();
+
+    let mut problems = Vec::new();
+    if !support.ogg && !mz {
+        let missing = ogg_without_m4a(&game.dir);
+        if missing.is_empty() {
+            log::info!("Ogg Vorbis is not supported; the game's .m4a files will be used");
+        } else if !support.aac {
+            problems.push("Neither Ogg Vorbis nor AAC audio can be played, so the game will have no sound.");
+        } else if app.state::<LauncherConfig>().transcode_audio && ffmpeg_available() {
+            let cache_dir = app
+                .path()
+                .app_cache_dir()
+                .map_err(|e| e.to_string())?
+                .join("transcoded")
+                .join(&game.id);
+            log::info!(
+                "Ogg Vorbis is not supported; transcoding {} files with ffmpeg",
+                missing.len()
+            );
+            start_transcoding(&app, missing, cache_dir);
+        } else {
+            problems.push("Ogg Vorbis audio can't be played, and the game has no AAC (.m4a) copies of its audio, so some sounds will be missing.");
+        }
+    }
+    if !support.webm && has_movies_without_mp4(&game.dir) {
+        problems.push("WebM video can't be played, so the game's movies will be skipped.");
+    }
+    if problems.is_empty() {
+        return Ok(false);
+    }
+
+    for problem in &problems {
+        log::warn!("{}", problem);
+    }
+    app.dialog()
+        .message(format!("{}\n\n{}", problems.join("\n\n"), codec_guidance()))
+        .title("Missing media codecs")
+        .kind(MessageDialogKind::Warning)
+        .show(|_| {});
+    Ok(true)
+}
@@ -0,0 +1,595 @@
+//! Community achievement sets, for games that never had achievements.
+//!
+//! A set is a JSON file dropped in `<app data>/community/<game id>/sets/`. Each achievement has
+//! a condition over the game's switches and variables, e.g. `s[12] && v[5] >= 3`. The injected
+//! `community.js` polls the switches and variables the conditions use and reports them with
+//! `report_game_state`; conditions are evaluated here and unlocks are kept in `unlocked.json`
+//! next to the sets. Unlocking emits `achievement-unlocked` like launcher achievements do, but
+//! community achievements are never reported to Steam or GOG Galaxy.
+//!
+//! Conditions support numbers, `true`/`false`, `s[id]` (switch), `v[id]` (variable), `+ - * /`,
+//! comparisons, `!`, `&&`, `||` and parentheses. A switch counts as `1` when on. Conditions are
+//! limited to 1000 tokens and 64 levels of nesting, so a broken set can't exhaust the stack.
+
+use std::collections::{BTreeSet, HashMap};
+use std::fs;
+use std::path::PathBuf;
+use std::sync::Mutex;
+
+use serde::{Deserialize, Serialize};
+use tauri::{AppHandle, Emitter, Manager, Runtime, State};
+
+use external_localhost_plugin::InjectedScripts;
+use crate::injection;
+
+const SETS_DIR_NAME: &str = "sets";
+const UNLOCKED_FILE_NAME: &str = "unlocked.json";
+const MAX_TOKENS: usize = 1000;
+/// Operands nested in parentheses, `!` or `-`.
+const MAX_DEPTH: usize = 64;
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct SetFile {
+    name: String,
+    #[serde(default)]
+    author: String,
+    achievements: Vec<Definition>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct Definition {
+    id: String,
+    name: String,
+    #[serde(default)]
+    description: String,
+    #[serde(default)]
+    hidden: bool,
+    condition: String,
+}
+
+struct CommunityAchievement {
+    /// `<set file name>/<achievement id>`, so sets can't collide.
+    key: String,
+    set: String,
+    author: String,
+    definition: Definition,
+    condition: Expr,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CommunityAchievementInfo {
+    pub id: String,
+    pub set: String,
+    pub author: String,
+    pub name: String,
+    pub description: String,
+    pub hidden: bool,
+    /// Unlock time in milliseconds since the Unix epoch, if unlocked.
+    pub unlocked_at: Option<i64>,
+}
+
+/// Same shape as a launcher achievement, for the toast.
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct Unlocked {
+    id: String,
+    name: String,
+    description: String,
+    icon: Option<String>,
+}
+
+/// The switches and variables the conditions read.
+#[derive(Debug, Clone, Default, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct WatchList {
+    pub switches: BTreeSet<u32>,
+    pub variables: BTreeSet<u32>,
+}
+
+/// Values reported by the page, keyed by switch or variable ID.
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(default)]
+pub struct GameState {
+    switches: HashMap<u32, bool>,
+    variables: HashMap<u32, f64>,
+}
+
+pub struct Community {
+    achievements: Vec<CommunityAchievement>,
+    unlocked_path: PathBuf,
+    unlocked: Mutex<HashMap<String, i64>>,
+}
+
+impl Community {
+    /// Load the sets in `dir`. Achievements with invalid conditions are skipped with a warning.
+    pub fn load(dir: PathBuf) -> Self {
+        let mut files: Vec<PathBuf> = fs::read_dir(dir.join(SETS_DIR_NAME))
+            .into_iter()
+            .flatten()
+            .flatten()
+            .map(|entry| entry.path())
+            .filter(|path| path.extension().is_some_and(|ext| ext.eq_ignore_ascii_case("json")))
+            .collect();
+        files.sort();
+
+        let mut achievements = Vec::new();
+        for path in files {
+            let set = match fs::read_to_string(&path)
+                .map_err(|e| e.to_string())
+                .and_then(|content| serde_json::from_str::<SetFile>(&content).map_err(|e| e.to_string()))
+            {
+                Ok(set) => set,
+                Err(e) => {
+                    log::warn!("Invalid achievement set {:?}: {}", path, e);
+                    continue;
+                }
+            };
+            let file_name = path.file_stem().unwrap_or_default().to_string_lossy().to_string();
+            for definition in set.achievements {
+                match parse(&definition.condition) {
+                    Ok(condition) => achievements.push(CommunityAchievement {
+                        key: format!("{}/{}", file_name, definition.id),
+                        set: set.name.clone(),
+                        author: set.author.clone(),
+                        definition,
+                        condition,
+                    }),
+                    Err(e) => log::warn!(
+                        "Skipping achievement {:?} of {:?}: invalid condition: {}",
+                        definition.id,
+                        path,
+                        e
+                    ),
+                }
+            }
+        }
+        if !achievements.is_empty() {
+            log::info!("Loaded {} community achievements", achievements.len());
+        }
+
+        let unlocked_path = dir.join(UNLOCKED_FILE_NAME);
+        let unlocked = fs::read_to_string(&unlocked_path)
+            .ok()
+            .and_then(|content| serde_json::from_str(&content).ok())
+            .unwrap_or_default();
+
+        Self {
+            achievements,
+            unlocked_path,
+            unlocked: Mutex::new(unlocked),
+        }
+    }
+
+    /// Start polling the game, if any set is installed.
+    pub fn apply<R: Runtime>(&self, app: &AppHandle<R>) {
+        if !self.achievements.is_empty() {
+            app.state::<InjectedScripts>().push(injection::community_script());
+        }
+    }
+
+    pub fn watch_list(&self) -> WatchList {
+        let mut watch = WatchList::default();
+        for achievement in &self.achievements {
+            achievement.condition.collect(&mut watch);
+        }
+        watch
+    }
+
+    pub fn list(&self) -> Vec<CommunityAchievementInfo> {
+        let unlocked = self.unlocked.lock().unwrap();
+        self.achievements
+            .iter()
+            .map(|achievement| {
+                let unlocked_at = unlocked.get(&achievement.key).copied();
+                let concealed = achievement.definition.hidden && unlocked_at.is_none();
+                CommunityAchievementInfo {
+                    id: achievement.key.clone(),
+                    set: achievement.set.clone(),
+                    author: achievement.author.clone(),
+                    name: if concealed {
+                        "Hidden achievement".to_string()
+                    } else {
+                        achievement.definition.name.clone()
+                    },
+                    description: if concealed {
+                        String::new()
+                    } else {
+                        achievement.definition.description.clone()
+                    },
+                    hidden: achievement.definition.hidden,
+                    unlocked_at,
+                }
+            })
+            .collect()
+    }
+
+    /// Unlock every achievement whose condition holds in `state`, returning the new ones.
+    fn evaluate(&self, state: &GameState) -> Result<Vec<&CommunityAchievement>, String> {
+        let mut unlocked = self.unlocked.lock().unwrap();
+        let now = chrono::Utc::now().timestamp_millis();
+        let newly: Vec<&CommunityAchievement> = self
+            .achievements
+            .iter()
+            .filter(|a| !unlocked.contains_key(&a.key) && a.condition.eval(state) != 0.0)
+            .collect();
+        if newly.is_empty() {
+            return Ok(newly);
+        }
+        for achievement in &newly {
+            unlocked.insert(achievement.key.clone(), now);
+        }
+        if let Some(parent) = self.unlocked_path.parent() {
+            fs::create_dir_all(parent).map_err(|e| e.to_string())?;
+        }
+        let content = serde_json::to_string_pretty(&*unlocked).map_err(|e| e.to_string())?;
+        fs::write(&self.unlocked_path, content).map_err(|e| e.to_string())?;
+        Ok(newly)
+    }
+}
+
+#[derive(Debug, Clone)]
+enum Expr {
+    Number(f64),
+    Switch(u32),
+    Variable(u32),
+    Not(Box<Expr>),
+    Binary(Box<Expr>, Op, Box<Expr>),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Op {
+    Or,
+    And,
+    Eq,
+    Ne,
+    Lt,
+    Le,
+    Gt,
+    Ge,
+    Add,
+    Sub,
+    Mul,
+    Div,
+}
+
+impl Expr {
+    fn eval(&self, state: &GameState) -> f64 {
+        let truth = |b: bool| if b { 1.0 } else { 0.0 };
+        match self {
+            Expr::Number(n) => *n,
+            Expr::Switch(id) => truth(state.switches.get(id).copied().unwrap_or(false)),
+            Expr::Variable(id) => state.variables.get(id).copied().unwrap_or(0.0),
+            Expr::Not(e) => truth(e.eval(state) == 0.0),
+            Expr::Binary(a, op, b) => {
+                let a = a.eval(state);
+                // Short-circuit like the JavaScript it reads like
+                match op {
+                    Op::Or if a != 0.0 => return 1.0,
+                    Op::And if a == 0.0 => return 0.0,
+                    _ => {}
+                }
+                let b = b.eval(state);
+                match op {
+                    Op::Or | Op::And => truth(b != 0.0),
+                    Op::Eq => truth(a == b),
+                    Op::Ne => truth(a != b),
+                    Op::Lt => truth(a < b),
+                    Op::Le => truth(a <= b),
+                    Op::Gt => truth(a > b),
+                    Op::Ge => truth(a >= b),
+                    Op::Add => a + b,
+                    Op::Sub => a - b,
+                    Op::Mul => a * b,
+                    Op::Div if b == 0.0 => 0.0,
+                    Op::Div => a / b,
+                }
+            }
+        }
+    }
+
+    fn collect(&self, watch: &mut WatchList) {
+        match self {
+            Expr::Number(_) => {}
+            Expr::Switch(id) => {
+                watch.switches.insert(*id);
+            }
+            Expr::Variable(id) => {
+                watch.variables.insert(*id);
+            }
+            Expr::Not(e) => e.collect(watch),
+            Expr::Binary(a, _, b) => {
+                a.collect(watch);
+                b.collect(watch);
+            }
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Token {
+    Number(f64),
+    Ident(String),
+    Op(&'static str),
+}
+
+const OPERATORS: [&str; 17] = [
+    "||", "&&", "==", "!=", "<=", ">=", "<", ">", "+", "-", "*", "/", "!", "(", ")", "[", "]",
+];
+
+fn tokenize(source: &str) -> Result<Vec<Token>, String> {
+    let mut tokens = Vec::new();
+    let mut rest = source.trim_start();
+    while !rest.is_empty() {
+        let c = rest.chars().next().unwrap_or_default();
+        if c.is_ascii_digit() || c == '.' {
+            let end = rest
+                .find(|c: char| !c.is_ascii_digit() && c != '.')
+                .unwrap_or(rest.len());
+            let number = rest[..end]
+                .parse()
+                .map_err(|_| format!("bad number {:?}", &rest[..end]))?;
+            tokens.push(Token::Number(number));
+            rest = &rest[end..];
+        } else if c.is_ascii_alphabetic() {
+            let end = rest.find(|c: char| !c.is_ascii_alphanumeric()).unwrap_or(rest.len());
+            tokens.push(Token::Ident(rest[..end].to_string()));
+            rest = &rest[end..];
+        } else if let Some(op) = OPERATORS.iter().find(|op| rest.starts_with(**op)) {
+            tokens.push(Token::Op(op));
+            rest = &rest[op.len()..];
+        } else {
+            return Err(format!("unexpected {:?}", c));
+        }
+        if tokens.len() > MAX_TOKENS {
+            return Err(format!("longer than {} tokens", MAX_TOKENS));
+        }
+        rest = rest.trim_start();
+    }
+    Ok(tokens)
+}
+
+fn parse(source: &str) -> Result<Expr, String> {
+    let tokens = tokenize(source)?;
+    let mut parser = Parser {
+        tokens,
+        pos: 0,
+        depth: 0,
+    };
+    let expr = parser.binary(0)?;
+    match parser.tokens.get(parser.pos) {
+        None => Ok(expr),
+        Some(token) => Err(format!("unexpected {:?}", token)),
+    }
+}
+
+/// Binary operators from the loosest to the tightest binding.
+const PRECEDENCE: [&[(&str, Op)]; 5] = [
+    &[("||", Op::Or)],
+    &[("&&", Op::And)],
+    &[
+        ("==", Op::Eq),
+        ("!=", Op::Ne),
+        ("<=", Op::Le),
+        (">=", Op::Ge),
+        ("<", Op::Lt),
+        (">", Op::Gt),
+    ],
+    &[("+", Op::Add), ("-", Op::Sub)],
+    &[("*", Op::Mul), ("/", Op::Div)],
+];
+
+struct Parser {
+    tokens: Vec<Token>,
+    pos: usize,
+    /// Operands being parsed, one inside the other.
+    depth: usize,
+}
+
+impl Parser {
+    fn next(&mut self) -> Option<Token> {
+        let token = self.tokens.get(self.pos).cloned();
+        self.pos += 1;
+        token
+    }
+
+    fn eat(&mut self, op: &str) -> bool {
+        if matches!(self.tokens.get(self.pos), Some(Token::Op(o)) if *o == op) {
+            self.pos += 1;
+            true
+        } else {
+            false
+        }
+    }
+
+    fn expect(&mut self, op: &str) -> Result<(), String> {
+        if self.eat(op) {
+            Ok(())
+        } else {
+            Err(format!("expected {:?}", op))
+        }
+    }
+
+    fn binary(&mut self, level: usize) -> Result<Expr, String> {
+        if level == PRECEDENCE.len() {
+            return self.unary();
+        }
+        let mut left = self.binary(level + 1)?;
+        'operators: loop {
+            for (symbol, op) in PRECEDENCE[level] {
+                if self.eat(symbol) {
+                    let right = self.binary(level + 1)?;
+                    left = Expr::Binary(Box::new(left), *op, Box::new(right));
+                    continue 'operators;
+                }
+            }
+            return Ok(left);
+        }
+    }
+
+    fn unary(&mut self) -> Result<Expr, String> {
+        if self.depth == MAX_DEPTH {
+            return Err(format!("nested more than {} deep", MAX_DEPTH));
+        }
+        self.depth += 1;
+        let expr = self.operand();
+        self.depth -= 1;
+        expr
+    }
+
+    fn operand(&mut self) -> Result<Expr, String> {
+        if self.eat("!") {
+            return Ok(Expr::Not(Box::new(self.unary()?)));
+        }
+        if self.eat("-") {
+            let operand = self.unary()?;
+            return Ok(Expr::Binary(Box::new(Expr::Number(0.0)), Op::Sub, Box::new(operand)));
+        }
+        match self.next() {
+            Some(Token::Number(n)) => Ok(Expr::Number(n)),
+            Some(Token::Op("(")) => {
+                let expr = self.binary(0)?;
+                self.expect(")")?;
+                Ok(expr)
+            }
+            Some(Token::Ident(name)) => match name.as_str() {
+                "true" => Ok(Expr::Number(1.0)),
+                "false" => Ok(Expr::Number(0.0)),
+                "s" | "v" => {
+                    self.expect("[")?;
+                    let id = match self.next() {
+                        Some(Token::Number(n)) if n >= 1.0 && n.fract() == 0.0 => n as u32,
+                        _ => return Err(format!("expected an ID after {}[", name)),
+                    };
+                    self.expect("]")?;
+                    Ok(if name == "s" {
+                        Expr::Switch(id)
+                    } else {
+                        Expr::Variable(id)
+                    })
+                }
+                _ => Err(format!("unknown name {:?}", name)),
+            },
+            Some(token) => Err(format!("unexpected {:?}", token)),
+            None => Err("unexpected end".to_string()),
+        }
+    }
+}
+
+#[tauri::command]
+pub fn get_community_achievements(community: State<'_, Community>) -> Vec<CommunityAchievementInfo> {
+    community.list()
+}
+
+#[tauri::command]
+pub fn get_community_watch_list(community: State<'_, Community>) -> WatchList {
+    community.watch_list()
+}
+
+/// Check the conditions against the switches and variables polled by the page.
+#[tauri::command]
+pub fn report_game_state<R: Runtime>(
+    app: AppHandle<R>,
+    community: State<'_, Community>,
+    state: GameState,
+) -> Result<(), String> {
+    for achievement in community.evaluate(&state)? {
+        log::info!("Community achievement unlocked: {}", achievement.key);
+        let unlocked = Unlocked {
+            id: achievement.key.clone(),
+            name: achievement.definition.name.clone(),
+            description: achievement.definition.description.clone(),
+            icon: None,
+        };
+        app.emit("achievement-unlocked", unlocked).map_err(|e| e.to_string())?;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn eval(source: &str, state: &GameState) -> f64 {
+        parse(source).unwrap().eval(state)
+    }
+
+    fn state() -> GameState {
+        GameState {
+            switches: HashMap::from([(1, true), (2, false)]),
+            variables: HashMap::from([(1, 5.0), (2, -2.5)]),
+        }
+    }
+
+    #[test]
+    fn binds_like_javascript() {
+        let state = GameState::default();
+        assert_eq!(eval("1 + 2 * 3", &state), 7.0);
+        assert_eq!(eval("(1 + 2) * 3", &state), 9.0);
+        assert_eq!(eval("10 - 4 - 3", &state), 3.0);
+        assert_eq!(eval("12 / 3 / 2", &state), 2.0);
+        assert_eq!(eval("1 + 2 == 3 && 2 < 1 || 4 >= 4", &state), 1.0);
+        assert_eq!(eval("!0 && 0", &state), 0.0);
+        assert_eq!(eval("-2 * -3", &state), 6.0);
+        assert_eq!(eval("!!7", &state), 1.0);
+    }
+
+    #[test]
+    fn reads_switches_and_variables() {
+        let state = state();
+        assert_eq!(eval("s[1]", &state), 1.0);
+        assert_eq!(eval("s[2] || s[99]", &state), 0.0);
+        assert_eq!(eval("v[1] * 2 + v[2]", &state), 7.5);
+        assert_eq!(eval("v[99]", &state), 0.0);
+        assert_eq!(eval("s[1] && v[1] >= 5 && v[2] != 0", &state), 1.0);
+
+        let mut watch = WatchList::default();
+        parse("s[3] && (v[4] > 1 || !s[5]) && v[4] < 9").unwrap().collect(&mut watch);
+        assert_eq!(watch.switches, BTreeSet::from([3, 5]));
+        assert_eq!(watch.variables, BTreeSet::from([4]));
+    }
+
+    #[test]
+    fn logical_operators_short_circuit_to_booleans() {
+        let state = state();
+        assert_eq!(eval("v[1] || v[99]", &state), 1.0);
+        assert_eq!(eval("v[99] || v[1]", &state), 1.0);
+        assert_eq!(eval("v[99] && v[1]", &state), 0.0);
+        assert_eq!(eval("v[1] && v[2]", &state), 1.0);
+        assert_eq!(eval("0 && 1 / 0", &state), 0.0);
+    }
+
+    #[test]
+    fn division_by_zero_is_zero() {
+        let state = state();
+        assert_eq!(eval("1 / 0", &state), 0.0);
+        assert_eq!(eval("v[1] / v[99] == 0", &state), 1.0);
+    }
+
+    #[test]
+    fn rejects_invalid_ids() {
+        for source in ["s[0]", "v[0]", "s[1.5]", "v[-1]", "s[]", "s 1", "v[1", "x[1]"] {
+            assert!(parse(source).is_err(), "{}", source);
+        }
+    }
+
+    #[test]
+    fn rejects_malformed_conditions() {
+        for source in ["", "1 +", "(1", "1)", "1 2", "&& 1", "1 $ 2", "1..2", "true false", "!"] {
+            assert!(parse(source).is_err(), "{}", source);
+        }
+    }
+
+    #[test]
+    fn limits_nesting_and_length() {
+        let nested = |depth: usize| format!("{}1{}", "(".repeat(depth), ")".repeat(depth));
+        assert!(parse(&nested(MAX_DEPTH - 1)).is_ok());
+        assert!(parse(&nested(MAX_DEPTH)).is_err());
+        assert!(parse(&nested(100_000)).is_err());
+        assert!(parse(&format!("{}1", "!".repeat(100_000))).is_err());
+        assert!(parse(&format!("{}1", "-".repeat(100_000))).is_err());
+        assert!(parse(&format!("{}1", "1 + ".repeat(MAX_TOKENS))).is_err());
+    }
+}
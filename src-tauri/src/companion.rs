@@ -0,0 +1,117 @@
+//! The companion panel: launcher UI docked beside the game, in the game window itself.
+//!
+//! With `companion` in launcher.json, the main window can host a second webview next to the
+//! game's, showing the saves, achievements and logs from `companion.html`. The game webview
+//! is resized to make room instead of being covered, and the layout follows window resizes.
+//! The panel is toggled from the menu bar or with `toggle_companion`.
+
+use tauri::{AppHandle, Manager, PhysicalPosition, PhysicalSize, Runtime, WebviewBuilder, WebviewUrl, WebviewWindow};
+
+use crate::config::{CompanionConfig, CompanionSide, LauncherConfig};
+use crate::display;
+
+pub const COMPANION_LABEL: &str = "companion";
+
+fn config<R: Runtime>(app: &AppHandle<R>) -> Result<CompanionConfig, String> {
+    app.state::<LauncherConfig>()
+        .companion
+        .clone()
+        .ok_or_else(|| "The companion panel is not enabled for this game".to_string())
+}
+
+/// Where the game and the panel go in a window of `size`, as (position, size) pairs.
+fn split(
+    size: PhysicalSize<u32>,
+    scale: f64,
+    config: &CompanionConfig,
+) -> (
+    (PhysicalPosition<i32>, PhysicalSize<u32>),
+    (PhysicalPosition<i32>, PhysicalSize<u32>),
+) {
+    // Never more than half the window, so the game stays playable
+    let panel = ((config.width * scale) as u32).min(size.width / 2);
+    let game_size = PhysicalSize::new(size.width - panel, size.height);
+    let panel_size = PhysicalSize::new(panel, size.height);
+    match config.side {
+        CompanionSide::Right => (
+            (PhysicalPosition::new(0, 0), game_size),
+            (PhysicalPosition::new(game_size.width as i32, 0), panel_size),
+        ),
+        CompanionSide::Left => (
+            (PhysicalPosition::new(panel as i32, 0), game_size),
+            (PhysicalPosition::new(0, 0), panel_size),
+        ),
+    }
+}
+
+/// Size the game webview and the panel, if open, to the window.
+pub fn layout<R: Runtime>(window: &WebviewWindow<R>) -> Result<(), String> {
+    let app = window.app_handle();
+    let Ok(config) = config(app) else {
+        return Ok(());
+    };
+    let size = window.inner_size().map_err(|e| e.to_string())?;
+    let game: &tauri::Webview<R> = window.as_ref();
+    match app.get_webview(COMPANION_LABEL) {
+        Some(panel) => {
+            let scale = window.scale_factor().map_err(|e| e.to_string())?;
+            let ((game_position, game_size), (panel_position, panel_size)) = split(size, scale, &config);
+            game.set_position(game_position).map_err(|e| e.to_string())?;
+            game.set_size(game_size).map_err(|e| e.to_string())?;
+            panel.set_position(panel_position).map_err(|e| e.to_string())?;
+            panel.set_size(panel_size).map_err(|e| e.to_string())
+        }
+        None => {
+            game.set_position(PhysicalPosition::new(0, 0))
+                .map_err(|e| e.to_string())?;
+            game.set_size(size).map_err(|e| e.to_string())
+        }
+    }
+}
+
+pub fn is_open<R: Runtime>(app: &AppHandle<R>) -> bool {
+    app.get_webview(COMPANION_LABEL).is_some()
+}
+
+pub fn open<R: Runtime>(app: &AppHandle<R>) -> Result<(), String> {
+    let config = config(app)?;
+    if is_open(app) {
+        return Ok(());
+    }
+    let main = display::main_window(app)?;
+    let size = main.inner_size().map_err(|e| e.to_string())?;
+    let scale = main.scale_factor().map_err(|e| e.to_string())?;
+    let (_, (position, panel_size)) = split(size, scale, &config);
+    main.as_ref()
+        .window()
+        .add_child(
+            WebviewBuilder::new(COMPANION_LABEL, WebviewUrl::App("companion.html".into())),
+            position,
+            panel_size,
+        )
+        .map_err(|e| e.to_string())?;
+    layout(&main)
+}
+
+pub fn close<R: Runtime>(app: &AppHandle<R>) -> Result<(), String> {
+    if let Some(panel) = app.get_webview(COMPANION_LABEL) {
+        panel.close().map_err(|e| e.to_string())?;
+    }
+    layout(&display::main_window(app)?)
+}
+
+/// Open or close the panel. Returns whether it is open now.
+pub fn toggle<R: Runtime>(app: &AppHandle<R>) -> Result<bool, String> {
+    if is_open(app) {
+        close(app)?;
+        Ok(false)
+    } else {
+        open(app)?;
+        Ok(true)
+    }
+}
+
+#[tauri::command]
+pub fn toggle_companion<R: Runtime>(app: AppHandle<R>) -> Result<bool, String> {
+    toggle(&app)
+}
@@ -0,0 +1,48 @@
+//! Serve-time path aliases for games that mix MV and MZ files, enabled with `pathAliases`
+//! in `launcher.json`.
+//!
+//! Ported games and plugin packs written for the other engine ask for files under the names
+//! that engine uses, most often encrypted assets (`.rpgmvp` in MV, `.png_` in MZ). When a
+//! requested file doesn't exist, the localhost server tries the other engine's name, then the
+//! game's own `custom` folder aliases, before answering 404.
+
+use crate::config::PathAliasConfig;
+
+/// Encrypted asset extensions: MV's on the left, MZ's on the right. The format is the same.
+const EXTENSION_ALIASES: [(&str, &str); 3] = [("rpgmvp", "png_"), ("rpgmvo", "ogg_"), ("rpgmvm", "m4a_")];
+
+/// Build the alias lookup for the localhost server.
+pub fn aliases(config: &PathAliasConfig) -> impl Fn(&str) -> Vec<String> + Send + Sync + 'static {
+    let builtin = config.builtin;
+    let custom: Vec<(String, String)> = config
+        .custom
+        .iter()
+        .map(|(from, to)| {
+            (
+                from.trim_start_matches('/').to_string(),
+                to.trim_start_matches('/').to_string(),
+            )
+        })
+        .collect();
+
+    move |path: &str| {
+        let mut candidates = Vec::new();
+        if builtin {
+            if let Some((stem, extension)) = path.rsplit_once('.') {
+                for (mv, mz) in EXTENSION_ALIASES {
+                    if extension.eq_ignore_ascii_case(mv) {
+                        candidates.push(format!("{}.{}", stem, mz));
+                    } else if extension.eq_ignore_ascii_case(mz) {
+                        candidates.push(format!("{}.{}", stem, mv));
+                    }
+                }
+            }
+        }
+        for (from, to) in &custom {
+            if let Some(rest) = path.strip_prefix(from.as_str()) {
+                candidates.push(format!("{}{}", to, rest));
+            }
+        }
+        candidates
+    }
+}
@@ -0,0 +1,230 @@
+//! The launcher configuration as the frontend sees it: launcher.json from the game, with the
+//! changes made through `set_config` on top.
+//!
+//! Changes are kept per game in `<app config>/config-overrides/<game id>.json` as a JSON merge
+//! patch (RFC 7386) over launcher.json, so an update of the game's own file still applies to
+//! everything that wasn't changed, and setting a field to `null` goes back to the game's value.
+//! `set_config` validates the result and sends `config-changed` to every window. The game
+//! window only ever gets the configuration without its passwords and keys. Most of the
+//! launcher reads its configuration once at startup and picks a change up on the next launch;
+//! the settings UI, the injected hotkey map and the debug tools follow the event.
+
+use std::fs;
+use std::path::PathBuf;
+use std::sync::Mutex;
+
+use serde::Serialize;
+use serde_json::{Map, Value};
+use tauri::{AppHandle, Emitter, EventTarget, Runtime, State, Webview};
+
+use crate::config::LauncherConfig;
+use crate::display::MAIN_WINDOW_LABEL;
+
+const OVERRIDES_DIR: &str = "config-overrides";
+
+#[derive(Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct ConfigChanged {
+    config: LauncherConfig,
+    /// Top-level fields whose value changed, as named in launcher.json.
+    changed: Vec<String>,
+}
+
+/// Where the changes to the configuration of the game `game_id` are kept. Tauri's app config
+/// directory, worked out before there is an app to ask for it.
+pub fn overrides_path(identifier: &str, game_id: &str) -> Option<PathBuf> {
+    Some(
+        dirs::config_dir()?
+            .join(identifier)
+            .join(OVERRIDES_DIR)
+            .join(format!("{}.json", game_id)),
+    )
+}
+
+struct Stored {
+    overrides: Value,
+    current: LauncherConfig,
+}
+
+/// The configuration in use, managed as app state.
+pub struct ConfigStore {
+    path: Option<PathBuf>,
+    base: Value,
+    state: Mutex<Stored>,
+}
+
+impl ConfigStore {
+    /// `base` with the changes stored at `path` on top. Changes that no longer apply, e.g.
+    /// after an update of the game, are ignored.
+    pub fn load(base: &LauncherConfig, path: Option<PathBuf>) -> Self {
+        let base_value = serde_json::to_value(base).unwrap_or_else(|_| Value::Object(Map::new()));
+        let overrides = path
+            .as_ref()
+            .and_then(|path| fs::read_to_string(path).ok())
+            .and_then(|content| serde_json::from_str::<Value>(&content).ok())
+            .unwrap_or_else(|| Value::Object(Map::new()));
+        let current = match resolve(&base_value, &overrides) {
+            Ok(config) => config,
+            Err(e) => {
+                log::warn!("Ignoring the stored configuration changes: {}", e);
+                base.clone()
+            }
+        };
+        Self {
+            path,
+            base: base_value,
+            state: Mutex::new(Stored { overrides, current }),
+        }
+    }
+
+    pub fn get(&self) -> LauncherConfig {
+        self.state.lock().unwrap().current.clone()
+    }
+
+    /// Apply `patch`, a JSON merge patch over the current configuration, and store it.
+    /// Returns the new configuration and the fields that changed.
+    fn update(&self, patch: Value) -> Result<(LauncherConfig, Vec<String>), String> {
+        let Value::Object(fields) = &patch else {
+            return Err("The configuration patch must be an object".to_string());
+        };
+        let known = self.base.as_object().cloned().unwrap_or_default();
+        if let Some(unknown) = fields.keys().find(|key| !known.contains_key(*key)) {
+            return Err(format!("Unknown configuration field: {}", unknown));
+        }
+
+        let mut state = self.state.lock().unwrap();
+        let mut overrides = state.overrides.clone();
+        merge_patch(&mut overrides, &patch);
+        let config = resolve(&self.base, &overrides)?;
+        validate(&config)?;
+
+        let before = serde_json::to_value(&state.current).map_err(|e| e.to_string())?;
+        let after = serde_json::to_value(&config).map_err(|e| e.to_string())?;
+        let changed = fields
+            .keys()
+            .filter(|key| before.get(key.as_str()) != after.get(key.as_str()))
+            .cloned()
+            .collect();
+
+        if let Some(path) = &self.path {
+            if let Some(parent) = path.parent() {
+                fs::create_dir_all(parent).map_err(|e| e.to_string())?;
+            }
+            let content = serde_json::to_string_pretty(&overrides).map_err(|e| e.to_string())?;
+            fs::write(path, content).map_err(|e| e.to_string())?;
+        }
+        state.overrides = overrides;
+        state.current = config.clone();
+        Ok((config, changed))
+    }
+}
+
+/// The configuration `overrides` make of `base`.
+fn resolve(base: &Value, overrides: &Value) -> Result<LauncherConfig, String> {
+    let mut merged = base.clone();
+    merge_patch(&mut merged, overrides);
+    serde_json::from_value(merged).map_err(|e| format!("Invalid configuration: {}", e))
+}
+
+/// Apply a JSON merge patch (RFC 7386) to `target`.
+fn merge_patch(target: &mut Value, patch: &Value) {
+    let Value::Object(fields) = patch else {
+        *target = patch.clone();
+        return;
+    };
+    if !target.is_object() {
+        *target = Value::Object(Map::new());
+    }
+    let target = target.as_object_mut().unwrap();
+    for (key, value) in fields {
+        if value.is_null() {
+            target.remove(key);
+        } else {
+            merge_patch(target.entry(key.clone()).or_insert(Value::Null), value);
+        }
+    }
+}
+
+/// Checks the types alone can't express.
+fn validate(config: &LauncherConfig) -> Result<(), String> {
+    if config
+        .automation
+        .as_ref()
+        .is_some_and(|automation| automation.port == 0)
+    {
+        return Err("automation.port must not be 0".to_string());
+    }
+    if config.remote.as_ref().is_some_and(|remote| remote.port == 0) {
+        return Err("remote.port must not be 0".to_string());
+    }
+    if config
+        .companion
+        .as_ref()
+        .is_some_and(|companion| companion.width.is_nan() || companion.width <= 0.0)
+    {
+        return Err("companion.width must be positive".to_string());
+    }
+    if let Some(proxy) = config.proxy.as_deref().filter(|p| !p.is_empty()) {
+        ureq::Proxy::new(proxy).map_err(|e| format!("Invalid proxy '{}': {}", proxy, e))?;
+    }
+    Ok(())
+}
+
+/// The configuration as `webview` may see it: the game window gets it without passwords and
+/// keys, see [`LauncherConfig::redacted`].
+fn visible_to<R: Runtime>(webview: &Webview<R>, config: LauncherConfig) -> LauncherConfig {
+    if webview.label() == MAIN_WINDOW_LABEL {
+        config.redacted()
+    } else {
+        config
+    }
+}
+
+fn is_game_window(target: &EventTarget) -> bool {
+    match target {
+        EventTarget::AnyLabel { label }
+        | EventTarget::Window { label }
+        | EventTarget::Webview { label }
+        | EventTarget::WebviewWindow { label } => label == MAIN_WINDOW_LABEL,
+        _ => false,
+    }
+}
+
+#[tauri::command]
+pub fn get_config<R: Runtime>(webview: Webview<R>, store: State<'_, ConfigStore>) -> LauncherConfig {
+    visible_to(&webview, store.get())
+}
+
+/// Change the configuration with a JSON merge patch, e.g. `{"hotkeys": {"zoomIn": ["F6"]}}`.
+/// `null` puts a field back to the game's own value.
+#[tauri::command]
+pub fn set_config<R: Runtime>(
+    app: AppHandle<R>,
+    webview: Webview<R>,
+    store: State<'_, ConfigStore>,
+    patch: Value,
+) -> Result<LauncherConfig, String> {
+    let (config, changed) = store.update(patch)?;
+    if !changed.is_empty() {
+        log::info!("Configuration changed: {}", changed.join(", "));
+        app.emit_filter(
+            "config-changed",
+            ConfigChanged {
+                config: config.clone(),
+                changed: changed.clone(),
+            },
+            |target| !is_game_window(target),
+        )
+        .map_err(|e| e.to_string())?;
+        app.emit_filter(
+            "config-changed",
+            ConfigChanged {
+                config: config.redacted(),
+                changed,
+            },
+            is_game_window,
+        )
+        .map_err(|e| e.to_string())?;
+    }
+    Ok(visible_to(&webview, config))
+}
@@ -0,0 +1,130 @@
+//! The game's database files (`data/*.json`).
+//!
+//! `read_data_file` lets launcher panels and tools read them as structured JSON, and the
+//! startup check reports missing or corrupt files in plain words, instead of the engine's
+//! "Failed to load: data/Map012.json" halfway through a play session.
+
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use serde::Deserialize;
+use tauri::{AppHandle, Runtime, State};
+use tauri_plugin_dialog::{DialogExt, MessageDialogButtons, MessageDialogKind};
+
+use crate::game::GameInfo;
+
+/// Files every MV and MZ project has.
+const REQUIRED_FILES: [&str; 14] = [
+    "Actors",
+    "Animations",
+    "Armors",
+    "Classes",
+    "CommonEvents",
+    "Enemies",
+    "Items",
+    "MapInfos",
+    "Skills",
+    "States",
+    "System",
+    "Tilesets",
+    "Troops",
+    "Weapons",
+];
+
+/// A data file the game will fail to load.
+pub struct DataProblem {
+    pub file: String,
+    pub problem: String,
+}
+
+#[derive(Deserialize)]
+struct MapInfo {
+    id: u32,
+}
+
+/// The `data` folder, which MV deployments keep under `www/`.
+pub fn data_dir(game_dir: &Path) -> PathBuf {
+    let data = game_dir.join("data");
+    let nested = game_dir.join("www").join("data");
+    if !data.is_dir() && nested.is_dir() {
+        nested
+    } else {
+        data
+    }
+}
+
+fn read_json(path: &Path) -> Result<serde_json::Value, String> {
+    let content = fs::read_to_string(path).map_err(|e| e.to_string())?;
+    // The editor doesn't write a BOM, but hand-edited files often have one
+    serde_json::from_str(content.trim_start_matches('\u{feff}')).map_err(|e| e.to_string())
+}
+
+/// Check the database and every map listed in `MapInfos.json`.
+pub fn check(game_dir: &Path) -> Vec<DataProblem> {
+    let dir = data_dir(game_dir);
+    if !dir.is_dir() {
+        return vec![DataProblem {
+            file: "data".to_string(),
+            problem: "the folder is missing".to_string(),
+        }];
+    }
+
+    let mut files: Vec<String> = REQUIRED_FILES.iter().map(|name| name.to_string()).collect();
+    // MapInfos is a list with `null` in place of deleted maps
+    if let Ok(infos) = read_json(&dir.join("MapInfos.json")) {
+        let maps: Vec<Option<MapInfo>> = serde_json::from_value(infos).unwrap_or_default();
+        files.extend(maps.into_iter().flatten().map(|map| format!("Map{:03}", map.id)));
+    }
+
+    files
+        .into_iter()
+        .filter_map(|name| {
+            let path = dir.join(format!("{}.json", name));
+            let problem = if !path.is_file() {
+                "the file is missing".to_string()
+            } else {
+                read_json(&path).err()?
+            };
+            Some(DataProblem {
+                file: format!("data/{}.json", name),
+                problem,
+            })
+        })
+        .collect()
+}
+
+/// Show the problems in a dialog. Returns `true` if the user chose to launch anyway.
+pub fn report<R: Runtime>(app: &AppHandle<R>, problems: &[DataProblem]) -> bool {
+    let mut message = String::from("Some of the game's data files can't be loaded:\n\n");
+    for problem in problems.iter().take(10) {
+        message.push_str(&format!("• {}: {}\n", problem.file, problem.problem));
+    }
+    if problems.len() > 10 {
+        message.push_str(&format!("• …and {} more\n", problems.len() - 10));
+    }
+    message.push_str("\nThe game files may be incomplete. Reinstalling or verifying them usually fixes this.");
+
+    app.dialog()
+        .message(message)
+        .title("Damaged game files")
+        .kind(MessageDialogKind::Warning)
+        .buttons(MessageDialogButtons::OkCancelCustom(
+            "Launch anyway".to_string(),
+            "Quit".to_string(),
+        ))
+        .blocking_show()
+}
+
+/// Read `data/<name>.json` as JSON, e.g. `read_data_file("Actors")`.
+#[tauri::command]
+pub fn read_data_file(game: State<'_, GameInfo>, name: String) -> Result<serde_json::Value, String> {
+    let name = name.strip_suffix(".json").unwrap_or(&name);
+    if name.is_empty() || !name.chars().all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '-') {
+        return Err(format!("Invalid data file name: {}", name));
+    }
+    let path = data_dir(&game.dir).join(format!("{}.json", name));
+    if !path.is_file() {
+        return Err(format!("data/{}.json not found", name));
+    }
+    read_json(&path).map_err(|e| format!("data/{}.json is corrupt: {}", name, e))
+}
@@ -0,0 +1,51 @@
+//! Debug console window: launcher logs, server requests and the game's console output,
+//! for platforms where the webview devtools aren't available.
+
+use tauri::{AppHandle, Emitter, Manager, Runtime, WebviewUrl, WebviewWindowBuilder};
+
+use crate::logging::{self, LogEntry};
+
+pub const DEBUG_CONSOLE_LABEL: &str = "debug-console";
+
+/// Open the debug console, or close it if it's already open. Returns whether it is now open.
+#[tauri::command]
+pub async fn toggle_debug_console<R: Runtime>(app: AppHandle<R>) -> Result<bool, String> {
+    let open = if let Some(window) = app.get_webview_window(DEBUG_CONSOLE_LABEL) {
+        window.close().map_err(|e| e.to_string())?;
+        false
+    } else {
+        WebviewWindowBuilder::new(&app, DEBUG_CONSOLE_LABEL, WebviewUrl::App("debug-console.html".into()))
+            .title("Debug Console")
+            .inner_size(960.0, 540.0)
+            .build()
+            .map_err(|e| e.to_string())?;
+        true
+    };
+
+    // The game page only forwards its console while someone is watching
+    app.emit("debug-console-toggled", open).map_err(|e| e.to_string())?;
+    Ok(open)
+}
+
+/// Entries logged so far, for the console to show when it opens.
+#[tauri::command]
+pub fn get_debug_log() -> Vec<LogEntry> {
+    logging::recent()
+}
+
+/// A `console.*` call forwarded from the game page, for the debug console and the log file.
+#[tauri::command]
+pub fn log_from_game(level: String, message: String) {
+    let level = match level.to_lowercase().as_str() {
+        "error" => "ERROR",
+        "warn" | "warning" => "WARN",
+        "debug" | "trace" => "DEBUG",
+        _ => "INFO",
+    };
+    logging::push(LogEntry {
+        time: chrono::Local::now().format("%H:%M:%S%.3f").to_string(),
+        level: level.to_string(),
+        source: "game".to_string(),
+        message,
+    });
+}
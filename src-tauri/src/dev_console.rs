@@ -0,0 +1,83 @@
+//! Developer console: evaluates JavaScript in the game page and offers shortcuts for
+//! switches, variables, teleporting and items, standing in for the NW.js devtools.
+//!
+//! Only available with `"devConsole": true` in `launcher.json`, since it is a cheat console
+//! as much as a debugging tool.
+
+use std::sync::atomic::{AtomicU64, Ordering};
+
+use serde::Serialize;
+use tauri::{AppHandle, Emitter, Manager, Runtime, State, WebviewUrl, WebviewWindowBuilder};
+
+use crate::config::LauncherConfig;
+use crate::display::main_window;
+
+pub const DEV_CONSOLE_LABEL: &str = "dev-console";
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct EvalResult {
+    id: u64,
+    ok: bool,
+    output: String,
+}
+
+/// Source of evaluation IDs, managed as app state.
+#[derive(Default)]
+pub struct DevConsole {
+    next_id: AtomicU64,
+}
+
+fn ensure_enabled(config: &LauncherConfig) -> Result<(), String> {
+    if config.dev_console {
+        Ok(())
+    } else {
+        Err("The developer console is disabled for this game".to_string())
+    }
+}
+
+/// Open the developer console, or close it if it's already open. Returns whether it is now open.
+#[tauri::command]
+pub async fn toggle_dev_console<R: Runtime>(app: AppHandle<R>) -> Result<bool, String> {
+    ensure_enabled(&app.state::<LauncherConfig>())?;
+
+    if let Some(window) = app.get_webview_window(DEV_CONSOLE_LABEL) {
+        window.close().map_err(|e| e.to_string())?;
+        return Ok(false);
+    }
+    WebviewWindowBuilder::new(&app, DEV_CONSOLE_LABEL, WebviewUrl::App("dev-console.html".into()))
+        .title("Developer Console")
+        .inner_size(720.0, 560.0)
+        .build()
+        .map_err(|e| e.to_string())?;
+    Ok(true)
+}
+
+/// Evaluate `code` in the game page. The result arrives as a `dev-console-result` event
+/// carrying the returned ID.
+#[tauri::command]
+pub fn dev_eval<R: Runtime>(
+    app: AppHandle<R>,
+    config: State<'_, LauncherConfig>,
+    console: State<'_, DevConsole>,
+    code: String,
+) -> Result<u64, String> {
+    ensure_enabled(&config)?;
+
+    let id = console.next_id.fetch_add(1, Ordering::Relaxed);
+    let code = serde_json::to_string(&code).map_err(|e| e.to_string())?;
+    main_window(&app)?
+        .eval(&format!(
+            "window.__RPGMAKER_LAUNCHER__ && window.__RPGMAKER_LAUNCHER__.devEval({}, {})",
+            id, code
+        ))
+        .map_err(|e| e.to_string())?;
+    Ok(id)
+}
+
+/// Called by the game page with the outcome of a [`dev_eval`].
+#[tauri::command]
+pub fn dev_eval_result<R: Runtime>(app: AppHandle<R>, id: u64, ok: bool, output: String) -> Result<(), String> {
+    app.emit_to(DEV_CONSOLE_LABEL, "dev-console-result", EvalResult { id, ok, output })
+        .map_err(|e| e.to_string())
+}
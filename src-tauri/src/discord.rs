@@ -0,0 +1,125 @@
+//! Discord Rich Presence, enabled with `discord.clientId` in `launcher.json`.
+//!
+//! The game can set the presence itself with `set_presence`. Until it does, the automatic
+//! mode shows the map and play time of the latest save, refreshed whenever the game saves.
+
+use std::path::PathBuf;
+use std::sync::Mutex;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use discord_rich_presence::activity::{Activity, Timestamps};
+use discord_rich_presence::{DiscordIpc, DiscordIpcClient};
+use tauri::State;
+
+use crate::config::DiscordConfig;
+use crate::save_data;
+
+struct Presence {
+    details: Option<String>,
+    state: Option<String>,
+}
+
+pub struct Discord {
+    client: Mutex<Option<DiscordIpcClient>>,
+    /// Set once the game sets the presence itself, which turns the automatic mode off.
+    manual: Mutex<bool>,
+    auto_presence: bool,
+    started_at: i64,
+    game_title: String,
+    game_dir: PathBuf,
+    save_dir: PathBuf,
+}
+
+impl Discord {
+    pub fn new(config: &DiscordConfig, game_title: String, game_dir: PathBuf, save_dir: PathBuf) -> Self {
+        let client = DiscordIpcClient::new(&config.client_id)
+            .and_then(|mut client| client.connect().map(|_| client))
+            .map_err(|e| log::info!("Discord is not available: {}", e))
+            .ok();
+
+        let discord = Self {
+            client: Mutex::new(client),
+            manual: Mutex::new(false),
+            auto_presence: config.auto_presence,
+            started_at: SystemTime::now()
+                .duration_since(UNIX_EPOCH)
+                .map(|d| d.as_secs() as i64)
+                .unwrap_or(0),
+            game_title,
+            game_dir,
+            save_dir,
+        };
+        discord.refresh_from_saves();
+        discord
+    }
+
+    fn set_activity(&self, presence: &Presence) -> Result<(), String> {
+        let mut client = self.client.lock().unwrap();
+        let Some(client) = client.as_mut() else {
+            return Ok(());
+        };
+
+        let details = presence.details.as_deref().unwrap_or(&self.game_title);
+        let mut activity = Activity::new()
+            .details(details)
+            .timestamps(Timestamps::new().start(self.started_at));
+        if let Some(state) = presence.state.as_deref() {
+            activity = activity.state(state);
+        }
+        client.set_activity(activity).map_err(|e| e.to_string())
+    }
+
+    /// Update the automatic presence from the latest save, unless the game set its own.
+    pub fn refresh_from_saves(&self) {
+        if !self.auto_presence || *self.manual.lock().unwrap() {
+            return;
+        }
+
+        let presence = match save_data::latest_slot(&self.save_dir) {
+            Some(slot) => {
+                let map = save_data::read(&self.save_dir.join(&slot.file_name))
+                    .ok()
+                    .and_then(|save| save_data::map_id(&save))
+                    .and_then(|map_id| save_data::map_name(&self.game_dir, map_id));
+                Presence {
+                    details: map,
+                    state: (!slot.playtime.is_empty()).then(|| format!("Play time {}", slot.playtime)),
+                }
+            }
+            None => Presence {
+                details: None,
+                state: None,
+            },
+        };
+
+        if let Err(e) = self.set_activity(&presence) {
+            log::warn!("Failed to update Discord presence: {}", e);
+        }
+    }
+}
+
+/// Set the presence shown on Discord. Stops the automatic presence for this session.
+#[tauri::command]
+pub fn set_presence(
+    discord: State<'_, Discord>,
+    details: Option<String>,
+    state: Option<String>,
+) -> Result<(), String> {
+    *discord.manual.lock().unwrap() = true;
+    discord.set_activity(&Presence { details, state })
+}
+
+/// Go back to the automatic presence.
+#[tauri::command]
+pub fn clear_presence(discord: State<'_, Discord>) -> Result<(), String> {
+    *discord.manual.lock().unwrap() = false;
+    if discord.auto_presence {
+        discord.refresh_from_saves();
+        Ok(())
+    } else {
+        discord.set_activity(&Presence {
+            details: None,
+            state: None,
+        })
+    }
+}
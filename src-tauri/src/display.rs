@@ -0,0 +1,264 @@
+//! Window and webview display commands.
+
+use std::sync::Mutex;
+
+use tauri::{AppHandle, Emitter, LogicalSize, Manager, PhysicalSize, Runtime, State, WebviewWindow};
+
+use crate::config::{LauncherConfig, WindowMode};
+use crate::game::GameInfo;
+use crate::settings::Settings;
+
+pub const MAIN_WINDOW_LABEL: &str = "main";
+
+pub const MIN_ZOOM: f64 = 0.25;
+pub const MAX_ZOOM: f64 = 5.0;
+
+pub const MIN_DPI_SCALE: f64 = 0.5;
+pub const MAX_DPI_SCALE: f64 = 4.0;
+
+/// The game window. Display commands act on it even when called from a launcher panel.
+pub fn main_window<R: Runtime>(app: &AppHandle<R>) -> Result<WebviewWindow<R>, String> {
+    app.get_webview_window(MAIN_WINDOW_LABEL)
+        .ok_or_else(|| "The game window is not open".to_string())
+}
+
+#[tauri::command]
+pub fn get_zoom(settings: State<'_, Settings>) -> f64 {
+    settings.get().zoom
+}
+
+/// Set the webview zoom factor and remember it for this game. Returns the factor applied.
+#[tauri::command]
+pub fn set_zoom<R: Runtime>(
+    app: AppHandle<R>,
+    settings: State<'_, Settings>,
+    factor: f64,
+) -> Result<f64, String> {
+    if !factor.is_finite() {
+        return Err(format!("Invalid zoom factor: {}", factor));
+    }
+
+    let factor = factor.clamp(MIN_ZOOM, MAX_ZOOM);
+    settings.update(|s| s.zoom = factor)?;
+    apply_zoom(&main_window(&app)?, &settings).map_err(|e| e.to_string())?;
+    Ok(factor)
+}
+
+/// Apply the player's zoom, adjusted so the page renders at the DPI scale override if there
+/// is one: zooming by `override / system scale` gives the same CSS pixel density as running
+/// the webview at that scale.
+pub fn apply_zoom<R: Runtime>(window: &WebviewWindow<R>, settings: &Settings) -> tauri::Result<()> {
+    let settings = settings.get();
+    let mut zoom = settings.zoom.clamp(MIN_ZOOM, MAX_ZOOM);
+    if let Some(dpi_scale) = settings.dpi_scale {
+        zoom *= dpi_scale / window.scale_factor()?;
+    }
+    window.set_zoom(zoom)
+}
+
+/// Override the DPI scale the game renders at (`None` follows the system). `1.0` fixes blurry
+/// rendering and misplaced clicks in older MV games on high-DPI displays.
+#[tauri::command]
+pub fn set_dpi_scale<R: Runtime>(
+    app: AppHandle<R>,
+    settings: State<'_, Settings>,
+    scale: Option<f64>,
+) -> Result<(), String> {
+    if let Some(scale) = scale {
+        if !scale.is_finite() || !(MIN_DPI_SCALE..=MAX_DPI_SCALE).contains(&scale) {
+            return Err(format!("Invalid DPI scale: {}", scale));
+        }
+    }
+
+    settings.update(|s| s.dpi_scale = scale)?;
+    apply_zoom(&main_window(&app)?, &settings).map_err(|e| e.to_string())?;
+    app.emit("settings-changed", settings.get()).map_err(|e| e.to_string())
+}
+
+/// Enter or leave fullscreen. Returns the resulting fullscreen state.
+#[tauri::command]
+pub fn set_fullscreen<R: Runtime>(
+    app: AppHandle<R>,
+    config: State<'_, LauncherConfig>,
+    settings: State<'_, Settings>,
+    fullscreen: bool,
+) -> Result<bool, String> {
+    // Kiosk mode never leaves fullscreen
+    if current_window_mode(&config, &settings) == WindowMode::Kiosk {
+        return Ok(true);
+    }
+
+    main_window(&app)?.set_fullscreen(fullscreen).map_err(|e| e.to_string())?;
+    Ok(fullscreen)
+}
+
+#[tauri::command]
+pub fn toggle_fullscreen<R: Runtime>(
+    app: AppHandle<R>,
+    config: State<'_, LauncherConfig>,
+    settings: State<'_, Settings>,
+) -> Result<bool, String> {
+    let fullscreen = !main_window(&app)?.is_fullscreen().map_err(|e| e.to_string())?;
+    set_fullscreen(app, config, settings, fullscreen)
+}
+
+/// The player's window mode, unless the packager locked the game in kiosk mode.
+pub fn current_window_mode(config: &LauncherConfig, settings: &Settings) -> WindowMode {
+    if config.window_mode == WindowMode::Kiosk {
+        return WindowMode::Kiosk;
+    }
+    settings.get().window_mode.unwrap_or(config.window_mode)
+}
+
+/// Apply `mode` to the window. With a custom titlebar, windowed mode drops the system one.
+pub fn apply_window_mode<R: Runtime>(
+    window: &WebviewWindow<R>,
+    mode: WindowMode,
+    config: &LauncherConfig,
+) -> tauri::Result<()> {
+    match mode {
+        WindowMode::Windowed => window.set_decorations(config.titlebar.is_none()),
+        WindowMode::Borderless => window.set_decorations(false),
+        WindowMode::Kiosk => {
+            window.set_decorations(false)?;
+            window.set_fullscreen(true)
+        }
+    }
+}
+
+#[tauri::command]
+pub fn get_window_mode(config: State<'_, LauncherConfig>, settings: State<'_, Settings>) -> WindowMode {
+    current_window_mode(&config, &settings)
+}
+
+/// Switch window mode and remember the choice for this game.
+#[tauri::command]
+pub fn set_window_mode<R: Runtime>(
+    app: AppHandle<R>,
+    config: State<'_, LauncherConfig>,
+    settings: State<'_, Settings>,
+    mode: WindowMode,
+) -> Result<WindowMode, String> {
+    if config.window_mode == WindowMode::Kiosk {
+        return Err("The window mode is locked to kiosk by the launcher configuration".to_string());
+    }
+
+    let window = main_window(&app)?;
+    if current_window_mode(&config, &settings) == WindowMode::Kiosk && mode != WindowMode::Kiosk {
+        window.set_fullscreen(false).map_err(|e| e.to_string())?;
+    }
+    apply_window_mode(&window, mode, &config).map_err(|e| e.to_string())?;
+    settings.update(|s| s.window_mode = Some(mode))?;
+    Ok(mode)
+}
+
+/// With integer scaling the window can't get smaller than the game's native resolution.
+pub fn apply_integer_scaling<R: Runtime>(
+    window: &WebviewWindow<R>,
+    game: &GameInfo,
+    enabled: bool,
+) -> tauri::Result<()> {
+    let min_size = enabled.then(|| LogicalSize::new(game.width as f64, game.height as f64));
+    window.set_min_size(min_size)
+}
+
+#[tauri::command]
+pub fn set_integer_scaling<R: Runtime>(
+    app: AppHandle<R>,
+    game: State<'_, GameInfo>,
+    settings: State<'_, Settings>,
+    enabled: bool,
+) -> Result<(), String> {
+    apply_integer_scaling(&main_window(&app)?, &game, enabled).map_err(|e| e.to_string())?;
+    settings.update(|s| s.integer_scaling = enabled)?;
+    app.emit("settings-changed", settings.get()).map_err(|e| e.to_string())
+}
+
+/// Corrects window resizes to the game's aspect ratio, along the edge the player dragged.
+#[derive(Default)]
+pub struct AspectRatioLock {
+    last_size: Mutex<Option<PhysicalSize<u32>>>,
+}
+
+impl AspectRatioLock {
+    pub fn on_resized<R: Runtime>(&self, window: &WebviewWindow<R>, game: &GameInfo, size: PhysicalSize<u32>) {
+        let previous = self.last_size.lock().unwrap().replace(size);
+
+        if size.width == 0 || size.height == 0 || game.height == 0 {
+            return;
+        }
+        if window.is_fullscreen().unwrap_or(false) || window.is_maximized().unwrap_or(false) {
+            return;
+        }
+
+        let ratio = game.width as f64 / game.height as f64;
+        let width_changed = previous.is_none_or(|p| p.width.abs_diff(size.width) >= p.height.abs_diff(size.height));
+        let target = if width_changed {
+            PhysicalSize::new(size.width, (size.width as f64 / ratio).round() as u32)
+        } else {
+            PhysicalSize::new((size.height as f64 * ratio).round() as u32, size.height)
+        };
+
+        // The corrected size comes back as another resize event; stop once it fits
+        if target.width.abs_diff(size.width) > 1 || target.height.abs_diff(size.height) > 1 {
+            let _ = window.set_size(target);
+        }
+    }
+}
+
+#[tauri::command]
+pub fn set_aspect_ratio_lock<R: Runtime>(
+    app: AppHandle<R>,
+    game: State<'_, GameInfo>,
+    settings: State<'_, Settings>,
+    enabled: bool,
+) -> Result<(), String> {
+    settings.update(|s| s.lock_aspect_ratio = enabled)?;
+    if enabled {
+        let window = main_window(&app)?;
+        let size = window.inner_size().map_err(|e| e.to_string())?;
+        AspectRatioLock::default().on_resized(&window, &game, size);
+    }
+    app.emit("settings-changed", settings.get()).map_err(|e| e.to_string())
+}
+
+/// Keep the game above other windows, and remember it for this game.
+#[tauri::command]
+pub fn set_always_on_top<R: Runtime>(
+    app: AppHandle<R>,
+    settings: State<'_, Settings>,
+    enabled: bool,
+) -> Result<(), String> {
+    main_window(&app)?.set_always_on_top(enabled).map_err(|e| e.to_string())?;
+    settings.update(|s| s.always_on_top = enabled)?;
+    app.emit("settings-changed", settings.get()).map_err(|e| e.to_string())
+}
+
+/// Change the game window's title, e.g. to show the current chapter.
+#[tauri::command]
+pub fn set_window_title<R: Runtime>(app: AppHandle<R>, title: String) -> Result<(), String> {
+    main_window(&app)?.set_title(&title).map_err(|e| e.to_string())
+}
+
+/// Hide the cursor after `delay_secs` of mouse inactivity over the game (`0` disables it).
+#[tauri::command]
+pub fn set_cursor_autohide<R: Runtime>(
+    app: AppHandle<R>,
+    settings: State<'_, Settings>,
+    delay_secs: u32,
+) -> Result<(), String> {
+    settings.update(|s| s.cursor_hide_delay_secs = delay_secs)?;
+    app.emit("settings-changed", settings.get()).map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+pub fn set_cursor_visible<R: Runtime>(window: WebviewWindow<R>, visible: bool) -> Result<(), String> {
+    window.set_cursor_visible(visible).map_err(|e| e.to_string())
+}
+
+/// Confine the cursor to the window and hide it, or release it.
+#[tauri::command]
+pub fn set_pointer_lock<R: Runtime>(window: WebviewWindow<R>, locked: bool) -> Result<(), String> {
+    window.set_cursor_grab(locked).map_err(|e| e.to_string())?;
+    window.set_cursor_visible(!locked).map_err(|e| e.to_string())
+}
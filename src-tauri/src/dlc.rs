@@ -0,0 +1,291 @@
+//! Installable DLC packs.
+//!
+//! A DLC pack is a zip with a `dlc.json` manifest at its root and game files at their
+//! relative paths (`img/`, `audio/`, `data/`...). Installing one extracts it to
+//! `<app data>/dlc/<game id>/<dlc id>/`, which the localhost server mounts on top of the
+//! overlay folder while the DLC is enabled, so the base game is never touched.
+//! The disabled packs are kept in `disabled.json` next to them.
+//!
+//! The injected script records the enabled DLC in each save slot's info, and asks
+//! `check_save_dlc` before a slot is loaded so the player is warned when a pack the save
+//! used is missing or disabled.
+
+use std::fs;
+use std::io::Read;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+
+use serde::{Deserialize, Serialize};
+use tauri::{AppHandle, Emitter, Manager, Runtime, State};
+use tauri_plugin_dialog::{DialogExt, MessageDialogKind};
+
+use external_localhost_plugin::Mounts;
+
+pub const MANIFEST_FILE_NAME: &str = "dlc.json";
+const DISABLED_FILE_NAME: &str = "disabled.json";
+
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(default)]
+struct Manifest {
+    id: String,
+    name: Option<String>,
+    version: String,
+    description: String,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DlcInfo {
+    pub id: String,
+    pub name: String,
+    pub version: String,
+    pub description: String,
+    pub enabled: bool,
+    #[serde(skip)]
+    pub dir: PathBuf,
+}
+
+pub struct Dlc {
+    dir: PathBuf,
+    disabled: Mutex<Vec<String>>,
+}
+
+/// A DLC id that is safe to use as a folder name.
+fn is_valid_id(id: &str) -> bool {
+    !id.is_empty()
+        && id
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_' || c == '.')
+        && !id.starts_with('.')
+}
+
+fn read_manifest(content: &str) -> Result<Manifest, String> {
+    let manifest: Manifest =
+        serde_json::from_str(content.trim_start_matches('\u{feff}')).map_err(|e| format!("Invalid dlc.json: {}", e))?;
+    if !is_valid_id(&manifest.id) {
+        return Err(format!("Invalid DLC id: {:?}", manifest.id));
+    }
+    Ok(manifest)
+}
+
+impl Dlc {
+    pub fn load(dir: PathBuf) -> Self {
+        let disabled = fs::read_to_string(dir.join(DISABLED_FILE_NAME))
+            .ok()
+            .and_then(|content| serde_json::from_str(&content).ok())
+            .unwrap_or_default();
+        Self {
+            dir,
+            disabled: Mutex::new(disabled),
+        }
+    }
+
+    /// Every installed pack, by id.
+    pub fn list(&self) -> Vec<DlcInfo> {
+        let disabled = self.disabled.lock().unwrap();
+        let mut packs: Vec<DlcInfo> = fs::read_dir(&self.dir)
+            .into_iter()
+            .flatten()
+            .flatten()
+            // Skips the staging folder an interrupted install leaves behind
+            .filter(|entry| !entry.file_name().to_string_lossy().starts_with('.'))
+            .filter_map(|entry| {
+                let dir = entry.path();
+                let content = fs::read_to_string(dir.join(MANIFEST_FILE_NAME)).ok()?;
+                let manifest = match read_manifest(&content) {
+                    Ok(manifest) => manifest,
+                    Err(e) => {
+                        log::warn!("{:?}: {}", dir, e);
+                        return None;
+                    }
+                };
+                Some(DlcInfo {
+                    enabled: !disabled.contains(&manifest.id),
+                    name: manifest.name.unwrap_or_else(|| manifest.id.clone()),
+                    id: manifest.id,
+                    version: manifest.version,
+                    description: manifest.description,
+                    dir,
+                })
+            })
+            .collect();
+        packs.sort_by(|a, b| a.id.cmp(&b.id));
+        packs
+    }
+
+    /// The ids of the enabled packs.
+    pub fn enabled(&self) -> Vec<String> {
+        self.list()
+            .into_iter()
+            .filter(|pack| pack.enabled)
+            .map(|pack| pack.id)
+            .collect()
+    }
+
+    /// Extract a pack into the DLC folder, replacing an older copy of it. New packs are enabled.
+    pub fn install(&self, archive: &Path) -> Result<DlcInfo, String> {
+        let file = fs::File::open(archive).map_err(|e| format!("{:?}: {}", archive, e))?;
+        let mut zip = zip::ZipArchive::new(file).map_err(|e| format!("{:?}: {}", archive, e))?;
+
+        let mut content = String::new();
+        zip.by_name(MANIFEST_FILE_NAME)
+            .map_err(|_| format!("{:?} has no {}", archive, MANIFEST_FILE_NAME))?
+            .read_to_string(&mut content)
+            .map_err(|e| e.to_string())?;
+        let manifest = read_manifest(&content)?;
+
+        // Extract next to the final folder, so a broken archive doesn't remove the installed copy
+        let target = self.dir.join(&manifest.id);
+        let staging = self.dir.join(format!(".{}.part", manifest.id));
+        let _ = fs::remove_dir_all(&staging);
+        let extracted = (|| {
+            for i in 0..zip.len() {
+                let mut entry = zip.by_index(i).map_err(|e| e.to_string())?;
+                // Skips entries that would land outside the folder
+                let Some(relative) = entry.enclosed_name() else {
+                    log::warn!("Skipping {:?} in {:?}", entry.name(), archive);
+                    continue;
+                };
+                let path = staging.join(relative);
+                if entry.is_dir() {
+                    fs::create_dir_all(&path).map_err(|e| e.to_string())?;
+                    continue;
+                }
+                if let Some(parent) = path.parent() {
+                    fs::create_dir_all(parent).map_err(|e| e.to_string())?;
+                }
+                let mut out = fs::File::create(&path).map_err(|e| format!("{:?}: {}", path, e))?;
+                std::io::copy(&mut entry, &mut out).map_err(|e| format!("{:?}: {}", path, e))?;
+            }
+            Ok::<(), String>(())
+        })();
+        if let Err(e) = extracted {
+            let _ = fs::remove_dir_all(&staging);
+            return Err(e);
+        }
+        if target.exists() {
+            fs::remove_dir_all(&target).map_err(|e| e.to_string())?;
+        }
+        fs::rename(&staging, &target).map_err(|e| e.to_string())?;
+        log::info!("Installed DLC '{}' {} to {:?}", manifest.id, manifest.version, target);
+
+        self.set_enabled(&manifest.id, true)?;
+        self.list()
+            .into_iter()
+            .find(|pack| pack.id == manifest.id)
+            .ok_or_else(|| format!("DLC '{}' was not installed", manifest.id))
+    }
+
+    pub fn set_enabled(&self, id: &str, enabled: bool) -> Result<(), String> {
+        let mut disabled = self.disabled.lock().unwrap();
+        disabled.retain(|d| d != id);
+        if !enabled {
+            disabled.push(id.to_string());
+        }
+        fs::create_dir_all(&self.dir).map_err(|e| e.to_string())?;
+        let content = serde_json::to_string_pretty(&*disabled).map_err(|e| e.to_string())?;
+        fs::write(self.dir.join(DISABLED_FILE_NAME), content).map_err(|e| e.to_string())
+    }
+
+    /// Mount the enabled packs on the localhost server.
+    pub fn apply<R: Runtime>(&self, app: &AppHandle<R>) {
+        let mounts = app.state::<Mounts>();
+        for pack in self.list().into_iter().filter(|pack| pack.enabled) {
+            log::info!("Loading DLC '{}' {} from {:?}", pack.id, pack.version, pack.dir);
+            mounts.mount(&pack.dir);
+        }
+    }
+}
+
+#[tauri::command]
+pub fn list_dlc(dlc: State<'_, Dlc>) -> Vec<DlcInfo> {
+    dlc.list()
+}
+
+/// Install a DLC zip, from `path` or a file picked by the player. Returns `None` if they cancelled.
+#[tauri::command]
+pub async fn install_dlc<R: Runtime>(app: AppHandle<R>, path: Option<String>) -> Result<Option<DlcInfo>, String> {
+    let path = match path {
+        Some(path) => PathBuf::from(path),
+        None => {
+            let Some(picked) = app
+                .dialog()
+                .file()
+                .set_title("Install DLC")
+                .add_filter("DLC pack", &["zip"])
+                .blocking_pick_file()
+            else {
+                return Ok(None);
+            };
+            picked.into_path().map_err(|e| e.to_string())?
+        }
+    };
+    let info = app.state::<Dlc>().install(&path)?;
+    app.state::<Mounts>().mount(&info.dir);
+    notify_changed(&app)?;
+    Ok(Some(info))
+}
+
+/// Enable a pack. Its files are served right away; data the game already loaded is only
+/// replaced when the game reads it again.
+#[tauri::command]
+pub fn enable_dlc<R: Runtime>(app: AppHandle<R>, id: String) -> Result<(), String> {
+    let dlc = app.state::<Dlc>();
+    let pack = dlc
+        .list()
+        .into_iter()
+        .find(|pack| pack.id == id)
+        .ok_or_else(|| format!("DLC '{}' is not installed", id))?;
+    dlc.set_enabled(&id, true)?;
+    app.state::<Mounts>().mount(&pack.dir);
+    notify_changed(&app)
+}
+
+#[tauri::command]
+pub fn disable_dlc<R: Runtime>(app: AppHandle<R>, id: String) -> Result<(), String> {
+    let dlc = app.state::<Dlc>();
+    dlc.set_enabled(&id, false)?;
+    app.state::<Mounts>().unmount(dlc.dir.join(&id));
+    notify_changed(&app)
+}
+
+/// Tell the running game which packs are enabled now.
+fn notify_changed<R: Runtime>(app: &AppHandle<R>) -> Result<(), String> {
+    app.emit("dlc-changed", app.state::<Dlc>().enabled())
+        .map_err(|e| e.to_string())
+}
+
+/// The ids of the enabled packs, recorded in each save.
+#[tauri::command]
+pub fn get_active_dlc(dlc: State<'_, Dlc>) -> Vec<String> {
+    dlc.enabled()
+}
+
+/// Warn the player when a save uses DLC that isn't enabled. Returns whether they were warned.
+#[tauri::command]
+pub async fn check_save_dlc<R: Runtime>(app: AppHandle<R>, saved_dlc: Vec<String>) -> Result<bool, String> {
+    let packs = app.state::<Dlc>().list();
+    let missing: Vec<String> = saved_dlc
+        .iter()
+        .filter(|id| !packs.iter().any(|pack| &pack.id == *id && pack.enabled))
+        .map(|id| match packs.iter().find(|pack| &pack.id == id) {
+            Some(pack) => format!("{} (disabled)", pack.name),
+            None => format!("{} (not installed)", id),
+        })
+        .collect();
+    if missing.is_empty() {
+        return Ok(false);
+    }
+    log::warn!("Loading a save that uses missing DLC: {}", missing.join(", "));
+
+    app.dialog()
+        .message(format!(
+            "This save was made with DLC that is not active:\n\n{}\n\n\
+             Some things may not work as expected after loading it.",
+            missing.join("\n")
+        ))
+        .title("Save uses other DLC")
+        .kind(MessageDialogKind::Warning)
+        .show(|_| {});
+    Ok(true)
+}
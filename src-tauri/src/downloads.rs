@@ -0,0 +1,375 @@
+//! Background downloads for optional game content (HD audio, voice packs, updates).
+//!
+//! Files are downloaded into the game's overlay folder, `<app data>/overlay/<game id>/`, which
+//! the localhost plugin mounts on top of `Game_Contents`, so a finished download is
+//! immediately served at its relative path.
+//! Partial downloads are kept as `<file>.part` and resumed with a `Range` request.
+//!
+//! A download can also be a binary patch for a file the player already has (see `patch`).
+//! The patch is applied to the overlay copy, or the game's own copy, into a temporary file
+//! that only replaces the old version once its hash checks out. If anything fails the old
+//! version stays in place, so a broken update never leaves a half-written asset behind.
+
+use std::collections::HashMap;
+use std::fs;
+use std::io::{Read, Write};
+use std::path::{Component, Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use tauri::{AppHandle, Emitter, Manager, Runtime, State};
+
+use crate::net;
+use crate::patch::{self, PatchFormat};
+
+/// Minimum delay between two progress events for the same download.
+const PROGRESS_INTERVAL: Duration = Duration::from_millis(250);
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DownloadRequest {
+    /// Identifier echoed back in every event for this download.
+    pub id: String,
+    pub url: String,
+    /// Destination path, relative to the overlay folder (e.g. `audio/bgm/Theme1.ogg`).
+    pub path: String,
+    /// Expected SHA-256 of the complete file, as lowercase hex. For patches, this is the
+    /// hash of the patched file and is required.
+    pub sha256: Option<String>,
+    /// Set when `url` points to a patch for the file at `path` rather than the file itself.
+    pub patch: Option<PatchInfo>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PatchInfo {
+    pub format: PatchFormat,
+    /// SHA-256 of the file the patch was made against, checked before applying it.
+    pub source_sha256: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct DownloadProgress {
+    id: String,
+    downloaded: u64,
+    total: Option<u64>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct DownloadFinished {
+    id: String,
+    path: String,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct DownloadFailed {
+    id: String,
+    error: String,
+}
+
+pub struct DownloadManager {
+    overlay_dir: PathBuf,
+    /// The game's own files, patched when the overlay has no copy yet.
+    game_dir: PathBuf,
+    active: Mutex<HashMap<String, Arc<AtomicBool>>>,
+}
+
+impl DownloadManager {
+    pub fn new(overlay_dir: PathBuf, game_dir: PathBuf) -> Self {
+        Self {
+            overlay_dir,
+            game_dir,
+            active: Mutex::new(HashMap::new()),
+        }
+    }
+
+    pub fn overlay_dir(&self) -> &Path {
+        &self.overlay_dir
+    }
+
+    /// Start a download on a background thread. Progress and completion are reported as
+    /// `download-progress`, `download-finished` and `download-failed` events.
+    pub fn start<R: Runtime>(&self, app: &AppHandle<R>, request: DownloadRequest) -> Result<(), String> {
+        let destination = resolve_destination(&self.overlay_dir, &request.path)?;
+        if request.patch.is_some() && request.sha256.is_none() {
+            return Err(format!("Patch for {} has no sha256 for the patched file", request.path));
+        }
+        let cancel = Arc::new(AtomicBool::new(false));
+
+        {
+            let mut active = self.active.lock().unwrap();
+            if active.contains_key(&request.id) {
+                return Err(format!("Download '{}' is already running", request.id));
+            }
+            active.insert(request.id.clone(), cancel.clone());
+        }
+
+        let app = app.clone();
+        std::thread::spawn(move || {
+            let result = download(&app, &request, &destination, &cancel);
+            app.state::<DownloadManager>()
+                .active
+                .lock()
+                .unwrap()
+                .remove(&request.id);
+
+            match result {
+                Ok(()) => {
+                    let _ = app.emit(
+                        "download-finished",
+                        DownloadFinished {
+                            id: request.id,
+                            path: request.path,
+                        },
+                    );
+                }
+                Err(error) => {
+                    log::error!("Download '{}' failed: {}", request.id, error);
+                    let _ = app.emit("download-failed", DownloadFailed { id: request.id, error });
+                }
+            }
+        });
+
+        Ok(())
+    }
+
+    /// Stop a running download. The partial file is kept so it can be resumed later.
+    pub fn cancel(&self, id: &str) -> bool {
+        match self.active.lock().unwrap().get(id) {
+            Some(cancel) => {
+                cancel.store(true, Ordering::Relaxed);
+                true
+            }
+            None => false,
+        }
+    }
+}
+
+/// Make sure the destination stays inside the overlay folder.
+fn resolve_destination(overlay_dir: &Path, relative: &str) -> Result<PathBuf, String> {
+    let relative = Path::new(relative);
+    if relative
+        .components()
+        .any(|c| !matches!(c, Component::Normal(_)))
+    {
+        return Err(format!("Invalid download path: {}", relative.display()));
+    }
+    Ok(overlay_dir.join(relative))
+}
+
+fn download<R: Runtime>(
+    app: &AppHandle<R>,
+    request: &DownloadRequest,
+    destination: &Path,
+    cancel: &AtomicBool,
+) -> Result<(), String> {
+    if let Some(parent) = destination.parent() {
+        fs::create_dir_all(parent).map_err(|e| e.to_string())?;
+    }
+
+    // A partial patch must not be resumed as a partial file, or the other way around
+    let suffix = if request.patch.is_some() {
+        ".patch.part"
+    } else {
+        ".part"
+    };
+    let part_path = with_suffix(destination, suffix);
+
+    // Resume from whatever is already on disk
+    let existing = fs::metadata(&part_path).map(|m| m.len()).unwrap_or(0);
+    let mut call = net::get(&request.url)?;
+    if existing > 0 {
+        call = call.set("Range", &format!("bytes={}-", existing));
+    }
+
+    let response = match call.call() {
+        Ok(response) => response,
+        // The server has nothing past what we already have
+        Err(ureq::Error::Status(416, _)) if existing > 0 => {
+            return finish(app, request, &part_path, destination);
+        }
+        Err(e) => return Err(e.to_string()),
+    };
+
+    let resumed = response.status() == 206;
+    let mut downloaded = if resumed { existing } else { 0 };
+    let total = response
+        .header("Content-Length")
+        .and_then(|len| len.parse::<u64>().ok())
+        .map(|len| len + downloaded);
+
+    let mut file = fs::OpenOptions::new()
+        .create(true)
+        .write(true)
+        .append(resumed)
+        .truncate(!resumed)
+        .open(&part_path)
+        .map_err(|e| e.to_string())?;
+
+    let mut reader = response.into_reader();
+    let mut buffer = [0u8; 64 * 1024];
+    let mut last_progress = Instant::now();
+
+    loop {
+        if cancel.load(Ordering::Relaxed) {
+            return Err("Download cancelled".to_string());
+        }
+
+        let read = reader.read(&mut buffer).map_err(|e| e.to_string())?;
+        if read == 0 {
+            break;
+        }
+        file.write_all(&buffer[..read]).map_err(|e| e.to_string())?;
+        downloaded += read as u64;
+
+        if last_progress.elapsed() >= PROGRESS_INTERVAL {
+            last_progress = Instant::now();
+            let _ = app.emit(
+                "download-progress",
+                DownloadProgress {
+                    id: request.id.clone(),
+                    downloaded,
+                    total,
+                },
+            );
+        }
+    }
+
+    file.flush().map_err(|e| e.to_string())?;
+    drop(file);
+
+    finish(app, request, &part_path, destination)
+}
+
+fn with_suffix(path: &Path, suffix: &str) -> PathBuf {
+    let mut name = path.as_os_str().to_owned();
+    name.push(suffix);
+    PathBuf::from(name)
+}
+
+/// Verify the completed `.part` file and move it into place.
+fn finish<R: Runtime>(
+    app: &AppHandle<R>,
+    request: &DownloadRequest,
+    part_path: &Path,
+    destination: &Path,
+) -> Result<(), String> {
+    if let Some(info) = &request.patch {
+        let result = apply_patch(app, request, info, part_path, destination);
+        // A patch that doesn't apply won't apply next time either, so don't resume it
+        let _ = fs::remove_file(part_path);
+        return result;
+    }
+
+    if let Some(expected) = &request.sha256 {
+        let actual = sha256_file(part_path).map_err(|e| e.to_string())?;
+        if !actual.eq_ignore_ascii_case(expected) {
+            let _ = fs::remove_file(part_path);
+            return Err(format!(
+                "Hash mismatch for {}: expected {}, got {}",
+                request.path, expected, actual
+            ));
+        }
+    }
+
+    fs::rename(part_path, destination).map_err(|e| e.to_string())
+}
+
+/// Apply a downloaded patch to the current version of the file and swap the result in.
+fn apply_patch<R: Runtime>(
+    app: &AppHandle<R>,
+    request: &DownloadRequest,
+    info: &PatchInfo,
+    patch_path: &Path,
+    destination: &Path,
+) -> Result<(), String> {
+    // The overlay copy is newer than the game's, if there is one
+    let source_path = if destination.is_file() {
+        destination.to_path_buf()
+    } else {
+        resolve_destination(&app.state::<DownloadManager>().game_dir, &request.path)?
+    };
+    let source = fs::read(&source_path).map_err(|e| format!("Can't read {}: {}", request.path, e))?;
+    if let Some(expected) = &info.source_sha256 {
+        let actual = hex::encode(Sha256::digest(&source));
+        if !actual.eq_ignore_ascii_case(expected) {
+            return Err(format!(
+                "{} is not the version the patch was made for (expected {}, got {})",
+                request.path, expected, actual
+            ));
+        }
+    }
+
+    let patch_data = fs::read(patch_path).map_err(|e| e.to_string())?;
+    let patched = patch::apply(info.format, &source, &patch_data)?;
+    if let Some(expected) = &request.sha256 {
+        let actual = hex::encode(Sha256::digest(&patched));
+        if !actual.eq_ignore_ascii_case(expected) {
+            return Err(format!(
+                "Hash mismatch for patched {}: expected {}, got {}",
+                request.path, expected, actual
+            ));
+        }
+    }
+
+    let new_path = with_suffix(destination, ".new");
+    fs::write(&new_path, &patched).map_err(|e| e.to_string())?;
+
+    // Keep the previous overlay copy until the new one is in place
+    let backup_path = with_suffix(destination, ".bak");
+    let had_previous = destination.is_file();
+    if had_previous {
+        if let Err(e) = fs::rename(destination, &backup_path) {
+            let _ = fs::remove_file(&new_path);
+            return Err(e.to_string());
+        }
+    }
+    if let Err(e) = fs::rename(&new_path, destination) {
+        if had_previous {
+            if let Err(restore) = fs::rename(&backup_path, destination) {
+                log::error!("Failed to restore {:?}: {}", destination, restore);
+            }
+        }
+        let _ = fs::remove_file(&new_path);
+        return Err(e.to_string());
+    }
+    if had_previous {
+        let _ = fs::remove_file(&backup_path);
+    }
+    log::info!("Patched {} ({:?} patch)", request.path, info.format);
+    Ok(())
+}
+
+pub fn sha256_file(path: &Path) -> std::io::Result<String> {
+    let mut file = fs::File::open(path)?;
+    let mut hasher = Sha256::new();
+    let mut buffer = [0u8; 64 * 1024];
+    loop {
+        let read = file.read(&mut buffer)?;
+        if read == 0 {
+            break;
+        }
+        hasher.update(&buffer[..read]);
+    }
+    Ok(hex::encode(hasher.finalize()))
+}
+
+#[tauri::command]
+pub fn start_download<R: Runtime>(
+    app: AppHandle<R>,
+    manager: State<'_, DownloadManager>,
+    request: DownloadRequest,
+) -> Result<(), String> {
+    manager.start(&app, request)
+}
+
+#[tauri::command]
+pub fn cancel_download(manager: State<'_, DownloadManager>, id: String) -> bool {
+    manager.cancel(&id)
+}
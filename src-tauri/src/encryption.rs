@@ -0,0 +1,245 @@
+//! RPG Maker asset encryption.
+//!
+//! Deployed games can encrypt their images (`.rpgmvp`, `.png_`) and audio (`.rpgmvo`, `.rpgmvm`,
+//! `.ogg_`, `.m4a_`). The flags and the key are in `data/System.json`, read once at startup and
+//! managed as app state. Encrypted files start with a fixed 16 byte header, followed by the
+//! original file with its first 16 bytes XORed with the key.
+//!
+//! `encrypt_game` does what the editor's deployment does, for packagers that prepare builds
+//! with the launcher: `--encrypt-assets` on the command line runs it on the game folder.
+
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use serde::{Deserialize, Serialize};
+use tauri::ipc::Response;
+use tauri::State;
+
+use crate::game::GameInfo;
+use crate::{data_files, game_files};
+
+pub const HEADER: [u8; 16] = [
+    0x52, 0x50, 0x47, 0x4d, 0x56, 0x00, 0x00, 0x00, 0x00, 0x03, 0x01, 0x00, 0x00, 0x00, 0x00, 0x00,
+];
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct SystemJson {
+    #[serde(default)]
+    has_encrypted_images: bool,
+    #[serde(default)]
+    has_encrypted_audio: bool,
+    encryption_key: Option<String>,
+}
+
+#[derive(Debug, Clone, Default, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct EncryptionInfo {
+    pub has_encrypted_images: bool,
+    pub has_encrypted_audio: bool,
+    /// The key as written in System.json: 32 hex digits.
+    pub key: Option<String>,
+}
+
+impl EncryptionInfo {
+    pub fn load(game_dir: &Path) -> Self {
+        let path = data_files::data_dir(game_dir).join("System.json");
+        let Ok(content) = fs::read_to_string(&path) else {
+            return Self::default();
+        };
+        // Some editors save System.json with a BOM
+        let system: SystemJson = match serde_json::from_str(content.trim_start_matches('\u{feff}')) {
+            Ok(system) => system,
+            Err(e) => {
+                log::warn!("Invalid {:?}: {}", path, e);
+                return Self::default();
+            }
+        };
+
+        let info = Self {
+            has_encrypted_images: system.has_encrypted_images,
+            has_encrypted_audio: system.has_encrypted_audio,
+            key: system.encryption_key.filter(|key| !key.is_empty()),
+        };
+        if info.is_encrypted() {
+            log::info!(
+                "Game assets are encrypted (images: {}, audio: {})",
+                info.has_encrypted_images,
+                info.has_encrypted_audio
+            );
+            if info.key_bytes().is_none() {
+                log::warn!("System.json has no valid encryption key");
+            }
+        }
+        info
+    }
+
+    pub fn is_encrypted(&self) -> bool {
+        self.has_encrypted_images || self.has_encrypted_audio
+    }
+
+    pub fn key_bytes(&self) -> Option<[u8; 16]> {
+        parse_key(self.key.as_deref()?)
+    }
+}
+
+pub fn parse_key(key: &str) -> Option<[u8; 16]> {
+    if key.len() != 32 || !key.is_ascii() {
+        return None;
+    }
+    let mut bytes = [0; 16];
+    for (i, byte) in bytes.iter_mut().enumerate() {
+        *byte = u8::from_str_radix(&key[i * 2..i * 2 + 2], 16).ok()?;
+    }
+    Some(bytes)
+}
+
+/// Whether `path` has one of the encrypted asset extensions.
+pub fn is_encrypted_path(path: &Path) -> bool {
+    let extension = path
+        .extension()
+        .and_then(|ext| ext.to_str())
+        .unwrap_or_default()
+        .to_lowercase();
+    matches!(extension.as_str(), "rpgmvp" | "rpgmvo" | "rpgmvm" | "rpgmvw" | "png_" | "ogg_" | "m4a_")
+}
+
+/// Decrypt an encrypted asset.
+pub fn decrypt(data: &[u8], key: &[u8; 16]) -> Result<Vec<u8>, String> {
+    if data.len() < HEADER.len() || data[..HEADER.len()] != HEADER {
+        return Err("Not an RPG Maker encrypted file".to_string());
+    }
+    let mut content = data[HEADER.len()..].to_vec();
+    for (byte, key) in content.iter_mut().zip(key) {
+        *byte ^= key;
+    }
+    Ok(content)
+}
+
+/// Encrypt an asset.
+pub fn encrypt(data: &[u8], key: &[u8; 16]) -> Vec<u8> {
+    let mut content = Vec::with_capacity(HEADER.len() + data.len());
+    content.extend_from_slice(&HEADER);
+    content.extend_from_slice(data);
+    for (byte, key) in content[HEADER.len()..].iter_mut().zip(key) {
+        *byte ^= key;
+    }
+    content
+}
+
+/// A random key, from the operating system's generator.
+fn generate_key() -> Result<String, String> {
+    let mut key = [0u8; 16];
+    getrandom::getrandom(&mut key).map_err(|e| format!("Failed to generate a key: {}", e))?;
+    Ok(hex::encode(key))
+}
+
+#[derive(Debug, Default)]
+pub struct EncryptSummary {
+    pub images: usize,
+    pub audio: usize,
+    pub key: String,
+}
+
+/// Encrypt the images and audio of the game in `game_dir` in place, and turn encryption on in
+/// System.json. Uses the key in System.json, or `key`, or a new one. Files are named like the
+/// editor would for the game's engine: `.rpgmvp` for MV, `.png_` for MZ.
+pub fn encrypt_game(game_dir: &Path, key: Option<&str>) -> Result<EncryptSummary, String> {
+    let system_path = data_files::data_dir(game_dir).join("System.json");
+    let content = fs::read_to_string(&system_path).map_err(|e| format!("{:?}: {}", system_path, e))?;
+    let mut system: serde_json::Value =
+        serde_json::from_str(content.trim_start_matches('\u{feff}')).map_err(|e| e.to_string())?;
+
+    let existing = system["encryptionKey"].as_str().filter(|k| parse_key(k).is_some());
+    let key = match (existing, key) {
+        (Some(existing), Some(key)) if !existing.eq_ignore_ascii_case(key) => {
+            return Err("The game is already encrypted with a different key".to_string());
+        }
+        (Some(existing), _) => existing.to_string(),
+        (None, Some(key)) => key.to_string(),
+        (None, None) => generate_key()?,
+    };
+    let key_bytes = parse_key(&key).ok_or_else(|| "The key must be 32 hexadecimal digits".to_string())?;
+
+    let mz = game_dir.join("js").join("rmmz_core.js").is_file();
+    let extension = |ext: &str| -> Option<&'static str> {
+        match (ext, mz) {
+            ("png", false) => Some("rpgmvp"),
+            ("ogg", false) => Some("rpgmvo"),
+            ("m4a", false) => Some("rpgmvm"),
+            ("png", true) => Some("png_"),
+            ("ogg", true) => Some("ogg_"),
+            ("m4a", true) => Some("m4a_"),
+            _ => None,
+        }
+    };
+
+    let mut summary = EncryptSummary {
+        key: key.clone(),
+        ..Default::default()
+    };
+    for (folder, is_image) in [("img", true), ("audio", false)] {
+        for path in files_in(&game_dir.join(folder)) {
+            let ext = path
+                .extension()
+                .and_then(|ext| ext.to_str())
+                .unwrap_or_default()
+                .to_lowercase();
+            let Some(encrypted_ext) = extension(&ext) else {
+                continue;
+            };
+            let data = fs::read(&path).map_err(|e| format!("{:?}: {}", path, e))?;
+            let target = path.with_extension(encrypted_ext);
+            fs::write(&target, encrypt(&data, &key_bytes)).map_err(|e| format!("{:?}: {}", target, e))?;
+            fs::remove_file(&path).map_err(|e| format!("{:?}: {}", path, e))?;
+            if is_image {
+                summary.images += 1;
+            } else {
+                summary.audio += 1;
+            }
+        }
+    }
+
+    system["hasEncryptedImages"] = serde_json::Value::Bool(true);
+    system["hasEncryptedAudio"] = serde_json::Value::Bool(true);
+    system["encryptionKey"] = serde_json::Value::String(key);
+    fs::write(&system_path, system.to_string()).map_err(|e| format!("{:?}: {}", system_path, e))?;
+    Ok(summary)
+}
+
+/// Every file under `dir`, recursively.
+pub fn files_in(dir: &Path) -> Vec<PathBuf> {
+    let mut files = Vec::new();
+    for entry in fs::read_dir(dir).into_iter().flatten().flatten() {
+        let path = entry.path();
+        if path.is_dir() {
+            files.extend(files_in(&path));
+        } else {
+            files.push(path);
+        }
+    }
+    files
+}
+
+#[tauri::command]
+pub fn get_encryption_info(encryption: State<'_, EncryptionInfo>) -> EncryptionInfo {
+    encryption.inner().clone()
+}
+
+/// Read an encrypted asset from the game folder, decrypted.
+#[tauri::command]
+pub fn decrypt_asset(
+    game: State<'_, GameInfo>,
+    encryption: State<'_, EncryptionInfo>,
+    path: String,
+) -> Result<Response, String> {
+    let key = encryption
+        .key_bytes()
+        .ok_or_else(|| "The game has no encryption key".to_string())?;
+    let full_path = game_files::resolve(&game.dir, &path)?;
+    if !is_encrypted_path(&full_path) {
+        return Err(format!("Not an encrypted asset: {}", path));
+    }
+    let data = fs::read(&full_path).map_err(|e| e.to_string())?;
+    Ok(Response::new(decrypt(&data, &key)?))
+}
@@ -0,0 +1,79 @@
+//! Muting the game, and muting or pausing it while its window is in the background.
+//!
+//! NW.js stops requestAnimationFrame for unfocused windows, and many games rely on that to
+//! pause. Webviews keep running, so the window focus events are forwarded to the page as
+//! `window-focus-changed` and the injected `focus.js` mutes or pauses the game.
+//!
+//! Hidden windows are also throttled by the webview, which the `background` launcher option
+//! turns off where the platform allows it, or replaces with a clean pause.
+
+use serde::Serialize;
+use tauri::{AppHandle, Emitter, Manager, Runtime, State, WebviewWindow, WebviewWindowBuilder};
+
+use crate::config::BackgroundMode;
+use crate::settings::Settings;
+
+/// WebView2 arguments Tauri uses when none are given, which must be kept when adding more.
+#[cfg(windows)]
+const DEFAULT_WEBVIEW2_ARGS: &str = "--disable-features=msWebOOUI,msPdfOOUI,msSmartScreenProtection";
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct FocusChanged {
+    focused: bool,
+}
+
+/// Set up the game window's background throttling for `mode`.
+pub fn configure_background<'a, R: Runtime, M: Manager<R>>(
+    builder: WebviewWindowBuilder<'a, R, M>,
+    mode: BackgroundMode,
+) -> WebviewWindowBuilder<'a, R, M> {
+    use tauri::utils::config::BackgroundThrottlingPolicy;
+
+    match mode {
+        BackgroundMode::Throttle => builder,
+        // The page pauses itself on `visibilitychange`; the webview may suspend it entirely
+        BackgroundMode::Suspend => builder.background_throttling(BackgroundThrottlingPolicy::Suspend),
+        BackgroundMode::Run => {
+            let builder = builder.background_throttling(BackgroundThrottlingPolicy::Disabled);
+            // WebView2 ignores the policy but has switches for the same thing
+            #[cfg(windows)]
+            let builder = builder.additional_browser_args(&format!(
+                "{} --disable-background-timer-throttling --disable-renderer-backgrounding \
+                 --disable-backgrounding-occluded-windows",
+                DEFAULT_WEBVIEW2_ARGS
+            ));
+            builder
+        }
+    }
+}
+
+pub fn emit_focus_changed<R: Runtime>(window: &WebviewWindow<R>, focused: bool) {
+    let _ = window.emit_to(window.label(), "window-focus-changed", FocusChanged { focused });
+}
+
+#[tauri::command]
+pub fn set_muted<R: Runtime>(app: AppHandle<R>, settings: State<'_, Settings>, muted: bool) -> Result<(), String> {
+    settings.update(|s| s.muted = muted)?;
+    app.emit("settings-changed", settings.get()).map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+pub fn set_mute_on_blur<R: Runtime>(
+    app: AppHandle<R>,
+    settings: State<'_, Settings>,
+    enabled: bool,
+) -> Result<(), String> {
+    settings.update(|s| s.mute_on_blur = enabled)?;
+    app.emit("settings-changed", settings.get()).map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+pub fn set_pause_on_blur<R: Runtime>(
+    app: AppHandle<R>,
+    settings: State<'_, Settings>,
+    enabled: bool,
+) -> Result<(), String> {
+    settings.update(|s| s.pause_on_blur = enabled)?;
+    app.emit("settings-changed", settings.get()).map_err(|e| e.to_string())
+}
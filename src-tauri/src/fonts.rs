@@ -0,0 +1,360 @@
+//! `@font-face` rules for the fonts shipped in the game's `fonts/` folder.
+//!
+//! MV loads its font through `fonts/gamefont.css`, which developers often edit by hand with
+//! Windows paths (`fonts\MyFont.ttf`, `C:\Users\...`) or the wrong letter case. Those work in
+//! the editor's test play on Windows and break everywhere else. Every rule of gamefont.css is
+//! rewritten to point at the matching file in `fonts/`, found by file name regardless of case,
+//! and every font file also gets a rule under its own name so plugins that refer to fonts by
+//! file name find them. The injected stylesheet is moved after the game's own once the page
+//! has loaded, so its rules win over the broken ones.
+//!
+//! Games written in Japanese, Chinese or Korean often rely on the system having a CJK font,
+//! which minimal Linux installs don't. When the game's text is CJK, the launcher finds a font
+//! for it (the `cjkFont` from launcher.json, or one installed on the system), serves it from a
+//! cache folder mounted on the localhost server, and the injected `cjk_fallback.js` appends it
+//! to the engine's font stack so the webview uses it for any glyph the game's font lacks.
+
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+use tauri::{AppHandle, Manager, Runtime};
+
+use crate::data_files;
+use crate::injection;
+use external_localhost_plugin::{InjectedScripts, Mounts};
+
+const FONT_EXTENSIONS: [&str; 4] = ["ttf", "otf", "woff", "woff2"];
+
+/// A `font-family` and the file it should load, relative to `fonts/`.
+struct FontFace {
+    family: String,
+    file: String,
+}
+
+/// Build the CSS for the game in `game_dir`. Empty when it has no fonts.
+pub fn font_face_css(game_dir: &Path) -> String {
+    let dir = game_dir.join("fonts");
+    let files: Vec<String> = fs::read_dir(&dir)
+        .into_iter()
+        .flatten()
+        .flatten()
+        .filter(|entry| entry.path().is_file())
+        .map(|entry| entry.file_name().to_string_lossy().to_string())
+        .filter(|name| {
+            let extension = name.rsplit_once('.').map(|(_, ext)| ext.to_lowercase()).unwrap_or_default();
+            FONT_EXTENSIONS.contains(&extension.as_str())
+        })
+        .collect();
+    if files.is_empty() {
+        return String::new();
+    }
+
+    let find = |reference: &str| -> Option<&String> {
+        let name = reference.rsplit(['/', '\\']).next().unwrap_or(reference);
+        files.iter().find(|file| file.eq_ignore_ascii_case(name))
+    };
+
+    let mut faces = Vec::new();
+    if let Ok(css) = fs::read_to_string(dir.join("gamefont.css")) {
+        for (family, source) in parse_font_faces(&css) {
+            match find(&source) {
+                Some(file) => faces.push(FontFace {
+                    family,
+                    file: file.clone(),
+                }),
+                None => log::warn!("gamefont.css refers to {:?}, which isn't in the fonts folder", source),
+            }
+        }
+    }
+    for file in &files {
+        let stem = file.rsplit_once('.').map(|(stem, _)| stem).unwrap_or(file);
+        faces.push(FontFace {
+            family: stem.to_string(),
+            file: file.clone(),
+        });
+    }
+
+    faces
+        .iter()
+        .map(|face| {
+            format!(
+                "@font-face {{ font-family: {}; src: url(\"fonts/{}\"); }}\n",
+                css_string(&face.family),
+                face.file.replace('"', "%22")
+            )
+        })
+        .collect()
+}
+
+/// Quote a font family name for CSS.
+fn css_string(value: &str) -> String {
+    format!("\"{}\"", value.replace('\\', "\\\\").replace('"', "\\\""))
+}
+
+/// Find the `font-family` and first `url()` of every `@font-face` block.
+fn parse_font_faces(css: &str) -> Vec<(String, String)> {
+    let mut faces = Vec::new();
+    let mut rest = css;
+    while let Some(start) = rest.find("@font-face") {
+        let block = &rest[start..];
+        let Some(end) = block.find('}') else {
+            break;
+        };
+        let body = &block[..end];
+        rest = &block[end..];
+
+        let family = declaration(body, "font-family");
+        let source = declaration(body, "src").and_then(|src| {
+            let start = src.find("url(")? + 4;
+            let end = src[start..].find(')')? + start;
+            Some(src[start..end].trim().trim_matches(['"', '\'']).to_string())
+        });
+        if let (Some(family), Some(source)) = (family, source) {
+            faces.push((family.trim_matches(['"', '\'']).to_string(), source));
+        }
+    }
+    faces
+}
+
+fn declaration<'a>(body: &'a str, property: &str) -> Option<&'a str> {
+    body.split(['{', ';']).find_map(|declaration| {
+        let (name, value) = declaration.split_once(':')?;
+        name.trim().eq_ignore_ascii_case(property).then_some(value.trim())
+    })
+}
+
+/// Family name of the fallback font in the page.
+const CJK_FAMILY: &str = "LauncherCJK";
+/// Data files whose text tells the game's language.
+const LANGUAGE_SAMPLE_FILES: [&str; 4] = ["System.json", "Actors.json", "Items.json", "CommonEvents.json"];
+/// Distribution packages with CJK fonts, for the log.
+const INSTALL_HINT: &str =
+    "fonts-noto-cjk (Debian/Ubuntu), google-noto-sans-cjk-fonts (Fedora) or noto-fonts-cjk (Arch)";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CjkScript {
+    Japanese,
+    Chinese,
+    Korean,
+}
+
+impl CjkScript {
+    /// Language for fontconfig.
+    fn lang(self) -> &'static str {
+        match self {
+            Self::Japanese => "ja",
+            Self::Chinese => "zh-cn",
+            Self::Korean => "ko",
+        }
+    }
+
+    /// Families the webview may find installed under their own name, best first.
+    fn local_families(self) -> &'static [&'static str] {
+        match self {
+            Self::Japanese => &[
+                "Noto Sans CJK JP",
+                "Source Han Sans JP",
+                "Hiragino Sans",
+                "Yu Gothic",
+                "Meiryo",
+                "MS Gothic",
+                "IPAGothic",
+                "TakaoGothic",
+            ],
+            Self::Chinese => &[
+                "Noto Sans CJK SC",
+                "Source Han Sans SC",
+                "PingFang SC",
+                "Microsoft YaHei",
+                "WenQuanYi Micro Hei",
+                "Droid Sans Fallback",
+            ],
+            Self::Korean => &[
+                "Noto Sans CJK KR",
+                "Source Han Sans KR",
+                "Apple SD Gothic Neo",
+                "Malgun Gothic",
+                "NanumGothic",
+            ],
+        }
+    }
+
+    /// Font files where the usual system fonts live, for systems without fontconfig.
+    fn system_files(self) -> &'static [&'static str] {
+        match self {
+            Self::Japanese => &[
+                "C:\\Windows\\Fonts\\YuGothM.ttc",
+                "C:\\Windows\\Fonts\\meiryo.ttc",
+                "C:\\Windows\\Fonts\\msgothic.ttc",
+                "/System/Library/Fonts/ヒラギノ角ゴシック W3.ttc",
+            ],
+            Self::Chinese => &[
+                "C:\\Windows\\Fonts\\msyh.ttc",
+                "C:\\Windows\\Fonts\\simsun.ttc",
+                "/System/Library/Fonts/PingFang.ttc",
+            ],
+            Self::Korean => &[
+                "C:\\Windows\\Fonts\\malgun.ttf",
+                "/System/Library/Fonts/AppleSDGothicNeo.ttc",
+            ],
+        }
+    }
+}
+
+/// The CJK script of the game's text, from MZ's locale or the characters in its data.
+pub fn cjk_script(game_dir: &Path) -> Option<CjkScript> {
+    let data_dir = data_files::data_dir(game_dir);
+    let system = fs::read_to_string(data_dir.join("System.json")).unwrap_or_default();
+    if let Some(locale) = serde_json::from_str::<serde_json::Value>(&system)
+        .ok()
+        .and_then(|system| system["locale"].as_str().map(str::to_lowercase))
+    {
+        match locale.get(..2) {
+            Some("ja") => return Some(CjkScript::Japanese),
+            Some("zh") => return Some(CjkScript::Chinese),
+            Some("ko") => return Some(CjkScript::Korean),
+            _ => {}
+        }
+    }
+
+    let (mut kana, mut hangul, mut han) = (0usize, 0usize, 0usize);
+    for file in LANGUAGE_SAMPLE_FILES {
+        let Ok(text) = fs::read_to_string(data_dir.join(file)) else {
+            continue;
+        };
+        for c in text.chars() {
+            match c {
+                '\u{3040}'..='\u{30ff}' => kana += 1,
+                '\u{ac00}'..='\u{d7af}' | '\u{1100}'..='\u{11ff}' => hangul += 1,
+                '\u{4e00}'..='\u{9fff}' | '\u{3400}'..='\u{4dbf}' => han += 1,
+                _ => {}
+            }
+        }
+    }
+    // Japanese mixes kana with kanji, so any kana at all decides it
+    if kana > 0 {
+        Some(CjkScript::Japanese)
+    } else if hangul > 0 {
+        Some(CjkScript::Korean)
+    } else if han > 0 {
+        Some(CjkScript::Chinese)
+    } else {
+        None
+    }
+}
+
+/// A font file for `script` installed on the system.
+fn system_font(script: CjkScript) -> Option<PathBuf> {
+    // fontconfig knows which installed fonts cover a language
+    let output = Command::new("fc-list")
+        .arg(format!(":lang={}", script.lang()))
+        .arg("file")
+        .output()
+        .ok()
+        .filter(|output| output.status.success());
+    if let Some(output) = output {
+        let listed = String::from_utf8_lossy(&output.stdout);
+        let mut files: Vec<&str> = listed
+            .lines()
+            .filter_map(|line| Some(line.trim().strip_suffix(':')?.trim()))
+            .collect();
+        // Prefer the regular weight of a sans-serif family
+        files.sort_by_key(|file| {
+            let name = file.to_lowercase();
+            (!name.contains("sans"), !name.contains("regular"), name.contains("bold"))
+        });
+        if let Some(file) = files.first() {
+            return Some(PathBuf::from(file));
+        }
+    }
+    script
+        .system_files()
+        .iter()
+        .map(PathBuf::from)
+        .find(|path| path.is_file())
+}
+
+/// Put `font` in `dir/fonts/`, where the localhost server finds it once `dir` is mounted.
+/// Returns its file name.
+fn stage_font(font: &Path, dir: &Path) -> Result<String, String> {
+    let extension = font
+        .extension()
+        .map(|ext| ext.to_string_lossy().to_lowercase())
+        .unwrap_or_else(|| "ttf".to_string());
+    let file_name = format!("launcher-cjk.{}", extension);
+    let target = dir.join("fonts").join(&file_name);
+
+    let size = |path: &Path| fs::metadata(path).map(|metadata| metadata.len()).ok();
+    if size(&target).is_some() && size(&target) == size(font) {
+        return Ok(file_name);
+    }
+    // Clear out a font of another type from an earlier launch
+    let _ = fs::remove_dir_all(dir.join("fonts"));
+    fs::create_dir_all(dir.join("fonts")).map_err(|e| e.to_string())?;
+    if fs::hard_link(font, &target).is_err() {
+        fs::copy(font, &target).map_err(|e| e.to_string())?;
+    }
+    Ok(file_name)
+}
+
+/// `@font-face` for the fallback family: fonts the webview may know by name, then `file`.
+fn cjk_font_face_css(script: CjkScript, file: Option<&str>) -> String {
+    let mut sources: Vec<String> = script
+        .local_families()
+        .iter()
+        .map(|family| format!("local({})", css_string(family)))
+        .collect();
+    if let Some(file) = file {
+        sources.push(format!("url(\"fonts/{}\")", file));
+    }
+    format!(
+        "@font-face {{ font-family: {}; src: {}; }}\n",
+        css_string(CJK_FAMILY),
+        sources.join(", ")
+    )
+}
+
+/// Add the CJK fallback font to the page when the game's text needs one. `configured` is the
+/// `cjkFont` from launcher.json, relative to the game folder.
+pub fn apply_cjk_fallback<R: Runtime>(
+    app: &AppHandle<R>,
+    game_dir: &Path,
+    configured: Option<&str>,
+) -> tauri::Result<()> {
+    let Some(script) = cjk_script(game_dir) else {
+        return Ok(());
+    };
+    let font = match configured {
+        Some(path) => Some(game_dir.join(path)).filter(|path| path.is_file()).or_else(|| {
+            log::warn!("cjkFont {:?} not found, looking for a system font", path);
+            system_font(script)
+        }),
+        None => system_font(script),
+    };
+
+    let mut file = None;
+    match &font {
+        Some(font) => {
+            let dir = app.path().app_cache_dir()?.join("cjk-font");
+            match stage_font(font, &dir) {
+                Ok(name) => {
+                    log::info!("Using {:?} as the {:?} fallback font", font, script);
+                    app.state::<Mounts>().mount(&dir);
+                    file = Some(name);
+                }
+                Err(e) => log::warn!("Failed to prepare the fallback font {:?}: {}", font, e),
+            }
+        }
+        None => log::warn!(
+            "The game's text is {:?} and no font for it was found. If it shows as boxes, install {}",
+            script,
+            INSTALL_HINT
+        ),
+    }
+
+    let css = cjk_font_face_css(script, file.as_deref());
+    app.state::<InjectedScripts>()
+        .push(injection::cjk_fallback_script(&css, CJK_FAMILY));
+    Ok(())
+}
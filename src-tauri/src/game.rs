@@ -0,0 +1,183 @@
+//! Metadata about the game being launched, read from its NW.js `package.json`.
+//!
+//! MV deployments often keep the game in a `www/` folder with `package.json` next to it,
+//! so the web root is found first and `package.json` is looked up there and one level up.
+
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use serde::Deserialize;
+use tauri::image::Image;
+
+use crate::data_files;
+
+#[derive(Debug, Default, Deserialize)]
+struct PackageJson {
+    #[serde(default)]
+    name: String,
+    version: Option<String>,
+    #[serde(default)]
+    window: PackageWindow,
+}
+
+#[derive(Debug, Default, Deserialize)]
+struct PackageWindow {
+    #[serde(default)]
+    title: String,
+    /// Window icon, relative to the folder of package.json.
+    icon: Option<String>,
+    width: Option<u32>,
+    height: Option<u32>,
+}
+
+/// The folder holding the game's `index.html`: `dir` itself or its `www/` subfolder.
+pub fn web_root(dir: &Path) -> PathBuf {
+    let nested = dir.join("www");
+    if !dir.join("index.html").is_file() && nested.join("index.html").is_file() {
+        log::info!("Serving the game from {:?}", nested);
+        nested
+    } else {
+        dir.to_path_buf()
+    }
+}
+
+/// Find and read `package.json`, returning it with the folder it was found in.
+fn read_package(game_dir: &Path) -> (PackageJson, PathBuf) {
+    let candidates = std::iter::once(game_dir).chain(game_dir.parent());
+    for dir in candidates {
+        let path = dir.join("package.json");
+        if let Ok(content) = fs::read_to_string(&path) {
+            match serde_json::from_str(&content) {
+                Ok(package) => return (package, dir.to_path_buf()),
+                Err(e) => log::warn!("Invalid {:?}: {}", path, e),
+            }
+        }
+    }
+    (PackageJson::default(), game_dir.to_path_buf())
+}
+
+#[derive(Debug, Clone)]
+pub struct GameInfo {
+    /// Folder the game is served from.
+    pub dir: PathBuf,
+    /// Stable identifier used to key per-game settings, safe to use as a file name.
+    pub id: String,
+    pub title: String,
+    /// Version of the game: `version` from package.json, or else the `versionId` the editor
+    /// writes to System.json on every save.
+    pub version: Option<String>,
+    /// Window and taskbar icon: `window.icon` from package.json, or `icon/icon.png`.
+    pub icon: PathBuf,
+    /// Native screen size of the game, as declared for the NW.js window.
+    pub width: u32,
+    pub height: u32,
+}
+
+impl GameInfo {
+    pub fn load(game_dir: &Path) -> Self {
+        let (package, package_dir) = read_package(game_dir);
+
+        // A `www` web root is named after the folder around it
+        let named_dir = if game_dir.file_name().is_some_and(|name| name == "www") {
+            game_dir.parent().unwrap_or(game_dir)
+        } else {
+            game_dir
+        };
+        let folder_name = named_dir
+            .canonicalize()
+            .ok()
+            .and_then(|dir| dir.file_name().map(|n| n.to_string_lossy().to_string()))
+            .unwrap_or_else(|| "game".to_string());
+
+        // MV deploys usually leave `name` empty, the window title is the next best thing
+        let title = if package.window.title.trim().is_empty() {
+            folder_name.clone()
+        } else {
+            package.window.title.trim().to_string()
+        };
+
+        let id_source = [package.name.as_str(), package.window.title.as_str(), folder_name.as_str()]
+            .into_iter()
+            .find(|s| !s.trim().is_empty())
+            .unwrap_or("game");
+
+        // NW.js resolves the icon from the folder package.json is in
+        let icon = match package.window.icon.as_deref().filter(|icon| !icon.trim().is_empty()) {
+            Some(icon) => package_dir.join(icon),
+            None => game_dir.join("icon/icon.png"),
+        };
+
+        let version = package
+            .version
+            .filter(|version| !version.trim().is_empty())
+            .or_else(|| system_version_id(game_dir));
+
+        Self {
+            dir: game_dir.to_path_buf(),
+            id: sanitize_id(id_source),
+            title,
+            version,
+            icon,
+            // MV's default resolution when package.json doesn't say
+            width: package.window.width.unwrap_or(816),
+            height: package.window.height.unwrap_or(624),
+        }
+    }
+}
+
+fn system_version_id(game_dir: &Path) -> Option<String> {
+    let content = fs::read_to_string(data_files::data_dir(game_dir).join("System.json")).ok()?;
+    let system: serde_json::Value = serde_json::from_str(content.trim_start_matches('\u{feff}')).ok()?;
+    system["versionId"].as_u64().map(|id| id.to_string())
+}
+
+/// Load the game's icon for use as a window icon.
+pub fn load_icon(game: &GameInfo) -> Option<Image<'static>> {
+    if !game.icon.is_file() {
+        return None;
+    }
+    match Image::from_path(&game.icon) {
+        Ok(icon) => Some(icon),
+        Err(e) => {
+            log::warn!("Failed to load game icon {:?}: {}", game.icon, e);
+            None
+        }
+    }
+}
+
+/// Give the process its own taskbar identity on Windows, so the game isn't grouped with
+/// (or pinned as) other games packaged with the same launcher.
+#[cfg(windows)]
+pub fn set_app_user_model_id(game: &GameInfo) {
+    #[link(name = "shell32")]
+    extern "system" {
+        fn SetCurrentProcessExplicitAppUserModelID(app_id: *const u16) -> i32;
+    }
+
+    let app_id: Vec<u16> = format!("com.rpgmaker.launcher.{}", game.id)
+        .encode_utf16()
+        .chain(std::iter::once(0))
+        .collect();
+    // SAFETY: `app_id` is a NUL-terminated UTF-16 string that outlives the call
+    let result = unsafe { SetCurrentProcessExplicitAppUserModelID(app_id.as_ptr()) };
+    if result != 0 {
+        log::warn!("Failed to set the AppUserModelID (HRESULT {:#x})", result);
+    }
+}
+
+#[cfg(not(windows))]
+pub fn set_app_user_model_id(_game: &GameInfo) {}
+
+fn sanitize_id(name: &str) -> String {
+    let id: String = name
+        .trim()
+        .chars()
+        .map(|c| if c.is_alphanumeric() { c.to_ascii_lowercase() } else { '-' })
+        .collect();
+    let id = id.trim_matches('-').to_string();
+    if id.is_empty() {
+        "game".to_string()
+    } else {
+        id
+    }
+}
@@ -0,0 +1,211 @@
+//! Reading the game's own files for plugins bridged through the launcher.
+//!
+//! Many Japanese games ship `.txt` and `.csv` files in Shift-JIS, which NW.js plugins read
+//! with `fs` and decode themselves. `read_game_file` decodes them to a string: with the
+//! `encoding` given (any WHATWG label, like `shift_jis` or `euc-jp`), or detected from a
+//! BOM, valid UTF-8, or else Shift-JIS. Text files served by the localhost server are
+//! converted to UTF-8 the same way. `read_game_file_bytes` returns a file as is, for images
+//! and archives, as a raw IPC response rather than a JSON array, optionally only `length`
+//! bytes from `offset` so a header can be read without loading a whole movie.
+//! `list_directory` lists a folder, for plugins that enumerate `img/pictures` or `audio/se`,
+//! and `file_stat` gives the size and modification time of a file. All of them look files up
+//! through [`GameFiles`], like the polyfill's synchronous `fs` helpers: mods, DLC and packed
+//! archives included, and the save folder under `save/` as in NW.js.
+
+use std::fs;
+use std::io::{Read, Seek, SeekFrom};
+use std::path::{Component, Path, PathBuf};
+
+use encoding_rs::{Encoding, SHIFT_JIS, UTF_8, WINDOWS_1252};
+use external_localhost_plugin::{GameFile, GameFiles};
+use serde::Serialize;
+use tauri::ipc::Response;
+use tauri::State;
+
+use crate::optimize;
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DirectoryEntry {
+    /// Path relative to the listed folder, with `/` separators.
+    pub path: String,
+    pub is_dir: bool,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct FileStat {
+    pub size: u64,
+    /// Milliseconds since the Unix epoch. Missing for packed files.
+    pub modified: Option<u64>,
+    pub is_dir: bool,
+}
+
+/// Which encoding `bytes` are most likely in, when none was given.
+fn detect(bytes: &[u8]) -> &'static Encoding {
+    if let Some((encoding, _)) = Encoding::for_bom(bytes) {
+        return encoding;
+    }
+    if std::str::from_utf8(bytes).is_ok() {
+        return UTF_8;
+    }
+    if !SHIFT_JIS.decode_without_bom_handling(bytes).1 {
+        return SHIFT_JIS;
+    }
+    // Never fails, so at least the ASCII parts come through
+    WINDOWS_1252
+}
+
+/// Decode a text file, with the encoding named by `label` or a detected one.
+pub fn decode(bytes: &[u8], label: Option<&str>) -> Result<String, String> {
+    let encoding = match label {
+        Some(label) => {
+            Encoding::for_label(label.trim().as_bytes()).ok_or_else(|| format!("Unknown encoding: {}", label))?
+        }
+        None => detect(bytes),
+    };
+    // `decode` still honors a BOM, which wins over the label as in browsers
+    let (text, _, _) = encoding.decode(bytes);
+    Ok(text.into_owned())
+}
+
+/// Re-encode a served text file as UTF-8, if it isn't already.
+pub fn to_utf8(path: &str, content: Vec<u8>) -> Vec<u8> {
+    let extension = Path::new(path)
+        .extension()
+        .and_then(|ext| ext.to_str())
+        .unwrap_or_default()
+        .to_lowercase();
+    if !matches!(extension.as_str(), "txt" | "csv") {
+        return content;
+    }
+    let encoding = detect(&content);
+    if encoding == UTF_8 {
+        return content;
+    }
+    log::debug!("Serving {} converted from {}", path, encoding.name());
+    encoding.decode(&content).0.into_owned().into_bytes()
+}
+
+/// `path` under the game folder, refusing paths that would leave it. Only plain names are
+/// accepted: no `..`, and no root or drive, which on Windows `is_absolute` alone lets through
+/// in `\Windows` or `C:file`.
+pub fn resolve(game_dir: &Path, path: &str) -> Result<PathBuf, String> {
+    let relative = Path::new(path);
+    if relative
+        .components()
+        .any(|c| !matches!(c, Component::Normal(_) | Component::CurDir))
+    {
+        return Err(format!("Invalid game file path: {}", path));
+    }
+    Ok(game_dir.join(relative))
+}
+
+fn find(files: &GameFiles, path: &str) -> Result<GameFile, String> {
+    files.find(path).ok_or_else(|| format!("{}: no such file", path))
+}
+
+/// Read a text file from the game folder, e.g. `read_game_file("data/Scenario.csv")`.
+#[tauri::command]
+pub fn read_game_file(files: State<'_, GameFiles>, path: String, encoding: Option<String>) -> Result<String, String> {
+    let bytes = match find(&files, &path)? {
+        GameFile::Loose(full_path) => fs::read(&full_path).map_err(|e| format!("{}: {}", path, e))?,
+        GameFile::Packed(data) => data,
+    };
+    decode(&bytes, encoding.as_deref())
+}
+
+/// Read any file from the game folder as raw bytes (an `ArrayBuffer` on the JS side), e.g.
+/// `read_game_file_bytes("img/pictures/Map.png", 0, 24)` for a PNG's size. Reading past the
+/// end returns what there is.
+#[tauri::command]
+pub fn read_game_file_bytes(
+    files: State<'_, GameFiles>,
+    path: String,
+    offset: Option<u64>,
+    length: Option<u64>,
+) -> Result<Response, String> {
+    let full_path = match find(&files, &path)? {
+        GameFile::Loose(full_path) => full_path,
+        GameFile::Packed(data) => {
+            let start = (offset.unwrap_or(0) as usize).min(data.len());
+            let end = match length {
+                Some(length) => start.saturating_add(length as usize).min(data.len()),
+                None => data.len(),
+            };
+            return Ok(Response::new(data[start..end].to_vec()));
+        }
+    };
+    let error = |e: std::io::Error| format!("{}: {}", path, e);
+    let mut file = fs::File::open(&full_path).map_err(error)?;
+    file.seek(SeekFrom::Start(offset.unwrap_or(0))).map_err(error)?;
+    let mut bytes = Vec::new();
+    match length {
+        Some(length) => file.take(length).read_to_end(&mut bytes),
+        None => file.read_to_end(&mut bytes),
+    }
+    .map_err(error)?;
+    Ok(Response::new(bytes))
+}
+
+/// List the folder at `path` in the game folder, sorted by path, e.g.
+/// `list_directory("img/pictures", false, "*.png")`. With `recursive`, the contents of the
+/// folders in it are listed too. `glob` keeps the entries whose path matches it, ignoring
+/// case, where `*` matches anything, `/` included.
+#[tauri::command]
+pub fn list_directory(
+    files: State<'_, GameFiles>,
+    path: String,
+    recursive: bool,
+    glob: Option<String>,
+) -> Result<Vec<DirectoryEntry>, String> {
+    let mut entries = Vec::new();
+    if !collect_entries(&files, &path, "", recursive, &mut entries) {
+        return Err(format!("{}: not a folder", path));
+    }
+    if let Some(glob) = glob.filter(|glob| !glob.is_empty()) {
+        let glob = glob.to_lowercase();
+        entries.retain(|entry| optimize::wildcard_match(&glob, &entry.path.to_lowercase()));
+    }
+    entries.sort_by(|a, b| a.path.cmp(&b.path));
+    Ok(entries)
+}
+
+/// Add the entries of the folder at `dir` to `entries`. Returns `false` if there is no such
+/// folder.
+fn collect_entries(
+    files: &GameFiles,
+    dir: &str,
+    prefix: &str,
+    recursive: bool,
+    entries: &mut Vec<DirectoryEntry>,
+) -> bool {
+    let Some(names) = files.read_dir(dir) else {
+        return false;
+    };
+    for name in names {
+        let full_path = format!("{}/{}", dir, name);
+        let path = format!("{}{}", prefix, name);
+        let is_dir = files.stat(&full_path).is_some_and(|stat| stat.is_directory);
+        if is_dir && recursive {
+            collect_entries(files, &full_path, &format!("{}/", path), recursive, entries);
+        }
+        entries.push(DirectoryEntry { path, is_dir });
+    }
+    true
+}
+
+/// Size and modification time of a file or folder in the game folder, e.g.
+/// `file_stat("img/pictures/Map.png")`. Paths under `save/` are in the save folder, where
+/// NW.js games find their saves.
+#[tauri::command]
+pub fn file_stat(files: State<'_, GameFiles>, path: String) -> Result<FileStat, String> {
+    let stat = files
+        .stat(&path)
+        .ok_or_else(|| format!("{}: no such file or folder", path))?;
+    Ok(FileStat {
+        size: stat.size,
+        modified: stat.mtime_ms,
+        is_dir: stat.is_directory,
+    })
+}
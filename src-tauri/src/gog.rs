@@ -0,0 +1,197 @@
+//! GOG Galaxy integration, built with the optional `gog` cargo feature and enabled by `gog`
+//! in `launcher.json`.
+//!
+//! Mirrors the Steam integration for achievements: launcher achievements unlocked through
+//! `unlock_achievement` are reported to Galaxy as well. When a game is configured for both
+//! stores, the GOG installer's `goggame-*.info` file next to the executable picks Galaxy.
+
+use std::path::PathBuf;
+
+use tauri::State;
+
+use crate::config::GogConfig;
+
+/// Whether the launcher was installed by GOG (installer or Galaxy).
+pub fn is_gog_install() -> bool {
+    let Some(dir) = std::env::current_exe()
+        .ok()
+        .and_then(|exe| exe.parent().map(PathBuf::from))
+    else {
+        return false;
+    };
+    std::fs::read_dir(dir).into_iter().flatten().flatten().any(|entry| {
+        let name = entry.file_name().to_string_lossy().to_lowercase();
+        name.starts_with("goggame-") && name.ends_with(".info")
+    })
+}
+
+#[cfg(feature = "gog")]
+mod ffi {
+    use std::os::raw::c_char;
+
+    extern "C" {
+        pub fn galaxy_shim_init(client_id: *const c_char, client_secret: *const c_char) -> bool;
+        pub fn galaxy_shim_process_data();
+        pub fn galaxy_shim_signed_in() -> bool;
+        pub fn galaxy_shim_request_stats() -> bool;
+        pub fn galaxy_shim_set_achievement(name: *const c_char) -> bool;
+        pub fn galaxy_shim_clear_achievement(name: *const c_char) -> bool;
+        pub fn galaxy_shim_is_achieved(name: *const c_char, unlocked: *mut bool) -> bool;
+    }
+}
+
+/// The Galaxy client, managed as app state. Empty when Galaxy isn't in use.
+pub struct Gog {
+    /// Galaxy calls are serialized, including the background `ProcessData` pump.
+    #[cfg(feature = "gog")]
+    lock: Option<std::sync::Arc<std::sync::Mutex<()>>>,
+}
+
+#[cfg(feature = "gog")]
+impl Gog {
+    pub fn init(config: Option<&GogConfig>) -> Self {
+        use std::ffi::CString;
+        use std::sync::{Arc, Mutex};
+
+        let Some(config) = config else {
+            return Self { lock: None };
+        };
+        let (Ok(client_id), Ok(client_secret)) =
+            (CString::new(config.client_id.as_str()), CString::new(config.client_secret.as_str()))
+        else {
+            return Self { lock: None };
+        };
+
+        if !unsafe { ffi::galaxy_shim_init(client_id.as_ptr(), client_secret.as_ptr()) } {
+            log::warn!("GOG Galaxy failed to initialize");
+            return Self { lock: None };
+        }
+        log::info!("GOG Galaxy initialized");
+
+        let lock = Arc::new(Mutex::new(()));
+        let pump = lock.clone();
+        let mut requested_stats = false;
+        std::thread::spawn(move || loop {
+            {
+                let _guard = pump.lock().unwrap();
+                unsafe { ffi::galaxy_shim_process_data() };
+                // Stats can only be requested once the sign-in finished
+                if !requested_stats && unsafe { ffi::galaxy_shim_signed_in() } {
+                    requested_stats = unsafe { ffi::galaxy_shim_request_stats() };
+                }
+            }
+            std::thread::sleep(std::time::Duration::from_millis(50));
+        });
+        Self { lock: Some(lock) }
+    }
+
+    fn call<T>(&self, f: impl FnOnce() -> Result<T, String>) -> Result<T, String> {
+        let lock = self.lock.as_ref().ok_or_else(|| "GOG Galaxy is not available".to_string())?;
+        let _guard = lock.lock().unwrap();
+        f()
+    }
+
+    pub fn available(&self) -> bool {
+        self.lock.is_some()
+    }
+}
+
+#[cfg(not(feature = "gog"))]
+impl Gog {
+    pub fn init(config: Option<&GogConfig>) -> Self {
+        if config.is_some() {
+            log::warn!("gog is set but this launcher was built without the `gog` feature");
+        }
+        Self {}
+    }
+
+    pub fn available(&self) -> bool {
+        false
+    }
+}
+
+#[cfg(feature = "gog")]
+mod commands {
+    use std::ffi::CString;
+
+    use super::{ffi, Gog};
+
+    fn name(name: &str) -> Result<CString, String> {
+        CString::new(name).map_err(|e| e.to_string())
+    }
+
+    pub fn unlock_achievement(gog: &Gog, id: &str) -> Result<(), String> {
+        let id = name(id)?;
+        gog.call(|| {
+            if unsafe { ffi::galaxy_shim_set_achievement(id.as_ptr()) } {
+                Ok(())
+            } else {
+                Err(format!("Galaxy rejected achievement {:?}", id))
+            }
+        })
+    }
+
+    pub fn clear_achievement(gog: &Gog, id: &str) -> Result<(), String> {
+        let id = name(id)?;
+        gog.call(|| {
+            if unsafe { ffi::galaxy_shim_clear_achievement(id.as_ptr()) } {
+                Ok(())
+            } else {
+                Err(format!("Galaxy rejected achievement {:?}", id))
+            }
+        })
+    }
+
+    pub fn is_achieved(gog: &Gog, id: &str) -> Result<bool, String> {
+        let id = name(id)?;
+        gog.call(|| {
+            let mut unlocked = false;
+            if unsafe { ffi::galaxy_shim_is_achieved(id.as_ptr(), &mut unlocked) } {
+                Ok(unlocked)
+            } else {
+                Err(format!("Unknown achievement: {:?}", id))
+            }
+        })
+    }
+}
+
+#[cfg(not(feature = "gog"))]
+mod commands {
+    use super::Gog;
+
+    const UNAVAILABLE: &str = "This launcher was built without GOG Galaxy support";
+
+    pub fn unlock_achievement(_gog: &Gog, _id: &str) -> Result<(), String> {
+        Err(UNAVAILABLE.to_string())
+    }
+
+    pub fn clear_achievement(_gog: &Gog, _id: &str) -> Result<(), String> {
+        Err(UNAVAILABLE.to_string())
+    }
+
+    pub fn is_achieved(_gog: &Gog, _id: &str) -> Result<bool, String> {
+        Err(UNAVAILABLE.to_string())
+    }
+}
+
+pub use commands::unlock_achievement;
+
+#[tauri::command]
+pub fn gog_status(gog: State<'_, Gog>) -> bool {
+    gog.available()
+}
+
+#[tauri::command]
+pub fn gog_unlock_achievement(gog: State<'_, Gog>, id: String) -> Result<(), String> {
+    commands::unlock_achievement(&gog, &id)
+}
+
+#[tauri::command]
+pub fn gog_clear_achievement(gog: State<'_, Gog>, id: String) -> Result<(), String> {
+    commands::clear_achievement(&gog, &id)
+}
+
+#[tauri::command]
+pub fn gog_is_achieved(gog: State<'_, Gog>, id: String) -> Result<bool, String> {
+    commands::is_achieved(&gog, &id)
+}
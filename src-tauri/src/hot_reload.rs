@@ -0,0 +1,51 @@
+//! Watch mode for game authors (`--watch`).
+//!
+//! The launcher polls the `data` folder and emits `data-file-changed` when a file is saved
+//! from the editor. The injected `hot_reload.js` swaps in the current map's events and the
+//! common events, so event changes show up without restarting the playthrough.
+
+use std::collections::HashMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, SystemTime};
+
+use tauri::{AppHandle, Emitter, Runtime};
+
+/// How often the data folder is scanned.
+const POLL_INTERVAL: Duration = Duration::from_secs(1);
+
+fn modified_times(dir: &Path) -> HashMap<String, SystemTime> {
+    fs::read_dir(dir)
+        .into_iter()
+        .flatten()
+        .flatten()
+        .filter_map(|entry| {
+            let name = entry.file_name().to_string_lossy().to_string();
+            if !name.to_lowercase().ends_with(".json") {
+                return None;
+            }
+            Some((name, entry.metadata().ok()?.modified().ok()?))
+        })
+        .collect()
+}
+
+/// Watch `data_dir` on a background thread for the rest of the session.
+pub fn watch<R: Runtime>(app: &AppHandle<R>, data_dir: PathBuf) {
+    log::info!("Watching {:?} for changes", data_dir);
+    let app = app.clone();
+    std::thread::spawn(move || {
+        let mut known = modified_times(&data_dir);
+        loop {
+            std::thread::sleep(POLL_INTERVAL);
+            let current = modified_times(&data_dir);
+            for (name, modified) in &current {
+                if known.get(name) == Some(modified) {
+                    continue;
+                }
+                log::info!("Data file changed: {}", name);
+                let _ = app.emit("data-file-changed", name);
+            }
+            known = current;
+        }
+    });
+}
@@ -0,0 +1,57 @@
+// Toasts for launcher achievements, and the API games use to unlock them.
+(function () {
+  "use strict";
+
+  var launcher = window.__RPGMAKER_LAUNCHER__;
+
+  launcher.unlockAchievement = function (id) {
+    return launcher.invoke("unlock_achievement", { id: String(id) });
+  };
+
+  launcher.getAchievements = function () {
+    return launcher.invoke("get_achievements");
+  };
+
+  function showToast(achievement) {
+    var toast = document.createElement("div");
+    toast.style.cssText =
+      "position:fixed;left:50%;top:16px;z-index:2147483647;display:flex;align-items:center;gap:12px;" +
+      "min-width:280px;padding:10px 16px;background:rgba(0,0,0,0.85);color:#fff;font:14px sans-serif;" +
+      "border-radius:6px;pointer-events:none;transition:opacity 0.4s,transform 0.4s;" +
+      "opacity:0;transform:translate(-50%,-20px)";
+
+    if (achievement.icon) {
+      var icon = document.createElement("img");
+      icon.src = "/" + achievement.icon.replace(/^\/+/, "");
+      icon.style.cssText = "width:48px;height:48px;image-rendering:pixelated";
+      toast.appendChild(icon);
+    }
+
+    var text = document.createElement("div");
+    var heading = document.createElement("div");
+    heading.textContent = "Achievement unlocked";
+    heading.style.cssText = "font-size:11px;color:#e5c07b;text-transform:uppercase";
+    var name = document.createElement("div");
+    name.textContent = achievement.name;
+    name.style.fontWeight = "bold";
+    var description = document.createElement("div");
+    description.textContent = achievement.description;
+    description.style.cssText = "font-size:12px;color:#bbb";
+    text.append(heading, name, description);
+    toast.appendChild(text);
+
+    (document.body || document.documentElement).appendChild(toast);
+    requestAnimationFrame(function () {
+      toast.style.opacity = "1";
+      toast.style.transform = "translate(-50%,0)";
+    });
+    setTimeout(function () {
+      toast.style.opacity = "0";
+      setTimeout(function () {
+        toast.remove();
+      }, 400);
+    }, 4000);
+  }
+
+  launcher.listen("achievement-unlocked", showToast);
+})();
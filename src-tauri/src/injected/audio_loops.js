@@ -0,0 +1,37 @@
+// Fills in BGM loop points the engine couldn't read from the file it played (e.g. the .m4a
+// fallback), using the LOOPSTART/LOOPLENGTH tags of the matching Ogg file.
+(function () {
+  "use strict";
+
+  var launcher = window.__RPGMAKER_LAUNCHER__;
+  var loops = {};
+
+  // "audio/bgm/Theme1.m4a", with or without origin and encoding, to "audio/bgm/Theme1"
+  function key(url) {
+    var path = String(url || "").replace(/^[a-z]+:\/\/[^/]+/i, "").replace(/[?#].*$/, "");
+    try {
+      path = decodeURIComponent(path);
+    } catch (e) {}
+    return path.replace(/^\/+/, "").replace(/\.[^./]+$/, "");
+  }
+
+  launcher.invoke("get_audio_loops").then(function (value) {
+    loops = value;
+  });
+
+  launcher.whenGraphicsReady(function () {
+    if (typeof WebAudio === "undefined" || !WebAudio.prototype._readLoopComments) {
+      return;
+    }
+    var readLoopComments = WebAudio.prototype._readLoopComments;
+    WebAudio.prototype._readLoopComments = function () {
+      readLoopComments.apply(this, arguments);
+      var info = loops[key(this._url)];
+      if (info && !(this._loopLength > 0)) {
+        this._loopStart = info.loopStart;
+        this._loopLength = info.loopLength;
+        this._sampleRate = info.sampleRate;
+      }
+    };
+  });
+})();
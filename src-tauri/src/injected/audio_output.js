@@ -0,0 +1,79 @@
+// Reports the audio output devices to the launcher and plays the game's WebAudio on the one
+// chosen in the game settings, where the webview can route an AudioContext (setSinkId).
+(function () {
+  "use strict";
+
+  var launcher = window.__RPGMAKER_LAUNCHER__;
+  var mediaDevices = navigator.mediaDevices;
+  var device = null;
+  var applied = null;
+
+  function context() {
+    return typeof WebAudio !== "undefined" ? WebAudio._context : null;
+  }
+
+  function canSelect() {
+    return typeof AudioContext !== "undefined" && typeof AudioContext.prototype.setSinkId === "function";
+  }
+
+  function report() {
+    if (!mediaDevices || !mediaDevices.enumerateDevices) {
+      return;
+    }
+    mediaDevices
+      .enumerateDevices()
+      .then(function (devices) {
+        var list = devices
+          .filter(function (d) {
+            return d.kind === "audiooutput" && d.deviceId !== "default" && d.deviceId !== "communications";
+          })
+          .map(function (d) {
+            return { id: d.deviceId, label: d.label };
+          });
+        return launcher.invoke("report_audio_devices", { list: list, canSelect: canSelect() });
+      })
+      .catch(function (error) {
+        console.warn("[launcher] Could not list audio devices:", error);
+      });
+  }
+
+  function apply() {
+    var ctx = context();
+    if (!ctx || !canSelect() || device === applied) {
+      return;
+    }
+    var target = device;
+    // An empty id is the system default
+    ctx
+      .setSinkId(target || "")
+      .then(function () {
+        applied = target;
+      })
+      .catch(function (error) {
+        console.warn("[launcher] Could not switch the audio device:", error);
+      });
+  }
+
+  window.addEventListener("load", function () {
+    // Not a game page, e.g. the diagnostics page
+    if (typeof SceneManager === "undefined" && typeof Main === "undefined") {
+      return;
+    }
+    report();
+    if (mediaDevices && mediaDevices.addEventListener) {
+      mediaDevices.addEventListener("devicechange", report);
+    }
+  });
+
+  // MZ creates the context after the page scripts load
+  launcher.whenGraphicsReady(apply);
+
+  launcher.invoke("get_settings").then(function (settings) {
+    device = settings.audioDevice || null;
+    apply();
+  });
+  launcher.listen("settings-changed", function (settings) {
+    device = settings.audioDevice || null;
+    apply();
+  });
+})();
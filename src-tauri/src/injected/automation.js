@@ -0,0 +1,41 @@
+// Page side of the automation endpoint: evaluates what the launcher sends in the page's global
+// scope and answers with the value as JSON, waiting for promises.
+(function () {
+  "use strict";
+
+  var launcher = window.__RPGMAKER_LAUNCHER__;
+
+  function toJson(value) {
+    if (value === undefined || typeof value === "function") {
+      return null;
+    }
+    try {
+      return JSON.parse(JSON.stringify(value));
+    } catch (e) {
+      return String(value);
+    }
+  }
+
+  function reply(id, ok, value) {
+    var output = ok ? toJson(value) : String(value && value.stack ? value.stack : value);
+    launcher.invoke("automation_result", { id: id, ok: ok, output: output }).catch(function () {});
+  }
+
+  launcher.automationEval = function (id, code) {
+    var result;
+    try {
+      result = (0, eval)(code);
+    } catch (e) {
+      reply(id, false, e);
+      return;
+    }
+    Promise.resolve(result).then(
+      function (value) {
+        reply(id, true, value);
+      },
+      function (e) {
+        reply(id, false, e);
+      }
+    );
+  };
+})();
@@ -0,0 +1,73 @@
+// Boot probe injected by the launcher. Reports JS errors and the moment the game
+// reaches its first real scene to the localhost server, which falls back to the
+// diagnostics page if that never happens.
+(function () {
+  "use strict";
+
+  function report(payload) {
+    try {
+      fetch("/__launcher/report", {
+        method: "POST",
+        body: JSON.stringify(payload),
+        keepalive: true,
+      });
+    } catch (e) {
+      // Nothing left to report to
+    }
+  }
+
+  window.addEventListener("error", function (event) {
+    report({
+      type: "error",
+      message: String(event.message),
+      source: (event.filename || "") + ":" + (event.lineno || 0) + ":" + (event.colno || 0),
+    });
+  });
+
+  window.addEventListener("unhandledrejection", function (event) {
+    report({ type: "error", message: "Unhandled rejection: " + String(event.reason), source: "" });
+  });
+
+  var started = false;
+  function markStarted() {
+    if (!started) {
+      started = true;
+      report({ type: "started" });
+    }
+  }
+
+  var painted = false;
+  function markPainted() {
+    if (!painted) {
+      painted = true;
+      // Give the frame a moment to reach the screen. Not requestAnimationFrame: the
+      // window is still hidden behind the splash screen, where it never fires.
+      setTimeout(function () {
+        report({ type: "painted" });
+      }, 50);
+    }
+  }
+
+  window.addEventListener("load", function () {
+    // Not an RPG Maker page, nothing to wait for. MZ only defines Main at this point,
+    // its core scripts are loaded afterwards.
+    if (typeof SceneManager === "undefined" && typeof Main === "undefined") {
+      markPainted();
+      markStarted();
+      return;
+    }
+
+    window.__RPGMAKER_LAUNCHER__.whenGraphicsReady(markPainted);
+
+    var timer = setInterval(function () {
+      if (typeof SceneManager === "undefined") {
+        return;
+      }
+      var scene = SceneManager._scene;
+      if (scene && (typeof Scene_Boot === "undefined" || !(scene instanceof Scene_Boot))) {
+        clearInterval(timer);
+        markStarted();
+      }
+    }, 250);
+  });
+})();
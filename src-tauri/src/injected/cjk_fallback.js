@@ -0,0 +1,27 @@
+// Falls back to a CJK font for glyphs the game's own font lacks: adds the @font-face rule and
+// appends the family to the font stack the engine draws text with.
+(function (css, family) {
+  "use strict";
+
+  var launcher = window.__RPGMAKER_LAUNCHER__;
+  var quoted = '"' + family + '"';
+
+  var style = document.createElement("style");
+  style.id = "launcher-cjk-fallback";
+  style.textContent = css + "body { font-family: sans-serif, " + quoted + "; }\n";
+  (document.head || document.documentElement).appendChild(style);
+
+  launcher.whenGraphicsReady(function () {
+    if (typeof Bitmap === "undefined" || !Bitmap.prototype._makeFontNameText) {
+      return;
+    }
+    var makeFontNameText = Bitmap.prototype._makeFontNameText;
+    Bitmap.prototype._makeFontNameText = function () {
+      return makeFontNameText.apply(this, arguments) + ", " + quoted;
+    };
+    // Start loading it now, so the first message doesn't draw before the font is ready
+    if (document.fonts && document.fonts.load) {
+      document.fonts.load("16px " + quoted, "あ一가").catch(function () {});
+    }
+  });
+})(__CSS__, __FAMILY__);
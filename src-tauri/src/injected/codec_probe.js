@@ -0,0 +1,33 @@
+// Asks the webview which media formats it can decode and reports them to the launcher,
+// which transcodes the game's audio or explains which codecs to install.
+(function () {
+  "use strict";
+
+  var launcher = window.__RPGMAKER_LAUNCHER__;
+
+  function canPlay(element, type) {
+    try {
+      return document.createElement(element).canPlayType(type) !== "";
+    } catch (e) {
+      return false;
+    }
+  }
+
+  window.addEventListener("load", function () {
+    // Not a game page, e.g. the diagnostics page
+    if (typeof SceneManager === "undefined" && typeof Main === "undefined") {
+      return;
+    }
+    launcher
+      .invoke("report_codecs", {
+        support: {
+          ogg: canPlay("audio", 'audio/ogg; codecs="vorbis"'),
+          webm: canPlay("video", 'video/webm; codecs="vp8, vorbis"') || canPlay("video", "video/webm"),
+          aac: canPlay("audio", 'audio/mp4; codecs="mp4a.40.2"'),
+        },
+      })
+      .catch(function (error) {
+        console.warn("[launcher] Codec check failed:", error);
+      });
+  });
+})();
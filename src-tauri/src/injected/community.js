@@ -0,0 +1,36 @@
+// Polls the switches and variables used by community achievement conditions and reports
+// them to the launcher, which evaluates the conditions. Only injected when a set is installed.
+(function () {
+  "use strict";
+
+  var launcher = window.__RPGMAKER_LAUNCHER__;
+  var POLL_INTERVAL = 1000;
+
+  function poll(watch) {
+    // Nothing to read on the title screen or before a game is loaded
+    if (typeof $gameSwitches === "undefined" || !$gameSwitches || !$gameMap || $gameMap.mapId() <= 0) {
+      return;
+    }
+    var state = { switches: {}, variables: {} };
+    watch.switches.forEach(function (id) {
+      state.switches[id] = !!$gameSwitches.value(id);
+    });
+    watch.variables.forEach(function (id) {
+      var value = Number($gameVariables.value(id));
+      state.variables[id] = isFinite(value) ? value : 0;
+    });
+    launcher.invoke("report_game_state", { state: state }).catch(function () {});
+  }
+
+  launcher.getCommunityAchievements = function () {
+    return launcher.invoke("get_community_achievements");
+  };
+
+  launcher.whenGraphicsReady(function () {
+    launcher.invoke("get_community_watch_list").then(function (watch) {
+      setInterval(function () {
+        poll(watch);
+      }, POLL_INTERVAL);
+    });
+  });
+})();
@@ -0,0 +1,38 @@
+// Forwards the game's console output to the launcher's debug console while it is open,
+// and always when the game's logs go to the log file (`logGameConsole` in launcher.json).
+(function () {
+  "use strict";
+
+  var launcher = window.__RPGMAKER_LAUNCHER__;
+  var logToFile = __LOG_GAME_CONSOLE__;
+  var forwarding = false;
+
+  function format(value) {
+    if (value instanceof Error) {
+      return value.stack || String(value);
+    }
+    if (typeof value === "object" && value !== null) {
+      try {
+        return JSON.stringify(value);
+      } catch (e) {
+        return String(value);
+      }
+    }
+    return String(value);
+  }
+
+  ["log", "info", "warn", "error", "debug"].forEach(function (level) {
+    var original = console[level];
+    console[level] = function () {
+      if (forwarding || logToFile) {
+        var message = Array.prototype.map.call(arguments, format).join(" ");
+        launcher.invoke("log_from_game", { level: level, message: message }).catch(function () {});
+      }
+      return original.apply(console, arguments);
+    };
+  });
+
+  launcher.listen("debug-console-toggled", function (open) {
+    forwarding = open;
+  });
+})();
@@ -0,0 +1,22 @@
+// Right-click handling: RPG Maker uses right click as cancel, which the webview's own
+// context menu gets in the way of.
+(function (mode) {
+  "use strict";
+
+  var launcher = window.__RPGMAKER_LAUNCHER__;
+
+  if (mode === "native") {
+    return;
+  }
+
+  window.addEventListener(
+    "contextmenu",
+    function (event) {
+      event.preventDefault();
+      if (mode === "launcher" && event.shiftKey) {
+        launcher.invoke("show_context_menu").catch(function () {});
+      }
+    },
+    true
+  );
+})(__CONTEXT_MENU__);
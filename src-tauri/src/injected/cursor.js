@@ -0,0 +1,70 @@
+// Cursor auto-hide after inactivity, and pointer lock helpers for mouse-driven games.
+(function () {
+  "use strict";
+
+  var launcher = window.__RPGMAKER_LAUNCHER__;
+  var hideDelay = 0;
+  var hideTimer = null;
+  var hidden = false;
+
+  var style = document.createElement("style");
+  style.textContent = "html, body, canvas, video { cursor: none !important; }";
+
+  function setHidden(value) {
+    if (value === hidden) {
+      return;
+    }
+    hidden = value;
+    if (hidden) {
+      document.head.appendChild(style);
+    } else if (style.parentNode) {
+      style.parentNode.removeChild(style);
+    }
+  }
+
+  function scheduleHide() {
+    clearTimeout(hideTimer);
+    if (hideDelay > 0) {
+      hideTimer = setTimeout(function () {
+        setHidden(true);
+      }, hideDelay * 1000);
+    }
+  }
+
+  function onActivity() {
+    setHidden(false);
+    scheduleHide();
+  }
+
+  ["mousemove", "mousedown", "wheel"].forEach(function (type) {
+    window.addEventListener(type, onActivity, { capture: true, passive: true });
+  });
+
+  function apply(settings) {
+    hideDelay = settings.cursorHideDelaySecs || 0;
+    if (hideDelay > 0) {
+      scheduleHide();
+    } else {
+      clearTimeout(hideTimer);
+      setHidden(false);
+    }
+  }
+
+  launcher.invoke("get_settings").then(apply);
+  launcher.listen("settings-changed", apply);
+
+  // Pointer lock through the DOM API, which also delivers movementX/Y to the game.
+  // Browsers only grant it from a user gesture, so call it from an input handler.
+  launcher.requestPointerLock = function () {
+    var target = (typeof Graphics !== "undefined" && Graphics._canvas) || document.querySelector("canvas");
+    if (target && target.requestPointerLock) {
+      target.requestPointerLock();
+    }
+  };
+
+  launcher.exitPointerLock = function () {
+    if (document.exitPointerLock) {
+      document.exitPointerLock();
+    }
+  };
+})();
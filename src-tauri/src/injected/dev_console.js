@@ -0,0 +1,43 @@
+// Game side of the developer console: evaluates the code it sends in the page's global scope.
+(function () {
+  "use strict";
+
+  var launcher = window.__RPGMAKER_LAUNCHER__;
+
+  function format(value) {
+    if (typeof value === "string") {
+      return value;
+    }
+    if (typeof value === "function") {
+      return value.toString();
+    }
+    try {
+      var json = JSON.stringify(value, null, 2);
+      return json === undefined ? String(value) : json;
+    } catch (e) {
+      return String(value);
+    }
+  }
+
+  function reply(id, ok, value) {
+    launcher.invoke("dev_eval_result", { id: id, ok: ok, output: format(value) }).catch(function () {});
+  }
+
+  launcher.devEval = function (id, code) {
+    var result;
+    try {
+      result = (0, eval)(code);
+    } catch (e) {
+      reply(id, false, e && e.stack ? e.stack : e);
+      return;
+    }
+    Promise.resolve(result).then(
+      function (value) {
+        reply(id, true, value);
+      },
+      function (e) {
+        reply(id, false, e && e.stack ? e.stack : e);
+      }
+    );
+  };
+})();
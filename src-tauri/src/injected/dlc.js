@@ -0,0 +1,41 @@
+// Records the enabled DLC in each save slot's info, and checks it before a slot is loaded
+// so the launcher can warn about saves that used DLC that is now missing or disabled.
+(function () {
+  "use strict";
+
+  var launcher = window.__RPGMAKER_LAUNCHER__;
+
+  launcher.whenGraphicsReady(function () {
+    if (typeof DataManager === "undefined") {
+      return;
+    }
+
+    var makeSavefileInfo = DataManager.makeSavefileInfo;
+    var active = [];
+    launcher.invoke("get_active_dlc").then(function (ids) {
+      active = ids;
+    });
+    // Packs can be toggled while the game runs
+    launcher.listen("dlc-changed", function (ids) {
+      active = ids;
+    });
+
+    DataManager.makeSavefileInfo = function () {
+      var info = makeSavefileInfo.apply(this, arguments);
+      info.dlc = active.slice();
+      return info;
+    };
+
+    var loadGame = DataManager.loadGame;
+    DataManager.loadGame = function (savefileId) {
+      // MZ keeps the slot infos in memory, MV reads them from the global save
+      var info = this.savefileInfo ? this.savefileInfo(savefileId) : this.loadSavefileInfo(savefileId);
+      if (info && info.dlc && info.dlc.length) {
+        launcher.invoke("check_save_dlc", { savedDlc: info.dlc }).catch(function (error) {
+          console.warn("[launcher] Save DLC check failed:", error);
+        });
+      }
+      return loadGame.apply(this, arguments);
+    };
+  });
+})();
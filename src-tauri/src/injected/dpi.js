@@ -0,0 +1,32 @@
+// DPI scale override: the launcher zooms the webview to the overridden scale, and the game
+// is told the same value through devicePixelRatio so its renderer sizes the canvas to match.
+(function () {
+  "use strict";
+
+  var launcher = window.__RPGMAKER_LAUNCHER__;
+  var descriptor = Object.getOwnPropertyDescriptor(window, "devicePixelRatio") ||
+    Object.getOwnPropertyDescriptor(Object.getPrototypeOf(window), "devicePixelRatio");
+  var dpiScale = null;
+
+  if (!descriptor || !descriptor.get) {
+    return;
+  }
+
+  Object.defineProperty(window, "devicePixelRatio", {
+    configurable: true,
+    get: function () {
+      return dpiScale !== null ? dpiScale : descriptor.get.call(window);
+    },
+  });
+
+  function apply(settings) {
+    var previous = dpiScale;
+    dpiScale = typeof settings.dpiScale === "number" ? settings.dpiScale : null;
+    if (dpiScale !== previous) {
+      window.dispatchEvent(new Event("resize"));
+    }
+  }
+
+  launcher.invoke("get_settings").then(apply);
+  launcher.listen("settings-changed", apply);
+})();
@@ -0,0 +1,114 @@
+// Applies the launcher's master volume, mutes the game, and mutes and/or pauses it while the window is in the background, per the
+// game settings and the launcher's background mode, or when the launcher pauses it.
+(function (background) {
+  "use strict";
+
+  var launcher = window.__RPGMAKER_LAUNCHER__;
+  var settings = {};
+  var focused = true;
+  var hidden = document.hidden;
+  var bossKey = false;
+  var launcherPaused = false;
+  var volume = null;
+  var paused = false;
+
+  function setVolume(value) {
+    if (value === volume || typeof WebAudio === "undefined") {
+      return;
+    }
+    volume = value;
+    WebAudio.setMasterVolume(value);
+  }
+
+  function setPaused(value) {
+    if (value === paused) {
+      return;
+    }
+    paused = value;
+    var context = typeof WebAudio !== "undefined" && WebAudio._context;
+    if (context) {
+      if (paused) {
+        context.suspend();
+      } else {
+        context.resume();
+      }
+    }
+    var videos = document.getElementsByTagName("video");
+    for (var i = 0; i < videos.length; i++) {
+      if (paused && !videos[i].paused) {
+        videos[i].pause();
+        videos[i].__launcherPaused = true;
+      } else if (!paused && videos[i].__launcherPaused) {
+        videos[i].__launcherPaused = false;
+        videos[i].play();
+      }
+    }
+    // MV's fixed-step loop would otherwise run every frame missed while paused
+    if (!paused && typeof SceneManager !== "undefined" && SceneManager._getTime) {
+      SceneManager._currentTime = SceneManager._getTime();
+      SceneManager._accumulator = 0;
+    }
+  }
+
+  function refresh() {
+    var muted = !!settings.muted || bossKey || (!focused && !!settings.muteOnBlur);
+    var level = typeof settings.masterVolume === "number" ? settings.masterVolume : 1;
+    setVolume(muted ? 0 : level);
+    setPaused(
+      launcherPaused || (!focused && !!settings.pauseOnBlur) || (hidden && background === "suspend")
+    );
+  }
+
+  document.addEventListener("visibilitychange", function () {
+    hidden = document.hidden;
+    refresh();
+  });
+
+  // Engines that ignore the throttling policy still don't throttle pages holding a Web Lock.
+  if (background === "run" && navigator.locks) {
+    navigator.locks.request("rpgmaker-launcher-keepalive", function () {
+      return new Promise(function () {});
+    });
+  }
+
+  launcher.isPaused = function () {
+    return paused;
+  };
+
+  // Skipping scene updates freezes the game without touching its timers or input state.
+  launcher.whenGraphicsReady(function () {
+    var updateScene = SceneManager.updateScene;
+    SceneManager.updateScene = function () {
+      if (!paused) {
+        updateScene.apply(this, arguments);
+      }
+    };
+    // Settings may have arrived before MZ defined WebAudio
+    refresh();
+  });
+
+  launcher.invoke("get_settings").then(function (value) {
+    settings = value;
+    refresh();
+  });
+  launcher.listen("settings-changed", function (value) {
+    settings = value;
+    refresh();
+  });
+  launcher.listen("boss-key", function (active) {
+    bossKey = active;
+    refresh();
+  });
+  launcher.invoke("is_game_paused").then(function (value) {
+    launcherPaused = value;
+    refresh();
+  });
+  launcher.listen("game-paused", function (value) {
+    launcherPaused = value;
+    refresh();
+  });
+  launcher.listen("window-focus-changed", function (payload) {
+    focused = payload.focused;
+    refresh();
+  });
+})(__BACKGROUND__);
@@ -0,0 +1,16 @@
+// Adds @font-face rules for the fonts in the game's fonts folder, with their paths fixed up.
+(function (css) {
+  "use strict";
+
+  var style = document.createElement("style");
+  style.id = "launcher-fonts";
+  style.textContent = css;
+
+  function append() {
+    (document.head || document.documentElement).appendChild(style);
+  }
+
+  append();
+  // Later rules for the same family win, so these go after gamefont.css once it is in the page
+  document.addEventListener("DOMContentLoaded", append);
+})(__FONT_FACES__);
@@ -0,0 +1,75 @@
+// greenworks shim: the subset of the greenworks (Steamworks for NW.js) API that MV/MZ Steam
+// plugins use, backed by the launcher's Steam commands. Callbacks follow greenworks'
+// (success, error) convention.
+(function () {
+  "use strict";
+
+  var launcher = window.__RPGMAKER_LAUNCHER__;
+  var status = { available: false };
+
+  function call(command, args, success, error, map) {
+    launcher.invoke(command, args).then(
+      function (result) {
+        if (success) {
+          success(map ? map(result) : result);
+        }
+      },
+      function (e) {
+        if (error) {
+          error(e);
+        }
+      }
+    );
+  }
+
+  var greenworks = {
+    initAPI: function () {
+      return status.available;
+    },
+    init: function () {
+      return status.available;
+    },
+    isSteamRunning: function () {
+      return status.available;
+    },
+    getSteamId: function () {
+      return { steamId: status.steamId, screenName: status.personaName, isValid: status.available };
+    },
+    activateAchievement: function (name, success, error) {
+      call("steam_unlock_achievement", { name: name }, success, error);
+    },
+    clearAchievement: function (name, success, error) {
+      call("steam_clear_achievement", { name: name }, success, error);
+    },
+    getAchievement: function (name, success, error) {
+      call("steam_is_achieved", { name: name }, success, error);
+    },
+    setStat: function (name, value) {
+      call("steam_set_stat", { name: name, value: Math.round(value) });
+      return true;
+    },
+    getStatInt: function (name, success, error) {
+      call("steam_get_stat", { name: name }, success, error);
+    },
+    storeStats: function (success) {
+      // Every change is stored right away by the launcher
+      if (success) {
+        success();
+      }
+    },
+    setRichPresence: function (key, value) {
+      call("steam_set_rich_presence", { key: key, value: value === "" ? null : value });
+      return true;
+    },
+    clearRichPresence: function () {
+      call("steam_set_rich_presence", { key: "steam_display", value: null });
+    },
+  };
+
+  launcher.invoke("steam_status").then(function (value) {
+    status = value;
+  });
+
+  window.greenworks = greenworks;
+  launcher.greenworks = greenworks;
+})();
@@ -0,0 +1,61 @@
+// Watch mode: reloads the current map's events and the common events when the editor saves
+// them. The files are read through read_data_file and swapped in place, instead of going
+// through DataManager.loadMapData, which clears $dataMap while the map is still running.
+(function () {
+  "use strict";
+
+  var launcher = window.__RPGMAKER_LAUNCHER__;
+
+  function load(name) {
+    return launcher.invoke("read_data_file", { name: name }).then(function (data) {
+      DataManager.onLoad(data);
+      return data;
+    });
+  }
+
+  function reloadMap(name) {
+    load(name).then(function (data) {
+      $dataMap = data;
+      $gameMap.events().forEach(function (event) {
+        // Force the page to be set up again, even if the same page is still active
+        if (event.event()) {
+          event._pageIndex = -2;
+          event.refresh();
+        }
+      });
+      $gameMap.requestRefresh();
+      var spriteset = SceneManager._scene && SceneManager._scene._spriteset;
+      if (spriteset && spriteset._tilemap) {
+        spriteset._tilemap.setData($dataMap.width, $dataMap.height, $dataMap.data);
+        spriteset._tilemap.refresh();
+      }
+      console.log("[launcher] Reloaded " + name + ". Added or removed events appear after a transfer.");
+    });
+  }
+
+  function reloadCommonEvents() {
+    load("CommonEvents").then(function (data) {
+      $dataCommonEvents = data;
+      $gameMap._commonEvents = $gameMap.parallelCommonEvents().map(function (commonEvent) {
+        return new Game_CommonEvent(commonEvent.id);
+      });
+      $gameMap.requestRefresh();
+      console.log("[launcher] Reloaded CommonEvents");
+    });
+  }
+
+  launcher.whenGraphicsReady(function () {
+    launcher.listen("data-file-changed", function (file) {
+      var name = String(file).replace(/\.json$/i, "");
+      // Nothing to update before a game is loaded
+      if (typeof $gameMap === "undefined" || !$gameMap || $gameMap.mapId() <= 0) {
+        return;
+      }
+      if (name === "Map" + String($gameMap.mapId()).padStart(3, "0")) {
+        reloadMap(name);
+      } else if (name === "CommonEvents") {
+        reloadCommonEvents();
+      }
+    });
+  });
+})();
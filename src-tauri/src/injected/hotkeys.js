@@ -0,0 +1,108 @@
+// Launcher hotkeys injected into the game page. Bindings come from launcher.json and set_config.
+(function (bindings) {
+  "use strict";
+
+  if (!window.__TAURI__) {
+    return;
+  }
+
+  var invoke = window.__TAURI__.core.invoke;
+  var ZOOM_STEP = 0.1;
+
+  function changeZoom(delta) {
+    invoke("get_zoom").then(function (zoom) {
+      return invoke("set_zoom", { factor: Math.round((zoom + delta) * 100) / 100 });
+    });
+  }
+
+  var actions = {
+    toggleFullscreen: function () {
+      invoke("toggle_fullscreen");
+    },
+    zoomIn: function () {
+      changeZoom(ZOOM_STEP);
+    },
+    zoomOut: function () {
+      changeZoom(-ZOOM_STEP);
+    },
+    zoomReset: function () {
+      invoke("set_zoom", { factor: 1 });
+    },
+    screenshot: function () {
+      invoke("capture_screenshot");
+    },
+    debugConsole: function () {
+      invoke("toggle_debug_console");
+    },
+    devConsole: function () {
+      invoke("toggle_dev_console").catch(function () {});
+    },
+  };
+
+  // "Ctrl+Shift+F" -> { ctrl: true, shift: true, alt: false, key: "f" }
+  function parseCombo(combo) {
+    var parsed = { ctrl: false, alt: false, shift: false, meta: false, key: "" };
+    var parts = combo.endsWith("++") ? combo.slice(0, -2).split("+").concat("+") : combo.split("+");
+    parts.forEach(function (part) {
+      var name = part.trim().toLowerCase();
+      if (name === "ctrl" || name === "control") parsed.ctrl = true;
+      else if (name === "alt") parsed.alt = true;
+      else if (name === "shift") parsed.shift = true;
+      else if (name === "meta" || name === "cmd") parsed.meta = true;
+      else if (name) parsed.key = name;
+    });
+    return parsed;
+  }
+
+  var shortcuts = [];
+
+  function bind(bindings) {
+    shortcuts = [];
+    Object.keys(bindings || {}).forEach(function (action) {
+      (bindings[action] || []).forEach(function (combo) {
+        if (actions[action]) {
+          shortcuts.push({ combo: parseCombo(combo), run: actions[action] });
+        }
+      });
+    });
+  }
+
+  bind(bindings);
+
+  // Bindings changed with set_config apply without reloading the game
+  window.__TAURI__.event.listen("config-changed", function (event) {
+    if (event.payload.changed.indexOf("hotkeys") >= 0) {
+      bind(event.payload.config.hotkeys);
+    }
+  });
+
+  function matches(combo, event) {
+    var key = event.key.toLowerCase();
+    // Shift is implied by keys like "+" on most layouts, so only check it for letters
+    var shiftMatches = combo.key.length > 1 || /[a-z0-9]/.test(combo.key) ? combo.shift === event.shiftKey : true;
+    return (
+      combo.key === key &&
+      combo.ctrl === event.ctrlKey &&
+      combo.alt === event.altKey &&
+      combo.meta === event.metaKey &&
+      shiftMatches
+    );
+  }
+
+  window.addEventListener(
+    "keydown",
+    function (event) {
+      for (var i = 0; i < shortcuts.length; i++) {
+        if (matches(shortcuts[i].combo, event)) {
+          event.preventDefault();
+          event.stopImmediatePropagation();
+          if (!event.repeat) {
+            shortcuts[i].run();
+          }
+          return;
+        }
+      }
+    },
+    true
+  );
+})(__HOTKEYS__);
@@ -0,0 +1,50 @@
+// Applies the key and gamepad bindings from the launcher. Bound keys are caught by
+// KeyboardEvent.code in the capture phase, before the engine's own listeners, and set the
+// action directly; a binding to null swallows the key. Gamepad bindings replace entries of
+// Input.gamepadMapper.
+(function () {
+  "use strict";
+
+  var launcher = window.__RPGMAKER_LAUNCHER__;
+  var keys = {};
+
+  launcher.whenGraphicsReady(function () {
+    var defaultGamepadMapper = Object.assign({}, Input.gamepadMapper);
+
+    function apply(bindings) {
+      keys = bindings.keys || {};
+      var mapper = Object.assign({}, defaultGamepadMapper);
+      Object.keys(bindings.gamepad || {}).forEach(function (button) {
+        var action = bindings.gamepad[button];
+        if (action) {
+          mapper[button] = action;
+        } else {
+          delete mapper[button];
+        }
+      });
+      Input.gamepadMapper = mapper;
+      Input.clear();
+    }
+
+    // The engine bound its handlers at startup, so bound keys are stopped before they reach them
+    function remap(pressed) {
+      return function (event) {
+        if (!Object.prototype.hasOwnProperty.call(keys, event.code)) {
+          return;
+        }
+        event.stopImmediatePropagation();
+        var action = keys[event.code];
+        if (action) {
+          event.preventDefault();
+          Input._currentState[action] = pressed;
+        }
+      };
+    }
+
+    window.addEventListener("keydown", remap(true), true);
+    window.addEventListener("keyup", remap(false), true);
+
+    launcher.invoke("get_input_bindings").then(apply).catch(function () {});
+    launcher.listen("input-bindings-changed", apply);
+  });
+})();
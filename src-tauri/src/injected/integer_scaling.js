@@ -0,0 +1,44 @@
+// Integer scaling: keeps the game canvas at whole multiples of its native resolution,
+// letterboxed in black, so pixel art stays sharp.
+(function () {
+  "use strict";
+
+  var launcher = window.__RPGMAKER_LAUNCHER__;
+  var enabled = false;
+
+  var style = document.createElement("style");
+  style.textContent =
+    "html, body { background: #000; }" +
+    "canvas, video { image-rendering: pixelated; image-rendering: crisp-edges; }";
+
+  function refresh() {
+    if (enabled && !style.parentNode) {
+      document.head.appendChild(style);
+    } else if (!enabled && style.parentNode) {
+      style.parentNode.removeChild(style);
+    }
+    if (typeof Graphics !== "undefined" && Graphics._width > 0 && Graphics._updateAllElements) {
+      Graphics._updateAllElements();
+    }
+  }
+
+  launcher.whenGraphicsReady(function (graphics) {
+    var updateRealScale = graphics._updateRealScale;
+    graphics._updateRealScale = function () {
+      updateRealScale.apply(this, arguments);
+      if (enabled && this._width > 0 && this._height > 0) {
+        var scale = Math.min(window.innerWidth / this._width, window.innerHeight / this._height);
+        this._realScale = Math.max(1, Math.floor(scale));
+      }
+    };
+    refresh();
+  });
+
+  function apply(settings) {
+    enabled = !!settings.integerScaling;
+    refresh();
+  }
+
+  launcher.invoke("get_settings").then(apply);
+  launcher.listen("settings-changed", apply);
+})();
@@ -0,0 +1,26 @@
+// Tells the launcher when a movie is playing, for the `video` keep-awake mode.
+(function () {
+  "use strict";
+
+  var launcher = window.__RPGMAKER_LAUNCHER__;
+  var playing = false;
+
+  function update() {
+    var videos = document.getElementsByTagName("video");
+    var anyPlaying = false;
+    for (var i = 0; i < videos.length; i++) {
+      if (!videos[i].paused && !videos[i].ended) {
+        anyPlaying = true;
+      }
+    }
+    if (anyPlaying !== playing) {
+      playing = anyPlaying;
+      launcher.invoke("set_video_playing", { playing: playing }).catch(function () {});
+    }
+  }
+
+  // Media events don't bubble, but they still go through the capture phase.
+  ["play", "playing", "pause", "ended", "emptied"].forEach(function (type) {
+    document.addEventListener(type, update, true);
+  });
+})();
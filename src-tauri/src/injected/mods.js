@@ -0,0 +1,18 @@
+// Appends the plugins of the enabled mods to the game's plugin list.
+(function (modPlugins) {
+  "use strict";
+
+  // plugins.js declares `var $plugins = [...]`; the declaration keeps this accessor and
+  // the assignment goes through the setter, before PluginManager.setup reads the list.
+  var plugins;
+  Object.defineProperty(window, "$plugins", {
+    configurable: true,
+    enumerable: true,
+    get: function () {
+      return plugins;
+    },
+    set: function (value) {
+      plugins = Array.isArray(value) ? value.concat(modPlugins) : value;
+    },
+  });
+})(__MOD_PLUGINS__);
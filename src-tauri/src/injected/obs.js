@@ -0,0 +1,33 @@
+// Reports the game events OBS triggers can react to.
+(function () {
+  "use strict";
+
+  var launcher = window.__RPGMAKER_LAUNCHER__;
+
+  function send(event, switchId) {
+    launcher.invoke("obs_event", { event: event, switch: switchId == null ? null : switchId }).catch(function () {});
+  }
+
+  launcher.whenGraphicsReady(function () {
+    var setValue = Game_Switches.prototype.setValue;
+    Game_Switches.prototype.setValue = function (switchId, value) {
+      var wasOn = this.value(switchId);
+      setValue.apply(this, arguments);
+      if (!wasOn && this.value(switchId)) {
+        send("switch", switchId);
+      }
+    };
+
+    var onSaveSuccess = Scene_Save.prototype.onSaveSuccess;
+    Scene_Save.prototype.onSaveSuccess = function () {
+      onSaveSuccess.apply(this, arguments);
+      send("save");
+    };
+
+    var gameoverStart = Scene_Gameover.prototype.start;
+    Scene_Gameover.prototype.start = function () {
+      gameoverStart.apply(this, arguments);
+      send("gameOver");
+    };
+  });
+})();
@@ -0,0 +1,112 @@
+// Checks which NW.js capabilities the page actually has, and reports them to the localhost
+// server for the parity report at /__launcher/parity.
+(function () {
+  "use strict";
+
+  var launcher = window.__RPGMAKER_LAUNCHER__;
+  var hasLauncher = !!window.__TAURI__;
+
+  function check(name, support, detail) {
+    return { name: name, support: support, detail: detail };
+  }
+
+  function canPlay(type) {
+    try {
+      return new Audio().canPlayType(type) !== "";
+    } catch (e) {
+      return false;
+    }
+  }
+
+  function hasWebGL() {
+    try {
+      var canvas = document.createElement("canvas");
+      return !!(canvas.getContext("webgl2") || canvas.getContext("webgl"));
+    } catch (e) {
+      return false;
+    }
+  }
+
+  function probe() {
+    var isNw = typeof require === "function" && typeof process !== "undefined" && !!process.versions.nw;
+    var capabilities = [
+      isNw
+        ? check("Filesystem (require('fs'))", "native", "Running under NW.js")
+        : check(
+            "Filesystem (require('fs'))",
+            hasLauncher ? "shimmed" : "missing",
+            hasLauncher
+              ? "require is not defined; saves are mirrored to files and game files are read through the launcher"
+              : "require is not defined and the launcher API is unavailable"
+          ),
+      check(
+        "process",
+        isNw ? "native" : typeof process !== "undefined" ? "shimmed" : "missing",
+        typeof process !== "undefined"
+          ? "argv, platform, env and cwd() only; no mainModule or execPath"
+          : "process is not defined"
+      ),
+      check(
+        "nw.Window / nw.App",
+        isNw ? "native" : typeof nw !== "undefined" ? "shimmed" : "missing",
+        typeof nw !== "undefined" ? "Fullscreen and App.argv only" : "nw is not defined"
+      ),
+      check(
+        "Developer tools",
+        hasLauncher ? "shimmed" : "missing",
+        hasLauncher ? "F8 in the editor's test play, or the open_devtools command" : "No way to open them from the page"
+      ),
+      check(
+        "Gamepad API",
+        typeof navigator.getGamepads === "function" ? "native" : "missing",
+        typeof navigator.getGamepads === "function"
+          ? "navigator.getGamepads() is available"
+          : "navigator.getGamepads() is not available in this webview"
+      ),
+      check(
+        "Fullscreen",
+        document.fullscreenEnabled || document.webkitFullscreenEnabled ? "native" : hasLauncher ? "shimmed" : "missing",
+        hasLauncher ? "Window fullscreen through the launcher" : "Element fullscreen only"
+      ),
+      check("WebGL", hasWebGL() ? "native" : "missing", hasWebGL() ? "" : "The game falls back to canvas rendering, if it can"),
+      check(
+        "Web Audio",
+        window.AudioContext || window.webkitAudioContext ? "native" : "missing",
+        ""
+      ),
+      check(
+        "Ogg Vorbis audio",
+        canPlay('audio/ogg; codecs="vorbis"') ? "native" : "missing",
+        canPlay('audio/ogg; codecs="vorbis"') ? "" : "The engine uses the .m4a files instead; games without them play no sound"
+      ),
+    ];
+
+    var storage = check("Storage quota", "native", "");
+    capabilities.push(storage);
+    var estimate =
+      navigator.storage && navigator.storage.estimate ? navigator.storage.estimate() : Promise.resolve(null);
+    estimate
+      .then(function (result) {
+        if (result && result.quota) {
+          storage.detail =
+            Math.round(result.usage / 1048576) + " MB used of " + Math.round(result.quota / 1048576) + " MB";
+        } else {
+          storage.detail = "Quota unknown";
+        }
+        if (hasLauncher) {
+          storage.detail += "; saves are also mirrored to files, which have no quota";
+        }
+      })
+      .catch(function () {
+        storage.detail = "Quota unknown";
+      })
+      .then(function () {
+        fetch("/__launcher/report", {
+          method: "POST",
+          body: JSON.stringify({ type: "parity", capabilities: capabilities }),
+        }).catch(function () {});
+      });
+  }
+
+  window.addEventListener("load", probe);
+})();
@@ -0,0 +1,168 @@
+// NW.js compatibility polyfill injected into the game page by the launcher.
+// Reads the launch context from window.__RPGMAKER_LAUNCHER__, set right before this script.
+(function () {
+  "use strict";
+
+  var launcher = window.__RPGMAKER_LAUNCHER__ || {};
+
+  // Only `process` is provided: defining `require` too would make Utils.isNwjs()
+  // return true and send the game down its NW.js-only code paths.
+  if (typeof window.process === "undefined") {
+    window.process = {
+      argv: [launcher.execPath].concat(launcher.argv || []),
+      env: {},
+      platform: launcher.platform,
+      versions: {},
+      cwd: function () {
+        return launcher.gameDir;
+      },
+      on: function () {},
+    };
+  }
+
+  // Minimal `nw` global: Utils.isOptionValid reads nw.App.argv, and plugins written for
+  // NW.js builds drive fullscreen through nw.Window.
+  if (typeof window.nw === "undefined") {
+    var invoke = window.__TAURI__ ? window.__TAURI__.core.invoke : function () {
+      return Promise.reject(new Error("Tauri API unavailable"));
+    };
+    var fullscreen = false;
+    var setFullscreen = function (promise) {
+      promise.then(function (state) {
+        fullscreen = state;
+      });
+    };
+
+    var gameWindow = {
+      enterFullscreen: function () {
+        setFullscreen(invoke("set_fullscreen", { fullscreen: true }));
+      },
+      leaveFullscreen: function () {
+        setFullscreen(invoke("set_fullscreen", { fullscreen: false }));
+      },
+      toggleFullscreen: function () {
+        setFullscreen(invoke("toggle_fullscreen"));
+      },
+      get isFullscreen() {
+        return fullscreen;
+      },
+      on: function () {},
+    };
+
+    window.nw = {
+      App: {
+        argv: (launcher.argv || []).slice(),
+      },
+      Window: {
+        get: function () {
+          return gameWindow;
+        },
+      },
+    };
+  }
+
+  // Synchronous, read-only access to the game's files for plugins ported from NW.js, where
+  // they used require("fs"): window.__RPGMAKER_LAUNCHER__.fs.readFileSync("data/Extra.json",
+  // "utf8"). Paths are relative to the game folder, or absolute under process.cwd(), and see
+  // the files the game does, mods and packed archives included, with the saves under "save/".
+  // The asynchronous helpers look files up the same way. Like them, they need the "fs-read"
+  // permission, and find nothing without it.
+  function relativePath(path) {
+    var normalized = String(path).replace(/\\/g, "/");
+    var root = String(launcher.gameDir || "").replace(/\\/g, "/").replace(/\/+$/, "");
+    if (root && normalized.indexOf(root + "/") === 0) {
+      normalized = normalized.slice(root.length + 1);
+    }
+    return normalized.replace(/^\/+/, "");
+  }
+
+  function fsRequest(route, path, mimeType) {
+    var xhr = new XMLHttpRequest();
+    xhr.open("GET", "/__launcher/fs/" + route + "?path=" + encodeURIComponent(relativePath(path)), false);
+    if (mimeType) {
+      xhr.overrideMimeType(mimeType);
+    }
+    xhr.send();
+    return xhr;
+  }
+
+  function notFound(syscall, path) {
+    var error = new Error("ENOENT: no such file or directory, " + syscall + " '" + path + "'");
+    error.code = "ENOENT";
+    error.syscall = syscall;
+    error.path = path;
+    return error;
+  }
+
+  launcher.fs = {
+    // A string with an encoding, otherwise a Uint8Array.
+    readFileSync: function (path, options) {
+      var encoding = typeof options === "string" ? options : options && options.encoding;
+      // Synchronous requests can't ask for an ArrayBuffer, but x-user-defined keeps every
+      // byte in the low half of a character
+      var mimeType = encoding ? "text/plain; charset=utf-8" : "text/plain; charset=x-user-defined";
+      var xhr = fsRequest("read", path, mimeType);
+      if (xhr.status !== 200) {
+        throw notFound("open", path);
+      }
+      if (encoding) {
+        return xhr.responseText;
+      }
+      var text = xhr.responseText;
+      var bytes = new Uint8Array(text.length);
+      for (var i = 0; i < text.length; i++) {
+        bytes[i] = text.charCodeAt(i) & 0xff;
+      }
+      return bytes;
+    },
+    existsSync: function (path) {
+      var xhr = fsRequest("exists", path);
+      return xhr.status === 200 && xhr.responseText === "true";
+    },
+    // Names in a folder, as in Node without options. listDirectory is the asynchronous,
+    // recursive version with a glob: listDirectory("img/pictures", { glob: "*.png" }).
+    readdirSync: function (path) {
+      var xhr = fsRequest("readdir", path);
+      if (xhr.status !== 200) {
+        throw notFound("scandir", path);
+      }
+      return JSON.parse(xhr.responseText);
+    },
+    listDirectory: function (path, options) {
+      options = options || {};
+      if (!window.__TAURI__) {
+        return Promise.reject(new Error("Tauri API unavailable"));
+      }
+      return window.__TAURI__.core.invoke("list_directory", {
+        path: relativePath(path),
+        recursive: !!options.recursive,
+        glob: options.glob || null,
+      });
+    },
+    // Size and modification time of a file, like statSync.
+    stat: function (path) {
+      if (!window.__TAURI__) {
+        return Promise.reject(new Error("Tauri API unavailable"));
+      }
+      return window.__TAURI__.core.invoke("file_stat", { path: relativePath(path) });
+    },
+    statSync: function (path) {
+      var xhr = fsRequest("stat", path);
+      if (xhr.status !== 200) {
+        throw notFound("stat", path);
+      }
+      var stat = JSON.parse(xhr.responseText);
+      return {
+        size: stat.size,
+        mtimeMs: stat.mtimeMs,
+        mtime: new Date(stat.mtimeMs || 0),
+        isFile: function () {
+          return stat.isFile;
+        },
+        isDirectory: function () {
+          return stat.isDirectory;
+        },
+      };
+    },
+  };
+})();
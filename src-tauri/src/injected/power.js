@@ -0,0 +1,28 @@
+// Recovers from system sleep: restarts the audio context, which browsers suspend with the
+// system, and resets the engine's frame timing so it doesn't try to catch up on the time
+// spent asleep.
+(function () {
+  "use strict";
+
+  var launcher = window.__RPGMAKER_LAUNCHER__;
+
+  launcher.listen("system-resumed", function () {
+    if (typeof WebAudio !== "undefined" && WebAudio._context && WebAudio._context.state !== "running") {
+      WebAudio._context.resume().catch(function () {});
+    }
+    if (typeof SceneManager === "undefined") {
+      return;
+    }
+    // MV runs a fixed step accumulator, MZ smooths the ticker's delta time
+    if ("_accumulator" in SceneManager) {
+      SceneManager._accumulator = 0;
+      SceneManager._currentTime = SceneManager._getTime ? SceneManager._getTime() : performance.now();
+    }
+    if ("_smoothDeltaTime" in SceneManager) {
+      SceneManager._smoothDeltaTime = 1;
+    }
+    if (typeof Graphics !== "undefined" && Graphics._app && Graphics._app.ticker) {
+      Graphics._app.ticker.lastTime = performance.now();
+    }
+  });
+})();
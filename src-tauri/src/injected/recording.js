@@ -0,0 +1,124 @@
+// Gameplay recording. Video uses MediaRecorder on the game canvas (plus the WebAudio
+// master output); GIFs are captured as small RGBA frames encoded on the Rust side.
+(function () {
+  "use strict";
+
+  var launcher = window.__RPGMAKER_LAUNCHER__;
+  var GIF_MAX_WIDTH = 480;
+  var GIF_FRAME_INTERVAL = 100;
+  var GIF_MAX_DURATION = 30000;
+
+  var active = null;
+
+  function gameCanvas() {
+    return (typeof Graphics !== "undefined" && Graphics._canvas) || document.querySelector("canvas");
+  }
+
+  function startVideo() {
+    var canvas = gameCanvas();
+    if (!canvas || !canvas.captureStream || typeof MediaRecorder === "undefined") {
+      return Promise.reject(new Error("Video recording is not supported by this webview"));
+    }
+
+    var stream = canvas.captureStream(30);
+    var audioDestination = null;
+    if (typeof WebAudio !== "undefined" && WebAudio._context && WebAudio._masterGainNode) {
+      audioDestination = WebAudio._context.createMediaStreamDestination();
+      WebAudio._masterGainNode.connect(audioDestination);
+      audioDestination.stream.getAudioTracks().forEach(function (track) {
+        stream.addTrack(track);
+      });
+    }
+
+    var mimeType = ["video/webm;codecs=vp9,opus", "video/webm", "video/mp4"].filter(function (type) {
+      return MediaRecorder.isTypeSupported(type);
+    })[0];
+    var recorder = new MediaRecorder(stream, mimeType ? { mimeType: mimeType } : undefined);
+    var chunks = [];
+
+    recorder.ondataavailable = function (event) {
+      if (event.data.size > 0) {
+        chunks.push(event.data);
+      }
+    };
+    recorder.onstop = function () {
+      if (audioDestination) {
+        WebAudio._masterGainNode.disconnect(audioDestination);
+      }
+      var blob = new Blob(chunks, { type: recorder.mimeType });
+      var format = recorder.mimeType.indexOf("mp4") >= 0 ? "mp4" : "webm";
+      blob.arrayBuffer().then(function (buffer) {
+        launcher.invoke("save_recording", new Uint8Array(buffer), {
+          headers: { "X-Recording-Format": format },
+        });
+      });
+    };
+
+    recorder.start(1000);
+    return Promise.resolve({
+      stop: function () {
+        recorder.stop();
+      },
+    });
+  }
+
+  function startGif() {
+    var frame = document.createElement("canvas");
+    var context = frame.getContext("2d");
+    var startedAt = Date.now();
+
+    var timer = setInterval(function () {
+      var source = launcher.frameCanvas();
+      if (!source || !source.width) {
+        return;
+      }
+      var scale = Math.min(1, GIF_MAX_WIDTH / source.width);
+      frame.width = Math.round(source.width * scale);
+      frame.height = Math.round(source.height * scale);
+      context.drawImage(source, 0, 0, frame.width, frame.height);
+
+      var pixels = context.getImageData(0, 0, frame.width, frame.height).data;
+      launcher.invoke("push_recording_frame", new Uint8Array(pixels.buffer), {
+        headers: {
+          "X-Frame-Width": String(frame.width),
+          "X-Frame-Height": String(frame.height),
+          "X-Frame-Delay": String(GIF_FRAME_INTERVAL),
+        },
+      });
+
+      if (Date.now() - startedAt >= GIF_MAX_DURATION) {
+        launcher.invoke("stop_recording");
+      }
+    }, GIF_FRAME_INTERVAL);
+
+    return Promise.resolve({
+      stop: function () {
+        clearInterval(timer);
+        launcher.invoke("finish_recording");
+      },
+    });
+  }
+
+  launcher.startRecording = function (format) {
+    if (active) {
+      return;
+    }
+    active = { stop: function () {} };
+    (format === "gif" ? startGif() : startVideo()).then(
+      function (recording) {
+        active = recording;
+      },
+      function (error) {
+        active = null;
+        launcher.invoke("abort_recording", { reason: String(error.message || error) });
+      }
+    );
+  };
+
+  launcher.stopRecording = function () {
+    if (active) {
+      active.stop();
+      active = null;
+    }
+  };
+})();
@@ -0,0 +1,46 @@
+// Game side of the LAN remote: presses buttons and runs quick saves sent from the phone.
+(function () {
+  "use strict";
+
+  var launcher = window.__RPGMAKER_LAUNCHER__;
+  // Long enough for Input.update to see the press on at least one frame
+  var PRESS_DURATION = 100;
+
+  function press(button) {
+    if (typeof Input === "undefined") {
+      return;
+    }
+    Input._currentState[button] = true;
+    setTimeout(function () {
+      Input._currentState[button] = false;
+    }, PRESS_DURATION);
+  }
+
+  function quickSave(slot) {
+    var scene = SceneManager._scene;
+    if (!(scene instanceof Scene_Map) || !$gameSystem.isSaveEnabled()) {
+      launcher.toast("Can't save right now");
+      return;
+    }
+    $gameSystem.onBeforeSave();
+    // MV saves synchronously and returns a boolean, MZ returns a promise
+    Promise.resolve(DataManager.saveGame(slot)).then(
+      function (saved) {
+        launcher.toast(saved === false ? "Quick save failed" : "Saved to slot " + slot);
+      },
+      function () {
+        launcher.toast("Quick save failed");
+      }
+    );
+  }
+
+  launcher.listen("remote-button", function (payload) {
+    press(payload.button);
+  });
+
+  launcher.listen("remote-action", function (payload) {
+    if (payload.action === "quickSave") {
+      quickSave(payload.slot);
+    }
+  });
+})();
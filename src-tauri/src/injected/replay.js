@@ -0,0 +1,83 @@
+// Input recording and playback. Seeds Math.random for the session, then hooks Input.update
+// to report button changes by frame, or to replace the real input with the replay's.
+(function () {
+  "use strict";
+
+  var launcher = window.__RPGMAKER_LAUNCHER__;
+
+  launcher.listen("replay-restart", function () {
+    location.reload();
+  });
+
+  // mulberry32: small, fast, and the same sequence for the same seed everywhere
+  function seededRandom(seed) {
+    var state = seed >>> 0;
+    return function () {
+      state = (state + 0x6d2b79f5) >>> 0;
+      var t = state;
+      t = Math.imul(t ^ (t >>> 15), t | 1);
+      t ^= t + Math.imul(t ^ (t >>> 7), t | 61);
+      return ((t ^ (t >>> 14)) >>> 0) / 4294967296;
+    };
+  }
+
+  launcher.invoke("get_replay_session").then(function (session) {
+    if (!session) {
+      return;
+    }
+    Math.random = seededRandom(session.seed);
+    console.info("[launcher] Replay " + (session.mode === "play" ? "playback" : "recording") + ", seed " + session.seed);
+
+    launcher.whenGraphicsReady(function () {
+      if (typeof Input === "undefined") {
+        return;
+      }
+      var frame = 0;
+      var last = {};
+      var state = {};
+      var events = session.events;
+      var next = 0;
+      var lastFrame = events.length ? events[events.length - 1].frame : 0;
+      var playing = session.mode === "play";
+
+      var pollGamepads = Input._pollGamepads;
+      Input._pollGamepads = function () {
+        if (!playing) {
+          pollGamepads.apply(this, arguments);
+        }
+      };
+
+      var update = Input.update;
+      Input.update = function () {
+        if (playing) {
+          while (next < events.length && events[next].frame <= frame) {
+            state[events[next].button] = events[next].pressed;
+            next++;
+          }
+          this._currentState = Object.assign({}, state);
+        }
+        update.apply(this, arguments);
+
+        if (session.mode === "record") {
+          var current = this._currentState;
+          var buttons = Object.keys(current).concat(Object.keys(last));
+          for (var i = 0; i < buttons.length; i++) {
+            var button = buttons[i];
+            if (!!current[button] !== !!last[button]) {
+              last[button] = !!current[button];
+              launcher
+                .invoke("record_input", { frame: frame, button: button, pressed: last[button] })
+                .catch(function () {});
+            }
+          }
+        } else if (playing && next >= events.length && frame >= lastFrame) {
+          // Hand the controls back to the player
+          playing = false;
+          this.clear();
+          launcher.invoke("replay_finished", { frame: frame }).catch(function () {});
+        }
+        frame++;
+      };
+    });
+  });
+})();
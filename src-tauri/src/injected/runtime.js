@@ -0,0 +1,78 @@
+// Helpers shared by the launcher's injected scripts, attached to window.__RPGMAKER_LAUNCHER__.
+(function () {
+  "use strict";
+
+  var launcher = (window.__RPGMAKER_LAUNCHER__ = window.__RPGMAKER_LAUNCHER__ || {});
+  var tauri = window.__TAURI__;
+
+  launcher.invoke = function (command, args, options) {
+    if (!tauri) {
+      return Promise.reject(new Error("Tauri API unavailable"));
+    }
+    return tauri.core.invoke(command, args, options);
+  };
+
+  launcher.listen = function (event, handler) {
+    if (tauri) {
+      tauri.event.listen(event, function (e) {
+        handler(e.payload);
+      });
+    }
+  };
+
+  // Discord Rich Presence. Missing fields fall back to the game title / nothing.
+  launcher.setPresence = function (details, state) {
+    return launcher.invoke("set_presence", { details: details || null, state: state || null });
+  };
+
+  launcher.clearPresence = function () {
+    return launcher.invoke("clear_presence");
+  };
+
+  // itch.io profile of the player, when the game was launched by the itch app.
+  launcher.getItchStatus = function () {
+    return launcher.invoke("itch_status");
+  };
+
+  // Twitch chat messages and channel point redemptions, when the launcher is connected to Twitch.
+  launcher.onTwitchChat = function (handler) {
+    launcher.listen("twitch-chat", handler);
+  };
+
+  launcher.onTwitchRedemption = function (handler) {
+    launcher.listen("twitch-redemption", handler);
+  };
+
+  // OS notification, shown even when the window is minimized. `icon` is relative to the game folder.
+  launcher.notify = function (title, body, icon) {
+    return launcher.invoke("notify", { title: String(title), body: body || null, icon: icon || null });
+  };
+
+  // Small notification in the corner of the game window.
+  launcher.toast = function (message, duration) {
+    var toast = document.createElement("div");
+    toast.textContent = message;
+    toast.style.cssText =
+      "position:fixed;right:16px;bottom:16px;z-index:2147483647;padding:8px 14px;" +
+      "background:rgba(0,0,0,0.75);color:#fff;font:14px sans-serif;border-radius:4px;" +
+      "pointer-events:none;transition:opacity 0.4s;opacity:1";
+    (document.body || document.documentElement).appendChild(toast);
+    setTimeout(function () {
+      toast.style.opacity = "0";
+      setTimeout(function () {
+        toast.remove();
+      }, 400);
+    }, duration || 2500);
+  };
+
+  // Run `callback` once the engine's Graphics object is initialized. MZ loads its core
+  // scripts after the window load event, so polling is the only hook that fits both.
+  launcher.whenGraphicsReady = function (callback) {
+    var timer = setInterval(function () {
+      if (typeof Graphics !== "undefined" && Graphics._width > 0) {
+        clearInterval(timer);
+        callback(Graphics);
+      }
+    }, 100);
+  };
+})();
@@ -0,0 +1,33 @@
+// Stamps the game version into each save slot's info, and checks it before a slot is
+// loaded so the launcher can back up the saves and warn about saves from older builds.
+(function () {
+  "use strict";
+
+  var launcher = window.__RPGMAKER_LAUNCHER__;
+
+  launcher.whenGraphicsReady(function () {
+    launcher.invoke("get_game_version").then(function (version) {
+      if (!version || typeof DataManager === "undefined") {
+        return;
+      }
+
+      var makeSavefileInfo = DataManager.makeSavefileInfo;
+      DataManager.makeSavefileInfo = function () {
+        var info = makeSavefileInfo.apply(this, arguments);
+        info.gameVersion = version;
+        return info;
+      };
+
+      var loadGame = DataManager.loadGame;
+      DataManager.loadGame = function (savefileId) {
+        // MZ keeps the slot infos in memory, MV reads them from the global save
+        var info = this.savefileInfo ? this.savefileInfo(savefileId) : this.loadSavefileInfo(savefileId);
+        var savedVersion = (info && info.gameVersion) || null;
+        launcher.invoke("check_save_version", { savedVersion: savedVersion }).catch(function (error) {
+          console.warn("[launcher] Save version check failed:", error);
+        });
+        return loadGame.apply(this, arguments);
+      };
+    });
+  });
+})();
@@ -0,0 +1,88 @@
+// Screenshot capture. The launcher's capture_screenshot command calls
+// captureScreenshot(), which renders the current frame and hands the PNG back to Rust.
+(function () {
+  "use strict";
+
+  var launcher = window.__RPGMAKER_LAUNCHER__;
+  var showToast = true;
+
+  // SceneManager.snap() renders the stage into a bitmap at the game's native resolution,
+  // which also works when the WebGL drawing buffer has already been cleared.
+  launcher.frameCanvas = function () {
+    if (typeof SceneManager !== "undefined" && SceneManager.snap && SceneManager._scene) {
+      var bitmap = SceneManager.snap();
+      if (bitmap) {
+        return bitmap.canvas || bitmap._canvas;
+      }
+    }
+    return document.querySelector("canvas");
+  };
+
+  launcher.captureScreenshot = function () {
+    var canvas = launcher.frameCanvas();
+    if (!canvas) {
+      return;
+    }
+    canvas.toBlob(function (blob) {
+      if (blob) {
+        blob.arrayBuffer().then(function (buffer) {
+          launcher.invoke("save_screenshot", new Uint8Array(buffer));
+        });
+      }
+    }, "image/png");
+  };
+
+  var THUMBNAIL_WIDTH = 320;
+
+  // Thumbnail for a save slot. The save screen is showing by then, so this uses the
+  // snapshot of the map the engine took for the menu background when there is one.
+  launcher.captureThumbnail = function (name) {
+    var source = null;
+    if (typeof SceneManager !== "undefined" && SceneManager.backgroundBitmap) {
+      var background = SceneManager.backgroundBitmap();
+      source = background && (background.canvas || background._canvas);
+    }
+    source = source || launcher.frameCanvas();
+    if (!source || !source.width) {
+      return;
+    }
+    var thumbnail = document.createElement("canvas");
+    thumbnail.width = THUMBNAIL_WIDTH;
+    thumbnail.height = Math.round((source.height * THUMBNAIL_WIDTH) / source.width);
+    thumbnail.getContext("2d").drawImage(source, 0, 0, thumbnail.width, thumbnail.height);
+    thumbnail.toBlob(function (blob) {
+      if (blob) {
+        blob.arrayBuffer().then(function (buffer) {
+          launcher.invoke("save_thumbnail", new Uint8Array(buffer), {
+            headers: { "X-Save-Name": name },
+          });
+        });
+      }
+    }, "image/png");
+  };
+
+  // For load screens: an object URL for the slot's thumbnail, or null if it has none
+  launcher.getSaveThumbnail = function (savefileId) {
+    return launcher
+      .invoke("get_save_thumbnail", { name: "file" + savefileId })
+      .then(function (png) {
+        return URL.createObjectURL(new Blob([png], { type: "image/png" }));
+      })
+      .catch(function () {
+        return null;
+      });
+  };
+
+  launcher.invoke("get_settings").then(function (settings) {
+    showToast = settings.screenshotToast;
+  });
+  launcher.listen("settings-changed", function (settings) {
+    showToast = settings.screenshotToast;
+  });
+
+  launcher.listen("screenshot-saved", function (payload) {
+    if (showToast) {
+      launcher.toast("Screenshot saved: " + payload.fileName);
+    }
+  });
+})();
@@ -0,0 +1,109 @@
+// Autosplitter: reports run start, splits and resets on the configured milestones.
+(function (config) {
+  "use strict";
+
+  var launcher = window.__RPGMAKER_LAUNCHER__;
+  var next = 0;
+  var running = false;
+  var startedAt = 0;
+  var finalTime = null;
+  var timerEl = null;
+
+  function send(event, split) {
+    launcher.invoke("speedrun_event", { event: event, split: split || null }).catch(function () {});
+  }
+
+  function start() {
+    next = 0;
+    running = true;
+    startedAt = performance.now();
+    finalTime = null;
+    send("start");
+  }
+
+  function reset() {
+    if (!running && finalTime === null) {
+      return;
+    }
+    next = 0;
+    running = false;
+    finalTime = null;
+    send("reset");
+  }
+
+  function matches(trigger) {
+    if (trigger.switch != null && !$gameSwitches.value(trigger.switch)) {
+      return false;
+    }
+    if (trigger.variable != null && !($gameVariables.value(trigger.variable) >= trigger.value)) {
+      return false;
+    }
+    if (trigger.map != null && (!$gameMap || $gameMap.mapId() !== trigger.map)) {
+      return false;
+    }
+    return true;
+  }
+
+  function check() {
+    if (!running || next >= config.splits.length || !matches(config.splits[next])) {
+      return;
+    }
+    send("split", config.splits[next].name);
+    next++;
+    if (next === config.splits.length) {
+      running = false;
+      finalTime = performance.now() - startedAt;
+    }
+  }
+
+  function after(target, name, hook) {
+    var original = target[name];
+    target[name] = function () {
+      var result = original.apply(this, arguments);
+      hook.apply(this, arguments);
+      return result;
+    };
+  }
+
+  function format(ms) {
+    var total = Math.floor(ms / 10);
+    var cs = total % 100;
+    var s = Math.floor(total / 100) % 60;
+    var m = Math.floor(total / 6000) % 60;
+    var h = Math.floor(total / 360000);
+    var pad = function (n) {
+      return n < 10 ? "0" + n : String(n);
+    };
+    return (h > 0 ? h + ":" + pad(m) : String(m)) + ":" + pad(s) + "." + pad(cs);
+  }
+
+  function showTimer() {
+    timerEl = document.createElement("div");
+    timerEl.style.cssText =
+      "position:fixed;right:8px;top:8px;z-index:2147483646;padding:4px 10px;background:rgba(0,0,0,0.6);" +
+      "color:#fff;font:bold 18px monospace;border-radius:4px;pointer-events:none";
+    document.body.appendChild(timerEl);
+    setInterval(function () {
+      var time = finalTime !== null ? finalTime : running ? performance.now() - startedAt : 0;
+      timerEl.textContent = format(time);
+      timerEl.style.color = finalTime !== null ? "#6f6" : "#fff";
+    }, 50);
+  }
+
+  launcher.speedrun = { start: start, reset: reset, split: check };
+
+  launcher.whenGraphicsReady(function () {
+    after(Game_Switches.prototype, "setValue", check);
+    after(Game_Variables.prototype, "setValue", check);
+    after(Game_Player.prototype, "performTransfer", check);
+    if (config.startOnNewGame) {
+      after(DataManager, "setupNewGame", start);
+    }
+    if (config.resetOnTitle) {
+      after(Scene_Title.prototype, "start", reset);
+    }
+    if (config.timer) {
+      showTimer();
+    }
+  });
+})(__SPEEDRUN__);
@@ -0,0 +1,40 @@
+// Reports the time spent on each map for the local play statistics. The launcher ignores
+// the reports unless the player opted in.
+(function () {
+  "use strict";
+
+  var launcher = window.__RPGMAKER_LAUNCHER__;
+  var FLUSH_INTERVAL = 60000;
+  var mapId = 0;
+  var since = 0;
+
+  function flush() {
+    var now = performance.now();
+    if (mapId > 0) {
+      var secs = (now - since) / 1000;
+      launcher.invoke("record_map_time", { map: String(mapId), secs: secs }).catch(function () {});
+    }
+    since = now;
+  }
+
+  launcher.whenGraphicsReady(function () {
+    var performTransfer = Game_Player.prototype.performTransfer;
+    Game_Player.prototype.performTransfer = function () {
+      var transferring = this.isTransferring();
+      performTransfer.apply(this, arguments);
+      if (transferring) {
+        flush();
+        mapId = $gameMap.mapId();
+      }
+    };
+
+    var titleStart = Scene_Title.prototype.start;
+    Scene_Title.prototype.start = function () {
+      titleStart.apply(this, arguments);
+      flush();
+      mapId = 0;
+    };
+
+    setInterval(flush, FLUSH_INTERVAL);
+  });
+})();
@@ -0,0 +1,249 @@
+// Keeps the game's saves as real files in the launcher's save folder, named and encoded the
+// way NW.js writes them, so the save manager can list, back up and import them.
+(function () {
+  "use strict";
+
+  var launcher = window.__RPGMAKER_LAUNCHER__;
+
+  function warn(error) {
+    console.warn("[launcher] Save sync failed:", error);
+  }
+
+  // Slot saves get a thumbnail; the global and config files don't
+  function thumbnail(name) {
+    if (/^file\d+\./.test(name) && launcher.captureThumbnail) {
+      launcher.captureThumbnail(name);
+    }
+  }
+
+  // MV's storage API is synchronous, so localStorage stays what the game reads from: writes
+  // are mirrored to disk, and the files on disk are copied back into localStorage at boot.
+  function patchMv() {
+    var synced = false;
+
+    function fileName(savefileId) {
+      if (savefileId < 0) {
+        return "config.rpgsave";
+      } else if (savefileId === 0) {
+        return "global.rpgsave";
+      }
+      return "file" + savefileId + ".rpgsave";
+    }
+
+    function savefileIdOf(name) {
+      var match = /^(?:(config)|(global)|file(\d+))\.rpgsave$/.exec(name);
+      if (!match) {
+        return null;
+      }
+      return match[1] ? -1 : match[2] ? 0 : Number(match[3]);
+    }
+
+    function sync() {
+      return launcher.invoke("list_saves").then(function (saves) {
+        var onDisk = {};
+        var reads = saves.map(function (save) {
+          var savefileId = savefileIdOf(save.name);
+          if (savefileId === null) {
+            return null;
+          }
+          onDisk[savefileId] = true;
+          return launcher.invoke("read_save", { name: save.name }).then(function (data) {
+            localStorage.setItem(StorageManager.webStorageKey(savefileId), data);
+          });
+        });
+
+        // Saves made before the launcher kept files
+        for (var i = 0; i < localStorage.length; i++) {
+          var match = /^RPG (?:(Config)|(Global)|File(\d+))$/.exec(localStorage.key(i));
+          if (match) {
+            var savefileId = match[1] ? -1 : match[2] ? 0 : Number(match[3]);
+            if (!onDisk[savefileId]) {
+              reads.push(
+                launcher.invoke("write_save", {
+                  name: fileName(savefileId),
+                  data: localStorage.getItem(localStorage.key(i)),
+                })
+              );
+            }
+          }
+        }
+        return Promise.all(reads);
+      });
+    }
+
+    var saveToWebStorage = StorageManager.saveToWebStorage;
+    StorageManager.saveToWebStorage = function (savefileId) {
+      saveToWebStorage.apply(this, arguments);
+      var data = localStorage.getItem(this.webStorageKey(savefileId));
+      var name = fileName(savefileId);
+      launcher
+        .invoke("write_save", { name: name, data: data })
+        .then(function () {
+          thumbnail(name);
+        })
+        .catch(warn);
+    };
+
+    var removeWebStorage = StorageManager.removeWebStorage;
+    StorageManager.removeWebStorage = function (savefileId) {
+      removeWebStorage.apply(this, arguments);
+      launcher.invoke("delete_save", { name: fileName(savefileId) }).catch(warn);
+    };
+
+    // Hold the boot scene until the saves on disk are in localStorage
+    var isReady = Scene_Boot.prototype.isReady;
+    Scene_Boot.prototype.isReady = function () {
+      return synced && isReady.apply(this, arguments);
+    };
+
+    sync()
+      .catch(warn)
+      .then(function () {
+        synced = true;
+      });
+
+    launcher.listen("save-changed", function (change) {
+      var savefileId = savefileIdOf(change.name);
+      if (savefileId === null) {
+        return;
+      }
+      var key = StorageManager.webStorageKey(savefileId);
+      if (change.data === null) {
+        localStorage.removeItem(key);
+      } else {
+        localStorage.setItem(key, change.data);
+      }
+    });
+
+    // Another save backend: drop the old saves from localStorage and load the new ones
+    launcher.listen("save-backend-changed", function () {
+      for (var i = localStorage.length - 1; i >= 0; i--) {
+        if (/^RPG (?:Config|Global|File\d+)$/.test(localStorage.key(i))) {
+          localStorage.removeItem(localStorage.key(i));
+        }
+      }
+      sync()
+        .catch(warn)
+        .then(function () {
+          DataManager._globalInfo = null;
+        });
+    });
+  }
+
+  // MZ's storage API is asynchronous, so it reads and writes the files directly.
+  function patchMz() {
+    var names = null;
+
+    function fileName(saveName) {
+      return saveName + ".rmmzsave";
+    }
+
+    function migrate() {
+      if (typeof localforage === "undefined") {
+        return Promise.resolve();
+      }
+      return Promise.all([launcher.invoke("list_saves"), localforage.keys()]).then(function (results) {
+        var onDisk = results[0].map(function (save) {
+          return save.name;
+        });
+        return Promise.all(
+          results[1]
+            .filter(function (key) {
+              return /^rmmzsave\.[^.]+\./.test(key);
+            })
+            .map(function (key) {
+              var name = fileName(key.replace(/^rmmzsave\.[^.]+\./, ""));
+              if (onDisk.indexOf(name) >= 0) {
+                return null;
+              }
+              return localforage.getItem(key).then(function (data) {
+                return data === null ? null : launcher.invoke("write_save", { name: name, data: data });
+              });
+            })
+        );
+      });
+    }
+
+    StorageManager.saveZip = function (saveName, zip) {
+      var name = fileName(saveName);
+      return launcher.invoke("write_save", { name: name, data: zip }).then(function () {
+        if (names && names.indexOf(name) < 0) {
+          names.push(name);
+        }
+        thumbnail(name);
+      });
+    };
+
+    StorageManager.loadZip = function (saveName) {
+      return launcher.invoke("read_save", { name: fileName(saveName) });
+    };
+
+    StorageManager.exists = function (saveName) {
+      return !!names && names.indexOf(fileName(saveName)) >= 0;
+    };
+
+    StorageManager.remove = function (saveName) {
+      var name = fileName(saveName);
+      if (names) {
+        names = names.filter(function (n) {
+          return n !== name;
+        });
+      }
+      return launcher.invoke("delete_save", { name: name });
+    };
+
+    StorageManager.updateForageKeys = function () {
+      names = null;
+      return migrate()
+        .catch(warn)
+        .then(function () {
+          return launcher.invoke("list_saves");
+        })
+        .then(function (saves) {
+          names = saves.map(function (save) {
+            return save.name;
+          });
+        })
+        .catch(function (error) {
+          warn(error);
+          names = [];
+        });
+    };
+
+    StorageManager.forageKeysUpdated = function () {
+      return names !== null;
+    };
+
+    launcher.listen("save-changed", function (change) {
+      if (!names) {
+        return;
+      }
+      names = names.filter(function (n) {
+        return n !== change.name;
+      });
+      if (change.data !== null) {
+        names.push(change.name);
+      }
+    });
+
+    launcher.listen("save-backend-changed", function () {
+      StorageManager.updateForageKeys().then(function () {
+        DataManager.loadGlobalInfo();
+      });
+    });
+  }
+
+  // MV defines its classes before the load event, MZ loads them one by one afterwards;
+  // either way they have to be patched before the boot scene starts.
+  var timer = setInterval(function () {
+    if (typeof StorageManager === "undefined" || typeof Scene_Boot === "undefined") {
+      return;
+    }
+    clearInterval(timer);
+    if (typeof StorageManager.saveZip === "function") {
+      patchMz();
+    } else if (typeof StorageManager.saveToWebStorage === "function") {
+      patchMv();
+    }
+  }, 10);
+})();
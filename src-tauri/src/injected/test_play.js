@@ -0,0 +1,13 @@
+// Test play: F8 opens the devtools, which the engine only does itself under NW.js.
+(function () {
+  "use strict";
+
+  var launcher = window.__RPGMAKER_LAUNCHER__;
+
+  window.addEventListener("keydown", function (event) {
+    if (event.key === "F8") {
+      event.preventDefault();
+      launcher.invoke("open_devtools").catch(function () {});
+    }
+  });
+})();
@@ -0,0 +1,73 @@
+// Window title and custom titlebar. Like NW.js, the window title follows document.title;
+// `launcher.setWindowTitle` sets it directly. With a titlebar theme configured, a bar in the
+// game's colors slides in when the cursor reaches the top of the window.
+(function (theme) {
+  "use strict";
+
+  var launcher = window.__RPGMAKER_LAUNCHER__;
+  var tauri = window.__TAURI__;
+
+  launcher.setWindowTitle = function (title) {
+    return launcher.invoke("set_window_title", { title: String(title) });
+  };
+
+  var lastTitle = null;
+  var titleEl = null;
+
+  function syncTitle() {
+    if (document.title && document.title !== lastTitle) {
+      lastTitle = document.title;
+      launcher.setWindowTitle(lastTitle).catch(function () {});
+      if (titleEl) {
+        titleEl.textContent = lastTitle;
+      }
+    }
+  }
+
+  function createTitlebar() {
+    var bar = document.createElement("div");
+    bar.setAttribute("data-tauri-drag-region", "");
+    bar.style.cssText =
+      "position:fixed;left:0;right:0;top:0;height:30px;z-index:2147483646;display:flex;" +
+      "align-items:center;font:13px sans-serif;user-select:none;transition:transform 0.15s;" +
+      "transform:translateY(-100%);background:" + theme.background + ";color:" + theme.color;
+
+    titleEl = document.createElement("div");
+    titleEl.setAttribute("data-tauri-drag-region", "");
+    titleEl.style.cssText = "flex:1;padding:0 10px;overflow:hidden;white-space:nowrap;text-overflow:ellipsis";
+    titleEl.textContent = document.title;
+    bar.appendChild(titleEl);
+
+    var current = tauri.window.getCurrentWindow();
+    [
+      ["–", function () { current.minimize(); }],
+      ["□", function () { current.toggleMaximize(); }],
+      ["✕", function () { current.close(); }],
+    ].forEach(function (button) {
+      var el = document.createElement("div");
+      el.textContent = button[0];
+      el.style.cssText = "width:40px;height:30px;line-height:30px;text-align:center;cursor:default";
+      el.addEventListener("click", button[1]);
+      bar.appendChild(el);
+    });
+
+    document.body.appendChild(bar);
+
+    // Shown while the cursor is at the top edge or over the bar, never in fullscreen
+    document.addEventListener("mousemove", function (event) {
+      var show = event.clientY < 8 || (event.clientY < 30 && bar.style.transform === "none");
+      current.isFullscreen().then(function (fullscreen) {
+        bar.style.transform = show && !fullscreen ? "none" : "translateY(-100%)";
+      });
+    });
+  }
+
+  document.addEventListener("DOMContentLoaded", function () {
+    var head = document.head || document.documentElement;
+    new MutationObserver(syncTitle).observe(head, { subtree: true, childList: true, characterData: true });
+    if (theme && tauri && tauri.window) {
+      createTitlebar();
+    }
+    syncTitle();
+  });
+})(__TITLEBAR__);
@@ -0,0 +1,228 @@
+//! Scripts the launcher injects into the game page through the localhost plugin.
+
+use std::path::Path;
+
+use serde_json::json;
+
+use crate::args::LaunchArgs;
+use crate::config::{BackgroundMode, ContextMenuMode, HotkeyConfig, SpeedrunConfig, TitlebarTheme};
+use crate::mods::PluginEntry;
+
+/// NW.js compatibility polyfill, prefixed with the launch context it reads from.
+pub fn polyfill_script(args: &LaunchArgs, game_dir: &Path) -> String {
+    let platform = match std::env::consts::OS {
+        "windows" => "win32",
+        "macos" => "darwin",
+        other => other,
+    };
+
+    let context = json!({
+        "execPath": args.exec_path,
+        "argv": args.game_args,
+        "platform": platform,
+        "gameDir": game_dir.to_string_lossy(),
+    });
+
+    format!(
+        "window.__RPGMAKER_LAUNCHER__ = {};\n{}",
+        context,
+        include_str!("injected/polyfill.js")
+    )
+}
+
+/// Helpers shared by the other injected scripts. Must come right after the polyfill.
+pub fn runtime_script() -> String {
+    include_str!("injected/runtime.js").to_string()
+}
+
+/// Integer scaling of the game canvas, driven by the per-game settings.
+pub fn integer_scaling_script() -> String {
+    include_str!("injected/integer_scaling.js").to_string()
+}
+
+/// Renders the current frame for the screenshot commands and shows the saved toast.
+pub fn screenshot_script() -> String {
+    include_str!("injected/screenshot.js").to_string()
+}
+
+/// Video and GIF capture for the recording commands. Must come after the screenshot script.
+pub fn recording_script() -> String {
+    include_str!("injected/recording.js").to_string()
+}
+
+/// Forwards `console.*` output to the debug console window while it is open, and to the
+/// log file if `log_to_file` is set.
+pub fn console_forward_script(log_to_file: bool) -> String {
+    include_str!("injected/console_forward.js").replace("__LOG_GAME_CONSOLE__", &log_to_file.to_string())
+}
+
+/// Reports movie playback for the `video` keep-awake mode.
+pub fn keep_awake_script() -> String {
+    include_str!("injected/keep_awake.js").to_string()
+}
+
+/// Mute/pause on blur, driven by the window focus events, and the background mode.
+pub fn focus_script(background: BackgroundMode) -> String {
+    let mode = serde_json::to_string(&background).unwrap_or_else(|_| "\"throttle\"".to_string());
+    include_str!("injected/focus.js").replace("__BACKGROUND__", &mode)
+}
+
+/// Mirrors the game's saves to files in the save folder.
+pub fn storage_script() -> String {
+    include_str!("injected/storage.js").to_string()
+}
+
+/// Replaces the webview's context menu according to the launcher config.
+pub fn context_menu_script(mode: ContextMenuMode) -> String {
+    let mode = serde_json::to_string(&mode).unwrap_or_else(|_| "\"none\"".to_string());
+    include_str!("injected/context_menu.js").replace("__CONTEXT_MENU__", &mode)
+}
+
+/// Keeps the window title in sync with the page, and draws the custom titlebar if configured.
+pub fn titlebar_script(theme: Option<&TitlebarTheme>) -> String {
+    let theme = serde_json::to_string(&theme).unwrap_or_else(|_| "null".to_string());
+    include_str!("injected/titlebar.js").replace("__TITLEBAR__", &theme)
+}
+
+/// greenworks API shim for Steam plugins, backed by the Steam commands.
+pub fn greenworks_script() -> String {
+    include_str!("injected/greenworks.js").to_string()
+}
+
+/// `@font-face` rules for the fonts the game ships, built by `fonts::font_face_css`.
+pub fn fonts_script(css: &str) -> String {
+    let css = serde_json::to_string(css).unwrap_or_else(|_| "\"\"".to_string());
+    include_str!("injected/fonts.js").replace("__FONT_FACES__", &css)
+}
+
+/// Adds the CJK fallback font to the page and to the engine's font stack.
+pub fn cjk_fallback_script(css: &str, family: &str) -> String {
+    let css = serde_json::to_string(css).unwrap_or_else(|_| "\"\"".to_string());
+    let family = serde_json::to_string(family).unwrap_or_else(|_| "\"\"".to_string());
+    include_str!("injected/cjk_fallback.js")
+        .replace("__CSS__", &css)
+        .replace("__FAMILY__", &family)
+}
+
+/// Records the game version in saves and checks it when one is loaded.
+pub fn save_version_script() -> String {
+    include_str!("injected/save_version.js").to_string()
+}
+
+/// Records the enabled DLC in saves and checks it when one is loaded.
+pub fn dlc_script() -> String {
+    include_str!("injected/dlc.js").to_string()
+}
+
+/// F8 for the devtools in the editor's test play.
+pub fn test_play_script() -> String {
+    include_str!("injected/test_play.js").to_string()
+}
+
+/// Reloads the current map and common events in watch mode.
+pub fn hot_reload_script() -> String {
+    include_str!("injected/hot_reload.js").to_string()
+}
+
+/// Restores BGM loop points lost when the engine plays a file without the loop tags.
+pub fn audio_loops_script() -> String {
+    include_str!("injected/audio_loops.js").to_string()
+}
+
+/// Achievement toasts and unlock helpers.
+pub fn achievements_script() -> String {
+    include_str!("injected/achievements.js").to_string()
+}
+
+/// Appends the plugins of the enabled mods to `$plugins`.
+pub fn mods_script(plugins: &[PluginEntry]) -> String {
+    let plugins = serde_json::to_string(plugins).unwrap_or_else(|_| "[]".to_string());
+    include_str!("injected/mods.js").replace("__MOD_PLUGINS__", &plugins)
+}
+
+/// Autosplitter hooks and the optional timer overlay.
+pub fn speedrun_script(config: &SpeedrunConfig) -> String {
+    let config = serde_json::to_string(config).unwrap_or_else(|_| "{}".to_string());
+    include_str!("injected/speedrun.js").replace("__SPEEDRUN__", &config)
+}
+
+/// Reports game events for the OBS triggers.
+pub fn obs_script() -> String {
+    include_str!("injected/obs.js").to_string()
+}
+
+/// Reports time spent on each map for the local play statistics.
+pub fn stats_script() -> String {
+    include_str!("injected/stats.js").to_string()
+}
+
+/// Presses the buttons and runs the quick saves sent by the LAN remote.
+pub fn remote_script() -> String {
+    include_str!("injected/remote.js").to_string()
+}
+
+/// Polls the switches and variables community achievements depend on.
+pub fn community_script() -> String {
+    include_str!("injected/community.js").to_string()
+}
+
+/// Applies the player's key and gamepad bindings before the engine reads the input.
+pub fn input_remap_script() -> String {
+    include_str!("injected/input_remap.js").to_string()
+}
+
+/// Evaluates developer console code in the page. Only injected when the console is enabled.
+pub fn dev_console_script() -> String {
+    include_str!("injected/dev_console.js").to_string()
+}
+
+/// Cursor auto-hide and pointer lock helpers.
+pub fn cursor_script() -> String {
+    include_str!("injected/cursor.js").to_string()
+}
+
+/// Reports the DPI scale override as `devicePixelRatio`. Must run before the engine loads.
+pub fn dpi_script() -> String {
+    include_str!("injected/dpi.js").to_string()
+}
+
+/// Reports script errors and the game's first scene to the localhost server.
+pub fn boot_probe_script() -> String {
+    include_str!("injected/boot_probe.js").to_string()
+}
+
+/// Records the game's button input for replays, or plays a replay back.
+pub fn replay_script() -> String {
+    include_str!("injected/replay.js").to_string()
+}
+
+/// Restarts the audio and resets the frame timing after the system resumes from sleep.
+pub fn power_script() -> String {
+    include_str!("injected/power.js").to_string()
+}
+
+/// Reports the media formats the webview can decode.
+pub fn codec_probe_script() -> String {
+    include_str!("injected/codec_probe.js").to_string()
+}
+
+/// Runs the automation endpoint's requests in the game page.
+pub fn automation_script() -> String {
+    include_str!("injected/automation.js").to_string()
+}
+
+/// Reports the audio output devices and plays the game on the chosen one.
+pub fn audio_output_script() -> String {
+    include_str!("injected/audio_output.js").to_string()
+}
+
+/// Checks the page's capabilities against NW.js for the parity report.
+pub fn parity_probe_script() -> String {
+    include_str!("injected/parity_probe.js").to_string()
+}
+
+/// Launcher keyboard shortcuts, bound to the combinations from the config.
+pub fn hotkeys_script(hotkeys: &HotkeyConfig) -> String {
+    let bindings = serde_json::to_string(hotkeys).unwrap_or_else(|_| "{}".to_string());
+    include_str!("injected/hotkeys.js").replace("__HOTKEYS__", &bindings)
+}
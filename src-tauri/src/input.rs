@@ -0,0 +1,107 @@
+//! Key and gamepad remapping.
+//!
+//! Bindings map a keyboard key (by `KeyboardEvent.code`, e.g. `KeyJ`) or a gamepad button
+//! index to one of RPG Maker's input actions (`ok`, `escape`, `menu`...), or to `null` to
+//! disable it. The developer's defaults come from `input` in `launcher.json` and the
+//! player's changes are stored with the game settings. The injected `input_remap.js`
+//! applies the merged bindings before the engine's `Input` sees the event, and follows
+//! `input-bindings-changed` so changes from the controls window apply immediately.
+
+use std::collections::BTreeMap;
+
+use serde::{Deserialize, Serialize};
+use tauri::{AppHandle, Emitter, Runtime, State};
+
+use crate::config::LauncherConfig;
+use crate::settings::Settings;
+
+/// Actions the engine's `Input` understands.
+pub const ACTIONS: [&str; 12] = [
+    "ok", "escape", "menu", "shift", "control", "tab", "pageup", "pagedown", "up", "down", "left", "right",
+];
+
+/// Bindings from a key code or gamepad button to an action, `None` disabling the input.
+pub type Bindings = BTreeMap<String, Option<String>>;
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default, rename_all = "camelCase")]
+pub struct InputBindings {
+    pub keys: Bindings,
+    /// Keyed by the button index of the standard gamepad mapping, e.g. `"0"` for A / Cross.
+    pub gamepad: Bindings,
+}
+
+impl InputBindings {
+    /// Lay `overrides` on top of these bindings.
+    fn merged(&self, overrides: &InputBindings) -> InputBindings {
+        let mut merged = self.clone();
+        merged.keys.extend(overrides.keys.clone());
+        merged.gamepad.extend(overrides.gamepad.clone());
+        merged
+    }
+}
+
+/// The bindings in effect: the config's, with the player's on top.
+pub fn current(config: &LauncherConfig, settings: &Settings) -> InputBindings {
+    config.input.merged(&settings.get().input_bindings)
+}
+
+fn validate(action: &Option<String>) -> Result<(), String> {
+    match action {
+        Some(action) if !ACTIONS.contains(&action.as_str()) => Err(format!("Unknown input action: {}", action)),
+        _ => Ok(()),
+    }
+}
+
+fn emit_changed<R: Runtime>(app: &AppHandle<R>, config: &LauncherConfig, settings: &Settings) -> Result<(), String> {
+    app.emit("input-bindings-changed", current(config, settings))
+        .map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+pub fn get_input_bindings(config: State<'_, LauncherConfig>, settings: State<'_, Settings>) -> InputBindings {
+    current(&config, &settings)
+}
+
+/// Bind a key to an action, or disable it with `null`.
+#[tauri::command]
+pub fn set_key_binding<R: Runtime>(
+    app: AppHandle<R>,
+    config: State<'_, LauncherConfig>,
+    settings: State<'_, Settings>,
+    code: String,
+    action: Option<String>,
+) -> Result<(), String> {
+    validate(&action)?;
+    settings.update(|s| {
+        s.input_bindings.keys.insert(code, action);
+    })?;
+    emit_changed(&app, &config, &settings)
+}
+
+/// Bind a gamepad button to an action, or disable it with `null`.
+#[tauri::command]
+pub fn set_gamepad_binding<R: Runtime>(
+    app: AppHandle<R>,
+    config: State<'_, LauncherConfig>,
+    settings: State<'_, Settings>,
+    button: u32,
+    action: Option<String>,
+) -> Result<(), String> {
+    validate(&action)?;
+    settings.update(|s| {
+        s.input_bindings.gamepad.insert(button.to_string(), action);
+    })?;
+    emit_changed(&app, &config, &settings)
+}
+
+/// Drop the player's changes and go back to the game's bindings.
+#[tauri::command]
+pub fn reset_input_bindings<R: Runtime>(
+    app: AppHandle<R>,
+    config: State<'_, LauncherConfig>,
+    settings: State<'_, Settings>,
+) -> Result<(), String> {
+    settings.update(|s| s.input_bindings = InputBindings::default())?;
+    emit_changed(&app, &config, &settings)
+}
@@ -0,0 +1,102 @@
+//! itch.io app integration.
+//!
+//! When the itch app launches a game whose `.itch.toml` manifest asks for the `profile:me`
+//! scope, it passes a short-lived API key in `ITCHIO_API_KEY`. The launcher uses it to look
+//! up the player's profile, which games can read with `itch_status`. Play time is already
+//! tracked by the itch app for games it launches, so nothing else is reported.
+
+use std::sync::Mutex;
+
+use serde::{Deserialize, Serialize};
+use tauri::State;
+
+use crate::net;
+
+const API_KEY_VAR: &str = "ITCHIO_API_KEY";
+const API_KEY_EXPIRES_VAR: &str = "ITCHIO_API_KEY_EXPIRES_AT";
+const ME_URL: &str = "https://itch.io/api/1/jwt/me";
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ItchUser {
+    pub id: u64,
+    pub username: String,
+    #[serde(default, alias = "display_name")]
+    pub display_name: Option<String>,
+    #[serde(default, alias = "cover_url")]
+    pub cover_url: Option<String>,
+    #[serde(default)]
+    pub url: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ItchStatus {
+    /// Whether the game was launched by the itch app with an API key.
+    pub available: bool,
+    pub user: Option<ItchUser>,
+    /// When the API key stops working, as given by the itch app.
+    pub expires_at: Option<String>,
+}
+
+#[derive(Deserialize)]
+struct MeResponse {
+    user: Option<ItchUser>,
+    #[serde(default)]
+    errors: Vec<String>,
+}
+
+pub struct Itch {
+    api_key: Option<String>,
+    expires_at: Option<String>,
+    user: Mutex<Option<ItchUser>>,
+}
+
+impl Itch {
+    pub fn from_env() -> Self {
+        let api_key = std::env::var(API_KEY_VAR).ok().filter(|key| !key.is_empty());
+        if api_key.is_some() {
+            log::info!("Launched by the itch app");
+        }
+        Self {
+            api_key,
+            expires_at: std::env::var(API_KEY_EXPIRES_VAR).ok(),
+            user: Mutex::new(None),
+        }
+    }
+
+    fn fetch_user(&self, api_key: &str) -> Result<ItchUser, String> {
+        let body = net::get(ME_URL)?
+            .set("Authorization", &format!("Bearer {}", api_key))
+            .call()
+            .map_err(|e| e.to_string())?
+            .into_string()
+            .map_err(|e| e.to_string())?;
+        let response: MeResponse = serde_json::from_str(&body).map_err(|e| e.to_string())?;
+        response
+            .user
+            .ok_or_else(|| response.errors.join(", "))
+    }
+
+    /// The player's profile, fetched once and cached for the session.
+    fn user(&self) -> Option<ItchUser> {
+        let api_key = self.api_key.as_deref()?;
+        let mut user = self.user.lock().unwrap();
+        if user.is_none() {
+            match self.fetch_user(api_key) {
+                Ok(fetched) => *user = Some(fetched),
+                Err(e) => log::warn!("Failed to fetch the itch.io profile: {}", e),
+            }
+        }
+        user.clone()
+    }
+}
+
+#[tauri::command]
+pub async fn itch_status(itch: State<'_, Itch>) -> Result<ItchStatus, String> {
+    Ok(ItchStatus {
+        available: itch.api_key.is_some(),
+        user: itch.user(),
+        expires_at: itch.expires_at.clone(),
+    })
+}
@@ -0,0 +1,103 @@
+//! Keeps the display and system awake while the game runs.
+//!
+//! Games are often played without touching the keyboard for minutes (cutscenes, idle
+//! mechanics, gamepad input the OS doesn't always count), which lets the screensaver or
+//! suspend kick in mid-play. The inhibitor is held according to `keepAwake` in `launcher.json`.
+
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Mutex;
+
+use serde::{Deserialize, Serialize};
+use tauri::State;
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum KeepAwakeMode {
+    /// Let the system sleep as usual.
+    Never,
+    /// Keep awake for as long as the game is open.
+    #[default]
+    Always,
+    /// Keep awake only while the game window is fullscreen.
+    Fullscreen,
+    /// Keep awake only while a movie is playing.
+    Video,
+}
+
+pub struct KeepAwake {
+    mode: KeepAwakeMode,
+    app_name: String,
+    fullscreen: AtomicBool,
+    video_playing: AtomicBool,
+    handle: Mutex<Option<keepawake::KeepAwake>>,
+}
+
+impl KeepAwake {
+    pub fn new(mode: KeepAwakeMode, app_name: String) -> Self {
+        let keep_awake = Self {
+            mode,
+            app_name,
+            fullscreen: AtomicBool::new(false),
+            video_playing: AtomicBool::new(false),
+            handle: Mutex::new(None),
+        };
+        keep_awake.refresh();
+        keep_awake
+    }
+
+    pub fn set_fullscreen(&self, fullscreen: bool) {
+        if self.fullscreen.swap(fullscreen, Ordering::Relaxed) != fullscreen {
+            self.refresh();
+        }
+    }
+
+    pub fn set_video_playing(&self, playing: bool) {
+        if self.video_playing.swap(playing, Ordering::Relaxed) != playing {
+            self.refresh();
+        }
+    }
+
+    fn wanted(&self) -> bool {
+        match self.mode {
+            KeepAwakeMode::Never => false,
+            KeepAwakeMode::Always => true,
+            KeepAwakeMode::Fullscreen => self.fullscreen.load(Ordering::Relaxed),
+            KeepAwakeMode::Video => self.video_playing.load(Ordering::Relaxed),
+        }
+    }
+
+    /// Acquire or release the inhibitor to match the current state.
+    fn refresh(&self) {
+        let wanted = self.wanted();
+        let mut handle = self.handle.lock().unwrap();
+        if wanted == handle.is_some() {
+            return;
+        }
+
+        if wanted {
+            match keepawake::Builder::default()
+                .display(true)
+                .idle(true)
+                .reason("Game is running")
+                .app_name(&self.app_name)
+                .app_reverse_domain("com.rpgmaker.launcher")
+                .create()
+            {
+                Ok(inhibitor) => {
+                    log::debug!("Keeping the system awake");
+                    *handle = Some(inhibitor);
+                }
+                Err(e) => log::warn!("Failed to keep the system awake: {}", e),
+            }
+        } else {
+            log::debug!("Allowing the system to sleep");
+            *handle = None;
+        }
+    }
+}
+
+/// Called by the injected script when a game movie starts or stops.
+#[tauri::command]
+pub fn set_video_playing(keep_awake: State<'_, KeepAwake>, playing: bool) {
+    keep_awake.set_video_playing(playing);
+}
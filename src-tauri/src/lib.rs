@@ -1,11 +1,88 @@
-mod external_localhost_plugin;
+mod achievements;
+mod args;
+mod audio;
+mod automation;
+mod boss_key;
+mod browser_saves;
+mod codecs;
+mod community;
+mod companion;
+mod compat;
+mod config;
+mod config_store;
+mod data_files;
+mod debug_console;
+mod dev_console;
+mod discord;
+mod display;
+mod dlc;
+mod downloads;
+mod encryption;
+mod focus;
+mod fonts;
+mod game;
+mod game_files;
+mod gog;
+mod hot_reload;
+mod injection;
+mod input;
+mod itch;
+mod keep_awake;
+mod logging;
+mod manifest;
+mod manual;
+mod menu;
+mod mixer;
+mod mods;
+mod monitors;
+mod net;
+mod notifications;
+mod obs;
+mod optimize;
+mod pak;
+mod parity;
+mod patch;
+mod pause;
+mod pip;
+mod plugin_compat;
+mod power;
+mod preflight;
+mod recording;
+mod remote;
+mod replay;
+mod reports;
+mod safe_mode;
+mod sandbox;
+mod save_backends;
+mod save_backups;
+mod save_conflicts;
+mod save_data;
+mod save_editor;
+mod save_share;
+mod save_thumbnails;
+mod save_versions;
+mod saves;
+mod screenshots;
+mod self_test;
+mod settings;
+mod speedrun;
+mod splash;
+mod stats;
+mod steam;
+mod test_play;
+mod tray;
+mod twitch;
+mod watchdog;
 
 use std::path::PathBuf;
-use tauri::{WebviewUrl, WebviewWindowBuilder};
+use tauri::{AppHandle, Manager, Runtime, WebviewUrl, WebviewWindowBuilder, WindowEvent};
 
 #[cfg_attr(mobile, tauri::mobile_entry_point)]
 pub fn run() {
+    logging::init();
+
     let port = portpicker::pick_unused_port().expect("failed to find unused port");
+    let launch_args = args::LaunchArgs::from_env();
     
     // Função para encontrar a pasta Game_Contents
     fn find_game_contents() -> Option<PathBuf> {
@@ -14,7 +91,7 @@ pub fn run() {
             if let Some(exe_dir) = exe_path.parent() {
                 let game_contents = exe_dir.join("Game_Contents");
                 if game_contents.exists() {
-                    println!("Found Game_Contents at: {:?}", game_contents);
+                    log::info!("Found Game_Contents at: {:?}", game_contents);
                     return Some(game_contents);
                 }
             }
@@ -24,7 +101,7 @@ pub fn run() {
         if let Ok(current_dir) = std::env::current_dir() {
             let game_contents = current_dir.join("Game_Contents");
             if game_contents.exists() {
-                println!("Found Game_Contents at: {:?}", game_contents);
+                log::info!("Found Game_Contents at: {:?}", game_contents);
                 return Some(game_contents);
             }
         }
@@ -40,7 +117,7 @@ pub fn run() {
         for path in &common_paths {
             let game_contents = PathBuf::from(path);
             if game_contents.exists() {
-                println!("Found Game_Contents at: {:?}", game_contents.canonicalize().unwrap_or(game_contents.clone()));
+                log::info!("Found Game_Contents at: {:?}", game_contents.canonicalize().unwrap_or(game_contents.clone()));
                 return Some(game_contents);
             }
         }
@@ -49,28 +126,27 @@ pub fn run() {
     }
     
     // Busca a pasta Game_Contents
-    let game_contents_path = match find_game_contents() {
+    let game_contents_path = match launch_args.game_dir.clone().or_else(find_game_contents) {
         Some(path) => {
-            println!("Using Game_Contents folder: {:?}", path);
+            log::info!("Using Game_Contents folder: {:?}", path);
             path
         }
         None => {
-            eprintln!("Error: Game_Contents folder not found!");
-            eprintln!("Searched in the following locations:");
+            log::error!("Game_Contents folder not found!");
+            log::error!("Searched in the following locations:");
             if let Ok(exe_path) = std::env::current_exe() {
                 if let Some(exe_dir) = exe_path.parent() {
-                    eprintln!("  - {:?}", exe_dir.join("Game_Contents"));
+                    log::error!("  - {:?}", exe_dir.join("Game_Contents"));
                 }
             }
             if let Ok(current_dir) = std::env::current_dir() {
-                eprintln!("  - {:?}", current_dir.join("Game_Contents"));
-            }
-            eprintln!("  - Game_Contents");
-            eprintln!("  - ../Game_Contents");
-            eprintln!("  - ../../Game_Contents");
-            eprintln!("  - ./dist/Game_Contents");
-            eprintln!("");
-            eprintln!("Please create a symlink or copy your RPG Maker game files to one of these locations.");
+                log::error!("  - {:?}", current_dir.join("Game_Contents"));
+            }
+            log::error!("  - Game_Contents");
+            log::error!("  - ../Game_Contents");
+            log::error!("  - ../../Game_Contents");
+            log::error!("  - ./dist/Game_Contents");
+            log::error!("Please create a symlink or copy your RPG Maker game files to one of these locations.");
             
             // Em caso de desenvolvimento, permite continuar sem a pasta
             std::env::current_dir().unwrap_or_default()
@@ -79,36 +155,762 @@ pub fn run() {
     
     // Verificar se é realmente um diretório
     if game_contents_path.exists() && !game_contents_path.is_dir() {
-        eprintln!("Warning: Game_Contents exists but is not a directory: {:?}", game_contents_path);
+        log::warn!("Game_Contents exists but is not a directory: {:?}", game_contents_path);
     }
+    // Exportações do MV costumam colocar o jogo em Game_Contents/www
+    let web_root = game::web_root(&game_contents_path);
     
-    println!("Starting server on port {} serving from: {:?}", port, game_contents_path);
+    // Ferramenta de empacotamento: criptografa os assets e sai sem abrir o jogo
+    if launch_args.encrypt_assets {
+        match encryption::encrypt_game(&web_root, launch_args.encryption_key.as_deref()) {
+            Ok(summary) => {
+                log::info!(
+                    "Encrypted {} images and {} audio files with key {}",
+                    summary.images,
+                    summary.audio,
+                    summary.key
+                );
+                println!("Encrypted {} images and {} audio files.", summary.images, summary.audio);
+                println!("Encryption key: {}", summary.key);
+            }
+            Err(e) => {
+                log::error!("Failed to encrypt the assets: {}", e);
+                eprintln!("Failed to encrypt the assets: {}", e);
+                std::process::exit(1);
+            }
+        }
+        return;
+    }
+
+    let mut config = config::LauncherConfig::load(&game_contents_path);
+
+    // Ferramenta de empacotamento: lista (ou apaga) imagens e áudios que o jogo não usa
+    if launch_args.optimize_assets {
+        let unused = optimize::find_unused(&web_root, &config.optimize_exclude);
+        let total: u64 = unused.iter().map(|asset| asset.size).sum();
+        for asset in &unused {
+            println!("{} ({} KB)", asset.path, asset.size / 1024);
+        }
+        println!("{} unreferenced files, {} MB", unused.len(), total / (1024 * 1024));
+        if launch_args.strip_unused {
+            match optimize::strip(&web_root, &unused) {
+                Ok(freed) => println!("Removed them, freeing {} MB.", freed / (1024 * 1024)),
+                Err(e) => {
+                    log::error!("Failed to remove the unused assets: {}", e);
+                    eprintln!("Failed to remove the unused assets: {}", e);
+                    std::process::exit(1);
+                }
+            }
+        }
+        return;
+    }
+
+    // Ferramentas de empacotamento: arquivo .pak com a pasta do jogo, e o caminho inverso
+    if let Some(output) = &launch_args.pack {
+        match pak::pack(&web_root, output, config.pak_key.as_deref()) {
+            Ok(summary) => println!(
+                "Packed {} files ({} MB) into {:?} ({} MB).",
+                summary.files,
+                summary.size / (1024 * 1024),
+                output,
+                summary.packed_size / (1024 * 1024)
+            ),
+            Err(e) => {
+                log::error!("Failed to pack the game: {}", e);
+                eprintln!("Failed to pack the game: {}", e);
+                std::process::exit(1);
+            }
+        }
+        return;
+    }
+    if let Some(archive) = &launch_args.unpack {
+        match pak::unpack(archive, &web_root, config.pak_key.as_deref()) {
+            Ok(files) => println!("Extracted {} files into {:?}.", files, web_root),
+            Err(e) => {
+                log::error!("Failed to unpack {:?}: {}", archive, e);
+                eprintln!("Failed to unpack {:?}: {}", archive, e);
+                std::process::exit(1);
+            }
+        }
+        return;
+    }
+    // Índice dos arquivos do jogo, usado na verificação, no atualizador e nos mods
+    if launch_args.generate_manifest {
+        match manifest::Manifest::generate(&web_root).and_then(|manifest| {
+            manifest.write(&web_root)?;
+            Ok(manifest.files.len())
+        }) {
+            Ok(files) => println!("Wrote {} with {} files.", manifest::MANIFEST_FILE_NAME, files),
+            Err(e) => {
+                log::error!("Failed to generate the manifest: {}", e);
+                eprintln!("Failed to generate the manifest: {}", e);
+                std::process::exit(1);
+            }
+        }
+        return;
+    }
+
+    // Assina o capabilities.json do jogo com a chave do empacotador
+    if let Some(key_file) = &launch_args.sign_capabilities {
+        match sandbox::sign_manifest(&web_root, key_file) {
+            Ok(public_key) => println!(
+                "Signed {}. Public key to trust: {}",
+                sandbox::MANIFEST_FILE_NAME,
+                public_key
+            ),
+            Err(e) => {
+                log::error!("Failed to sign the capability manifest: {}", e);
+                eprintln!("Failed to sign the capability manifest: {}", e);
+                std::process::exit(1);
+            }
+        }
+        return;
+    }
+
+    let game_info = game::GameInfo::load(&web_root);
+    // Mudanças feitas com set_config valem por cima do launcher.json do jogo
+    let context = tauri::generate_context!();
+    let config_store = config_store::ConfigStore::load(
+        &config,
+        config_store::overrides_path(&context.config().identifier, &game_info.id),
+    );
+    config = config_store.get();
+    let paks = pak::Paks::load(&web_root, config.pak_key.as_deref());
+    let plugin_compat = plugin_compat::PluginCompat::load(&game_info.dir, &config.disabled_compat_rules);
+    // Teste pelo editor: console de desenvolvedor e devtools, como no NW.js
+    let test_mode = launch_args.test_mode();
+    let safe_mode = launch_args.safe_mode;
+    if test_mode.is_some() || safe_mode {
+        config.dev_console = true;
+    }
+    if safe_mode {
+        log::warn!("Safe mode: mods, compatibility patches and launcher extras are disabled");
+    }
+    // Jogos abertos de outra pasta não recebem acesso total sem um manifesto assinado
+    let trusted_location = launch_args.game_dir.is_none() || test_mode.is_some();
+    let self_test_dir = launch_args.self_test.clone();
+    net::init(&config);
+
+    // Com as duas lojas configuradas, a instalação da GOG tem prioridade sobre a Steam
+    let steam_app_id = config
+        .steam_app_id
+        .filter(|_| config.gog.is_none() || !gog::is_gog_install());
+    let gog_config = config.gog.as_ref().filter(|_| steam_app_id.is_none());
+
+    // Jogos da Steam precisam ser abertos pela Steam para ter o overlay
+    if let Some(app_id) = steam_app_id {
+        if steam::Steam::restart_if_necessary(app_id) {
+            log::info!("Relaunching through Steam");
+            return;
+        }
+    }
+    let steam_client = steam::Steam::init(steam_app_id);
+    let gog_client = gog::Gog::init(gog_config);
+
+    log::info!("Starting server on port {} serving from: {:?}", port, web_root);
     
-    let url_string = format!("http://127.0.0.1:{}/", port);
+    // Argumentos não reconhecidos vão para o jogo (ex.: --test, --etest)
+    let query = launch_args.query_string();
+    let url_string = if query.is_empty() {
+        format!("http://127.0.0.1:{}/", port)
+    } else {
+        format!("http://127.0.0.1:{}/?{}", port, query)
+    };
     let webview_url = WebviewUrl::External(url_string.parse().expect("Invalid localhost URL format"));
     
+    let mut server = external_localhost_plugin::Builder::new(port)
+        .host("127.0.0.1")
+        .external_folder(&web_root)
+        .inject_script(injection::polyfill_script(&launch_args, &web_root))
+        .inject_script(injection::runtime_script());
+    // No modo de segurança só entra o necessário para o jogo rodar e salvar
+    if safe_mode {
+        server = server
+            .inject_script(injection::boot_probe_script())
+            .inject_script(injection::storage_script())
+            .inject_script(injection::save_version_script())
+            .inject_script(injection::dlc_script())
+            .inject_script(injection::console_forward_script(true));
+    } else {
+        server = server
+            .inject_script(injection::dpi_script())
+            .inject_script(injection::boot_probe_script())
+            .inject_script(injection::parity_probe_script())
+            .inject_script(injection::codec_probe_script())
+            .inject_script(injection::power_script())
+            .inject_script(injection::replay_script())
+            .inject_script(injection::audio_output_script())
+            .inject_script(injection::storage_script())
+            .inject_script(injection::save_version_script())
+            .inject_script(injection::dlc_script())
+            .inject_script(injection::hotkeys_script(&config.hotkeys))
+            .inject_script(injection::integer_scaling_script())
+            .inject_script(injection::screenshot_script())
+            .inject_script(injection::recording_script())
+            .inject_script(injection::console_forward_script(config.log_game_console))
+            .inject_script(injection::cursor_script())
+            .inject_script(injection::context_menu_script(config.context_menu))
+            .inject_script(injection::titlebar_script(config.titlebar.as_ref()))
+            .inject_script(injection::keep_awake_script())
+            .inject_script(injection::focus_script(config.background))
+            .inject_script(injection::achievements_script())
+            .inject_script(injection::stats_script())
+            .inject_script(injection::input_remap_script())
+            .inject_script(injection::audio_loops_script());
+    }
+    let font_faces = fonts::font_face_css(&web_root);
+    if !font_faces.is_empty() && !safe_mode {
+        server = server.inject_script(injection::fonts_script(&font_faces));
+    }
+    if config.remote.is_some() && !safe_mode {
+        server = server.inject_script(injection::remote_script());
+    }
+    if config.dev_console {
+        server = server.inject_script(injection::dev_console_script());
+    }
+    if config.automation.is_some() {
+        server = server.inject_script(injection::automation_script());
+    }
+    if test_mode.is_some() || safe_mode {
+        server = server.inject_script(injection::test_play_script());
+    }
+    let watch = launch_args.watch;
+    if watch {
+        server = server.inject_script(injection::hot_reload_script());
+    }
+    // O shim do greenworks só é útil para jogos publicados na Steam
+    if steam_app_id.is_some() {
+        server = server.inject_script(injection::greenworks_script());
+    }
+    if let Some(speedrun) = config.speedrun.as_ref().filter(|_| !safe_mode) {
+        server = server.inject_script(injection::speedrun_script(speedrun));
+    }
+    if config.obs.is_some() && !safe_mode {
+        server = server.inject_script(injection::obs_script());
+    }
+    if !paks.is_empty() {
+        let paks = paks.clone();
+        server = server.archive(move |path| paks.read(path));
+    }
+    // Imagens e áudios criptografados chegam ao jogo já decifrados, mesmo quando o
+    // decifrador do próprio jogo não funciona
+    server = server.decrypt_assets();
+    // Textos em Shift-JIS e outras codificações antigas chegam ao jogo em UTF-8
+    server = server.transform(game_files::to_utf8);
+    if !plugin_compat.is_empty() && !safe_mode {
+        let plugin_compat = plugin_compat.clone();
+        server = server.transform(move |path, content| plugin_compat.apply(path, content));
+    }
+    if let Some(path_aliases) = &config.path_aliases {
+        server = server.aliases(compat::aliases(path_aliases));
+    }
+    // O manual do jogo é servido sem os scripts do launcher
+    if let Some(manual) = manual::find(&game_info.dir, &config) {
+        server = server.skip_injection(move |path| manual::is_manual_page(&manual, path));
+    }
+    // No modo offline a página só pode acessar o servidor local e o IPC, e no modo de
+    // segurança nada vem do cache
+    let offline = config.offline;
+    if offline || safe_mode {
+        server = server.on_request(move |_, response| {
+            if offline {
+                response.add_header("Content-Security-Policy", net::OFFLINE_CSP);
+            }
+            if safe_mode {
+                response.add_header("Cache-Control", "no-store");
+            }
+        });
+    }
+
+    // Comandos chamados pela janela do jogo passam pela política do sandbox
+    let commands = tauri::generate_handler![
+        downloads::start_download,
+        downloads::cancel_download,
+        display::get_zoom,
+        display::set_zoom,
+        display::set_fullscreen,
+        display::toggle_fullscreen,
+        display::get_window_mode,
+        display::set_window_mode,
+        display::set_integer_scaling,
+        display::set_aspect_ratio_lock,
+        display::set_dpi_scale,
+        display::set_window_title,
+        display::set_always_on_top,
+        pip::set_pip,
+        pip::toggle_pip,
+        steam::steam_status,
+        steam::steam_unlock_achievement,
+        steam::steam_clear_achievement,
+        steam::steam_is_achieved,
+        steam::steam_set_stat,
+        steam::steam_get_stat,
+        steam::steam_set_rich_presence,
+        achievements::get_achievements,
+        achievements::unlock_achievement,
+        achievements::reset_achievement,
+        audio::get_audio_loop_info,
+        audio::get_audio_loops,
+        community::get_community_achievements,
+        community::get_community_watch_list,
+        community::report_game_state,
+        discord::set_presence,
+        discord::clear_presence,
+        data_files::read_data_file,
+        encryption::get_encryption_info,
+        encryption::decrypt_asset,
+        display::set_cursor_autohide,
+        display::set_cursor_visible,
+        display::set_pointer_lock,
+        settings::get_settings,
+        screenshots::capture_screenshot,
+        screenshots::save_screenshot,
+        recording::start_recording,
+        recording::stop_recording,
+        recording::is_recording,
+        recording::save_recording,
+        recording::push_recording_frame,
+        recording::finish_recording,
+        recording::abort_recording,
+        debug_console::toggle_debug_console,
+        debug_console::get_debug_log,
+        debug_console::log_from_game,
+        dev_console::toggle_dev_console,
+        dev_console::dev_eval,
+        dev_console::dev_eval_result,
+        automation::automation_result,
+        sandbox::get_sandbox_policy,
+        test_play::open_devtools,
+        splash::get_splash_info,
+        keep_awake::set_video_playing,
+        focus::set_mute_on_blur,
+        focus::set_pause_on_blur,
+        focus::set_muted,
+        tray::set_minimize_to_tray,
+        config_store::get_config,
+        config_store::set_config,
+        saves::list_saves,
+        saves::list_saves_detailed,
+        saves::read_save,
+        saves::write_save,
+        saves::delete_save,
+        saves::remove_save,
+        saves::export_save,
+        saves::import_save,
+        saves::show_save_folder,
+        save_backends::get_save_backend,
+        save_backends::set_save_backend,
+        save_backups::list_save_backups,
+        save_backups::restore_save_backup,
+        browser_saves::import_browser_saves,
+        save_conflicts::report_save_conflict,
+        save_conflicts::list_save_conflicts,
+        save_conflicts::resolve_conflict,
+        save_editor::list_save_slots,
+        save_editor::decode_save,
+        save_editor::write_decoded_save,
+        save_share::export_save_code,
+        save_share::import_save_code,
+        save_thumbnails::save_thumbnail,
+        save_thumbnails::get_save_thumbnail,
+        plugin_compat::get_plugin_compat,
+        save_versions::get_game_version,
+        save_versions::check_save_version,
+        monitors::list_monitors,
+        monitors::move_to_monitor,
+        gog::gog_status,
+        gog::gog_unlock_achievement,
+        gog::gog_clear_achievement,
+        gog::gog_is_achieved,
+        input::get_input_bindings,
+        input::set_key_binding,
+        input::set_gamepad_binding,
+        input::reset_input_bindings,
+        itch::itch_status,
+        menu::show_context_menu,
+        pause::pause_game,
+        pause::resume_game,
+        pause::is_game_paused,
+        parity::get_parity_report,
+        manual::open_manual,
+        codecs::report_codecs,
+        companion::toggle_companion,
+        replay::start_replay_recording,
+        replay::record_input,
+        replay::stop_replay_recording,
+        replay::list_replays,
+        replay::play_replay,
+        replay::get_replay_session,
+        replay::replay_finished,
+        mixer::report_audio_devices,
+        mixer::list_audio_devices,
+        mixer::set_audio_device,
+        mixer::set_master_volume,
+        game_files::read_game_file,
+        game_files::read_game_file_bytes,
+        game_files::list_directory,
+        game_files::file_stat,
+        manifest::generate_manifest,
+        manifest::verify_game_files,
+        manifest::manifest_changes,
+        manifest::find_mod_conflicts,
+        dlc::list_dlc,
+        dlc::install_dlc,
+        dlc::enable_dlc,
+        dlc::disable_dlc,
+        dlc::get_active_dlc,
+        dlc::check_save_dlc,
+        mods::list_mods,
+        mods::set_mod_order,
+        mods::set_mod_enabled,
+        mods::open_mods_folder,
+        notifications::notify,
+        obs::obs_event,
+        remote::remote_info,
+        reports::get_report_preview,
+        reports::send_report,
+        reports::discard_report,
+        speedrun::speedrun_event,
+        stats::get_stats,
+        stats::set_collect_stats,
+        stats::record_map_time,
+        stats::export_stats,
+        self_test::self_test_frame,
+    ];
+
     tauri::Builder::default()
-        .plugin(
-            external_localhost_plugin::Builder::new(port)
-                .host("127.0.0.1")
-                .external_folder(&game_contents_path)
-                .build()
-        )
+        .plugin(server.build())
         .setup(move |app| {
-            println!("Creating window with URL: {}", url_string);
-            
-            // Aguarda um pouco para garantir que o servidor esteja rodando
-            std::thread::sleep(std::time::Duration::from_millis(500));
-            
-            WebviewWindowBuilder::new(app, "main", webview_url)
-                .title("RPG Maker Game Launcher")
-                .inner_size(1280.0, 720.0)
-                .resizable(true)
-                .build()?;
-            
+            logging::attach(app.handle());
+            logging::open_file(&app.path().app_log_dir()?);
+            app.manage(safe_mode::SafeMode(safe_mode));
+            app.manage(sandbox::Sandbox::load(
+                &game_info.dir,
+                &app.path().app_config_dir()?,
+                trusted_location,
+            ));
+            // The synchronous fs helpers read what read_game_file and list_directory would
+            let fs_read = app.state::<sandbox::Sandbox>().allows(sandbox::Permission::FsRead);
+            app.state::<external_localhost_plugin::GameFiles>().allow_routes(fs_read);
+            app.manage(safe_mode::LaunchGuard::start(
+                app.path().app_data_dir()?.join("launches").join(format!("{}.json", game_info.id)),
+            ));
+            if let Some(dir) = &self_test_dir {
+                app.manage(self_test::SelfTest::new(dir.clone()));
+            }
+            // Relatórios de erro só existem se o jogo tiver para onde enviá-los
+            if config.crash_reports.is_some() {
+                let reports = reports::Reports::new(app.path().app_data_dir()?.join("crash_reports"));
+                reports.install_panic_hook();
+                app.manage(reports);
+            }
+
+            // Mostra a splash screen antes de qualquer outra coisa
+            game::set_app_user_model_id(&game_info);
+            app.manage(config.clone());
+            app.manage(config_store);
+            app.manage(game_info.clone());
+            app.manage(encryption::EncryptionInfo::load(&game_info.dir));
+            app.manage(paks.clone());
+            app.manage(plugin_compat.clone());
+            saves::migrate_legacy_dir(app.handle())?;
+            app.manage(save_backends::SaveBackends::new(app.handle(), &config.save_backend)?);
+            power::watch(app.handle());
+            watchdog::watch(app.handle());
+            if watch {
+                hot_reload::watch(app.handle(), data_files::data_dir(&game_info.dir));
+            }
+            if let Some(mode) = test_mode {
+                test_play::start(app.handle(), mode);
+            }
+            if config.splash {
+                splash::open(app.handle(), &game_info)?;
+            }
+
+            // Configurações salvas deste jogo (zoom, etc.)
+            let settings_path = app
+                .path()
+                .app_config_dir()?
+                .join("games")
+                .join(format!("{}.json", game_info.id));
+            app.manage(settings::Settings::load(settings_path));
+
+            // Estatísticas locais, só se o jogador permitir
+            let stats_path = app
+                .path()
+                .app_data_dir()?
+                .join("stats")
+                .join(format!("{}.json", game_info.id));
+            let collect_stats = app.state::<settings::Settings>().get().collect_stats;
+            let launcher_version = app.package_info().version.to_string();
+            app.manage(stats::Stats::load(stats_path, launcher_version, collect_stats));
+
+            // Conteúdo opcional baixado pelo jogo é servido por cima do Game_Contents
+            let overlay_dir = app.path().app_data_dir()?.join("overlay").join(&game_info.id);
+            app.state::<external_localhost_plugin::Mounts>().mount(&overlay_dir);
+            app.manage(downloads::DownloadManager::new(overlay_dir, game_info.dir.clone()));
+
+            // DLC instalado pelo jogador fica entre o overlay e os mods
+            let dlc = dlc::Dlc::load(app.path().app_data_dir()?.join("dlc").join(&game_info.id));
+            dlc.apply(app.handle());
+            app.manage(dlc);
+
+            // Mods instalados pelo jogador entram por cima do overlay, na ordem escolhida
+            let mods = mods::Mods::load(app.path().app_data_dir()?.join("mods").join(&game_info.id));
+            if !safe_mode {
+                mods.apply(app.handle());
+            }
+            app.manage(mods);
+            app.manage(recording::Recorder::default());
+            app.manage(pip::Pip::default());
+            app.manage(mixer::AudioDevices::default());
+            app.manage(save_conflicts::SaveConflicts::default());
+            app.manage(replay::Replays::new(
+                app.path().app_data_dir()?.join("replays").join(&game_info.id),
+            ));
+            app.manage(dev_console::DevConsole::default());
+            if let Some(speedrun) = &config.speedrun {
+                app.manage(speedrun::Speedrun::new(speedrun));
+            }
+            if let Some(obs_config) = config.obs.clone() {
+                app.manage(obs::Obs::new(obs_config));
+            }
+            app.manage(steam_client);
+            app.manage(gog_client);
+            app.manage(itch::Itch::from_env());
+            if let Some(remote) = config.remote.as_ref().and_then(|r| remote::Remote::start(app.handle(), r)) {
+                app.manage(remote);
+            }
+            if let Some(automation_config) = &config.automation {
+                let endpoint_file = app
+                    .path()
+                    .app_data_dir()?
+                    .join("automation")
+                    .join(format!("{}.json", game_info.id));
+                if let Some(automation) = automation::Automation::start(app.handle(), automation_config, endpoint_file) {
+                    app.manage(automation);
+                }
+            }
+            if let Some(twitch_config) = &config.twitch {
+                twitch::start(app.handle(), twitch_config);
+            }
+            let achievements_path = app
+                .path()
+                .app_data_dir()?
+                .join("achievements")
+                .join(format!("{}.json", game_info.id));
+            app.manage(achievements::Achievements::load(&game_info.dir, achievements_path));
+            let community_dir = app.path().app_data_dir()?.join("community").join(&game_info.id);
+            let community = community::Community::load(community_dir);
+            if !safe_mode {
+                community.apply(app.handle());
+            }
+            app.manage(community);
+            // Texto em japonês, chinês ou coreano ganha uma fonte CJK de reserva
+            if !safe_mode {
+                fonts::apply_cjk_fallback(app.handle(), &game_info.dir, config.cjk_font.as_deref())?;
+            }
+            if let Some(discord_config) = config.discord.clone().filter(|d| !d.client_id.is_empty()) {
+                let app_handle = app.handle().clone();
+                let game_dir = game_info.dir.clone();
+                let title = game_info.title.clone();
+                let save_dir = saves::save_dir(app.handle())?;
+                // Conectar ao Discord pode demorar, então não bloqueia a inicialização
+                std::thread::spawn(move || {
+                    app_handle.manage(discord::Discord::new(&discord_config, title, game_dir, save_dir));
+                });
+            }
+            app.manage(keep_awake::KeepAwake::new(config.keep_awake, game_info.title.clone()));
+            app.manage(pause::Pause::default());
+            pause::watch_suspend(app.handle());
+            if config.tray {
+                tray::create(app.handle(), &game_info)?;
+            }
+            boss_key::register(app.handle(), &config.hotkeys.boss_key);
+
+            // O resto roda fora da thread principal: os diálogos bloqueiam e a splash
+            // screen precisa do event loop livre para aparecer
+            log::info!("Creating window with URL: {}", url_string);
+            let app_handle = app.handle().clone();
+            std::thread::spawn(move || {
+                if let Err(e) = open_game_window(&app_handle, webview_url, port) {
+                    log::error!("Failed to open the game window: {}", e);
+                    app_handle.exit(1);
+                }
+            });
+
             Ok(())
         })
         .plugin(tauri_plugin_opener::init())
-        .run(tauri::generate_context!())
-        .expect("error while running tauri application");
-}
\ No newline at end of file
+        .plugin(tauri_plugin_dialog::init())
+        .plugin(tauri_plugin_notification::init())
+        .plugin(boss_key::plugin())
+        .invoke_handler(move |invoke| sandbox::guard(invoke, &commands))
+        .build(context)
+        .expect("error while running tauri application")
+        .run(|app, event| {
+            if let tauri::RunEvent::Exit = event {
+                app.state::<stats::Stats>().end_session();
+                app.state::<safe_mode::LaunchGuard>().end();
+                if let Some(automation) = app.try_state::<automation::Automation>() {
+                    automation.stop();
+                }
+            }
+        });
+}
+fn open_game_window<R: Runtime>(
+    app: &AppHandle<R>,
+    webview_url: WebviewUrl,
+    port: u16,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let config = app.state::<config::LauncherConfig>().inner().clone();
+    let game_info = app.state::<game::GameInfo>().inner().clone();
+
+    // Verifica dependências do sistema antes de abrir a janela
+    let missing = preflight::run_checks();
+    if !missing.is_empty() && !preflight::report(app, &missing) {
+        app.exit(1);
+        return Ok(());
+    }
+    // E se os arquivos de dados do jogo podem ser carregados (num .pak eles não ficam soltos)
+    let packed = !app.state::<pak::Paks>().is_empty();
+    let problems = if packed { Vec::new() } else { data_files::check(&game_info.dir) };
+    for problem in &problems {
+        log::error!("{}: {}", problem.file, problem.problem);
+    }
+    if !problems.is_empty() && !data_files::report(app, &problems) {
+        app.exit(1);
+        return Ok(());
+    }
+
+    // Depois de vários crashes seguidos, oferece o modo de segurança (num autoteste não há quem responda)
+    let safe_mode = app.state::<safe_mode::SafeMode>().0;
+    let self_testing = app.try_state::<self_test::SelfTest>().is_some();
+    if !safe_mode && !self_testing && app.state::<safe_mode::LaunchGuard>().is_crash_loop() && safe_mode::offer(app) {
+        return Ok(());
+    }
+
+    // Impede que duas instâncias escrevam nos mesmos saves
+    let save_dir = saves::save_dir(app)?;
+    match saves::SaveDirLock::acquire(&save_dir) {
+        Ok(Some(lock)) => {
+            app.manage(lock);
+        }
+        Ok(None) => {
+            if !saves::warn_locked(app, &save_dir) {
+                app.exit(1);
+                return Ok(());
+            }
+        }
+        Err(e) => log::warn!("Failed to lock save folder {:?}: {}", save_dir, e),
+    }
+
+    let settings = app.state::<settings::Settings>().get();
+    let window_mode = display::current_window_mode(&config, &app.state::<settings::Settings>());
+
+    // Aguarda um pouco para garantir que o servidor esteja rodando
+    std::thread::sleep(std::time::Duration::from_millis(500));
+
+    let title = if safe_mode {
+        format!("{} (safe mode)", game_info.title)
+    } else {
+        game_info.title.clone()
+    };
+    let mut builder = WebviewWindowBuilder::new(app, display::MAIN_WINDOW_LABEL, webview_url)
+        .title(&title)
+        .inner_size(1280.0, 720.0)
+        .resizable(true)
+        .visible(!config.splash)
+        .always_on_top(settings.always_on_top)
+        .data_directory(saves::webview_data_dir(&save_dir));
+    builder = focus::configure_background(builder, config.background);
+    if config.offline {
+        builder = builder.on_navigation(|url| net::is_local_url(url));
+    }
+    if config.menu_bar {
+        builder = builder.menu(menu::build(app)?);
+    }
+    // Ícone do jogo na janela e na barra de tarefas
+    if let Some(icon) = game::load_icon(&game_info) {
+        builder = builder.icon(icon)?;
+    }
+    let window = builder.build()?;
+    window.on_menu_event(|window, event| menu::on_event(window.app_handle(), event));
+    if config.companion.as_ref().is_some_and(|companion| companion.open_at_start) {
+        companion::open(app)?;
+    }
+
+    // Oferece enviar o relatório se a última sessão terminou num crash
+    if app.try_state::<reports::Reports>().is_some_and(|r| r.has_pending()) {
+        menu::open_report(app)?;
+    }
+
+    if settings.zoom != 1.0 || settings.dpi_scale.is_some() {
+        display::apply_zoom(&window, &app.state::<settings::Settings>())?;
+    }
+    monitors::apply_preferred_monitor(&window, &config, &app.state::<settings::Settings>())?;
+    display::apply_window_mode(&window, window_mode, &config)?;
+    display::apply_integer_scaling(&window, &game_info, settings.integer_scaling)?;
+    app.state::<keep_awake::KeepAwake>()
+        .set_fullscreen(window.is_fullscreen().unwrap_or(false));
+    if config.splash {
+        splash::swap_when_ready(app, window.clone());
+    }
+    if self_testing {
+        self_test::start(app);
+    }
+
+    let app_handle = app.clone();
+    let event_window = window.clone();
+    let aspect_ratio_lock = display::AspectRatioLock::default();
+    window.on_window_event(move |event| match event {
+        // No modo quiosque, Alt+F4 só fecha o jogo se a configuração permitir
+        WindowEvent::CloseRequested { api, .. } => {
+            let launcher_config = app_handle.state::<config::LauncherConfig>();
+            let game_settings = app_handle.state::<settings::Settings>();
+            if display::current_window_mode(&launcher_config, &game_settings) == config::WindowMode::Kiosk
+                && !launcher_config.kiosk_allow_close
+            {
+                api.prevent_close();
+            }
+        }
+        WindowEvent::Resized(size) => {
+            app_handle
+                .state::<keep_awake::KeepAwake>()
+                .set_fullscreen(event_window.is_fullscreen().unwrap_or(false));
+            tray::on_resized(&event_window);
+            let _ = companion::layout(&event_window);
+            if app_handle.state::<settings::Settings>().get().lock_aspect_ratio {
+                let game = app_handle.state::<game::GameInfo>();
+                aspect_ratio_lock.on_resized(&event_window, &game, *size);
+            }
+        }
+        // A escala do sistema muda ao trocar de monitor
+        WindowEvent::ScaleFactorChanged { .. } => {
+            let game_settings = app_handle.state::<settings::Settings>();
+            if game_settings.get().dpi_scale.is_some() {
+                let _ = display::apply_zoom(&event_window, &game_settings);
+            }
+        }
+        WindowEvent::Focused(focused) => focus::emit_focus_changed(&event_window, *focused),
+        _ => {}
+    });
+
+    // Se o jogo não iniciar a tempo, mostra a página de diagnóstico em vez de uma tela preta
+    if config.boot_timeout_secs > 0 {
+        let diagnostics = app.state::<external_localhost_plugin::Diagnostics>().inner().clone();
+        let diagnostics_url = format!("http://127.0.0.1:{}/__launcher/diagnostics", port);
+        let timeout = std::time::Duration::from_secs(config.boot_timeout_secs);
+        std::thread::spawn(move || {
+            std::thread::sleep(timeout);
+            if !diagnostics.game_started() {
+                log::warn!("Game did not start within {:?}, showing diagnostics page", timeout);
+                if let Ok(url) = diagnostics_url.parse() {
+                    let _ = window.navigate(url);
+                }
+            }
+        });
+    }
+
+    Ok(())
+}
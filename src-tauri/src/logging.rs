@@ -0,0 +1,198 @@
+//! Launcher logging.
+//!
+//! Records from the launcher (and the localhost plugin) are written to stderr and kept in
+//! a small in-memory buffer, which the debug console window reads when it opens and then
+//! follows through `debug-console-entry` events.
+//!
+//! Every entry, including the game's console output, also goes to `launcher.log` in the
+//! app's log folder. The file is rotated when a session starts and when it grows past
+//! 1 MB, keeping the last few as `launcher.1.log`, `launcher.2.log`...
+
+use std::collections::VecDeque;
+use std::fs::{self, File, OpenOptions};
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::sync::mpsc::{self, Sender};
+use std::sync::{Mutex, OnceLock};
+
+use log::{Level, LevelFilter, Log, Metadata, Record};
+use serde::{Deserialize, Serialize};
+use tauri::{AppHandle, Emitter, Runtime};
+
+use crate::debug_console::DEBUG_CONSOLE_LABEL;
+
+/// How many entries are kept for the debug console.
+const MAX_ENTRIES: usize = 2000;
+/// Size at which the log file is rotated.
+const MAX_FILE_SIZE: u64 = 1024 * 1024;
+/// How many log files are kept, the current one included.
+const MAX_FILES: usize = 5;
+const LOG_FILE_NAME: &str = "launcher";
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct LogEntry {
+    pub time: String,
+    pub level: String,
+    /// `launcher`, `server` or `game`.
+    pub source: String,
+    pub message: String,
+}
+
+struct LogFile {
+    dir: PathBuf,
+    file: File,
+    size: u64,
+}
+
+struct Logger {
+    entries: Mutex<VecDeque<LogEntry>>,
+    listener: Mutex<Option<Sender<LogEntry>>>,
+    file: Mutex<Option<LogFile>>,
+}
+
+static LOGGER: OnceLock<Logger> = OnceLock::new();
+
+fn logger() -> &'static Logger {
+    LOGGER.get_or_init(|| Logger {
+        entries: Mutex::new(VecDeque::new()),
+        listener: Mutex::new(None),
+        file: Mutex::new(None),
+    })
+}
+
+impl Log for Logger {
+    fn enabled(&self, metadata: &Metadata) -> bool {
+        // Our own records (and the localhost plugin's) down to debug, dependencies only when
+        // something is wrong
+        let target = metadata.target();
+        if target.starts_with(env!("CARGO_CRATE_NAME")) || target.starts_with("external_localhost_plugin") {
+            metadata.level() <= Level::Debug
+        } else {
+            metadata.level() <= Level::Warn
+        }
+    }
+
+    fn log(&self, record: &Record) {
+        if !self.enabled(record.metadata()) {
+            return;
+        }
+
+        let source = if record.target().contains("external_localhost_plugin") {
+            "server"
+        } else {
+            "launcher"
+        };
+        push(LogEntry {
+            time: chrono::Local::now().format("%H:%M:%S%.3f").to_string(),
+            level: record.level().to_string(),
+            source: source.to_string(),
+            message: record.args().to_string(),
+        });
+    }
+
+    fn flush(&self) {}
+}
+
+/// Install the logger. Call once, before anything logs.
+pub fn init() {
+    if log::set_logger(logger()).is_ok() {
+        log::set_max_level(LevelFilter::Debug);
+    }
+}
+
+/// Start forwarding new entries to the debug console window.
+pub fn attach<R: Runtime>(app: &AppHandle<R>) {
+    let (sender, receiver) = mpsc::channel::<LogEntry>();
+    *logger().listener.lock().unwrap() = Some(sender);
+
+    // Events are emitted from their own thread so logging never re-enters the event system
+    let app = app.clone();
+    std::thread::spawn(move || {
+        for entry in receiver {
+            let _ = app.emit_to(DEBUG_CONSOLE_LABEL, "debug-console-entry", entry);
+        }
+    });
+}
+
+fn log_path(dir: &Path, index: usize) -> PathBuf {
+    match index {
+        0 => dir.join(format!("{}.log", LOG_FILE_NAME)),
+        n => dir.join(format!("{}.{}.log", LOG_FILE_NAME, n)),
+    }
+}
+
+/// Shift the log files by one, dropping the oldest, and start an empty one.
+fn rotate(dir: &Path) -> std::io::Result<File> {
+    for index in (0..MAX_FILES - 1).rev() {
+        let from = log_path(dir, index);
+        if from.is_file() {
+            fs::rename(&from, log_path(dir, index + 1))?;
+        }
+    }
+    File::create(log_path(dir, 0))
+}
+
+/// Start writing to `launcher.log` in `dir`, with the entries logged so far.
+pub fn open_file(dir: &Path) {
+    let opened = fs::create_dir_all(dir).and_then(|_| rotate(dir));
+    let file = match opened {
+        Ok(file) => file,
+        Err(e) => {
+            log::warn!("Can't write the log file in {:?}: {}", dir, e);
+            return;
+        }
+    };
+    let mut log_file = LogFile {
+        dir: dir.to_path_buf(),
+        file,
+        size: 0,
+    };
+    for entry in recent() {
+        write_entry(&mut log_file, &entry);
+    }
+    *logger().file.lock().unwrap() = Some(log_file);
+}
+
+fn write_entry(log_file: &mut LogFile, entry: &LogEntry) {
+    if log_file.size >= MAX_FILE_SIZE {
+        match rotate(&log_file.dir) {
+            Ok(file) => {
+                log_file.file = file;
+                log_file.size = 0;
+            }
+            // Keep appending to the current file rather than losing entries
+            Err(e) => eprintln!("Failed to rotate the log file: {}", e),
+        }
+    }
+    let line = format!("[{} {} {}] {}\n", entry.time, entry.level, entry.source, entry.message);
+    if log_file.file.write_all(line.as_bytes()).is_ok() {
+        log_file.size += line.len() as u64;
+    }
+}
+
+/// Add an entry, e.g. a console message forwarded from the game page.
+pub fn push(entry: LogEntry) {
+    if entry.source != "game" {
+        eprintln!("[{} {} {}] {}", entry.time, entry.level, entry.source, entry.message);
+    }
+    if let Some(log_file) = logger().file.lock().unwrap().as_mut() {
+        write_entry(log_file, &entry);
+    }
+
+    let logger = logger();
+    {
+        let mut entries = logger.entries.lock().unwrap();
+        if entries.len() == MAX_ENTRIES {
+            entries.pop_front();
+        }
+        entries.push_back(entry.clone());
+    }
+    if let Some(listener) = logger.listener.lock().unwrap().as_ref() {
+        let _ = listener.send(entry);
+    }
+}
+
+pub fn recent() -> Vec<LogEntry> {
+    logger().entries.lock().unwrap().iter().cloned().collect()
+}
@@ -0,0 +1,194 @@
+//! The asset manifest: every file of the game folder with its SHA-256 and size.
+//!
+//! `manifest.json` at the root of `Game_Contents` is the one index the other subsystems
+//! share: integrity verification compares the folder against it, the updater compares it
+//! with the server's to know which files to download, and mod conflict detection uses it to
+//! tell which game files a mod replaces. Packagers generate it with `--generate-manifest`,
+//! or from the game with `generate_manifest`.
+
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::Path;
+
+use serde::{Deserialize, Serialize};
+use tauri::State;
+
+use crate::downloads::sha256_file;
+use crate::encryption::files_in;
+use crate::game::GameInfo;
+use crate::mods::Mods;
+
+pub const MANIFEST_FILE_NAME: &str = "manifest.json";
+const MANIFEST_VERSION: u32 = 1;
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct FileEntry {
+    pub sha256: String,
+    pub size: u64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Manifest {
+    pub version: u32,
+    /// By path relative to the game folder, with forward slashes.
+    pub files: BTreeMap<String, FileEntry>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Problem {
+    Missing,
+    Modified,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct FileProblem {
+    pub path: String,
+    pub problem: Problem,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ModConflict {
+    pub path: String,
+    /// The mods shipping the file, in load order. The last one wins.
+    pub mods: Vec<String>,
+    /// Whether the file replaces one of the game's own files.
+    pub replaces_game_file: bool,
+}
+
+/// The path of `path` under `root`, with forward slashes.
+fn relative_path(root: &Path, path: &Path) -> Option<String> {
+    let relative = path.strip_prefix(root).ok()?;
+    Some(relative.to_string_lossy().replace('\\', "/"))
+}
+
+impl Manifest {
+    /// Hash every file under `dir`, except the manifest itself.
+    pub fn generate(dir: &Path) -> Result<Self, String> {
+        let mut files = BTreeMap::new();
+        for path in files_in(dir) {
+            let Some(relative) = relative_path(dir, &path) else {
+                continue;
+            };
+            if relative == MANIFEST_FILE_NAME {
+                continue;
+            }
+            let size = fs::metadata(&path).map_err(|e| format!("{:?}: {}", path, e))?.len();
+            let sha256 = sha256_file(&path).map_err(|e| format!("{:?}: {}", path, e))?;
+            files.insert(relative, FileEntry { sha256, size });
+        }
+        Ok(Self {
+            version: MANIFEST_VERSION,
+            files,
+        })
+    }
+
+    /// The manifest shipped with the game, if it has one.
+    pub fn load(game_dir: &Path) -> Option<Self> {
+        let path = game_dir.join(MANIFEST_FILE_NAME);
+        let content = fs::read_to_string(&path).ok()?;
+        match serde_json::from_str(&content) {
+            Ok(manifest) => Some(manifest),
+            Err(e) => {
+                log::warn!("Invalid {:?}: {}", path, e);
+                None
+            }
+        }
+    }
+
+    pub fn write(&self, game_dir: &Path) -> Result<(), String> {
+        let path = game_dir.join(MANIFEST_FILE_NAME);
+        let content = serde_json::to_string_pretty(self).map_err(|e| e.to_string())?;
+        fs::write(&path, content).map_err(|e| format!("{:?}: {}", path, e))
+    }
+
+    /// The files of `game_dir` that are missing or differ from the manifest. Sizes are
+    /// compared first so unchanged folders are checked without hashing everything twice.
+    pub fn verify(&self, game_dir: &Path) -> Vec<FileProblem> {
+        let mut problems = Vec::new();
+        for (path, entry) in &self.files {
+            let full_path = game_dir.join(path);
+            let problem = match fs::metadata(&full_path) {
+                Err(_) => Some(Problem::Missing),
+                Ok(metadata) if metadata.len() != entry.size => Some(Problem::Modified),
+                Ok(_) => match sha256_file(&full_path) {
+                    Ok(sha256) if sha256.eq_ignore_ascii_case(&entry.sha256) => None,
+                    _ => Some(Problem::Modified),
+                },
+            };
+            if let Some(problem) = problem {
+                problems.push(FileProblem {
+                    path: path.clone(),
+                    problem,
+                });
+            }
+        }
+        problems
+    }
+
+    /// The files of `newer` that are new or different here, which an update has to download.
+    pub fn changed_in(&self, newer: &Manifest) -> Vec<String> {
+        newer
+            .files
+            .iter()
+            .filter(|(path, entry)| self.files.get(*path) != Some(entry))
+            .map(|(path, _)| path.clone())
+            .collect()
+    }
+}
+
+/// The game's manifest, or one generated on the spot when it doesn't ship one.
+fn game_manifest(game_dir: &Path) -> Result<Manifest, String> {
+    match Manifest::load(game_dir) {
+        Some(manifest) => Ok(manifest),
+        None => Manifest::generate(game_dir),
+    }
+}
+
+/// Index the game folder. With `write`, the result is saved as `manifest.json` in it too.
+#[tauri::command]
+pub async fn generate_manifest(game: State<'_, GameInfo>, write: Option<bool>) -> Result<Manifest, String> {
+    let manifest = Manifest::generate(&game.dir)?;
+    if write.unwrap_or(false) {
+        manifest.write(&game.dir)?;
+    }
+    Ok(manifest)
+}
+
+/// Compare the game folder with its `manifest.json`.
+#[tauri::command]
+pub async fn verify_game_files(game: State<'_, GameInfo>) -> Result<Vec<FileProblem>, String> {
+    let manifest = Manifest::load(&game.dir).ok_or_else(|| "The game has no manifest.json".to_string())?;
+    Ok(manifest.verify(&game.dir))
+}
+
+/// The files an update described by the server's manifest has to download.
+#[tauri::command]
+pub async fn manifest_changes(game: State<'_, GameInfo>, newer: Manifest) -> Result<Vec<String>, String> {
+    Ok(game_manifest(&game.dir)?.changed_in(&newer))
+}
+
+/// The files more than one enabled mod ships, and the mod files that replace game files.
+#[tauri::command]
+pub async fn find_mod_conflicts(game: State<'_, GameInfo>, mods: State<'_, Mods>) -> Result<Vec<ModConflict>, String> {
+    let game_manifest = game_manifest(&game.dir)?;
+    let mut shipped: BTreeMap<String, Vec<String>> = BTreeMap::new();
+    for info in mods.list().into_iter().filter(|m| m.enabled) {
+        for path in Manifest::generate(&info.dir)?.files.into_keys() {
+            if path == crate::mods::MANIFEST_FILE_NAME {
+                continue;
+            }
+            shipped.entry(path).or_default().push(info.id.clone());
+        }
+    }
+    Ok(shipped
+        .into_iter()
+        .map(|(path, mods)| ModConflict {
+            replaces_game_file: game_manifest.files.contains_key(&path),
+            path,
+            mods,
+        })
+        .filter(|conflict| conflict.mods.len() > 1 || conflict.replaces_game_file)
+        .collect())
+}
@@ -0,0 +1,76 @@
+//! The manual shipped with the game, opened from Help > Manual or with `open_manual`.
+//!
+//! The manual is `manual/index.html` in the game folder, or the page or PDF set as `manual`
+//! in launcher.json. It is served by the game's server like the game itself, but without
+//! the injected scripts, and shown in a window of its own. WebKitGTK can't show PDFs, so on
+//! Linux a PDF manual opens in the system viewer instead.
+
+use std::path::Path;
+
+use tauri::{AppHandle, Manager, Runtime, WebviewUrl, WebviewWindowBuilder};
+use tauri_plugin_opener::OpenerExt;
+
+use crate::config::LauncherConfig;
+use crate::display;
+use crate::game::GameInfo;
+use crate::game_files;
+
+pub const MANUAL_LABEL: &str = "manual";
+const DEFAULT_MANUAL: &str = "manual/index.html";
+
+/// Path of the game's manual relative to the game folder, if it has one.
+pub fn find(game_dir: &Path, config: &LauncherConfig) -> Option<String> {
+    let manual = config
+        .manual
+        .as_deref()
+        .unwrap_or(DEFAULT_MANUAL)
+        .replace('\\', "/")
+        .trim_start_matches('/')
+        .to_string();
+    game_files::resolve(game_dir, &manual)
+        .ok()
+        .filter(|path| path.is_file())
+        .map(|_| manual)
+}
+
+/// Whether the page at `path` belongs to the manual: the manual itself, or a page in its
+/// folder when it has one of its own.
+pub fn is_manual_page(manual: &str, path: &str) -> bool {
+    match manual.rsplit_once('/') {
+        Some((folder, _)) => path.strip_prefix(folder).is_some_and(|rest| rest.starts_with('/')),
+        None => path == manual,
+    }
+}
+
+/// Open the manual, or focus it if it is already open.
+pub fn open<R: Runtime>(app: &AppHandle<R>) -> Result<(), String> {
+    let game_info = app.state::<GameInfo>();
+    let manual =
+        find(&game_info.dir, &app.state::<LauncherConfig>()).ok_or_else(|| "This game has no manual".to_string())?;
+
+    if cfg!(target_os = "linux") && manual.to_lowercase().ends_with(".pdf") {
+        let path = game_info.dir.join(&manual);
+        return app
+            .opener()
+            .open_path(path.to_string_lossy(), None::<&str>)
+            .map_err(|e| e.to_string());
+    }
+
+    if let Some(window) = app.get_webview_window(MANUAL_LABEL) {
+        return window.set_focus().map_err(|e| e.to_string());
+    }
+    let mut url = display::main_window(app)?.url().map_err(|e| e.to_string())?;
+    url.set_path(&manual);
+    url.set_query(None);
+    WebviewWindowBuilder::new(app, MANUAL_LABEL, WebviewUrl::External(url))
+        .title(format!("{} Manual", game_info.title))
+        .inner_size(800.0, 600.0)
+        .build()
+        .map_err(|e| e.to_string())?;
+    Ok(())
+}
+
+#[tauri::command]
+pub async fn open_manual<R: Runtime>(app: AppHandle<R>) -> Result<(), String> {
+    open(&app)
+}
@@ -0,0 +1,191 @@
+//! Optional application menu bar, enabled with `"menuBar": true` in `launcher.json`.
+//!
+//! Its entries open the launcher's own panels (save manager, settings, logs) as separate
+//! windows next to the game. The right-click launcher menu shares the same actions.
+
+use tauri::menu::{Menu, MenuEvent, MenuItem, PredefinedMenuItem, Submenu};
+use tauri::{AppHandle, Manager, Runtime, WebviewUrl, WebviewWindow, WebviewWindowBuilder};
+use tauri_plugin_dialog::{DialogExt, MessageDialogKind};
+
+use crate::companion;
+use crate::config::LauncherConfig;
+use crate::debug_console;
+use crate::display;
+use crate::game::GameInfo;
+use crate::manual;
+use crate::parity;
+use crate::pause;
+use crate::remote::{self, Remote};
+use crate::reports::REPORT_LABEL;
+use crate::save_editor::SAVE_EDITOR_LABEL;
+use crate::saves;
+use crate::screenshots;
+use crate::settings::Settings;
+
+pub const SAVE_MANAGER_LABEL: &str = "save-manager";
+pub const SETTINGS_LABEL: &str = "settings";
+pub const MODS_LABEL: &str = "mods";
+pub const CONTROLS_LABEL: &str = "controls";
+
+pub fn build<R: Runtime>(app: &AppHandle<R>) -> tauri::Result<Menu<R>> {
+    let file = Submenu::with_items(
+        app,
+        "File",
+        true,
+        &[
+            &MenuItem::with_id(app, "save-manager", "Save manager…", true, None::<&str>)?,
+            &MenuItem::with_id(app, "open-saves", "Open save folder", true, None::<&str>)?,
+            &MenuItem::with_id(app, "mods", "Mods…", true, None::<&str>)?,
+            &PredefinedMenuItem::separator(app)?,
+            &MenuItem::with_id(app, "quit", "Quit", true, Some("CmdOrCtrl+Q"))?,
+        ],
+    )?;
+    let display = Submenu::with_items(
+        app,
+        "Display",
+        true,
+        &[
+            &MenuItem::with_id(app, "fullscreen", "Toggle fullscreen", true, None::<&str>)?,
+            &MenuItem::with_id(app, "pause", "Pause / resume", true, None::<&str>)?,
+            &MenuItem::with_id(app, "settings", "Settings…", true, None::<&str>)?,
+            &MenuItem::with_id(app, "controls", "Controls…", true, None::<&str>)?,
+        ],
+    )?;
+    let help = Submenu::with_items(
+        app,
+        "Help",
+        true,
+        &[
+            &MenuItem::with_id(app, "logs", "Logs", true, None::<&str>)?,
+            &MenuItem::with_id(app, "parity", "Compatibility report", true, None::<&str>)?,
+            &MenuItem::with_id(app, "about", "About", true, None::<&str>)?,
+        ],
+    )?;
+    if app.state::<LauncherConfig>().companion.is_some() {
+        display.append(&MenuItem::with_id(app, "companion", "Companion panel", true, Some("CmdOrCtrl+K"))?)?;
+    }
+    if app.state::<LauncherConfig>().save_editor {
+        file.insert(&MenuItem::with_id(app, "save-editor", "Save editor…", true, None::<&str>)?, 1)?;
+    }
+    if app.state::<LauncherConfig>().crash_reports.is_some() {
+        help.insert(&MenuItem::with_id(app, "report", "Report a problem…", true, None::<&str>)?, 0)?;
+    }
+    if app.state::<LauncherConfig>().remote.is_some() {
+        help.insert(&MenuItem::with_id(app, "remote", "Remote control…", true, None::<&str>)?, 0)?;
+    }
+    if manual::find(&app.state::<GameInfo>().dir, &app.state::<LauncherConfig>()).is_some() {
+        help.insert(&MenuItem::with_id(app, "manual", "Manual", true, None::<&str>)?, 0)?;
+    }
+    Menu::with_items(app, &[&file, &display, &help])
+}
+
+/// Handle a click on the menu bar. Runs off the main thread, which opening windows would block.
+pub fn on_event<R: Runtime>(app: &AppHandle<R>, event: MenuEvent) {
+    let app = app.clone();
+    std::thread::spawn(move || run_action(&app, event.id().as_ref()));
+}
+
+fn run_action<R: Runtime>(app: &AppHandle<R>, id: &str) {
+    let result = match id {
+        "save-manager" => open_panel(app, SAVE_MANAGER_LABEL, "saves.html", "Save Manager", 640.0, 480.0),
+        "save-editor" => open_panel(app, SAVE_EDITOR_LABEL, "save-editor.html", "Save Editor", 720.0, 560.0),
+        "open-saves" => saves::open_save_folder(app),
+        "mods" => open_panel(app, MODS_LABEL, "mods.html", "Mods", 560.0, 420.0),
+        "quit" => {
+            app.exit(0);
+            Ok(())
+        }
+        "fullscreen" => display::toggle_fullscreen(
+            app.clone(),
+            app.state::<LauncherConfig>(),
+            app.state::<Settings>(),
+        )
+        .map(|_| ()),
+        "screenshot" => display::main_window(app).and_then(screenshots::capture_screenshot),
+        "pause" => pause::toggle(app),
+        "settings" => open_panel(app, SETTINGS_LABEL, "settings.html", "Settings", 420.0, 520.0),
+        "controls" => open_panel(app, CONTROLS_LABEL, "controls.html", "Controls", 460.0, 520.0),
+        "parity" => parity::open(app),
+        "manual" => manual::open(app),
+        "companion" => companion::toggle(app).map(|_| ()),
+        "logs" => {
+            tauri::async_runtime::spawn(debug_console::toggle_debug_console(app.clone()));
+            Ok(())
+        }
+        "report" => open_report(app),
+        "remote" => {
+            if let Some(remote) = app.try_state::<Remote>() {
+                remote::show_pairing(app, &remote);
+            }
+            Ok(())
+        }
+        "about" => {
+            show_about(app);
+            Ok(())
+        }
+        _ => Ok(()),
+    };
+
+    if let Err(e) = result {
+        log::warn!("Menu action '{}' failed: {}", id, e);
+    }
+}
+
+/// Show the launcher's context menu at the cursor.
+#[tauri::command]
+pub fn show_context_menu<R: Runtime>(app: AppHandle<R>, window: WebviewWindow<R>) -> Result<(), String> {
+    let menu = Menu::with_items(
+        &app,
+        &[
+            &MenuItem::with_id(&app, "fullscreen", "Toggle fullscreen", true, None::<&str>)
+                .map_err(|e| e.to_string())?,
+            &MenuItem::with_id(&app, "screenshot", "Take screenshot", true, None::<&str>)
+                .map_err(|e| e.to_string())?,
+            &MenuItem::with_id(&app, "save-manager", "Save manager…", true, None::<&str>)
+                .map_err(|e| e.to_string())?,
+        ],
+    )
+    .map_err(|e| e.to_string())?;
+    window.popup_menu(&menu).map_err(|e| e.to_string())
+}
+
+/// Open the crash and bug report window.
+pub fn open_report<R: Runtime>(app: &AppHandle<R>) -> Result<(), String> {
+    open_panel(app, REPORT_LABEL, "report.html", "Report a problem", 480.0, 560.0)
+}
+
+/// Focus a panel window, opening it first if needed.
+fn open_panel<R: Runtime>(
+    app: &AppHandle<R>,
+    label: &str,
+    page: &str,
+    title: &str,
+    width: f64,
+    height: f64,
+) -> Result<(), String> {
+    if let Some(window) = app.get_webview_window(label) {
+        return window.set_focus().map_err(|e| e.to_string());
+    }
+    WebviewWindowBuilder::new(app, label, WebviewUrl::App(page.into()))
+        .title(title)
+        .inner_size(width, height)
+        .build()
+        .map_err(|e| e.to_string())?;
+    Ok(())
+}
+
+fn show_about<R: Runtime>(app: &AppHandle<R>) {
+    let game = app.state::<GameInfo>();
+    let package = app.package_info();
+    app.dialog()
+        .message(format!(
+            "{}\n\nRunning on {} {}\nWebview {}",
+            game.title,
+            package.name,
+            package.version,
+            tauri::webview_version().unwrap_or_else(|_| "unknown".to_string())
+        ))
+        .title("About")
+        .kind(MessageDialogKind::Info)
+        .show(|_| {});
+}
@@ -0,0 +1,93 @@
+//! Master volume and audio output device of the game.
+//!
+//! Many MV games have no master volume option at all, so the launcher keeps its own, per
+//! game, and the injected `focus.js` applies it through the engine's master gain along with
+//! muting. Output devices are only known to the webview: the injected `audio_output.js`
+//! reports them with `report_audio_devices` and routes the game's `AudioContext` to the
+//! chosen one, where the webview supports `setSinkId` (WebView2 at the time of writing).
+
+use std::sync::Mutex;
+
+use serde::{Deserialize, Serialize};
+use tauri::{AppHandle, Emitter, Runtime, State};
+
+use crate::settings::Settings;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AudioDevice {
+    pub id: String,
+    /// Empty when the webview hides device names.
+    pub label: String,
+}
+
+#[derive(Debug, Clone, Default, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AudioOutputs {
+    pub devices: Vec<AudioDevice>,
+    /// Whether the game's audio can be moved to another device than the default.
+    pub can_select: bool,
+    /// Device chosen by the player, `None` for the system default.
+    pub selected: Option<String>,
+}
+
+/// Last device list reported by the page.
+#[derive(Default)]
+pub struct AudioDevices(Mutex<AudioOutputs>);
+
+#[tauri::command]
+pub fn report_audio_devices<R: Runtime>(
+    app: AppHandle<R>,
+    devices: State<'_, AudioDevices>,
+    settings: State<'_, Settings>,
+    list: Vec<AudioDevice>,
+    can_select: bool,
+) -> Result<(), String> {
+    let outputs = {
+        let mut current = devices.0.lock().unwrap();
+        current.devices = list;
+        current.can_select = can_select;
+        current.selected = settings.get().audio_device;
+        current.clone()
+    };
+    app.emit("audio-devices-changed", outputs).map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+pub fn list_audio_devices(devices: State<'_, AudioDevices>, settings: State<'_, Settings>) -> AudioOutputs {
+    let mut outputs = devices.0.lock().unwrap().clone();
+    outputs.selected = settings.get().audio_device;
+    outputs
+}
+
+/// Play the game on `id`, or on the system default for `None`.
+#[tauri::command]
+pub fn set_audio_device<R: Runtime>(
+    app: AppHandle<R>,
+    devices: State<'_, AudioDevices>,
+    settings: State<'_, Settings>,
+    id: Option<String>,
+) -> Result<(), String> {
+    if let Some(id) = &id {
+        let known = devices.0.lock().unwrap().devices.iter().any(|device| &device.id == id);
+        if !known {
+            return Err(format!("Unknown audio output device: {}", id));
+        }
+    }
+    settings.update(|s| s.audio_device = id)?;
+    app.emit("settings-changed", settings.get()).map_err(|e| e.to_string())
+}
+
+/// Set the launcher's master volume, from 0 to 1.
+#[tauri::command]
+pub fn set_master_volume<R: Runtime>(
+    app: AppHandle<R>,
+    settings: State<'_, Settings>,
+    volume: f64,
+) -> Result<(), String> {
+    if !volume.is_finite() {
+        return Err(format!("Invalid volume: {}", volume));
+    }
+    settings.update(|s| s.master_volume = volume.clamp(0.0, 1.0))?;
+    app.emit("settings-changed", settings.get()).map_err(|e| e.to_string())
+}
@@ -0,0 +1,198 @@
+//! Mod loader for community patches.
+//!
+//! Each mod is a folder under `<app data>/mods/<game id>/` with a `mod.json` manifest.
+//! Its files are mounted on top of `Game_Contents` by the localhost server, so a mod
+//! replaces assets by shipping them at the same relative path (e.g. `img/faces/Actor1.png`),
+//! and the plugins listed in its manifest are appended to the game's `$plugins`.
+//! The load order and the disabled mods are kept in `load_order.json` next to them;
+//! later mods win over earlier ones. Changes apply the next time the game starts.
+
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+
+use serde::{Deserialize, Serialize};
+use tauri::{AppHandle, Manager, Runtime, State};
+use tauri_plugin_opener::OpenerExt;
+
+use external_localhost_plugin::{InjectedScripts, Mounts};
+use crate::injection;
+
+pub const MANIFEST_FILE_NAME: &str = "mod.json";
+const LOAD_ORDER_FILE_NAME: &str = "load_order.json";
+
+/// A `plugins.js` entry contributed by a mod. The file goes in the mod's `js/plugins/` folder.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PluginEntry {
+    pub name: String,
+    #[serde(default = "default_plugin_status")]
+    pub status: bool,
+    #[serde(default)]
+    pub description: String,
+    #[serde(default)]
+    pub parameters: serde_json::Map<String, serde_json::Value>,
+}
+
+fn default_plugin_status() -> bool {
+    true
+}
+
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(default, rename_all = "camelCase")]
+struct Manifest {
+    id: Option<String>,
+    name: Option<String>,
+    version: String,
+    author: String,
+    description: String,
+    plugins: Vec<PluginEntry>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ModInfo {
+    pub id: String,
+    pub name: String,
+    pub version: String,
+    pub author: String,
+    pub description: String,
+    pub plugins: Vec<PluginEntry>,
+    pub enabled: bool,
+    #[serde(skip)]
+    pub dir: PathBuf,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+struct LoadOrder {
+    order: Vec<String>,
+    disabled: Vec<String>,
+}
+
+pub struct Mods {
+    dir: PathBuf,
+    load_order: Mutex<LoadOrder>,
+}
+
+impl Mods {
+    pub fn load(dir: PathBuf) -> Self {
+        let load_order = fs::read_to_string(dir.join(LOAD_ORDER_FILE_NAME))
+            .ok()
+            .and_then(|content| serde_json::from_str(&content).ok())
+            .unwrap_or_default();
+        Self {
+            dir,
+            load_order: Mutex::new(load_order),
+        }
+    }
+
+    pub fn dir(&self) -> &Path {
+        &self.dir
+    }
+
+    /// Every installed mod, in load order. Mods missing from the load order go last, by id.
+    pub fn list(&self) -> Vec<ModInfo> {
+        let load_order = self.load_order.lock().unwrap();
+        let mut mods: Vec<ModInfo> = fs::read_dir(&self.dir)
+            .into_iter()
+            .flatten()
+            .flatten()
+            .filter(|entry| entry.path().join(MANIFEST_FILE_NAME).is_file())
+            .filter_map(|entry| read_mod(&entry.path(), &load_order))
+            .collect();
+
+        let position = |id: &str| load_order.order.iter().position(|o| o == id).unwrap_or(usize::MAX);
+        mods.sort_by(|a, b| position(&a.id).cmp(&position(&b.id)).then_with(|| a.id.cmp(&b.id)));
+        mods
+    }
+
+    pub fn set_order(&self, order: Vec<String>) -> Result<(), String> {
+        let mut load_order = self.load_order.lock().unwrap();
+        load_order.order = order;
+        self.save(&load_order)
+    }
+
+    pub fn set_enabled(&self, id: &str, enabled: bool) -> Result<(), String> {
+        let mut load_order = self.load_order.lock().unwrap();
+        load_order.disabled.retain(|d| d != id);
+        if !enabled {
+            load_order.disabled.push(id.to_string());
+        }
+        self.save(&load_order)
+    }
+
+    fn save(&self, load_order: &LoadOrder) -> Result<(), String> {
+        fs::create_dir_all(&self.dir).map_err(|e| e.to_string())?;
+        let content = serde_json::to_string_pretty(load_order).map_err(|e| e.to_string())?;
+        fs::write(self.dir.join(LOAD_ORDER_FILE_NAME), content).map_err(|e| e.to_string())
+    }
+
+    /// Mount the enabled mods on the localhost server and inject their plugins.
+    pub fn apply<R: Runtime>(&self, app: &AppHandle<R>) {
+        let enabled: Vec<ModInfo> = self.list().into_iter().filter(|m| m.enabled).collect();
+        if enabled.is_empty() {
+            return;
+        }
+
+        let mounts = app.state::<Mounts>();
+        let mut plugins = Vec::new();
+        for info in &enabled {
+            log::info!("Loading mod '{}' {} from {:?}", info.id, info.version, info.dir);
+            mounts.mount(&info.dir);
+            plugins.extend(info.plugins.iter().cloned());
+        }
+        if !plugins.is_empty() {
+            app.state::<InjectedScripts>().push(injection::mods_script(&plugins));
+        }
+    }
+}
+
+fn read_mod(dir: &Path, load_order: &LoadOrder) -> Option<ModInfo> {
+    let manifest_path = dir.join(MANIFEST_FILE_NAME);
+    let manifest: Manifest = match fs::read_to_string(&manifest_path) {
+        Ok(content) => match serde_json::from_str(&content) {
+            Ok(manifest) => manifest,
+            Err(e) => {
+                log::warn!("Invalid {:?}: {}", manifest_path, e);
+                return None;
+            }
+        },
+        Err(_) => return None,
+    };
+
+    let folder_name = dir.file_name()?.to_string_lossy().to_string();
+    let id = manifest.id.unwrap_or_else(|| folder_name.clone());
+    Some(ModInfo {
+        enabled: !load_order.disabled.contains(&id),
+        name: manifest.name.unwrap_or(folder_name),
+        id,
+        version: manifest.version,
+        author: manifest.author,
+        description: manifest.description,
+        plugins: manifest.plugins,
+        dir: dir.to_path_buf(),
+    })
+}
+
+#[tauri::command]
+pub fn list_mods(mods: State<'_, Mods>) -> Vec<ModInfo> {
+    mods.list()
+}
+
+#[tauri::command]
+pub fn set_mod_order(mods: State<'_, Mods>, order: Vec<String>) -> Result<(), String> {
+    mods.set_order(order)
+}
+
+#[tauri::command]
+pub fn set_mod_enabled(mods: State<'_, Mods>, id: String, enabled: bool) -> Result<(), String> {
+    mods.set_enabled(&id, enabled)
+}
+
+#[tauri::command]
+pub fn open_mods_folder<R: Runtime>(app: AppHandle<R>, mods: State<'_, Mods>) -> Result<(), String> {
+    fs::create_dir_all(mods.dir()).map_err(|e| e.to_string())?;
+    app.opener()
+        .open_path(mods.dir().to_string_lossy(), None::<&str>)
+        .map_err(|e| e.to_string())
+}
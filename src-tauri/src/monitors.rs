@@ -0,0 +1,123 @@
+//! Choosing which display the game window (and its fullscreen) goes to.
+//!
+//! The packager can pick a default display with `monitor` in `launcher.json`; the player's
+//! choice is remembered per game by monitor name, since indices shift when displays are
+//! plugged in or out.
+
+use serde::Serialize;
+use tauri::{AppHandle, Monitor, PhysicalPosition, Runtime, State, WebviewWindow};
+
+use crate::config::LauncherConfig;
+use crate::display::main_window;
+use crate::settings::Settings;
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct MonitorInfo {
+    pub index: usize,
+    pub name: Option<String>,
+    pub x: i32,
+    pub y: i32,
+    pub width: u32,
+    pub height: u32,
+    pub scale_factor: f64,
+    pub primary: bool,
+    /// Whether the game window is currently on this monitor.
+    pub current: bool,
+}
+
+/// The monitor the game should open on: the player's choice if it's still connected,
+/// else the one from the launcher config.
+fn preferred_monitor<R: Runtime>(
+    window: &WebviewWindow<R>,
+    config: &LauncherConfig,
+    settings: &Settings,
+) -> tauri::Result<Option<Monitor>> {
+    let monitors = window.available_monitors()?;
+    if let Some(name) = settings.get().monitor {
+        if let Some(monitor) = monitors.iter().find(|m| m.name() == Some(&name)) {
+            return Ok(Some(monitor.clone()));
+        }
+        log::info!("Monitor {:?} is no longer connected", name);
+    }
+    Ok(config.monitor.and_then(|index| monitors.get(index).cloned()))
+}
+
+/// Center the window on `monitor`. A fullscreen window is taken out of fullscreen for the
+/// move and put back, since most platforms won't move it otherwise.
+fn move_window<R: Runtime>(window: &WebviewWindow<R>, monitor: &Monitor) -> tauri::Result<()> {
+    let fullscreen = window.is_fullscreen()?;
+    if fullscreen {
+        window.set_fullscreen(false)?;
+    }
+
+    let size = window.outer_size()?;
+    let area = monitor.size();
+    let origin = monitor.position();
+    window.set_position(PhysicalPosition::new(
+        origin.x + (area.width.saturating_sub(size.width) / 2) as i32,
+        origin.y + (area.height.saturating_sub(size.height) / 2) as i32,
+    ))?;
+
+    if fullscreen {
+        window.set_fullscreen(true)?;
+    }
+    Ok(())
+}
+
+/// Move the new game window to its preferred monitor. Must run before fullscreen is applied.
+pub fn apply_preferred_monitor<R: Runtime>(
+    window: &WebviewWindow<R>,
+    config: &LauncherConfig,
+    settings: &Settings,
+) -> tauri::Result<()> {
+    match preferred_monitor(window, config, settings)? {
+        Some(monitor) => move_window(window, &monitor),
+        None => Ok(()),
+    }
+}
+
+#[tauri::command]
+pub fn list_monitors<R: Runtime>(app: AppHandle<R>) -> Result<Vec<MonitorInfo>, String> {
+    let window = main_window(&app)?;
+    let primary = window.primary_monitor().map_err(|e| e.to_string())?;
+    let current = window.current_monitor().map_err(|e| e.to_string())?;
+    let same = |a: &Option<Monitor>, b: &Monitor| {
+        a.as_ref()
+            .is_some_and(|a| a.name() == b.name() && a.position() == b.position())
+    };
+
+    let monitors = window.available_monitors().map_err(|e| e.to_string())?;
+    Ok(monitors
+        .iter()
+        .enumerate()
+        .map(|(index, monitor)| MonitorInfo {
+            index,
+            name: monitor.name().cloned(),
+            x: monitor.position().x,
+            y: monitor.position().y,
+            width: monitor.size().width,
+            height: monitor.size().height,
+            scale_factor: monitor.scale_factor(),
+            primary: same(&primary, monitor),
+            current: same(&current, monitor),
+        })
+        .collect())
+}
+
+/// Move the game to the monitor at `index` in [`list_monitors`] and remember it for this game.
+#[tauri::command]
+pub fn move_to_monitor<R: Runtime>(
+    app: AppHandle<R>,
+    settings: State<'_, Settings>,
+    index: usize,
+) -> Result<(), String> {
+    let window = main_window(&app)?;
+    let monitors = window.available_monitors().map_err(|e| e.to_string())?;
+    let monitor = monitors
+        .get(index)
+        .ok_or_else(|| format!("No monitor at index {}", index))?;
+
+    move_window(&window, monitor).map_err(|e| e.to_string())?;
+    settings.update(|s| s.monitor = monitor.name().cloned())
+}
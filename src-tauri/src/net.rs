@@ -0,0 +1,73 @@
+//! Outbound network access, governed by the `proxy` and `offline` options of `launcher.json`.
+//!
+//! Every HTTP request the launcher makes itself (downloads, itch.io) goes through [`request`], so
+//! it uses the configured proxy, or fails in offline mode. In offline mode the game page is
+//! also served with a Content Security Policy that only allows the localhost server and the
+//! Tauri IPC, and the webview refuses to navigate anywhere else.
+
+use std::sync::OnceLock;
+
+use crate::config::LauncherConfig;
+
+/// Sources the game page may load from in offline mode: the localhost server it is served
+/// from, inline scripts and generated data, and the Tauri IPC on each platform.
+pub const OFFLINE_CSP: &str = "default-src 'self' 'unsafe-inline' 'unsafe-eval' data: blob: \
+     ipc: http://ipc.localhost asset: http://asset.localhost";
+
+struct Network {
+    offline: bool,
+    agent: ureq::Agent,
+}
+
+static NETWORK: OnceLock<Network> = OnceLock::new();
+
+/// Set up outbound access from the launcher config. Called once at startup.
+pub fn init(config: &LauncherConfig) {
+    let mut builder = ureq::AgentBuilder::new();
+    if let Some(proxy) = config.proxy.as_deref().filter(|p| !p.is_empty()) {
+        match ureq::Proxy::new(proxy) {
+            Ok(proxy) => builder = builder.proxy(proxy),
+            Err(e) => log::warn!("Invalid proxy '{}', connecting directly: {}", proxy, e),
+        }
+    }
+    if config.offline {
+        log::info!("Offline mode: outbound requests are blocked");
+    }
+
+    let _ = NETWORK.set(Network {
+        offline: config.offline,
+        agent: builder.build(),
+    });
+}
+
+/// Fail if the launcher is in offline mode, for connections that don't go through [`request`].
+pub fn check_online(url: &str) -> Result<(), String> {
+    match NETWORK.get() {
+        Some(network) if network.offline => Err(format!("Offline mode: not connecting to {}", url)),
+        _ => Ok(()),
+    }
+}
+
+/// Start a request, or fail if the launcher is in offline mode.
+pub fn request(method: &str, url: &str) -> Result<ureq::Request, String> {
+    check_online(url)?;
+    Ok(match NETWORK.get() {
+        Some(network) => network.agent.request(method, url),
+        None => ureq::request(method, url),
+    })
+}
+
+pub fn get(url: &str) -> Result<ureq::Request, String> {
+    request("GET", url)
+}
+
+/// Whether the webview may navigate to the URL in offline mode.
+pub fn is_local_url(url: &tauri::Url) -> bool {
+    match url.scheme() {
+        "http" | "https" | "ws" | "wss" => matches!(
+            url.host_str(),
+            Some("127.0.0.1" | "localhost" | "ipc.localhost" | "asset.localhost" | "tauri.localhost")
+        ),
+        _ => true,
+    }
+}
@@ -0,0 +1,39 @@
+//! Native desktop notifications raised by the game, which show even while its window is
+//! minimized or in the background.
+
+use std::path::{Component, Path};
+
+use tauri::{AppHandle, Runtime, State};
+use tauri_plugin_notification::NotificationExt;
+
+use crate::game::GameInfo;
+
+/// Show an OS notification. `icon` is relative to the game folder and defaults to the game icon.
+#[tauri::command]
+pub fn notify<R: Runtime>(
+    app: AppHandle<R>,
+    game: State<'_, GameInfo>,
+    title: String,
+    body: Option<String>,
+    icon: Option<String>,
+) -> Result<(), String> {
+    let icon = match icon {
+        Some(icon) => {
+            let relative = Path::new(&icon);
+            if relative.components().any(|c| !matches!(c, Component::Normal(_))) {
+                return Err(format!("Invalid icon path: {}", icon));
+            }
+            game.dir.join(relative)
+        }
+        None => game.icon.clone(),
+    };
+
+    let mut notification = app.notification().builder().title(title);
+    if let Some(body) = body {
+        notification = notification.body(body);
+    }
+    if icon.is_file() {
+        notification = notification.icon(icon.to_string_lossy());
+    }
+    notification.show().map_err(|e| e.to_string())
+}
@@ -0,0 +1,184 @@
+//! obs-websocket integration, enabled with `obs` in `launcher.json`.
+//!
+//! The injected `obs.js` reports game events (saves, switches turning on, game over); the
+//! triggers from the config turn them into OBS requests: saving the replay buffer, adding a
+//! recording chapter marker or switching scenes. Speaks the obs-websocket 5 protocol.
+
+use std::net::TcpStream;
+use std::sync::Mutex;
+use std::time::Duration;
+
+use base64::engine::general_purpose::STANDARD as BASE64;
+use base64::Engine;
+use serde_json::{json, Value};
+use sha2::{Digest, Sha256};
+use tauri::{AppHandle, Manager, Runtime};
+use tungstenite::stream::MaybeTlsStream;
+use tungstenite::{Message, WebSocket};
+
+use crate::config::{ObsAction, ObsConfig, ObsGameEvent, ObsTrigger};
+
+const RPC_VERSION: u32 = 1;
+const RESPONSE_TIMEOUT: Duration = Duration::from_secs(2);
+
+// Opcodes of the obs-websocket 5 protocol
+const OP_HELLO: u64 = 0;
+const OP_IDENTIFY: u64 = 1;
+const OP_IDENTIFIED: u64 = 2;
+const OP_REQUEST: u64 = 6;
+const OP_REQUEST_RESPONSE: u64 = 7;
+
+type Socket = WebSocket<MaybeTlsStream<TcpStream>>;
+
+pub struct Obs {
+    config: ObsConfig,
+    socket: Mutex<Option<Socket>>,
+    next_request_id: Mutex<u64>,
+}
+
+impl Obs {
+    pub fn new(config: ObsConfig) -> Self {
+        Self {
+            config,
+            socket: Mutex::new(None),
+            next_request_id: Mutex::new(0),
+        }
+    }
+
+    /// Run the actions of every trigger matching the event.
+    fn handle(&self, event: ObsGameEvent, switch: Option<u32>) {
+        let triggers: Vec<&ObsTrigger> = self
+            .config
+            .triggers
+            .iter()
+            .filter(|t| t.event == event && (event != ObsGameEvent::Switch || t.switch == switch))
+            .collect();
+
+        for trigger in triggers {
+            let (request_type, data) = match trigger.action {
+                ObsAction::ReplayBuffer => ("SaveReplayBuffer", json!({})),
+                ObsAction::Marker => {
+                    let name = trigger.name.clone().unwrap_or_else(|| event_name(event, switch));
+                    ("CreateRecordChapter", json!({ "chapterName": name }))
+                }
+                ObsAction::Scene => match &trigger.name {
+                    Some(scene) => ("SetCurrentProgramScene", json!({ "sceneName": scene })),
+                    None => {
+                        log::warn!("OBS scene trigger for {:?} has no scene name", event);
+                        continue;
+                    }
+                },
+            };
+            if let Err(e) = self.request(request_type, data) {
+                log::warn!("OBS request {} failed: {}", request_type, e);
+            }
+        }
+    }
+
+    /// Send a request, reconnecting once if OBS was restarted since the last one.
+    fn request(&self, request_type: &str, data: Value) -> Result<(), String> {
+        let mut socket = self.socket.lock().unwrap();
+        let mut last_error = String::new();
+        for _ in 0..2 {
+            if socket.is_none() {
+                *socket = Some(self.connect()?);
+            }
+            match self.send_request(socket.as_mut().unwrap(), request_type, &data) {
+                Ok(()) => return Ok(()),
+                Err(e) => {
+                    last_error = e;
+                    *socket = None;
+                }
+            }
+        }
+        Err(last_error)
+    }
+
+    fn connect(&self) -> Result<Socket, String> {
+        let (mut socket, _) =
+            tungstenite::connect(format!("ws://{}", self.config.address)).map_err(|e| e.to_string())?;
+        if let MaybeTlsStream::Plain(stream) = socket.get_ref() {
+            stream
+                .set_read_timeout(Some(RESPONSE_TIMEOUT))
+                .map_err(|e| e.to_string())?;
+        }
+
+        let hello = read_op(&mut socket, OP_HELLO)?;
+        let mut identify = json!({ "rpcVersion": RPC_VERSION, "eventSubscriptions": 0 });
+        if let Some(auth) = hello.get("authentication") {
+            let challenge = auth["challenge"].as_str().unwrap_or_default();
+            let salt = auth["salt"].as_str().unwrap_or_default();
+            identify["authentication"] = json!(authentication(&self.config.password, salt, challenge));
+        }
+        send_op(&mut socket, OP_IDENTIFY, identify)?;
+        read_op(&mut socket, OP_IDENTIFIED)?;
+        log::info!("Connected to OBS at {}", self.config.address);
+        Ok(socket)
+    }
+
+    fn send_request(&self, socket: &mut Socket, request_type: &str, data: &Value) -> Result<(), String> {
+        let request_id = {
+            let mut next = self.next_request_id.lock().unwrap();
+            *next += 1;
+            next.to_string()
+        };
+        send_op(
+            socket,
+            OP_REQUEST,
+            json!({ "requestType": request_type, "requestId": request_id, "requestData": data }),
+        )?;
+
+        loop {
+            let response = read_op(socket, OP_REQUEST_RESPONSE)?;
+            if response["requestId"] != request_id {
+                continue;
+            }
+            let status = &response["requestStatus"];
+            return if status["result"].as_bool() == Some(true) {
+                Ok(())
+            } else {
+                Err(status["comment"].as_str().unwrap_or("request failed").to_string())
+            };
+        }
+    }
+}
+
+fn event_name(event: ObsGameEvent, switch: Option<u32>) -> String {
+    match (event, switch) {
+        (ObsGameEvent::Save, _) => "Save".to_string(),
+        (ObsGameEvent::Switch, Some(id)) => format!("Switch {}", id),
+        (ObsGameEvent::Switch, None) => "Switch".to_string(),
+        (ObsGameEvent::GameOver, _) => "Game over".to_string(),
+    }
+}
+
+/// `base64(sha256(base64(sha256(password + salt)) + challenge))`, as the protocol defines it.
+fn authentication(password: &str, salt: &str, challenge: &str) -> String {
+    let secret = BASE64.encode(Sha256::digest(format!("{}{}", password, salt)));
+    BASE64.encode(Sha256::digest(format!("{}{}", secret, challenge)))
+}
+
+fn send_op(socket: &mut Socket, op: u64, data: Value) -> Result<(), String> {
+    let message = json!({ "op": op, "d": data }).to_string();
+    socket.send(Message::Text(message)).map_err(|e| e.to_string())
+}
+
+/// Read messages until one with the given opcode arrives, and return its data.
+fn read_op(socket: &mut Socket, op: u64) -> Result<Value, String> {
+    loop {
+        let message = socket.read().map_err(|e| e.to_string())?;
+        let Message::Text(text) = message else {
+            continue;
+        };
+        let mut value: Value = serde_json::from_str(&text).map_err(|e| e.to_string())?;
+        if value["op"].as_u64() == Some(op) {
+            return Ok(value["d"].take());
+        }
+    }
+}
+
+/// Report a game event. The OBS requests run in the background.
+#[tauri::command]
+pub fn obs_event<R: Runtime>(app: AppHandle<R>, event: ObsGameEvent, switch: Option<u32>) {
+    std::thread::spawn(move || app.state::<Obs>().handle(event, switch));
+}
@@ -0,0 +1,201 @@
+//! Finding images and audio the game never uses (`--optimize-assets`).
+//!
+//! RPG Maker's "exclude unused files" misses assets named in plugin parameters and strips
+//! ones it doesn't know about, so this errs the other way: a file counts as used if its name
+//! appears anywhere in the database, the plugin parameters or the plugin sources. Only what
+//! is clearly unreferenced is reported, and only removed with `--strip-unused`.
+
+use std::collections::HashSet;
+use std::fs;
+use std::path::Path;
+
+use crate::data_files;
+use crate::encryption;
+
+/// Asset folders that are checked.
+const ASSET_FOLDERS: [&str; 2] = ["img", "audio"];
+/// Folders the engine loads by fixed names, which never appear in the data.
+const ENGINE_FOLDERS: [&str; 1] = ["img/system"];
+
+#[derive(Debug)]
+pub struct UnusedAsset {
+    /// Path relative to the game folder, with `/` separators.
+    pub path: String,
+    pub size: u64,
+}
+
+/// Names mentioned by the game, lowercased, and the plugin sources to search for the rest.
+struct References {
+    names: HashSet<String>,
+    plugin_sources: String,
+}
+
+impl References {
+    fn add_value(&mut self, value: &serde_json::Value) {
+        match value {
+            serde_json::Value::String(text) => self.add_string(text),
+            serde_json::Value::Array(items) => items.iter().for_each(|item| self.add_value(item)),
+            serde_json::Value::Object(map) => map.values().for_each(|item| self.add_value(item)),
+            _ => {}
+        }
+    }
+
+    fn add_string(&mut self, text: &str) {
+        let text = text.trim();
+        if text.is_empty() {
+            return;
+        }
+        // Plugin parameters nest structs and lists as JSON inside strings
+        if text.starts_with(['{', '[', '"']) {
+            if let Ok(nested) = serde_json::from_str::<serde_json::Value>(text) {
+                self.add_value(&nested);
+                return;
+            }
+        }
+        let text = text.replace('\\', "/").to_lowercase();
+        let name = text.rsplit('/').next().unwrap_or(&text).to_string();
+        if let Some((stem, _)) = name.rsplit_once('.') {
+            self.names.insert(stem.to_string());
+        }
+        self.names.insert(name);
+        self.names.insert(text);
+    }
+
+    fn is_used(&self, stem: &str) -> bool {
+        let stem = stem.to_lowercase();
+        self.names.contains(&stem) || self.plugin_sources.contains(&stem)
+    }
+}
+
+fn read_json(path: &Path) -> Option<serde_json::Value> {
+    let content = fs::read_to_string(path).ok()?;
+    serde_json::from_str(content.trim_start_matches('\u{feff}')).ok()
+}
+
+fn collect_references(game_dir: &Path) -> References {
+    let mut references = References {
+        names: HashSet::new(),
+        plugin_sources: String::new(),
+    };
+
+    for entry in fs::read_dir(data_files::data_dir(game_dir))
+        .into_iter()
+        .flatten()
+        .flatten()
+    {
+        let path = entry.path();
+        if path.extension().is_some_and(|ext| ext.eq_ignore_ascii_case("json")) {
+            if let Some(value) = read_json(&path) {
+                references.add_value(&value);
+            }
+        }
+    }
+
+    // `var $plugins = [...];`
+    if let Ok(content) = fs::read_to_string(game_dir.join("js").join("plugins.js")) {
+        let list = content
+            .find('[')
+            .zip(content.rfind(']'))
+            .and_then(|(start, end)| content.get(start..=end));
+        if let Some(value) = list.and_then(|list| serde_json::from_str(list).ok()) {
+            references.add_value(&value);
+        }
+    }
+
+    for path in encryption::files_in(&game_dir.join("js").join("plugins")) {
+        if path.extension().is_some_and(|ext| ext.eq_ignore_ascii_case("js")) {
+            if let Ok(source) = fs::read_to_string(&path) {
+                references.plugin_sources.push_str(&source.to_lowercase());
+                references.plugin_sources.push('\n');
+            }
+        }
+    }
+    references
+}
+
+/// Whether `path` matches one of the exclusion patterns. A pattern is a path relative to the
+/// game folder where `*` matches anything; without a `*` it also matches everything under it.
+fn is_excluded(path: &str, patterns: &[String]) -> bool {
+    let path = path.to_lowercase();
+    patterns.iter().any(|pattern| {
+        let pattern = pattern.trim().trim_start_matches("./").to_lowercase();
+        if pattern.is_empty() {
+            return false;
+        }
+        if !pattern.contains('*') {
+            let folder = pattern.trim_end_matches('/');
+            return path == folder || path.starts_with(&format!("{}/", folder));
+        }
+        wildcard_match(&pattern, &path)
+    })
+}
+
+/// Whether `text` matches `pattern`, where `*` matches anything.
+pub fn wildcard_match(pattern: &str, text: &str) -> bool {
+    let mut parts = pattern.split('*');
+    let first = parts.next().unwrap_or_default();
+    let Some(mut rest) = text.strip_prefix(first) else {
+        return false;
+    };
+    let parts: Vec<&str> = parts.collect();
+    for (i, part) in parts.iter().enumerate() {
+        if i == parts.len() - 1 {
+            return rest.ends_with(part);
+        }
+        match rest.find(part) {
+            Some(at) => rest = &rest[at + part.len()..],
+            None => return false,
+        }
+    }
+    rest.is_empty()
+}
+
+/// List the images and audio nothing refers to, skipping the `exclude` patterns.
+pub fn find_unused(game_dir: &Path, exclude: &[String]) -> Vec<UnusedAsset> {
+    let references = collect_references(game_dir);
+    let mut unused = Vec::new();
+    for folder in ASSET_FOLDERS {
+        for path in encryption::files_in(&game_dir.join(folder)) {
+            let Some(relative) = relative_path(game_dir, &path) else {
+                continue;
+            };
+            let in_engine_folder = ENGINE_FOLDERS
+                .iter()
+                .any(|folder| relative.to_lowercase().starts_with(&format!("{}/", folder)));
+            if in_engine_folder || is_excluded(&relative, exclude) {
+                continue;
+            }
+            let Some(stem) = path.file_stem().map(|stem| stem.to_string_lossy().to_string()) else {
+                continue;
+            };
+            if !references.is_used(&stem) {
+                let size = fs::metadata(&path).map(|m| m.len()).unwrap_or(0);
+                unused.push(UnusedAsset { path: relative, size });
+            }
+        }
+    }
+    unused.sort_by(|a, b| a.path.cmp(&b.path));
+    unused
+}
+
+fn relative_path(game_dir: &Path, path: &Path) -> Option<String> {
+    let relative = path.strip_prefix(game_dir).ok()?;
+    Some(
+        relative
+            .components()
+            .map(|c| c.as_os_str().to_string_lossy())
+            .collect::<Vec<_>>()
+            .join("/"),
+    )
+}
+
+/// Delete the given assets from the game folder. Returns how many bytes were freed.
+pub fn strip(game_dir: &Path, assets: &[UnusedAsset]) -> Result<u64, String> {
+    let mut freed = 0;
+    for asset in assets {
+        let path = game_dir.join(&asset.path);
+        fs::remove_file(&path).map_err(|e| format!("{:?}: {}", path, e))?;
+        freed += asset.size;
+    }
+    Ok(freed)
+}
@@ -0,0 +1,467 @@
+//! The launcher's `.pak` archive format.
+//!
+//! A game with tens of thousands of loose files spends a long time opening them, on cold
+//! starts and on antivirus-heavy systems especially. `--pack=<file>` puts the game folder in
+//! a single archive, which the server reads from when a file isn't found loose, so loose
+//! files (and the overlay and mods) still win over packed ones. `--unpack=<file>` extracts it.
+//!
+//! Layout, all integers little endian:
+//!
+//! ```text
+//! "RMPAK\0" u16 version
+//! entry data, back to back
+//! index: JSON list of entries
+//! u64 index offset, u64 index length, "RMPAKEND"
+//! ```
+//!
+//! Each entry can be deflate-compressed and/or encrypted. Entries stored as-is are plain
+//! slices of the file. The archive isn't memory-mapped: the server reads the requested entry
+//! into memory, inflating it if needed, and answers range requests from that copy, so entries
+//! are limited to 1 GiB. The index is checked against the file's length on opening, so a
+//! truncated or corrupt archive fails to open. Encryption XORs the entry with a SHA-256
+//! keystream derived from `pakKey` in launcher.json and the entry path. Like RPG Maker's own
+//! asset encryption, it keeps casual eyes out rather than determined ones, since the key
+//! ships with the game.
+
+use std::collections::HashMap;
+use std::fs;
+use std::io::{Read, Seek, SeekFrom, Write};
+use std::path::{Component, Path, PathBuf};
+use std::sync::Arc;
+
+use flate2::read::DeflateDecoder;
+use flate2::write::DeflateEncoder;
+use flate2::Compression;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+
+use crate::encryption;
+
+const MAGIC: &[u8; 6] = b"RMPAK\0";
+const VERSION: u16 = 1;
+const FOOTER_MAGIC: &[u8; 8] = b"RMPAKEND";
+const FOOTER_LEN: u64 = 24;
+const HEADER_LEN: u64 = 8;
+/// Largest entry read from an archive, packed or extracted.
+const MAX_ENTRY_SIZE: u64 = 1 << 30;
+
+/// Formats that don't get any smaller with deflate.
+const COMPRESSED_EXTENSIONS: [&str; 20] = [
+    "png", "jpg", "jpeg", "webp", "gif", "ogg", "m4a", "mp3", "webm", "mp4", "ogv", "woff", "woff2", "rpgmvp",
+    "rpgmvo", "rpgmvm", "png_", "ogg_", "m4a_", "zip",
+];
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct PakEntry {
+    /// Path relative to the game folder, with `/` separators.
+    path: String,
+    offset: u64,
+    /// Size in the archive.
+    stored_size: u64,
+    /// Size once extracted.
+    size: u64,
+    #[serde(default)]
+    compressed: bool,
+    #[serde(default)]
+    encrypted: bool,
+}
+
+/// XOR `data` with the keystream of the entry at `path`.
+pub fn apply_keystream(key: &[u8; 32], path: &str, data: &mut [u8]) {
+    let mut block_index = 0;
+    let mut block = [0u8; 32];
+    let refill = |index: u64, block: &mut [u8; 32]| {
+        let mut hasher = Sha256::new();
+        hasher.update(key);
+        hasher.update(path.as_bytes());
+        hasher.update(index.to_le_bytes());
+        block.copy_from_slice(&hasher.finalize());
+    };
+    refill(block_index, &mut block);
+    let mut offset = 0;
+    for byte in data {
+        if offset == 32 {
+            block_index += 1;
+            refill(block_index, &mut block);
+            offset = 0;
+        }
+        *byte ^= block[offset];
+        offset += 1;
+    }
+}
+
+pub fn derive_key(key: &str) -> [u8; 32] {
+    Sha256::digest(key.as_bytes()).into()
+}
+
+pub struct Pak {
+    path: PathBuf,
+    entries: HashMap<String, PakEntry>,
+    key: Option<[u8; 32]>,
+}
+
+impl Pak {
+    pub fn open(path: &Path, key: Option<&str>) -> Result<Self, String> {
+        let mut file = fs::File::open(path).map_err(|e| e.to_string())?;
+        let file_len = file.metadata().map_err(|e| e.to_string())?.len();
+        if file_len < HEADER_LEN + FOOTER_LEN {
+            return Err("The .pak file is truncated".to_string());
+        }
+        let mut header = [0u8; HEADER_LEN as usize];
+        file.read_exact(&mut header).map_err(|e| e.to_string())?;
+        if &header[..6] != MAGIC {
+            return Err("Not a launcher .pak file".to_string());
+        }
+        let version = u16::from_le_bytes([header[6], header[7]]);
+        if version != VERSION {
+            return Err(format!("Unsupported .pak version {}", version));
+        }
+
+        let mut footer = [0u8; FOOTER_LEN as usize];
+        file.seek(SeekFrom::End(-(FOOTER_LEN as i64)))
+            .map_err(|e| e.to_string())?;
+        file.read_exact(&mut footer).map_err(|e| e.to_string())?;
+        if &footer[16..] != FOOTER_MAGIC {
+            return Err("The .pak file is truncated".to_string());
+        }
+        let index_offset = u64::from_le_bytes(footer[..8].try_into().unwrap());
+        let index_len = u64::from_le_bytes(footer[8..16].try_into().unwrap());
+        let index_end = index_offset.checked_add(index_len);
+        if index_offset < HEADER_LEN || !index_end.is_some_and(|end| end <= file_len - FOOTER_LEN) {
+            return Err("The .pak index is out of bounds".to_string());
+        }
+
+        let mut index = vec![0u8; index_len as usize];
+        file.seek(SeekFrom::Start(index_offset)).map_err(|e| e.to_string())?;
+        file.read_exact(&mut index).map_err(|e| e.to_string())?;
+        let entries: Vec<PakEntry> =
+            serde_json::from_slice(&index).map_err(|e| format!("Corrupt .pak index: {}", e))?;
+        for entry in &entries {
+            let end = entry.offset.checked_add(entry.stored_size);
+            let valid = entry.offset >= HEADER_LEN
+                && end.is_some_and(|end| end <= index_offset)
+                && entry.size <= MAX_ENTRY_SIZE
+                && (entry.compressed || entry.size == entry.stored_size);
+            if !valid {
+                return Err(format!("Corrupt .pak index: {} is out of bounds", entry.path));
+            }
+        }
+
+        let key = key.map(derive_key);
+        if key.is_none() && entries.iter().any(|entry| entry.encrypted) {
+            log::warn!("{:?} has encrypted entries but launcher.json has no pakKey", path);
+        }
+        Ok(Self {
+            path: path.to_path_buf(),
+            entries: entries.into_iter().map(|entry| (entry.path.clone(), entry)).collect(),
+            key,
+        })
+    }
+
+    pub fn file_count(&self) -> usize {
+        self.entries.len()
+    }
+
+    /// Read a file from the archive, or `None` if it isn't in it.
+    pub fn read(&self, path: &str) -> Option<Result<Vec<u8>, String>> {
+        let entry = self.entries.get(path)?;
+        Some(self.read_entry(entry))
+    }
+
+    fn read_entry(&self, entry: &PakEntry) -> Result<Vec<u8>, String> {
+        let mut file = fs::File::open(&self.path).map_err(|e| e.to_string())?;
+        file.seek(SeekFrom::Start(entry.offset)).map_err(|e| e.to_string())?;
+        let mut data = vec![0u8; entry.stored_size as usize];
+        file.read_exact(&mut data).map_err(|e| e.to_string())?;
+
+        if entry.encrypted {
+            let key = self
+                .key
+                .as_ref()
+                .ok_or("The entry is encrypted and there is no pakKey")?;
+            apply_keystream(key, &entry.path, &mut data);
+        }
+        if entry.compressed {
+            let mut inflated = Vec::with_capacity(entry.size as usize);
+            // One byte more than expected is enough to tell the entry is corrupt
+            DeflateDecoder::new(data.as_slice())
+                .take(entry.size + 1)
+                .read_to_end(&mut inflated)
+                .map_err(|e| e.to_string())?;
+            if inflated.len() as u64 != entry.size {
+                return Err("The entry doesn't inflate to its size".to_string());
+            }
+            data = inflated;
+        }
+        Ok(data)
+    }
+}
+
+/// The archives in the game folder, managed as app state and shared with the server.
+#[derive(Clone, Default)]
+pub struct Paks(Arc<Vec<Pak>>);
+
+impl Paks {
+    /// Open every `*.pak` in `game_dir`, in name order. Later archives win, so updates can
+    /// ship as `data_2.pak` next to `data.pak`.
+    pub fn load(game_dir: &Path, key: Option<&str>) -> Self {
+        let mut paths: Vec<PathBuf> = fs::read_dir(game_dir)
+            .into_iter()
+            .flatten()
+            .flatten()
+            .map(|entry| entry.path())
+            .filter(|path| path.is_file() && is_pak(path))
+            .collect();
+        paths.sort();
+
+        let paks = paths
+            .iter()
+            .filter_map(|path| match Pak::open(path, key) {
+                Ok(pak) => {
+                    log::info!("Mounted {:?} ({} files)", path, pak.file_count());
+                    Some(pak)
+                }
+                Err(e) => {
+                    log::error!("Failed to open {:?}: {}", path, e);
+                    None
+                }
+            })
+            .collect();
+        Self(Arc::new(paks))
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+
+    pub fn read(&self, path: &str) -> Option<Vec<u8>> {
+        self.0.iter().rev().find_map(|pak| match pak.read(path)? {
+            Ok(data) => Some(data),
+            Err(e) => {
+                log::error!("Failed to read {} from {:?}: {}", path, pak.path, e);
+                None
+            }
+        })
+    }
+}
+
+fn is_pak(path: &Path) -> bool {
+    path.extension().is_some_and(|ext| ext.eq_ignore_ascii_case("pak"))
+}
+
+fn should_compress(path: &str) -> bool {
+    let extension = path
+        .rsplit_once('.')
+        .map(|(_, ext)| ext.to_lowercase())
+        .unwrap_or_default();
+    !COMPRESSED_EXTENSIONS.contains(&extension.as_str())
+}
+
+#[derive(Debug, Default)]
+pub struct PackSummary {
+    pub files: usize,
+    pub size: u64,
+    pub packed_size: u64,
+}
+
+/// Pack every file of `game_dir` into `output`, except saves and other archives. Entries are
+/// compressed when that makes them smaller, and encrypted when `key` is set.
+pub fn pack(game_dir: &Path, output: &Path, key: Option<&str>) -> Result<PackSummary, String> {
+    let key = key.map(derive_key);
+    let mut out = fs::File::create(output).map_err(|e| format!("{:?}: {}", output, e))?;
+    out.write_all(MAGIC).map_err(|e| e.to_string())?;
+    out.write_all(&VERSION.to_le_bytes()).map_err(|e| e.to_string())?;
+
+    let mut summary = PackSummary::default();
+    let mut entries = Vec::new();
+    let mut offset = (MAGIC.len() + 2) as u64;
+    let mut files = encryption::files_in(game_dir);
+    files.sort();
+    for path in files {
+        let Ok(relative) = path.strip_prefix(game_dir) else {
+            continue;
+        };
+        let relative = relative
+            .components()
+            .map(|c| c.as_os_str().to_string_lossy())
+            .collect::<Vec<_>>()
+            .join("/");
+        if is_pak(&path) || relative.starts_with("save/") {
+            continue;
+        }
+
+        let original = fs::read(&path).map_err(|e| format!("{:?}: {}", path, e))?;
+        if original.len() as u64 > MAX_ENTRY_SIZE {
+            return Err(format!("{:?} is too large to pack", path));
+        }
+        let mut data = original.clone();
+        let mut compressed = false;
+        if should_compress(&relative) {
+            let mut encoder = DeflateEncoder::new(Vec::new(), Compression::best());
+            encoder.write_all(&original).map_err(|e| e.to_string())?;
+            let deflated = encoder.finish().map_err(|e| e.to_string())?;
+            if deflated.len() < original.len() {
+                data = deflated;
+                compressed = true;
+            }
+        }
+        if let Some(key) = &key {
+            apply_keystream(key, &relative, &mut data);
+        }
+
+        out.write_all(&data).map_err(|e| e.to_string())?;
+        summary.files += 1;
+        summary.size += original.len() as u64;
+        entries.push(PakEntry {
+            path: relative,
+            offset,
+            stored_size: data.len() as u64,
+            size: original.len() as u64,
+            compressed,
+            encrypted: key.is_some(),
+        });
+        offset += data.len() as u64;
+    }
+
+    let index = serde_json::to_vec(&entries).map_err(|e| e.to_string())?;
+    out.write_all(&index).map_err(|e| e.to_string())?;
+    out.write_all(&offset.to_le_bytes()).map_err(|e| e.to_string())?;
+    out.write_all(&(index.len() as u64).to_le_bytes())
+        .map_err(|e| e.to_string())?;
+    out.write_all(FOOTER_MAGIC).map_err(|e| e.to_string())?;
+    out.flush().map_err(|e| e.to_string())?;
+
+    summary.packed_size = offset + index.len() as u64 + FOOTER_LEN;
+    Ok(summary)
+}
+
+/// Extract every file of the archive at `pak_path` into `output_dir`. Returns the file count.
+pub fn unpack(pak_path: &Path, output_dir: &Path, key: Option<&str>) -> Result<usize, String> {
+    let pak = Pak::open(pak_path, key)?;
+    for entry in pak.entries.values() {
+        let relative = Path::new(&entry.path);
+        if relative.components().any(|c| !matches!(c, Component::Normal(_))) {
+            return Err(format!("Invalid path in the archive: {}", entry.path));
+        }
+        let data = pak.read_entry(entry).map_err(|e| format!("{}: {}", entry.path, e))?;
+        let target = output_dir.join(relative);
+        if let Some(parent) = target.parent() {
+            fs::create_dir_all(parent).map_err(|e| e.to_string())?;
+        }
+        fs::write(&target, data).map_err(|e| format!("{:?}: {}", target, e))?;
+    }
+    Ok(pak.entries.len())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// A fresh folder under the system temp folder.
+    fn temp_dir(name: &str) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!("pak-{}-{}", std::process::id(), name));
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(&dir).unwrap();
+        dir
+    }
+
+    /// An archive with `index` as its index and `data` before it.
+    fn write_pak(dir: &Path, data: &[u8], index: &str) -> PathBuf {
+        let path = dir.join("game.pak");
+        let mut content = MAGIC.to_vec();
+        content.extend_from_slice(&VERSION.to_le_bytes());
+        content.extend_from_slice(data);
+        content.extend_from_slice(index.as_bytes());
+        content.extend_from_slice(&(HEADER_LEN + data.len() as u64).to_le_bytes());
+        content.extend_from_slice(&(index.len() as u64).to_le_bytes());
+        content.extend_from_slice(FOOTER_MAGIC);
+        fs::write(&path, content).unwrap();
+        path
+    }
+
+    #[test]
+    fn packs_and_reads_back() {
+        let dir = temp_dir("round-trip");
+        let game = dir.join("game");
+        fs::create_dir_all(game.join("data")).unwrap();
+        fs::write(game.join("data/Map001.json"), "{\"events\":[]}".repeat(100)).unwrap();
+        fs::write(game.join("data/System.json"), "{}").unwrap();
+        let output = dir.join("game.pak");
+
+        let summary = pack(&game, &output, Some("secret")).unwrap();
+        assert_eq!(summary.files, 2);
+        let pak = Pak::open(&output, Some("secret")).unwrap();
+        assert_eq!(
+            pak.read("data/Map001.json").unwrap().unwrap(),
+            "{\"events\":[]}".repeat(100).into_bytes()
+        );
+        assert_eq!(pak.read("data/System.json").unwrap().unwrap(), b"{}");
+        assert!(pak.read("data/Missing.json").is_none());
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn rejects_truncated_archive() {
+        let dir = temp_dir("truncated");
+        let path = write_pak(&dir, b"abc", r#"[{"path":"a.txt","offset":8,"storedSize":3,"size":3}]"#);
+        assert!(Pak::open(&path, None).is_ok());
+
+        let content = fs::read(&path).unwrap();
+        fs::write(&path, &content[..content.len() - 4]).unwrap();
+        assert!(Pak::open(&path, None).is_err());
+        fs::write(&path, &content[..10]).unwrap();
+        assert!(Pak::open(&path, None).is_err());
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn rejects_index_out_of_bounds() {
+        let dir = temp_dir("index");
+        let path = write_pak(&dir, b"abc", "[]");
+        let mut content = fs::read(&path).unwrap();
+        let length = content.len();
+        content[length - 16..length - 8].copy_from_slice(&u64::MAX.to_le_bytes());
+        fs::write(&path, content).unwrap();
+        assert_eq!(
+            Pak::open(&path, None).err().as_deref(),
+            Some("The .pak index is out of bounds")
+        );
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn rejects_entries_out_of_bounds() {
+        let dir = temp_dir("entries");
+        for index in [
+            // Past the index
+            r#"[{"path":"a.txt","offset":8,"storedSize":4,"size":4}]"#,
+            // Overflowing
+            r#"[{"path":"a.txt","offset":8,"storedSize":18446744073709551615,"size":3}]"#,
+            // Inflating to more than the limit
+            r#"[{"path":"a.txt","offset":8,"storedSize":3,"size":18446744073709551615,"compressed":true}]"#,
+            // Stored as-is, but with another size
+            r#"[{"path":"a.txt","offset":8,"storedSize":3,"size":1000}]"#,
+        ] {
+            let path = write_pak(&dir, b"abc", index);
+            assert!(Pak::open(&path, None).is_err(), "{}", index);
+        }
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn rejects_entry_inflating_to_another_size() {
+        let dir = temp_dir("inflate");
+        let mut encoder = DeflateEncoder::new(Vec::new(), Compression::best());
+        encoder.write_all(&[0u8; 4096]).unwrap();
+        let deflated = encoder.finish().unwrap();
+        let index = format!(
+            r#"[{{"path":"a.bin","offset":8,"storedSize":{},"size":16,"compressed":true}}]"#,
+            deflated.len()
+        );
+        let path = write_pak(&dir, &deflated, &index);
+        let pak = Pak::open(&path, None).unwrap();
+        assert!(pak.read("a.bin").unwrap().is_err());
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}
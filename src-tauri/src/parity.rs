@@ -0,0 +1,33 @@
+//! The NW.js parity report: which capabilities the game page has compared with NW.js.
+//!
+//! The injected probe checks the page once it loads and reports to the localhost server,
+//! which renders the results at `/__launcher/parity`. `get_parity_report` returns the same
+//! results, and Help > Compatibility report opens the page in a window.
+
+use tauri::{AppHandle, Manager, Runtime, State, WebviewUrl, WebviewWindowBuilder};
+
+use crate::display;
+use external_localhost_plugin::{Capability, Diagnostics};
+
+pub const PARITY_LABEL: &str = "parity";
+
+#[tauri::command]
+pub fn get_parity_report(diagnostics: State<'_, Diagnostics>) -> Vec<Capability> {
+    diagnostics.parity()
+}
+
+/// Open the report page from the game's server, or focus it if it is already open.
+pub fn open<R: Runtime>(app: &AppHandle<R>) -> Result<(), String> {
+    if let Some(window) = app.get_webview_window(PARITY_LABEL) {
+        return window.set_focus().map_err(|e| e.to_string());
+    }
+    let mut url = display::main_window(app)?.url().map_err(|e| e.to_string())?;
+    url.set_path("/__launcher/parity");
+    url.set_query(None);
+    WebviewWindowBuilder::new(app, PARITY_LABEL, WebviewUrl::External(url))
+        .title("NW.js parity report")
+        .inner_size(640.0, 480.0)
+        .build()
+        .map_err(|e| e.to_string())?;
+    Ok(())
+}
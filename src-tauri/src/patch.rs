@@ -0,0 +1,401 @@
+//! Binary patches for the content updater.
+//!
+//! Two formats are supported: classic bsdiff (`BSDIFF40`, through qbsdiff) and VCDIFF as
+//! written by xdelta3. The VCDIFF decoder only handles patches without secondary
+//! compression, so xdelta3 patches must be created with `-S none`:
+//! `xdelta3 -e -S none -s old_file new_file patch_file`.
+
+use serde::Deserialize;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum PatchFormat {
+    Bsdiff,
+    Xdelta3,
+}
+
+/// Apply `patch` to `source`, returning the patched file.
+pub fn apply(format: PatchFormat, source: &[u8], patch: &[u8]) -> Result<Vec<u8>, String> {
+    match format {
+        PatchFormat::Bsdiff => {
+            let mut target = Vec::new();
+            qbsdiff::Bspatch::new(patch)
+                .and_then(|patcher| patcher.apply(source, &mut target))
+                .map_err(|e| format!("Invalid bsdiff patch: {}", e))?;
+            Ok(target)
+        }
+        PatchFormat::Xdelta3 => vcdiff_decode(source, patch),
+    }
+}
+
+const VCDIFF_MAGIC: [u8; 3] = [0xd6, 0xc3, 0xc4];
+
+const VCD_DECOMPRESS: u8 = 0x01;
+const VCD_CODETABLE: u8 = 0x02;
+const VCD_APPHEADER: u8 = 0x04;
+
+const VCD_SOURCE: u8 = 0x01;
+const VCD_TARGET: u8 = 0x02;
+const VCD_ADLER32: u8 = 0x04;
+
+/// Largest target window a patch may declare, far above the windows xdelta3 writes, so a
+/// corrupt or crafted patch can't make the launcher allocate without end.
+const MAX_WINDOW_SIZE: usize = 64 * 1024 * 1024;
+/// Largest file a patch may produce. Source and target are both held in memory.
+const MAX_TARGET_SIZE: usize = 1024 * 1024 * 1024;
+
+const NEAR_SIZE: usize = 4;
+const SAME_SIZE: usize = 3;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Inst {
+    Noop,
+    Add,
+    Run,
+    Copy,
+}
+
+#[derive(Debug, Clone, Copy)]
+struct Half {
+    inst: Inst,
+    size: u8,
+    mode: u8,
+}
+
+const NOOP: Half = Half {
+    inst: Inst::Noop,
+    size: 0,
+    mode: 0,
+};
+
+/// The default instruction code table of RFC 3284, section 5.6.
+fn default_code_table() -> Vec<(Half, Half)> {
+    let half = |inst, size, mode| Half { inst, size, mode };
+    let mut table = vec![(half(Inst::Run, 0, 0), NOOP)];
+    for size in 0..18 {
+        table.push((half(Inst::Add, size, 0), NOOP));
+    }
+    for mode in 0..9 {
+        table.push((half(Inst::Copy, 0, mode), NOOP));
+        for size in 4..19 {
+            table.push((half(Inst::Copy, size, mode), NOOP));
+        }
+    }
+    for mode in 0..6 {
+        for add in 1..5 {
+            for copy in 4..7 {
+                table.push((half(Inst::Add, add, 0), half(Inst::Copy, copy, mode)));
+            }
+        }
+    }
+    for mode in 6..9 {
+        for add in 1..5 {
+            table.push((half(Inst::Add, add, 0), half(Inst::Copy, 4, mode)));
+        }
+    }
+    for mode in 0..9 {
+        table.push((half(Inst::Copy, 4, mode), half(Inst::Add, 1, 0)));
+    }
+    table
+}
+
+/// A cursor over a section of the patch.
+struct Reader<'a> {
+    data: &'a [u8],
+    pos: usize,
+}
+
+impl<'a> Reader<'a> {
+    fn new(data: &'a [u8]) -> Self {
+        Self { data, pos: 0 }
+    }
+
+    fn is_empty(&self) -> bool {
+        self.pos >= self.data.len()
+    }
+
+    fn byte(&mut self) -> Result<u8, String> {
+        let byte = *self.data.get(self.pos).ok_or("Truncated VCDIFF patch")?;
+        self.pos += 1;
+        Ok(byte)
+    }
+
+    fn bytes(&mut self, len: usize) -> Result<&'a [u8], String> {
+        let bytes = self
+            .data
+            .get(self.pos..self.pos.saturating_add(len))
+            .ok_or("Truncated VCDIFF patch")?;
+        self.pos += len;
+        Ok(bytes)
+    }
+
+    /// A VCDIFF integer: base 128, most significant digit first.
+    fn integer(&mut self) -> Result<usize, String> {
+        let mut value: usize = 0;
+        loop {
+            let byte = self.byte()?;
+            value = value
+                .checked_mul(128)
+                .and_then(|v| v.checked_add((byte & 0x7f) as usize))
+                .ok_or("Invalid VCDIFF integer")?;
+            if byte & 0x80 == 0 {
+                return Ok(value);
+            }
+        }
+    }
+}
+
+struct AddressCache {
+    near: [usize; NEAR_SIZE],
+    next_slot: usize,
+    same: [usize; SAME_SIZE * 256],
+}
+
+impl AddressCache {
+    fn new() -> Self {
+        Self {
+            near: [0; NEAR_SIZE],
+            next_slot: 0,
+            same: [0; SAME_SIZE * 256],
+        }
+    }
+
+    fn decode(&mut self, addresses: &mut Reader, here: usize, mode: u8) -> Result<usize, String> {
+        let mode = mode as usize;
+        let address = match mode {
+            0 => addresses.integer()?,
+            1 => here.checked_sub(addresses.integer()?).ok_or("Invalid VCDIFF address")?,
+            m if m < 2 + NEAR_SIZE => self.near[m - 2]
+                .checked_add(addresses.integer()?)
+                .ok_or("Invalid VCDIFF address")?,
+            m if m < 2 + NEAR_SIZE + SAME_SIZE => self.same[(m - 2 - NEAR_SIZE) * 256 + addresses.byte()? as usize],
+            _ => return Err("Invalid VCDIFF address mode".to_string()),
+        };
+        self.near[self.next_slot] = address;
+        self.next_slot = (self.next_slot + 1) % NEAR_SIZE;
+        self.same[address % (SAME_SIZE * 256)] = address;
+        Ok(address)
+    }
+}
+
+fn vcdiff_decode(source: &[u8], patch: &[u8]) -> Result<Vec<u8>, String> {
+    let mut reader = Reader::new(patch);
+    if reader.bytes(3)? != VCDIFF_MAGIC {
+        return Err("Not a VCDIFF patch".to_string());
+    }
+    let _version = reader.byte()?;
+    let indicator = reader.byte()?;
+    if indicator & VCD_DECOMPRESS != 0 {
+        return Err("The patch uses secondary compression; create it with xdelta3 -S none".to_string());
+    }
+    if indicator & VCD_CODETABLE != 0 {
+        return Err("Patches with a custom code table are not supported".to_string());
+    }
+    if indicator & VCD_APPHEADER != 0 {
+        let length = reader.integer()?;
+        reader.bytes(length)?;
+    }
+
+    let table = default_code_table();
+    let mut target: Vec<u8> = Vec::new();
+    while !reader.is_empty() {
+        let window = reader.byte()?;
+        let segment = if window & (VCD_SOURCE | VCD_TARGET) != 0 {
+            let length = reader.integer()?;
+            let position = reader.integer()?;
+            let end = position.checked_add(length).ok_or("Invalid VCDIFF source segment")?;
+            let from = if window & VCD_SOURCE != 0 {
+                source
+            } else {
+                target.as_slice()
+            };
+            from.get(position..end)
+                .ok_or("VCDIFF source segment out of range")?
+                .to_vec()
+        } else {
+            Vec::new()
+        };
+
+        let _delta_length = reader.integer()?;
+        let window_length = reader.integer()?;
+        if window_length > MAX_WINDOW_SIZE {
+            return Err("VCDIFF window is too large".to_string());
+        }
+        if target.len() + window_length > MAX_TARGET_SIZE {
+            return Err("The patched file would be too large".to_string());
+        }
+        if reader.byte()? != 0 {
+            return Err("The patch uses secondary compression; create it with xdelta3 -S none".to_string());
+        }
+        let data_length = reader.integer()?;
+        let instructions_length = reader.integer()?;
+        let addresses_length = reader.integer()?;
+        if window & VCD_ADLER32 != 0 {
+            reader.bytes(4)?;
+        }
+        let mut data = Reader::new(reader.bytes(data_length)?);
+        let mut instructions = Reader::new(reader.bytes(instructions_length)?);
+        let mut addresses = Reader::new(reader.bytes(addresses_length)?);
+
+        let mut output: Vec<u8> = Vec::with_capacity(window_length);
+        let mut cache = AddressCache::new();
+        while !instructions.is_empty() {
+            let (first, second) = table[instructions.byte()? as usize];
+            for half in [first, second] {
+                if half.inst == Inst::Noop {
+                    continue;
+                }
+                let size = match half.size {
+                    0 => instructions.integer()?,
+                    size => size as usize,
+                };
+                if size > window_length - output.len() {
+                    return Err("VCDIFF instruction overruns its window".to_string());
+                }
+                match half.inst {
+                    Inst::Noop => {}
+                    Inst::Add => output.extend_from_slice(data.bytes(size)?),
+                    Inst::Run => {
+                        let byte = data.byte()?;
+                        output.resize(output.len() + size, byte);
+                    }
+                    Inst::Copy => {
+                        let here = segment.len() + output.len();
+                        let address = cache.decode(&mut addresses, here, half.mode)?;
+                        if address >= here {
+                            return Err("Invalid VCDIFF copy address".to_string());
+                        }
+                        // Copies from the target can overlap what they produce, so go byte by byte
+                        for i in 0..size {
+                            let at = address + i;
+                            let byte = if at < segment.len() {
+                                segment[at]
+                            } else {
+                                *output.get(at - segment.len()).ok_or("Invalid VCDIFF copy")?
+                            };
+                            output.push(byte);
+                        }
+                    }
+                }
+            }
+        }
+        if output.len() != window_length {
+            return Err("VCDIFF window has the wrong size".to_string());
+        }
+        target.extend_from_slice(&output);
+    }
+    Ok(target)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SOURCE: &[u8] = b"The quick brown fox jumps over the lazy dog.\n";
+    const TARGET: &[u8] = b"The quick brown cat jumps over the lazy dog!\n";
+
+    /// Magic, version and the application header naming the files, as `xdelta3 -e -S none`
+    /// starts a patch.
+    const HEADER: &[u8] = b"\xd6\xc3\xc4\x00\x04\x11new.txt//old.txt/";
+
+    /// `SOURCE` to `TARGET` in a single window over the whole source, with the Adler-32 of
+    /// the window that xdelta3 adds.
+    fn xdelta3_patch() -> Vec<u8> {
+        let mut patch = HEADER.to_vec();
+        patch.extend_from_slice(&[
+            VCD_SOURCE | VCD_ADLER32,
+            45, // source segment length
+            0,  // source segment position
+            21, // delta length
+            45, // target window length
+            0,  // no secondary compression
+            5,  // data length
+            5,  // instructions length
+            2,  // addresses length
+            0x79, 0x91, 0x0f, 0xf0,
+        ]);
+        patch.extend_from_slice(b"cat!\n");
+        // COPY 16, ADD 3, COPY with its size (24) after it, ADD 2
+        patch.extend_from_slice(&[32, 4, 19, 24, 3]);
+        patch.extend_from_slice(&[0, 19]);
+        patch
+    }
+
+    fn integer(mut value: usize) -> Vec<u8> {
+        let mut bytes = vec![(value & 0x7f) as u8];
+        value >>= 7;
+        while value > 0 {
+            bytes.insert(0, (value & 0x7f) as u8 | 0x80);
+            value >>= 7;
+        }
+        bytes
+    }
+
+    /// A patch with one window that doesn't copy from the source.
+    fn single_window(window_length: usize, data: &[u8], instructions: &[u8], addresses: &[u8]) -> Vec<u8> {
+        let mut delta = integer(window_length);
+        delta.push(0);
+        delta.extend(integer(data.len()));
+        delta.extend(integer(instructions.len()));
+        delta.extend(integer(addresses.len()));
+        delta.extend_from_slice(data);
+        delta.extend_from_slice(instructions);
+        delta.extend_from_slice(addresses);
+
+        let mut patch = HEADER.to_vec();
+        patch.push(0);
+        patch.extend(integer(delta.len()));
+        patch.extend(delta);
+        patch
+    }
+
+    #[test]
+    fn applies_xdelta3_patch() {
+        assert_eq!(apply(PatchFormat::Xdelta3, SOURCE, &xdelta3_patch()).unwrap(), TARGET);
+    }
+
+    #[test]
+    fn rejects_truncated_patch() {
+        let patch = xdelta3_patch();
+        // A patch cut right after its header is a valid patch to an empty file
+        for length in (0..patch.len()).filter(|&length| length != HEADER.len()) {
+            assert!(vcdiff_decode(SOURCE, &patch[..length]).is_err(), "cut at {}", length);
+        }
+    }
+
+    #[test]
+    fn rejects_oversized_window() {
+        let patch = single_window(MAX_WINDOW_SIZE + 1, &[], &[], &[]);
+        assert_eq!(vcdiff_decode(SOURCE, &patch).unwrap_err(), "VCDIFF window is too large");
+    }
+
+    #[test]
+    fn rejects_instruction_larger_than_window() {
+        // RUN of 2^40 bytes
+        let mut instructions = vec![0];
+        instructions.extend(integer(1 << 40));
+        let patch = single_window(10, b"A", &instructions, &[]);
+        assert_eq!(
+            vcdiff_decode(SOURCE, &patch).unwrap_err(),
+            "VCDIFF instruction overruns its window"
+        );
+
+        // ADD 1, then COPY of 2^40 bytes
+        let mut instructions = vec![2, 19];
+        instructions.extend(integer(1 << 40));
+        let patch = single_window(10, b"a", &instructions, &[0]);
+        assert_eq!(
+            vcdiff_decode(SOURCE, &patch).unwrap_err(),
+            "VCDIFF instruction overruns its window"
+        );
+    }
+
+    #[test]
+    fn rejects_overflowing_near_address() {
+        // ADD 2, COPY 4 from address 1, then COPY 4 from near[0] (1) + usize::MAX
+        let mut addresses = vec![1];
+        addresses.extend(integer(usize::MAX));
+        let patch = single_window(10, b"ab", &[3, 20, 52], &addresses);
+        assert_eq!(vcdiff_decode(SOURCE, &patch).unwrap_err(), "Invalid VCDIFF address");
+    }
+}
@@ -0,0 +1,77 @@
+//! Pausing the game from the launcher.
+//!
+//! `pause_game` and `resume_game`, also reachable from the menu and tray, emit `game-paused`
+//! and the injected `focus.js` stops the scene updates and suspends the audio context. The
+//! game is also paused after the computer wakes from sleep, so it doesn't play on unattended
+//! and the engine doesn't try to catch up on the time spent asleep.
+
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::time::{Duration, SystemTime};
+
+use tauri::{AppHandle, Emitter, Manager, Runtime, State};
+
+/// How often the sleep watcher wakes up.
+const WATCH_INTERVAL: Duration = Duration::from_secs(2);
+/// A wake-up this much later than expected means the computer was asleep.
+const SUSPEND_THRESHOLD: Duration = Duration::from_secs(10);
+
+#[derive(Default)]
+pub struct Pause {
+    paused: AtomicBool,
+}
+
+impl Pause {
+    pub fn is_paused(&self) -> bool {
+        self.paused.load(Ordering::Relaxed)
+    }
+}
+
+pub fn set_paused<R: Runtime>(app: &AppHandle<R>, paused: bool) -> Result<(), String> {
+    let was_paused = app.state::<Pause>().paused.swap(paused, Ordering::Relaxed);
+    if was_paused != paused {
+        log::info!("Game {}", if paused { "paused" } else { "resumed" });
+    }
+    app.emit("game-paused", paused).map_err(|e| e.to_string())
+}
+
+pub fn toggle<R: Runtime>(app: &AppHandle<R>) -> Result<(), String> {
+    let paused = app.state::<Pause>().is_paused();
+    set_paused(app, !paused)
+}
+
+/// Pause the game when the computer comes back from sleep. There is no portable suspend
+/// notification, but a thread that sleeps for a few seconds and wakes up much later than
+/// that, by the wall clock, was frozen along with the rest of the system.
+pub fn watch_suspend<R: Runtime>(app: &AppHandle<R>) {
+    let app = app.clone();
+    std::thread::spawn(move || {
+        let mut last = SystemTime::now();
+        loop {
+            std::thread::sleep(WATCH_INTERVAL);
+            let now = SystemTime::now();
+            let elapsed = now.duration_since(last).unwrap_or_default();
+            last = now;
+            if elapsed > WATCH_INTERVAL + SUSPEND_THRESHOLD {
+                log::info!("Resumed from sleep after {}s", elapsed.as_secs());
+                if let Err(e) = set_paused(&app, true) {
+                    log::warn!("Failed to pause after sleep: {}", e);
+                }
+            }
+        }
+    });
+}
+
+#[tauri::command]
+pub fn pause_game<R: Runtime>(app: AppHandle<R>) -> Result<(), String> {
+    set_paused(&app, true)
+}
+
+#[tauri::command]
+pub fn resume_game<R: Runtime>(app: AppHandle<R>) -> Result<(), String> {
+    set_paused(&app, false)
+}
+
+#[tauri::command]
+pub fn is_game_paused(pause: State<'_, Pause>) -> bool {
+    pause.is_paused()
+}
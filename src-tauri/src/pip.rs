@@ -0,0 +1,98 @@
+//! Compact picture-in-picture mode: the game shrinks to a small always-on-top view in the
+//! corner of its monitor, with sound still playing, and goes back to where it was after.
+
+use std::sync::Mutex;
+
+use tauri::{AppHandle, LogicalSize, PhysicalPosition, PhysicalSize, Runtime, State};
+
+use crate::display::main_window;
+use crate::game::GameInfo;
+use crate::settings::Settings;
+
+/// Width of the PiP view, in logical pixels. The height follows the game's aspect ratio.
+const PIP_WIDTH: f64 = 320.0;
+/// Gap between the PiP view and the monitor edges, in logical pixels.
+const PIP_MARGIN: f64 = 24.0;
+
+struct SavedWindow {
+    position: PhysicalPosition<i32>,
+    size: PhysicalSize<u32>,
+    fullscreen: bool,
+    decorated: bool,
+}
+
+/// Window geometry from before PiP, managed as app state.
+#[derive(Default)]
+pub struct Pip {
+    saved: Mutex<Option<SavedWindow>>,
+}
+
+/// Enter or leave PiP mode. Returns whether PiP is now active.
+#[tauri::command]
+pub fn set_pip<R: Runtime>(
+    app: AppHandle<R>,
+    pip: State<'_, Pip>,
+    game: State<'_, GameInfo>,
+    settings: State<'_, Settings>,
+    enabled: bool,
+) -> Result<bool, String> {
+    let window = main_window(&app)?;
+    let mut saved = pip.saved.lock().unwrap();
+    if enabled == saved.is_some() {
+        return Ok(enabled);
+    }
+
+    let result = if enabled {
+        let monitor = window
+            .current_monitor()
+            .map_err(|e| e.to_string())?
+            .ok_or("The game window is not on any monitor")?;
+        *saved = Some(SavedWindow {
+            position: window.outer_position().map_err(|e| e.to_string())?,
+            size: window.inner_size().map_err(|e| e.to_string())?,
+            fullscreen: window.is_fullscreen().map_err(|e| e.to_string())?,
+            decorated: window.is_decorated().map_err(|e| e.to_string())?,
+        });
+
+        let scale = monitor.scale_factor();
+        let height = PIP_WIDTH * game.height as f64 / game.width.max(1) as f64;
+        let size = LogicalSize::new(PIP_WIDTH, height).to_physical::<u32>(scale);
+        let margin = (PIP_MARGIN * scale) as i32;
+        let position = PhysicalPosition::new(
+            monitor.position().x + monitor.size().width as i32 - size.width as i32 - margin,
+            monitor.position().y + monitor.size().height as i32 - size.height as i32 - margin,
+        );
+
+        // Integer scaling's minimum size would keep the window from shrinking
+        window
+            .set_fullscreen(false)
+            .and_then(|_| window.set_min_size(None::<LogicalSize<f64>>))
+            .and_then(|_| window.set_decorations(false))
+            .and_then(|_| window.set_size(size))
+            .and_then(|_| window.set_position(position))
+            .and_then(|_| window.set_always_on_top(true))
+    } else {
+        let previous = saved.take().unwrap();
+        window
+            .set_always_on_top(settings.get().always_on_top)
+            .and_then(|_| window.set_decorations(previous.decorated))
+            .and_then(|_| window.set_size(previous.size))
+            .and_then(|_| window.set_position(previous.position))
+            .and_then(|_| window.set_fullscreen(previous.fullscreen))
+            .and_then(|_| crate::display::apply_integer_scaling(&window, &game, settings.get().integer_scaling))
+    };
+
+    result.map_err(|e| e.to_string())?;
+    Ok(enabled)
+}
+
+#[tauri::command]
+pub fn toggle_pip<R: Runtime>(
+    app: AppHandle<R>,
+    pip: State<'_, Pip>,
+    game: State<'_, GameInfo>,
+    settings: State<'_, Settings>,
+) -> Result<bool, String> {
+    let enabled = pip.saved.lock().unwrap().is_none();
+    set_pip(app, pip, game, settings, enabled)
+}
@@ -0,0 +1,159 @@
+//! Serve-time patches for plugins that break outside NW.js.
+//!
+//! `compat/plugin_rules.json` is a versioned list of rules, each a set of find/replace
+//! patches for one or more plugins (`*` for any). At startup the enabled plugins in
+//! `js/plugins.js` are checked against it, and the rules whose code is found are applied
+//! to the plugin files as they are served. The game's files on disk are never changed.
+//! A rule can be turned off with its ID in `disabledCompatRules` in launcher.json.
+
+use std::collections::HashMap;
+use std::fs;
+use std::path::Path;
+use std::sync::Arc;
+
+use serde::{Deserialize, Serialize};
+use tauri::State;
+
+#[derive(Debug, Deserialize)]
+struct RuleDatabase {
+    version: u32,
+    rules: Vec<Rule>,
+}
+
+#[derive(Debug, Deserialize)]
+struct Rule {
+    id: String,
+    /// Plugin names, as listed in plugins.js, or `*`.
+    plugins: Vec<String>,
+    description: String,
+    patches: Vec<Patch>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+struct Patch {
+    find: String,
+    replace: String,
+}
+
+#[derive(Debug, Deserialize)]
+struct PluginEntry {
+    name: String,
+    #[serde(default)]
+    status: bool,
+}
+
+/// A rule applied to one of the game's plugins.
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AppliedRule {
+    pub id: String,
+    pub plugin: String,
+    pub description: String,
+}
+
+/// The patches to apply, by served path. Managed as app state and shared with the server.
+#[derive(Clone, Default)]
+pub struct PluginCompat {
+    applied: Arc<Vec<AppliedRule>>,
+    patches: Arc<HashMap<String, Vec<Patch>>>,
+}
+
+/// The enabled plugins listed in `js/plugins.js`.
+fn enabled_plugins(game_dir: &Path) -> Vec<String> {
+    let Ok(content) = fs::read_to_string(game_dir.join("js").join("plugins.js")) else {
+        return Vec::new();
+    };
+    // `var $plugins = [...];`
+    let list = content
+        .find('[')
+        .zip(content.rfind(']'))
+        .and_then(|(start, end)| content.get(start..=end))
+        .unwrap_or("[]");
+    let plugins: Vec<PluginEntry> = serde_json::from_str(list).unwrap_or_default();
+    plugins
+        .into_iter()
+        .filter(|plugin| plugin.status)
+        .map(|plugin| plugin.name)
+        .collect()
+}
+
+impl PluginCompat {
+    pub fn load(game_dir: &Path, disabled: &[String]) -> Self {
+        let database: RuleDatabase = match serde_json::from_str(include_str!("compat/plugin_rules.json")) {
+            Ok(database) => database,
+            Err(e) => {
+                log::error!("Invalid plugin compatibility rules: {}", e);
+                return Self::default();
+            }
+        };
+
+        let mut applied = Vec::new();
+        let mut patches: HashMap<String, Vec<Patch>> = HashMap::new();
+        for plugin in enabled_plugins(game_dir) {
+            let path = format!("js/plugins/{}.js", plugin);
+            let Ok(source) = fs::read_to_string(game_dir.join(&path)) else {
+                continue;
+            };
+            for rule in &database.rules {
+                let targets = rule
+                    .plugins
+                    .iter()
+                    .any(|name| name == "*" || name.eq_ignore_ascii_case(&plugin));
+                let found: Vec<Patch> = rule
+                    .patches
+                    .iter()
+                    .filter(|patch| source.contains(&patch.find))
+                    .cloned()
+                    .collect();
+                if !targets || found.is_empty() {
+                    continue;
+                }
+                if disabled.contains(&rule.id) {
+                    log::info!("Compatibility rule {} matches {} but is disabled", rule.id, plugin);
+                    continue;
+                }
+                log::info!("Compatibility rule {} applies to {}", rule.id, plugin);
+                patches.entry(path.clone()).or_default().extend(found);
+                applied.push(AppliedRule {
+                    id: rule.id.clone(),
+                    plugin: plugin.clone(),
+                    description: rule.description.clone(),
+                });
+            }
+        }
+
+        log::info!(
+            "Plugin compatibility rules v{}: {} applied",
+            database.version,
+            applied.len()
+        );
+        Self {
+            applied: Arc::new(applied),
+            patches: Arc::new(patches),
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.patches.is_empty()
+    }
+
+    /// Patch a file being served, if any rule applies to it.
+    pub fn apply(&self, path: &str, content: Vec<u8>) -> Vec<u8> {
+        let Some(patches) = self.patches.get(path) else {
+            return content;
+        };
+        let Ok(mut source) = String::from_utf8(content.clone()) else {
+            return content;
+        };
+        for patch in patches {
+            source = source.replace(&patch.find, &patch.replace);
+        }
+        source.into_bytes()
+    }
+}
+
+/// The rules applied to this game's plugins.
+#[tauri::command]
+pub fn get_plugin_compat(compat: State<'_, PluginCompat>) -> Vec<AppliedRule> {
+    compat.applied.as_ref().clone()
+}
@@ -0,0 +1,60 @@
+//! System sleep and resume.
+//!
+//! Tauri has no power events on desktop, so a background thread notices resumes instead:
+//! it wakes up every couple of seconds, and when much more wall clock time passed than it
+//! slept, the machine was suspended in between. On resume the localhost server is checked
+//! and started again if its socket died, and the game is sent `system-resumed` so the
+//! injected script can restart the audio and reset the frame timing.
+
+use std::time::{Duration, SystemTime};
+
+use serde::Serialize;
+use tauri::{AppHandle, Emitter, Manager, Runtime};
+
+use crate::display::MAIN_WINDOW_LABEL;
+use external_localhost_plugin::ServerHandle;
+
+/// How often the watcher thread wakes up.
+const POLL_INTERVAL: Duration = Duration::from_secs(2);
+/// A gap longer than this beyond the poll interval counts as a suspend.
+const SUSPEND_THRESHOLD: Duration = Duration::from_secs(8);
+
+#[derive(Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct Resumed {
+    /// Roughly how long the system was asleep.
+    slept_secs: u64,
+    /// Whether the localhost server had to be started again.
+    server_restarted: bool,
+}
+
+/// Start the watcher thread.
+pub fn watch<R: Runtime>(app: &AppHandle<R>) {
+    let app = app.clone();
+    std::thread::spawn(move || {
+        let mut last = SystemTime::now();
+        loop {
+            std::thread::sleep(POLL_INTERVAL);
+            let now = SystemTime::now();
+            // A clock set backwards isn't a resume
+            let elapsed = now.duration_since(last).unwrap_or_default();
+            last = now;
+            if elapsed > POLL_INTERVAL + SUSPEND_THRESHOLD {
+                on_resume(&app, elapsed);
+            }
+        }
+    });
+}
+
+fn on_resume<R: Runtime>(app: &AppHandle<R>, slept: Duration) {
+    log::info!("System resumed after about {}s", slept.as_secs());
+    let server_restarted = app.state::<ServerHandle>().ensure_running();
+    let _ = app.emit_to(
+        MAIN_WINDOW_LABEL,
+        "system-resumed",
+        Resumed {
+            slept_secs: slept.as_secs(),
+            server_restarted,
+        },
+    );
+}
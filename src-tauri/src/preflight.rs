@@ -0,0 +1,134 @@
+//! Pre-launch checks for the system components the game webview depends on.
+//!
+//! A missing WebView2 runtime or GStreamer codec doesn't crash the launcher, it just
+//! produces a black window or a silent game. These checks run before the main window
+//! is created and explain what is missing instead.
+
+use tauri::{AppHandle, Runtime};
+use tauri_plugin_dialog::{DialogExt, MessageDialogButtons, MessageDialogKind};
+
+/// Oldest WebKitGTK release known to run RPG Maker MV/MZ games correctly.
+#[cfg(target_os = "linux")]
+const MIN_WEBKITGTK_VERSION: (u32, u32) = (2, 38);
+
+/// A system component the game needs but that could not be found.
+pub struct MissingDependency {
+    pub name: String,
+    pub guidance: String,
+}
+
+/// Run every check for the current platform and return what is missing.
+pub fn run_checks() -> Vec<MissingDependency> {
+    let mut missing = Vec::new();
+
+    #[cfg(windows)]
+    check_webview2(&mut missing);
+
+    #[cfg(target_os = "linux")]
+    {
+        check_webkitgtk(&mut missing);
+        check_gstreamer(&mut missing);
+    }
+
+    missing
+}
+
+/// Show the missing dependencies in a dialog. Returns `true` if the user chose to launch anyway.
+pub fn report<R: Runtime>(app: &AppHandle<R>, missing: &[MissingDependency]) -> bool {
+    let mut message = String::from("Some components required by the game were not found:\n\n");
+    for dependency in missing {
+        message.push_str(&format!("• {}\n  {}\n\n", dependency.name, dependency.guidance));
+    }
+    message.push_str("The game may show a black screen or play without sound until they are installed.");
+
+    app.dialog()
+        .message(message)
+        .title("Missing dependencies")
+        .kind(MessageDialogKind::Warning)
+        .buttons(MessageDialogButtons::OkCancelCustom(
+            "Launch anyway".to_string(),
+            "Quit".to_string(),
+        ))
+        .blocking_show()
+}
+
+#[cfg(windows)]
+fn check_webview2(missing: &mut Vec<MissingDependency>) {
+    if tauri::webview_version().is_err() {
+        missing.push(MissingDependency {
+            name: "Microsoft Edge WebView2 Runtime".to_string(),
+            guidance: "Download the Evergreen Bootstrapper from https://developer.microsoft.com/microsoft-edge/webview2/ and run it.".to_string(),
+        });
+    }
+}
+
+#[cfg(target_os = "linux")]
+fn check_webkitgtk(missing: &mut Vec<MissingDependency>) {
+    let guidance = "Install or update WebKitGTK 4.1 (Debian/Ubuntu: libwebkit2gtk-4.1-0, Fedora: webkit2gtk4.1, Arch: webkit2gtk-4.1).";
+
+    match tauri::webview_version() {
+        Ok(version) => {
+            let mut parts = version.split('.').map(|p| p.parse::<u32>().unwrap_or(0));
+            let current = (parts.next().unwrap_or(0), parts.next().unwrap_or(0));
+            if current < MIN_WEBKITGTK_VERSION {
+                missing.push(MissingDependency {
+                    name: format!(
+                        "WebKitGTK {}.{} or newer (found {})",
+                        MIN_WEBKITGTK_VERSION.0, MIN_WEBKITGTK_VERSION.1, version
+                    ),
+                    guidance: guidance.to_string(),
+                });
+            }
+        }
+        Err(_) => missing.push(MissingDependency {
+            name: "WebKitGTK 4.1".to_string(),
+            guidance: guidance.to_string(),
+        }),
+    }
+}
+
+/// GStreamer plugins WebKitGTK needs to decode the formats RPG Maker ships (ogg/vorbis audio, webm movies).
+#[cfg(target_os = "linux")]
+fn check_gstreamer(missing: &mut Vec<MissingDependency>) {
+    const REQUIRED_PLUGINS: [(&str, &str); 4] = [
+        ("libgstogg.so", "Ogg demuxer"),
+        ("libgstvorbis.so", "Vorbis audio decoder"),
+        ("libgstmatroska.so", "WebM demuxer"),
+        ("libgstvpx.so", "VP8/VP9 video decoder"),
+    ];
+
+    let search_dirs = gstreamer_plugin_dirs();
+    let absent: Vec<&str> = REQUIRED_PLUGINS
+        .iter()
+        .filter(|(file, _)| !search_dirs.iter().any(|dir| dir.join(file).exists()))
+        .map(|(_, description)| *description)
+        .collect();
+
+    if !absent.is_empty() {
+        missing.push(MissingDependency {
+            name: format!("GStreamer codecs ({})", absent.join(", ")),
+            guidance: "Install the GStreamer base and good plugin sets (Debian/Ubuntu: gstreamer1.0-plugins-base gstreamer1.0-plugins-good, Fedora: gstreamer1-plugins-base gstreamer1-plugins-good, Arch: gst-plugins-base gst-plugins-good).".to_string(),
+        });
+    }
+}
+
+#[cfg(target_os = "linux")]
+fn gstreamer_plugin_dirs() -> Vec<std::path::PathBuf> {
+    let mut dirs: Vec<std::path::PathBuf> = ["GST_PLUGIN_PATH", "GST_PLUGIN_SYSTEM_PATH_1_0", "GST_PLUGIN_SYSTEM_PATH"]
+        .iter()
+        .filter_map(|var| std::env::var_os(var))
+        .flat_map(|value| std::env::split_paths(&value).collect::<Vec<_>>())
+        .collect();
+
+    for dir in [
+        "/usr/lib/gstreamer-1.0",
+        "/usr/lib64/gstreamer-1.0",
+        "/usr/lib/x86_64-linux-gnu/gstreamer-1.0",
+        "/usr/lib/aarch64-linux-gnu/gstreamer-1.0",
+        "/usr/local/lib/gstreamer-1.0",
+    ] {
+        dirs.push(std::path::PathBuf::from(dir));
+    }
+
+    dirs
+}
@@ -0,0 +1,263 @@
+//! Gameplay recording to video (webm/mp4) or short GIF clips.
+//!
+//! Capture happens in the injected recording script: video through `MediaRecorder`, which
+//! hands over the finished file, and GIFs as raw RGBA frames that are encoded here on a
+//! worker thread so the game page never holds more than one frame.
+
+use std::fs;
+use std::path::PathBuf;
+use std::sync::mpsc::{self, Sender};
+use std::sync::Mutex;
+use std::thread::JoinHandle;
+
+use serde::{Deserialize, Serialize};
+use tauri::ipc::{InvokeBody, Request};
+use tauri::{AppHandle, Emitter, Manager, Runtime, State, WebviewWindow};
+
+use crate::game::GameInfo;
+use crate::screenshots::{sanitize_file_name, timestamped_path};
+use crate::settings::Settings;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum RecordingFormat {
+    /// WebM (or MP4 where the webview can't produce WebM), with game audio.
+    Video,
+    /// Silent animated GIF, downscaled and capped in length.
+    Gif,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct RecordingSaved {
+    path: PathBuf,
+}
+
+struct GifFrame {
+    width: u16,
+    height: u16,
+    delay_ms: u16,
+    rgba: Vec<u8>,
+}
+
+struct GifWriter {
+    frames: Sender<GifFrame>,
+    worker: JoinHandle<Result<PathBuf, String>>,
+}
+
+/// Recording in progress, managed as app state.
+#[derive(Default)]
+pub struct Recorder {
+    active: Mutex<Option<RecordingFormat>>,
+    gif: Mutex<Option<GifWriter>>,
+}
+
+/// Folder recordings are written to: the player's choice, or `Videos/<game title>`.
+pub fn recording_dir<R: Runtime>(
+    app: &AppHandle<R>,
+    game: &GameInfo,
+    settings: &Settings,
+) -> tauri::Result<PathBuf> {
+    if let Some(dir) = settings.get().recording_dir {
+        return Ok(dir);
+    }
+
+    let videos = app
+        .path()
+        .video_dir()
+        .or_else(|_| app.path().app_data_dir())?;
+    Ok(videos.join(sanitize_file_name(&game.title)))
+}
+
+fn header<'a>(request: &'a Request<'_>, name: &str) -> Option<&'a str> {
+    request.headers().get(name).and_then(|value| value.to_str().ok())
+}
+
+fn spawn_gif_writer(path: PathBuf) -> GifWriter {
+    let (frames, receiver) = mpsc::channel::<GifFrame>();
+    let worker = std::thread::spawn(move || {
+        let mut encoder: Option<gif::Encoder<fs::File>> = None;
+
+        for mut frame in receiver {
+            if encoder.is_none() {
+                let file = fs::File::create(&path).map_err(|e| e.to_string())?;
+                let mut new_encoder =
+                    gif::Encoder::new(file, frame.width, frame.height, &[]).map_err(|e| e.to_string())?;
+                new_encoder
+                    .set_repeat(gif::Repeat::Infinite)
+                    .map_err(|e| e.to_string())?;
+                encoder = Some(new_encoder);
+            }
+
+            let mut gif_frame = gif::Frame::from_rgba_speed(frame.width, frame.height, &mut frame.rgba, 10);
+            // GIF delays are in hundredths of a second
+            gif_frame.delay = frame.delay_ms / 10;
+            if let Some(encoder) = encoder.as_mut() {
+                encoder.write_frame(&gif_frame).map_err(|e| e.to_string())?;
+            }
+        }
+
+        if encoder.is_none() {
+            return Err("No frames were recorded".to_string());
+        }
+        Ok(path)
+    });
+
+    GifWriter { frames, worker }
+}
+
+fn finish<R: Runtime>(app: &AppHandle<R>, result: Result<PathBuf, String>) -> Result<PathBuf, String> {
+    match &result {
+        Ok(path) => {
+            log::info!("Recording saved to {:?}", path);
+            let _ = app.emit("recording-saved", RecordingSaved { path: path.clone() });
+        }
+        Err(error) => {
+            log::error!("Recording failed: {}", error);
+            let _ = app.emit("recording-failed", error.clone());
+        }
+    }
+    result
+}
+
+#[tauri::command]
+pub fn start_recording<R: Runtime>(
+    app: AppHandle<R>,
+    window: WebviewWindow<R>,
+    recorder: State<'_, Recorder>,
+    game: State<'_, GameInfo>,
+    settings: State<'_, Settings>,
+    format: RecordingFormat,
+) -> Result<(), String> {
+    let mut active = recorder.active.lock().unwrap();
+    if active.is_some() {
+        return Err("A recording is already in progress".to_string());
+    }
+
+    if format == RecordingFormat::Gif {
+        let dir = recording_dir(&app, &game, &settings).map_err(|e| e.to_string())?;
+        fs::create_dir_all(&dir).map_err(|e| e.to_string())?;
+        *recorder.gif.lock().unwrap() = Some(spawn_gif_writer(timestamped_path(&dir, "Recording", "gif")));
+    }
+
+    let script_format = match format {
+        RecordingFormat::Video => "video",
+        RecordingFormat::Gif => "gif",
+    };
+    window
+        .eval(format!(
+            "window.__RPGMAKER_LAUNCHER__.startRecording({:?})",
+            script_format
+        ))
+        .map_err(|e| e.to_string())?;
+
+    *active = Some(format);
+    let _ = app.emit("recording-started", format);
+    Ok(())
+}
+
+#[tauri::command]
+pub fn stop_recording<R: Runtime>(window: WebviewWindow<R>, recorder: State<'_, Recorder>) -> Result<(), String> {
+    if recorder.active.lock().unwrap().is_none() {
+        return Err("No recording in progress".to_string());
+    }
+    window
+        .eval("window.__RPGMAKER_LAUNCHER__.stopRecording()")
+        .map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+pub fn is_recording(recorder: State<'_, Recorder>) -> Option<RecordingFormat> {
+    *recorder.active.lock().unwrap()
+}
+
+/// Receives the finished video file from the recording script.
+#[tauri::command]
+pub fn save_recording<R: Runtime>(
+    app: AppHandle<R>,
+    recorder: State<'_, Recorder>,
+    game: State<'_, GameInfo>,
+    settings: State<'_, Settings>,
+    request: Request<'_>,
+) -> Result<PathBuf, String> {
+    // Only a recording the player started may write a video
+    if recorder.active.lock().unwrap().take() != Some(RecordingFormat::Video) {
+        return Err("No video recording in progress".to_string());
+    }
+
+    let InvokeBody::Raw(video) = request.body() else {
+        return finish(&app, Err("Expected the video data as the request body".to_string()));
+    };
+    let extension = match header(&request, "X-Recording-Format") {
+        Some("mp4") => "mp4",
+        _ => "webm",
+    };
+
+    let result = recording_dir(&app, &game, &settings)
+        .map_err(|e| e.to_string())
+        .and_then(|dir| {
+            fs::create_dir_all(&dir).map_err(|e| e.to_string())?;
+            let path = timestamped_path(&dir, "Recording", extension);
+            fs::write(&path, video).map_err(|e| e.to_string())?;
+            Ok(path)
+        });
+    finish(&app, result)
+}
+
+/// Receives one RGBA frame of a GIF recording.
+#[tauri::command]
+pub fn push_recording_frame(recorder: State<'_, Recorder>, request: Request<'_>) -> Result<(), String> {
+    let InvokeBody::Raw(rgba) = request.body() else {
+        return Err("Expected the frame pixels as the request body".to_string());
+    };
+    let parse = |name: &str| header(&request, name).and_then(|v| v.parse::<u16>().ok());
+    let (Some(width), Some(height)) = (parse("X-Frame-Width"), parse("X-Frame-Height")) else {
+        return Err("Missing frame size".to_string());
+    };
+    if rgba.len() != width as usize * height as usize * 4 {
+        return Err("Frame size doesn't match the pixel data".to_string());
+    }
+
+    let gif = recorder.gif.lock().unwrap();
+    let Some(writer) = gif.as_ref() else {
+        return Err("No GIF recording in progress".to_string());
+    };
+    writer
+        .frames
+        .send(GifFrame {
+            width,
+            height,
+            delay_ms: parse("X-Frame-Delay").unwrap_or(100),
+            rgba: rgba.clone(),
+        })
+        .map_err(|_| "The GIF encoder stopped".to_string())
+}
+
+/// Called by the recording script once the last GIF frame was sent.
+#[tauri::command]
+pub fn finish_recording<R: Runtime>(app: AppHandle<R>, recorder: State<'_, Recorder>) -> Result<PathBuf, String> {
+    recorder.active.lock().unwrap().take();
+    let Some(writer) = recorder.gif.lock().unwrap().take() else {
+        return Err("No GIF recording in progress".to_string());
+    };
+
+    drop(writer.frames);
+    let result = writer
+        .worker
+        .join()
+        .unwrap_or_else(|_| Err("The GIF encoder crashed".to_string()));
+    finish(&app, result)
+}
+
+/// Called by the recording script when the recording couldn't start.
+#[tauri::command]
+pub fn abort_recording<R: Runtime>(app: AppHandle<R>, recorder: State<'_, Recorder>, reason: String) {
+    recorder.active.lock().unwrap().take();
+    if let Some(writer) = recorder.gif.lock().unwrap().take() {
+        drop(writer.frames);
+        if let Ok(Ok(path)) = writer.worker.join() {
+            let _ = fs::remove_file(path);
+        }
+    }
+    let _ = finish(&app, Err(reason));
+}
@@ -0,0 +1,240 @@
+//! LAN remote control, enabled with `remote` in `launcher.json`.
+//!
+//! A small page served on the local network turns a phone into a gamepad with a quick save
+//! button and a log viewer. Every request has to carry the token generated at startup, which
+//! is part of the pairing link shown by "Remote control…" in the menu, so other devices on the
+//! network can't drive the game. Buttons reach the game as `remote-button` events, handled by
+//! the injected `remote.js`.
+
+use std::net::{TcpListener, UdpSocket};
+use std::sync::Arc;
+
+use axum::body::Body;
+use axum::extract::State;
+use axum::http::{header, Method, Request, StatusCode};
+use axum::response::{IntoResponse, Response};
+use axum::Router;
+use serde::{Deserialize, Serialize};
+use subtle::ConstantTimeEq;
+use tauri::{AppHandle, Emitter, Manager, Runtime};
+use tauri_plugin_dialog::{DialogExt, MessageDialogKind};
+
+use crate::config::RemoteConfig;
+use crate::display;
+use crate::logging;
+
+/// Largest request body read, here and by the automation endpoint. Both only take small JSON
+/// requests.
+pub const MAX_BODY_SIZE: usize = 1024 * 1024;
+
+/// Log entries sent to the remote's log viewer.
+const LOG_ENTRIES: usize = 200;
+
+/// Game buttons the remote can press, named like RPG Maker's `Input` keys.
+const BUTTONS: [&str; 10] = [
+    "up", "down", "left", "right", "ok", "escape", "menu", "shift", "pageup", "pagedown",
+];
+
+#[derive(Deserialize)]
+struct ButtonRequest {
+    button: String,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct RemoteButton {
+    button: String,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct RemoteAction {
+    action: String,
+    slot: u32,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RemoteInfo {
+    /// Link to open on the phone, including the token.
+    pub url: String,
+}
+
+/// What the remote's requests are handled with.
+struct Server<R: Runtime> {
+    app: AppHandle<R>,
+    token: String,
+    slot: u32,
+}
+
+pub struct Remote {
+    url: String,
+}
+
+impl Remote {
+    /// Start serving the remote page. Returns `None` if the port is taken.
+    pub fn start<R: Runtime>(app: &AppHandle<R>, config: &RemoteConfig) -> Option<Self> {
+        let listener = match bind(("0.0.0.0", config.port)) {
+            Ok(listener) => listener,
+            Err(e) => {
+                log::warn!("Failed to start the remote control server on port {}: {}", config.port, e);
+                return None;
+            }
+        };
+
+        let token = generate_token();
+        let host = lan_address().unwrap_or_else(|| "localhost".to_string());
+        let url = format!("http://{}:{}/?token={}", host, config.port, token);
+        log::info!("Remote control available on port {}", config.port);
+
+        let server = Arc::new(Server {
+            app: app.clone(),
+            token,
+            slot: config.quick_save_slot,
+        });
+        serve(
+            listener,
+            Router::new().fallback(handle::<R>).with_state(server),
+            "remote control server",
+        );
+
+        Some(Self { url })
+    }
+}
+
+/// Bind `address` for [`serve`]. Binding up front lets callers report a port that is taken.
+pub fn bind(address: (&str, u16)) -> std::io::Result<TcpListener> {
+    let listener = TcpListener::bind(address)?;
+    listener.set_nonblocking(true)?;
+    Ok(listener)
+}
+
+/// Serve `router` on `listener` on Tauri's async runtime, like the localhost server.
+pub fn serve(listener: TcpListener, router: Router, name: &'static str) {
+    tauri::async_runtime::spawn(async move {
+        let listener = match tokio::net::TcpListener::from_std(listener) {
+            Ok(listener) => listener,
+            Err(e) => {
+                log::error!("Failed to start the {}: {}", name, e);
+                return;
+            }
+        };
+        if let Err(e) = axum::serve(listener, router).await {
+            log::error!("The {} stopped: {}", name, e);
+        }
+    });
+}
+
+/// 128 random bits from the operating system's generator, as hex.
+pub fn generate_token() -> String {
+    let mut bytes = [0u8; 16];
+    getrandom::getrandom(&mut bytes).expect("The system's random number generator failed");
+    hex::encode(bytes)
+}
+
+/// Whether `given` is `token`, compared in constant time so response times don't give away
+/// how much of a guess was right.
+pub fn token_matches(given: Option<&str>, token: &str) -> bool {
+    given.is_some_and(|given| bool::from(given.as_bytes().ct_eq(token.as_bytes())))
+}
+
+/// The address other devices on the network reach this one at. Connecting a UDP socket
+/// only picks the outgoing interface; nothing is sent.
+fn lan_address() -> Option<String> {
+    let socket = UdpSocket::bind("0.0.0.0:0").ok()?;
+    socket.connect("192.0.2.1:80").ok()?;
+    Some(socket.local_addr().ok()?.ip().to_string())
+}
+
+fn query_token(query: &str) -> Option<&str> {
+    query
+        .split('&')
+        .find_map(|pair| pair.strip_prefix("token="))
+}
+
+async fn handle<R: Runtime>(
+    State(server): State<Arc<Server<R>>>,
+    request: Request<Body>,
+) -> Response {
+    let header_token = request
+        .headers()
+        .get("X-Remote-Token")
+        .and_then(|value| value.to_str().ok());
+    let given = header_token.or_else(|| request.uri().query().and_then(query_token));
+    let authorized = token_matches(given, &server.token);
+    let method = request.method().clone();
+    let path = request.uri().path().to_string();
+
+    let (status, content_type, body) = if !authorized {
+        (
+            StatusCode::FORBIDDEN,
+            "text/plain",
+            "Open the link shown in the game's Remote control menu.".to_string(),
+        )
+    } else {
+        match (method, path.as_str()) {
+            (Method::GET, "/") => (
+                StatusCode::OK,
+                "text/html; charset=utf-8",
+                include_str!("remote.html").to_string(),
+            ),
+            (Method::POST, "/api/button") => {
+                let body = axum::body::to_bytes(request.into_body(), MAX_BODY_SIZE)
+                    .await
+                    .unwrap_or_default();
+                match serde_json::from_slice::<ButtonRequest>(&body) {
+                    Ok(b) if BUTTONS.contains(&b.button.as_str()) => {
+                        let button = RemoteButton { button: b.button };
+                        let app = &server.app;
+                        let _ = app.emit_to(display::MAIN_WINDOW_LABEL, "remote-button", button);
+                        (StatusCode::NO_CONTENT, "text/plain", String::new())
+                    }
+                    _ => (
+                        StatusCode::BAD_REQUEST,
+                        "text/plain",
+                        "Unknown button".to_string(),
+                    ),
+                }
+            }
+            (Method::POST, "/api/quicksave") => {
+                let action = RemoteAction {
+                    action: "quickSave".to_string(),
+                    slot: server.slot,
+                };
+                let _ = server
+                    .app
+                    .emit_to(display::MAIN_WINDOW_LABEL, "remote-action", action);
+                (StatusCode::NO_CONTENT, "text/plain", String::new())
+            }
+            (Method::GET, "/api/logs") => {
+                let entries = logging::recent();
+                let start = entries.len().saturating_sub(LOG_ENTRIES);
+                let json = serde_json::to_string(&entries[start..]).unwrap_or_else(|_| "[]".to_string());
+                (StatusCode::OK, "application/json", json)
+            }
+            _ => (StatusCode::NOT_FOUND, "text/plain", "Not Found".to_string()),
+        }
+    };
+
+    (status, [(header::CONTENT_TYPE, content_type)], body).into_response()
+}
+
+/// Pairing link for the remote, if it is enabled.
+#[tauri::command]
+pub fn remote_info<R: Runtime>(app: AppHandle<R>) -> Option<RemoteInfo> {
+    app.try_state::<Remote>().map(|remote| RemoteInfo {
+        url: remote.url.clone(),
+    })
+}
+
+/// Show the pairing link in a dialog.
+pub fn show_pairing<R: Runtime>(app: &AppHandle<R>, remote: &Remote) {
+    app.dialog()
+        .message(format!(
+            "Open this link on a phone connected to the same network:\n\n{}\n\nAnyone with the link can control the game until it restarts.",
+            remote.url
+        ))
+        .title("Remote control")
+        .kind(MessageDialogKind::Info)
+        .show(|_| {});
+}
@@ -0,0 +1,273 @@
+//! Input recording and playback, for reproducing bug reports and scripted QA runs.
+//!
+//! A recording restarts the game with a fixed random seed and captures every change to the
+//! engine's button states (`ok`, `cancel`, `up`...), stamped with the game frame it happened
+//! on and the time since the recording started. Playing a replay restarts the game with the
+//! same seed and feeds the changes back on the same frames, with the real input ignored, so
+//! the game goes through the same states as long as it only depends on the buttons and
+//! `Math.random`. Replays are JSON files in `<app data>/replays/<game id>/`.
+//!
+//! The page asks `get_replay_session` when it loads, which is how a restart picks up a
+//! recording or playback requested from a launcher panel or the developer console.
+
+use std::fs;
+use std::path::PathBuf;
+use std::sync::Mutex;
+use std::time::{Instant, SystemTime, UNIX_EPOCH};
+
+use serde::{Deserialize, Serialize};
+use tauri::{AppHandle, Emitter, Manager, Runtime, State};
+
+use crate::display::MAIN_WINDOW_LABEL;
+use crate::game::GameInfo;
+
+const REPLAY_VERSION: u32 = 1;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct InputEvent {
+    /// Game frames since the session started.
+    pub frame: u64,
+    /// Milliseconds since the recording started, for reference only: playback goes by frame.
+    pub time_ms: u64,
+    pub button: String,
+    pub pressed: bool,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Replay {
+    pub version: u32,
+    pub game_version: Option<String>,
+    /// Seed for `Math.random`.
+    pub seed: u32,
+    pub recorded_at: i64,
+    /// Frames the recording lasted.
+    pub frames: u64,
+    pub events: Vec<InputEvent>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum SessionMode {
+    Record,
+    Play,
+}
+
+/// What the page should do after it loads.
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ReplaySession {
+    pub mode: SessionMode,
+    pub seed: u32,
+    /// The events to feed back, when playing.
+    pub events: Vec<InputEvent>,
+}
+
+struct Recording {
+    started: Instant,
+    replay: Replay,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ReplayFile {
+    pub name: String,
+    pub frames: u64,
+    pub events: usize,
+    pub recorded_at: i64,
+}
+
+pub struct Replays {
+    dir: PathBuf,
+    session: Mutex<Option<ReplaySession>>,
+    recording: Mutex<Option<Recording>>,
+}
+
+fn now_millis() -> i64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_millis() as i64)
+        .unwrap_or_default()
+}
+
+/// A seed that differs between recordings, from the clock.
+fn new_seed() -> u32 {
+    let nanos = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap_or_default()
+        .subsec_nanos();
+    nanos ^ (now_millis() as u32).rotate_left(16)
+}
+
+fn valid_name(name: &str) -> Result<&str, String> {
+    let name = name.strip_suffix(".json").unwrap_or(name);
+    if name.is_empty() || !name.chars().all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '-') {
+        return Err(format!("Invalid replay name: {}", name));
+    }
+    Ok(name)
+}
+
+impl Replays {
+    pub fn new(dir: PathBuf) -> Self {
+        Self {
+            dir,
+            session: Mutex::new(None),
+            recording: Mutex::new(None),
+        }
+    }
+
+    fn path(&self, name: &str) -> Result<PathBuf, String> {
+        Ok(self.dir.join(format!("{}.json", valid_name(name)?)))
+    }
+
+    pub fn list(&self) -> Vec<ReplayFile> {
+        let mut replays: Vec<ReplayFile> = fs::read_dir(&self.dir)
+            .into_iter()
+            .flatten()
+            .flatten()
+            .filter_map(|entry| {
+                let path = entry.path();
+                let name = path.file_stem()?.to_string_lossy().to_string();
+                let replay: Replay = serde_json::from_str(&fs::read_to_string(&path).ok()?).ok()?;
+                Some(ReplayFile {
+                    name,
+                    frames: replay.frames,
+                    events: replay.events.len(),
+                    recorded_at: replay.recorded_at,
+                })
+            })
+            .collect();
+        replays.sort_by(|a, b| b.recorded_at.cmp(&a.recorded_at));
+        replays
+    }
+}
+
+/// Restart the game page so the session starts from boot.
+fn restart_game<R: Runtime>(app: &AppHandle<R>) -> Result<(), String> {
+    app.emit_to(MAIN_WINDOW_LABEL, "replay-restart", ())
+        .map_err(|e| e.to_string())
+}
+
+/// Restart the game and record its input. Returns the seed.
+#[tauri::command]
+pub fn start_replay_recording<R: Runtime>(app: AppHandle<R>) -> Result<u32, String> {
+    let replays = app.state::<Replays>();
+    let seed = new_seed();
+    *replays.recording.lock().unwrap() = Some(Recording {
+        started: Instant::now(),
+        replay: Replay {
+            version: REPLAY_VERSION,
+            game_version: app.state::<GameInfo>().version.clone(),
+            seed,
+            recorded_at: now_millis(),
+            frames: 0,
+            events: Vec::new(),
+        },
+    });
+    *replays.session.lock().unwrap() = Some(ReplaySession {
+        mode: SessionMode::Record,
+        seed,
+        events: Vec::new(),
+    });
+    log::info!("Recording a replay with seed {}", seed);
+    restart_game(&app)?;
+    Ok(seed)
+}
+
+/// A button state change in the page being recorded.
+#[tauri::command]
+pub fn record_input(replays: State<'_, Replays>, frame: u64, button: String, pressed: bool) {
+    if let Some(recording) = replays.recording.lock().unwrap().as_mut() {
+        let time_ms = recording.started.elapsed().as_millis() as u64;
+        recording.replay.frames = recording.replay.frames.max(frame);
+        recording.replay.events.push(InputEvent {
+            frame,
+            time_ms,
+            button,
+            pressed,
+        });
+    }
+}
+
+/// Stop recording and save the replay as `<name>.json`, or a name from the date.
+/// Returns the name it was saved under.
+#[tauri::command]
+pub fn stop_replay_recording(
+    replays: State<'_, Replays>,
+    name: Option<String>,
+    frames: Option<u64>,
+) -> Result<String, String> {
+    let mut recording = replays
+        .recording
+        .lock()
+        .unwrap()
+        .take()
+        .ok_or_else(|| "No replay is being recorded".to_string())?;
+    replays.session.lock().unwrap().take();
+    if let Some(frames) = frames {
+        recording.replay.frames = recording.replay.frames.max(frames);
+    }
+
+    let name = match name {
+        Some(name) => valid_name(&name)?.to_string(),
+        None => chrono::Local::now().format("replay-%Y%m%d-%H%M%S").to_string(),
+    };
+    fs::create_dir_all(&replays.dir).map_err(|e| e.to_string())?;
+    let content = serde_json::to_string_pretty(&recording.replay).map_err(|e| e.to_string())?;
+    fs::write(replays.path(&name)?, content).map_err(|e| e.to_string())?;
+    log::info!(
+        "Saved replay {} ({} events over {} frames)",
+        name,
+        recording.replay.events.len(),
+        recording.replay.frames
+    );
+    Ok(name)
+}
+
+#[tauri::command]
+pub fn list_replays(replays: State<'_, Replays>) -> Vec<ReplayFile> {
+    replays.list()
+}
+
+/// Restart the game and play a saved replay.
+#[tauri::command]
+pub fn play_replay<R: Runtime>(app: AppHandle<R>, name: String) -> Result<(), String> {
+    let replays = app.state::<Replays>();
+    let path = replays.path(&name)?;
+    let content = fs::read_to_string(&path).map_err(|e| format!("{}: {}", name, e))?;
+    let replay: Replay = serde_json::from_str(&content).map_err(|e| format!("{}: {}", name, e))?;
+    if replay.game_version != app.state::<GameInfo>().version {
+        log::warn!(
+            "Replay {} was recorded with game version {:?}; it may not play back the same",
+            name,
+            replay.game_version
+        );
+    }
+    replays.recording.lock().unwrap().take();
+    *replays.session.lock().unwrap() = Some(ReplaySession {
+        mode: SessionMode::Play,
+        seed: replay.seed,
+        events: replay.events,
+    });
+    log::info!("Playing replay {}", name);
+    restart_game(&app)
+}
+
+/// The session the page should start after loading, if any. Playback is only handed out
+/// once, so a reload after it ends starts a normal game.
+#[tauri::command]
+pub fn get_replay_session(replays: State<'_, Replays>) -> Option<ReplaySession> {
+    let mut session = replays.session.lock().unwrap();
+    match session.as_ref().map(|s| s.mode) {
+        Some(SessionMode::Play) => session.take(),
+        _ => session.clone(),
+    }
+}
+
+/// Called by the page when a playback reached its last event.
+#[tauri::command]
+pub fn replay_finished<R: Runtime>(app: AppHandle<R>, frame: u64) -> Result<(), String> {
+    log::info!("Replay finished at frame {}", frame);
+    app.emit("replay-finished", frame).map_err(|e| e.to_string())
+}
@@ -0,0 +1,231 @@
+//! Crash and bug reports, enabled with `crashReports.endpoint` in `launcher.json`.
+//!
+//! A panic writes a pending report (message, backtrace, recent logs) to the app data folder.
+//! On the next start, or when the player picks "Report a problem…", the report window shows
+//! what would be sent and asks for consent; the player can add a description and choose to
+//! include their latest save. Sending bundles everything into a zip archive that is POSTed
+//! to the endpoint. Nothing is uploaded without the player pressing Send.
+
+use std::fs;
+use std::io::{Cursor, Write};
+use std::path::{Path, PathBuf};
+
+use serde::{Deserialize, Serialize};
+use tauri::{AppHandle, Manager, Runtime, State};
+use zip::write::SimpleFileOptions;
+use zip::ZipWriter;
+
+use crate::config::LauncherConfig;
+use crate::game::GameInfo;
+use crate::logging;
+use crate::net;
+use crate::save_data;
+use crate::saves;
+
+pub const REPORT_LABEL: &str = "report";
+const PENDING_FILE_NAME: &str = "pending.json";
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CrashInfo {
+    pub time: String,
+    pub message: String,
+    pub location: Option<String>,
+    pub backtrace: String,
+    pub logs: Vec<logging::LogEntry>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SystemInfo {
+    pub os: String,
+    pub arch: String,
+    pub webview: Option<String>,
+    pub launcher_version: String,
+    pub game_id: String,
+    pub game_title: String,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ReportPreview {
+    /// The crash from the last session, if there was one.
+    pub crash: Option<CrashInfo>,
+    pub system: SystemInfo,
+    /// File name of the save that can be attached.
+    pub latest_save: Option<String>,
+    pub log_lines: usize,
+}
+
+/// Where crash reports are kept until they are sent or discarded.
+pub struct Reports {
+    dir: PathBuf,
+}
+
+impl Reports {
+    pub fn new(dir: PathBuf) -> Self {
+        Self { dir }
+    }
+
+    fn pending_path(&self) -> PathBuf {
+        self.dir.join(PENDING_FILE_NAME)
+    }
+
+    pub fn has_pending(&self) -> bool {
+        self.pending_path().is_file()
+    }
+
+    fn pending(&self) -> Option<CrashInfo> {
+        let content = fs::read_to_string(self.pending_path()).ok()?;
+        serde_json::from_str(&content).ok()
+    }
+
+    fn discard(&self) {
+        let _ = fs::remove_file(self.pending_path());
+    }
+
+    /// Write a pending report when the launcher panics, then run the default hook.
+    pub fn install_panic_hook(&self) {
+        let path = self.pending_path();
+        let default_hook = std::panic::take_hook();
+        std::panic::set_hook(Box::new(move |info| {
+            let message = info
+                .payload()
+                .downcast_ref::<&str>()
+                .map(|s| s.to_string())
+                .or_else(|| info.payload().downcast_ref::<String>().cloned())
+                .unwrap_or_else(|| "unknown panic".to_string());
+            let crash = CrashInfo {
+                time: chrono::Local::now().to_rfc3339(),
+                message,
+                location: info.location().map(|l| l.to_string()),
+                backtrace: std::backtrace::Backtrace::force_capture().to_string(),
+                logs: logging::recent(),
+            };
+            if let Some(parent) = path.parent() {
+                let _ = fs::create_dir_all(parent);
+            }
+            if let Ok(content) = serde_json::to_string(&crash) {
+                let _ = fs::write(&path, content);
+            }
+            default_hook(info);
+        }));
+    }
+}
+
+fn system_info<R: Runtime>(app: &AppHandle<R>) -> SystemInfo {
+    let game = app.state::<GameInfo>();
+    SystemInfo {
+        os: std::env::consts::OS.to_string(),
+        arch: std::env::consts::ARCH.to_string(),
+        webview: tauri::webview_version().ok(),
+        launcher_version: app.package_info().version.to_string(),
+        game_id: game.id.clone(),
+        game_title: game.title.clone(),
+    }
+}
+
+fn latest_save(save_dir: &Path) -> Option<String> {
+    save_data::latest_slot(save_dir).map(|slot| slot.file_name)
+}
+
+fn build_archive(
+    crash: Option<&CrashInfo>,
+    system: &SystemInfo,
+    description: &str,
+    save: Option<(&str, &[u8])>,
+) -> Result<Vec<u8>, String> {
+    let mut archive = ZipWriter::new(Cursor::new(Vec::new()));
+    let options = SimpleFileOptions::default();
+    let mut add = |name: &str, data: &[u8]| -> Result<(), String> {
+        archive.start_file(name, options).map_err(|e| e.to_string())?;
+        archive.write_all(data).map_err(|e| e.to_string())
+    };
+
+    add("description.txt", description.as_bytes())?;
+    add(
+        "system.json",
+        serde_json::to_string_pretty(system).map_err(|e| e.to_string())?.as_bytes(),
+    )?;
+    // The crash keeps the logs from when it happened, a bug report the current ones
+    let logs = crash.map(|c| c.logs.clone()).unwrap_or_else(logging::recent);
+    let log_text: String = logs
+        .iter()
+        .map(|e| format!("{} {} [{}] {}\n", e.time, e.level, e.source, e.message))
+        .collect();
+    add("logs.txt", log_text.as_bytes())?;
+    if let Some(crash) = crash {
+        add(
+            "crash.json",
+            serde_json::to_string_pretty(crash).map_err(|e| e.to_string())?.as_bytes(),
+        )?;
+    }
+    if let Some((name, data)) = save {
+        add(&format!("saves/{}", name), data)?;
+    }
+
+    let cursor = archive.finish().map_err(|e| e.to_string())?;
+    Ok(cursor.into_inner())
+}
+
+#[tauri::command]
+pub fn get_report_preview<R: Runtime>(app: AppHandle<R>, reports: State<'_, Reports>) -> Result<ReportPreview, String> {
+    let save_dir = saves::save_dir(&app).map_err(|e| e.to_string())?;
+    Ok(ReportPreview {
+        crash: reports.pending(),
+        system: system_info(&app),
+        latest_save: latest_save(&save_dir),
+        log_lines: logging::recent().len(),
+    })
+}
+
+/// Upload a report, with the player's description and optionally their latest save.
+#[tauri::command]
+pub async fn send_report<R: Runtime>(
+    app: AppHandle<R>,
+    config: State<'_, LauncherConfig>,
+    reports: State<'_, Reports>,
+    description: String,
+    include_save: bool,
+) -> Result<(), String> {
+    let endpoint = config
+        .crash_reports
+        .as_ref()
+        .map(|c| c.endpoint.clone())
+        .filter(|e| !e.is_empty())
+        .ok_or_else(|| "This game has no report address configured".to_string())?;
+
+    let save_dir = saves::save_dir(&app).map_err(|e| e.to_string())?;
+    let save = match latest_save(&save_dir).filter(|_| include_save) {
+        Some(name) => {
+            let data = fs::read(save_dir.join(&name)).map_err(|e| e.to_string())?;
+            Some((name, data))
+        }
+        None => None,
+    };
+
+    let crash = reports.pending();
+    let system = system_info(&app);
+    let archive = build_archive(
+        crash.as_ref(),
+        &system,
+        &description,
+        save.as_ref().map(|(name, data)| (name.as_str(), data.as_slice())),
+    )?;
+
+    net::request("POST", &endpoint)?
+        .set("Content-Type", "application/zip")
+        .set("X-Game-Id", &system.game_id)
+        .set("X-Launcher-Version", &system.launcher_version)
+        .send_bytes(&archive)
+        .map_err(|e| e.to_string())?;
+
+    log::info!("Report sent ({} bytes)", archive.len());
+    reports.discard();
+    Ok(())
+}
+
+#[tauri::command]
+pub fn discard_report(reports: State<'_, Reports>) {
+    reports.discard();
+}
@@ -0,0 +1,124 @@
+//! Safe mode, for telling a broken game from a broken setup.
+//!
+//! `--safe-mode` runs the game without mods, community achievement sets, plugin compatibility
+//! patches or any injected script the game doesn't need to run and save, turns HTTP caching
+//! off, and enables the developer console and F8 for the devtools.
+//!
+//! Each launch is recorded as running until the launcher exits normally. When several launches
+//! in a row never got there, the launcher offers to relaunch itself in safe mode.
+
+use std::fs;
+use std::path::PathBuf;
+
+use serde::{Deserialize, Serialize};
+use tauri::{AppHandle, Runtime};
+use tauri_plugin_dialog::{DialogExt, MessageDialogButtons, MessageDialogKind};
+
+use crate::args::SAFE_MODE_OPTION;
+
+/// Unclean exits in a row that count as a crash loop.
+const CRASH_LOOP_EXITS: u32 = 3;
+
+/// Whether this launch is in safe mode, managed as app state.
+pub struct SafeMode(pub bool);
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+#[serde(default, rename_all = "camelCase")]
+struct LaunchState {
+    running: bool,
+    unclean_exits: u32,
+}
+
+/// Tracks launches that never reached a normal exit, managed as app state.
+pub struct LaunchGuard {
+    path: PathBuf,
+    unclean_exits: u32,
+}
+
+impl LaunchGuard {
+    /// Record the start of a launch, counting the previous one if it is still marked running.
+    pub fn start(path: PathBuf) -> Self {
+        let previous: LaunchState = fs::read_to_string(&path)
+            .ok()
+            .and_then(|content| serde_json::from_str(&content).ok())
+            .unwrap_or_default();
+        let unclean_exits = if previous.running {
+            previous.unclean_exits + 1
+        } else {
+            0
+        };
+        if unclean_exits > 0 {
+            log::warn!("The last {} launch(es) did not exit cleanly", unclean_exits);
+        }
+
+        let guard = Self { path, unclean_exits };
+        guard.write(&LaunchState {
+            running: true,
+            unclean_exits,
+        });
+        guard
+    }
+
+    pub fn is_crash_loop(&self) -> bool {
+        self.unclean_exits >= CRASH_LOOP_EXITS
+    }
+
+    /// Record a normal exit.
+    pub fn end(&self) {
+        self.write(&LaunchState::default());
+    }
+
+    fn write(&self, state: &LaunchState) {
+        let result = self
+            .path
+            .parent()
+            .map_or(Ok(()), fs::create_dir_all)
+            .and_then(|_| fs::write(&self.path, serde_json::to_string(state).unwrap_or_default()));
+        if let Err(e) = result {
+            log::warn!("Failed to write the launch state {:?}: {}", self.path, e);
+        }
+    }
+}
+
+/// Offer safe mode after a crash loop. Returns `true` if the launcher is relaunching in it.
+pub fn offer<R: Runtime>(app: &AppHandle<R>) -> bool {
+    let accepted = app
+        .dialog()
+        .message(
+            "The game didn't close properly the last few times it ran.\n\n\
+             Safe mode starts it without mods and launcher extras, which helps find out whether \
+             the problem is the game itself. Your saves are kept either way.",
+        )
+        .title("Start in safe mode?")
+        .kind(MessageDialogKind::Warning)
+        .buttons(MessageDialogButtons::OkCancelCustom(
+            "Safe mode".to_string(),
+            "Start normally".to_string(),
+        ))
+        .blocking_show();
+    if !accepted {
+        return false;
+    }
+
+    let exe = match std::env::current_exe() {
+        Ok(exe) => exe,
+        Err(e) => {
+            log::error!("Failed to locate the launcher executable: {}", e);
+            return false;
+        }
+    };
+    match std::process::Command::new(exe)
+        .args(std::env::args_os().skip(1))
+        .arg(SAFE_MODE_OPTION)
+        .spawn()
+    {
+        Ok(_) => {
+            app.exit(0);
+            true
+        }
+        Err(e) => {
+            log::error!("Failed to relaunch in safe mode: {}", e);
+            false
+        }
+    }
+}
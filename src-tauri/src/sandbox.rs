@@ -0,0 +1,354 @@
+//! What the game page is allowed to ask of the launcher.
+//!
+//! The game's scripts share the page with the injected launcher API, so anything the page can
+//! invoke, an untrusted game can too. A game declares what it needs in `capabilities.json`:
+//!
+//! ```json
+//! { "permissions": ["saves", "fs-read"] }
+//! ```
+//!
+//! signed by its packager with `--sign-capabilities=<key file>` into `capabilities.json.sig`.
+//! The signature must come from a key the launcher trusts: one built in through
+//! `RPGMAKER_CAPABILITY_KEYS` (comma-separated hex public keys) or listed in
+//! `<app config>/trusted_keys.json`. An unsigned or untrusted manifest only gets `saves`, and
+//! so does a game opened from another folder without one. The bundled game and the editor's
+//! test play keep full access when they have no manifest.
+//!
+//! [`guard`] checks every command from the game window before it runs; the launcher's own
+//! windows are never restricted. The game window may only call the commands listed in
+//! [`access_for`], so a command added later stays out of its reach until it is classified.
+
+use std::collections::BTreeSet;
+use std::fs;
+use std::path::Path;
+
+use ed25519_dalek::{Signature, Signer, SigningKey, Verifier, VerifyingKey};
+use serde::{Deserialize, Serialize};
+use tauri::ipc::Invoke;
+use tauri::{Manager, Runtime, State, Webview};
+
+use crate::display::MAIN_WINDOW_LABEL;
+
+pub const MANIFEST_FILE_NAME: &str = "capabilities.json";
+pub const SIGNATURE_FILE_NAME: &str = "capabilities.json.sig";
+const TRUSTED_KEYS_FILE_NAME: &str = "trusted_keys.json";
+/// Public keys trusted by this build of the launcher.
+const BUILT_IN_KEYS: Option<&str> = option_env!("RPGMAKER_CAPABILITY_KEYS");
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum Permission {
+    /// Read and write the game's own saves.
+    Saves,
+    /// Read files beyond what the localhost server serves, or picked by path.
+    FsRead,
+    /// Change installed content: mods, DLC, the manifest.
+    FsWrite,
+    /// Open folders in the system file manager.
+    Shell,
+    /// Download files, fetch shared saves from links and send reports.
+    Network,
+}
+
+#[derive(Debug, Deserialize)]
+struct CapabilityManifest {
+    permissions: BTreeSet<Permission>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SandboxPolicy {
+    /// `None` when the game page may call everything.
+    pub permissions: Option<BTreeSet<Permission>>,
+    /// Why this policy applies, for the logs and the diagnostics.
+    pub reason: String,
+}
+
+/// The game's policy, managed as app state.
+pub struct Sandbox(SandboxPolicy);
+
+/// What the game page needs to call a command.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Access {
+    /// Any game may call it.
+    Open,
+    /// Only games granted the permission may call it.
+    Needs(Permission),
+}
+
+/// What the game page needs to call `command`, or `None` if it may never call it.
+pub fn access_for(command: &str) -> Option<Access> {
+    let permission = match command {
+        "list_saves"
+        | "list_saves_detailed"
+        | "read_save"
+        | "write_save"
+        | "delete_save"
+        | "remove_save"
+        | "export_save"
+        | "import_save"
+        | "save_thumbnail"
+        | "get_save_thumbnail"
+        | "check_save_version"
+        | "check_save_dlc"
+        | "report_save_conflict"
+        | "list_save_conflicts"
+        | "resolve_conflict"
+        | "list_save_slots"
+        | "decode_save"
+        | "write_decoded_save"
+        | "import_save_code"
+        | "get_save_backend"
+        | "list_save_backups"
+        | "restore_save_backup" => Permission::Saves,
+        "read_game_file"
+        | "read_game_file_bytes"
+        | "list_directory"
+        | "file_stat"
+        | "read_data_file"
+        | "decrypt_asset"
+        | "import_browser_saves"
+        | "verify_game_files"
+        | "manifest_changes"
+        | "find_mod_conflicts"
+        | "get_config" => Permission::FsRead,
+        "generate_manifest" | "install_dlc" | "enable_dlc" | "disable_dlc" | "set_mod_order" | "set_mod_enabled"
+        | "export_stats" | "set_save_backend" | "set_config" => Permission::FsWrite,
+        "show_save_folder" | "open_mods_folder" => Permission::Shell,
+        "start_download" | "cancel_download" | "send_report" | "export_save_code" => Permission::Network,
+        // The window, input and audio controls, the platform integrations, and the callbacks
+        // the injected scripts answer the launcher with
+        "get_zoom"
+        | "set_zoom"
+        | "set_fullscreen"
+        | "toggle_fullscreen"
+        | "get_window_mode"
+        | "set_window_mode"
+        | "set_integer_scaling"
+        | "set_aspect_ratio_lock"
+        | "set_dpi_scale"
+        | "set_window_title"
+        | "set_always_on_top"
+        | "set_pip"
+        | "toggle_pip"
+        | "set_cursor_autohide"
+        | "set_cursor_visible"
+        | "set_pointer_lock"
+        | "list_monitors"
+        | "move_to_monitor"
+        | "get_settings"
+        | "get_input_bindings"
+        | "set_key_binding"
+        | "set_gamepad_binding"
+        | "reset_input_bindings"
+        | "show_context_menu"
+        | "pause_game"
+        | "resume_game"
+        | "is_game_paused"
+        | "set_video_playing"
+        | "report_audio_devices"
+        | "list_audio_devices"
+        | "set_audio_device"
+        | "set_master_volume"
+        | "get_audio_loop_info"
+        | "get_audio_loops"
+        | "get_encryption_info"
+        | "get_game_version"
+        | "get_plugin_compat"
+        | "get_parity_report"
+        | "report_codecs"
+        | "get_sandbox_policy"
+        | "get_splash_info"
+        | "list_dlc"
+        | "get_active_dlc"
+        | "list_mods"
+        | "open_manual"
+        | "toggle_companion"
+        | "steam_status"
+        | "steam_unlock_achievement"
+        | "steam_clear_achievement"
+        | "steam_is_achieved"
+        | "steam_set_stat"
+        | "steam_get_stat"
+        | "steam_set_rich_presence"
+        | "gog_status"
+        | "gog_unlock_achievement"
+        | "gog_clear_achievement"
+        | "gog_is_achieved"
+        | "itch_status"
+        | "get_achievements"
+        | "unlock_achievement"
+        | "reset_achievement"
+        | "get_community_achievements"
+        | "get_community_watch_list"
+        | "report_game_state"
+        | "set_presence"
+        | "clear_presence"
+        | "notify"
+        | "obs_event"
+        | "speedrun_event"
+        | "record_map_time"
+        | "capture_screenshot"
+        | "save_screenshot"
+        | "stop_recording"
+        | "is_recording"
+        | "save_recording"
+        | "push_recording_frame"
+        | "finish_recording"
+        | "abort_recording"
+        | "toggle_debug_console"
+        | "get_debug_log"
+        | "log_from_game"
+        | "toggle_dev_console"
+        | "dev_eval_result"
+        | "automation_result"
+        | "open_devtools"
+        | "start_replay_recording"
+        | "record_input"
+        | "stop_replay_recording"
+        | "list_replays"
+        | "play_replay"
+        | "get_replay_session"
+        | "replay_finished"
+        | "self_test_frame" => return Some(Access::Open),
+        _ => return None,
+    };
+    Some(Access::Needs(permission))
+}
+
+fn parse_key(hex_key: &str) -> Option<VerifyingKey> {
+    let bytes: [u8; 32] = hex::decode(hex_key.trim()).ok()?.try_into().ok()?;
+    VerifyingKey::from_bytes(&bytes).ok()
+}
+
+fn trusted_keys(config_dir: &Path) -> Vec<VerifyingKey> {
+    let mut keys: Vec<VerifyingKey> = BUILT_IN_KEYS
+        .unwrap_or_default()
+        .split(',')
+        .filter(|key| !key.trim().is_empty())
+        .filter_map(parse_key)
+        .collect();
+    let listed: Vec<String> = fs::read_to_string(config_dir.join(TRUSTED_KEYS_FILE_NAME))
+        .ok()
+        .and_then(|content| serde_json::from_str(&content).ok())
+        .unwrap_or_default();
+    keys.extend(listed.iter().filter_map(|key| parse_key(key)));
+    keys
+}
+
+fn verify(manifest: &[u8], game_dir: &Path, keys: &[VerifyingKey]) -> Result<(), String> {
+    let signature = fs::read_to_string(game_dir.join(SIGNATURE_FILE_NAME)).map_err(|_| "it is not signed")?;
+    let signature: [u8; 64] = hex::decode(signature.trim())
+        .ok()
+        .and_then(|bytes| bytes.try_into().ok())
+        .ok_or("its signature is malformed")?;
+    let signature = Signature::from_bytes(&signature);
+    if keys.iter().any(|key| key.verify(manifest, &signature).is_ok()) {
+        Ok(())
+    } else {
+        Err("it is not signed by a trusted key".to_string())
+    }
+}
+
+impl Sandbox {
+    /// Decide the policy for the game in `game_dir`. `trusted_location` is `false` for games
+    /// opened from another folder than the bundled one.
+    pub fn load(game_dir: &Path, config_dir: &Path, trusted_location: bool) -> Self {
+        let saves_only = || Some(BTreeSet::from([Permission::Saves]));
+        let policy = match fs::read(game_dir.join(MANIFEST_FILE_NAME)) {
+            Err(_) if trusted_location => SandboxPolicy {
+                permissions: None,
+                reason: "bundled game without a capability manifest".to_string(),
+            },
+            Err(_) => SandboxPolicy {
+                permissions: saves_only(),
+                reason: "external game without a capability manifest".to_string(),
+            },
+            Ok(content) => match verify(&content, game_dir, &trusted_keys(config_dir)).and_then(|_| {
+                serde_json::from_slice::<CapabilityManifest>(&content).map_err(|e| format!("it is invalid: {}", e))
+            }) {
+                Ok(manifest) => SandboxPolicy {
+                    permissions: Some(manifest.permissions),
+                    reason: "signed capability manifest".to_string(),
+                },
+                Err(e) => SandboxPolicy {
+                    permissions: saves_only(),
+                    reason: format!("capability manifest ignored: {}", e),
+                },
+            },
+        };
+        log::info!("Game sandbox: {:?} ({})", policy.permissions, policy.reason);
+        Self(policy)
+    }
+
+    pub fn allows(&self, permission: Permission) -> bool {
+        match &self.0.permissions {
+            Some(permissions) => permissions.contains(&permission),
+            None => true,
+        }
+    }
+}
+
+/// Fail unless `webview` is one of the launcher's own windows or the game's policy grants
+/// `permission`, for commands that only need it for some of what they do.
+pub fn require<R: Runtime>(webview: &Webview<R>, permission: Permission) -> Result<(), String> {
+    let denied = webview.label() == MAIN_WINDOW_LABEL
+        && webview
+            .try_state::<Sandbox>()
+            .is_some_and(|sandbox| !sandbox.allows(permission));
+    if denied {
+        return Err(format!("{:?} access is not allowed for this game", permission));
+    }
+    Ok(())
+}
+
+/// Run `handler` for `invoke`, unless it comes from the game window and the sandbox forbids it.
+pub fn guard<R: Runtime>(invoke: Invoke<R>, handler: &dyn Fn(Invoke<R>) -> bool) -> bool {
+    let webview = invoke.message.webview_ref();
+    let command = invoke.message.command();
+    let denied = if webview.label() != MAIN_WINDOW_LABEL {
+        None
+    } else {
+        match access_for(command) {
+            Some(Access::Open) => None,
+            Some(Access::Needs(permission)) => webview
+                .try_state::<Sandbox>()
+                .is_some_and(|sandbox| !sandbox.allows(permission))
+                .then_some("its capability manifest doesn't allow"),
+            None => Some("only the launcher's own windows may call"),
+        }
+    };
+    if let Some(reason) = denied {
+        let command = command.to_string();
+        log::warn!("The game called {}, which {}", command, reason);
+        invoke
+            .resolver
+            .reject(format!("{} is not allowed for this game", command));
+        return true;
+    }
+    handler(invoke)
+}
+
+/// Sign `capabilities.json` in `game_dir` with the Ed25519 key whose 32-byte seed is in
+/// `key_file` as hex. Returns the public key to give to players or build into the launcher.
+pub fn sign_manifest(game_dir: &Path, key_file: &Path) -> Result<String, String> {
+    let seed: [u8; 32] = hex::decode(fs::read_to_string(key_file).map_err(|e| e.to_string())?.trim())
+        .ok()
+        .and_then(|bytes| bytes.try_into().ok())
+        .ok_or("The key file must hold 32 bytes as hex")?;
+    let key = SigningKey::from_bytes(&seed);
+
+    let manifest_path = game_dir.join(MANIFEST_FILE_NAME);
+    let content = fs::read(&manifest_path).map_err(|e| format!("{:?}: {}", manifest_path, e))?;
+    serde_json::from_slice::<CapabilityManifest>(&content)
+        .map_err(|e| format!("Invalid {}: {}", MANIFEST_FILE_NAME, e))?;
+
+    let signature = key.sign(&content);
+    fs::write(game_dir.join(SIGNATURE_FILE_NAME), hex::encode(signature.to_bytes())).map_err(|e| e.to_string())?;
+    Ok(hex::encode(key.verifying_key().to_bytes()))
+}
+
+#[tauri::command]
+pub fn get_sandbox_policy(sandbox: State<'_, Sandbox>) -> SandboxPolicy {
+    sandbox.0.clone()
+}
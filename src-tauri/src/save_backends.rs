@@ -0,0 +1,475 @@
+//! Where the save commands keep the save files.
+//!
+//! `saveBackend` in launcher.json picks one of:
+//!
+//! - `local`: the launcher's save folder in the app data directory (the default);
+//! - `nwjs`: `save/` next to the game's `index.html`, where NW.js keeps them, so the game's
+//!   folder can be moved between the launcher and NW.js with its saves;
+//! - `encrypted`: the launcher's save folder, with every file sealed with ChaCha20-Poly1305
+//!   under a random nonce, so a damaged or edited save fails to load instead of loading
+//!   corrupted. The cipher key is derived from the `key` passphrase with PBKDF2, salted with
+//!   the game's id. The key ships in launcher.json, so this keeps players from editing their
+//!   saves by hand rather than keeping them secret;
+//! - `mirrored`: the launcher's save folder, with every write and delete copied to `folder`,
+//!   typically one a cloud client syncs. Saves missing locally are read from the mirror, so
+//!   a fresh install picks up the player's progress.
+//!
+//! The backend can be switched while the game runs with `set_save_backend`, optionally copying
+//! the saves over, as the save manager does.
+//!
+//! Every backend writes a save into a temporary file first, flushes it to disk and renames it
+//! over the old one, so a crash or power loss mid-write leaves the previous save intact. That
+//! makes a new file on every write, so the time each save was first written is kept in
+//! `.created.json` in its folder rather than in the file's creation time.
+
+use std::collections::HashMap;
+use std::fs::{self, File};
+use std::io::Write;
+use std::num::NonZeroU32;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex, OnceLock, RwLock};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use external_localhost_plugin::GameFiles;
+use ring::aead::{Aad, LessSafeKey, Nonce, UnboundKey, CHACHA20_POLY1305, NONCE_LEN};
+use ring::pbkdf2;
+use serde::{Deserialize, Serialize};
+use tauri::{AppHandle, Emitter, Manager, Runtime, State, Webview};
+
+use crate::display::MAIN_WINDOW_LABEL;
+use crate::game::GameInfo;
+use crate::{pak, saves};
+
+/// Marks a file written by the `encrypted` backend, followed by the nonce and the sealed save.
+const ENCRYPTED_MAGIC: &[u8] = b"RMSAVE2\0";
+/// Marks a file from the first version of the backend, XORed with a SHA-256 keystream, which
+/// is still read, followed by its 32-byte nonce.
+const LEGACY_MAGIC: &[u8] = b"RMSAVE1\0";
+const LEGACY_NONCE_LENGTH: usize = 32;
+/// PBKDF2-HMAC-SHA256 rounds turning the `key` passphrase into the cipher key.
+const KEY_ITERATIONS: u32 = 100_000;
+
+/// When each save in a folder was first written, in milliseconds since the Unix epoch, by name.
+const CREATED_FILE: &str = ".created.json";
+
+/// Locks serializing the writes to each save file, by path, whichever backend writes it.
+static WRITE_LOCKS: OnceLock<Mutex<HashMap<PathBuf, Arc<Mutex<()>>>>> = OnceLock::new();
+/// Lock serializing the updates to every [`CREATED_FILE`].
+static CREATED_LOCK: Mutex<()> = Mutex::new(());
+
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "camelCase")]
+pub enum SaveBackendConfig {
+    #[default]
+    Local,
+    Nwjs,
+    Encrypted {
+        key: String,
+    },
+    Mirrored {
+        /// Absolute path of the folder the saves are copied to.
+        folder: PathBuf,
+    },
+}
+
+impl SaveBackendConfig {
+    /// This configuration without the encryption key, for the game page.
+    pub fn redacted(&self) -> Self {
+        match self {
+            Self::Encrypted { .. } => Self::Encrypted { key: String::new() },
+            config => config.clone(),
+        }
+    }
+}
+
+/// Storage for the game's save files, by file name.
+pub trait SaveBackend: Send + Sync {
+    /// Folder the files are kept in, for the tools that work on them directly.
+    fn dir(&self) -> &Path;
+    fn list(&self) -> Result<Vec<saves::SaveFile>, String>;
+    fn read(&self, name: &str) -> Result<String, String>;
+    fn write(&self, name: &str, data: &str) -> Result<(), String>;
+    /// Delete a save. Deleting one that doesn't exist is not an error.
+    fn delete(&self, name: &str) -> Result<(), String>;
+}
+
+/// Save files kept as they are in a folder.
+struct Folder {
+    dir: PathBuf,
+}
+
+impl Folder {
+    fn created_times(&self) -> HashMap<String, u64> {
+        fs::read(self.dir.join(CREATED_FILE))
+            .ok()
+            .and_then(|content| serde_json::from_slice(&content).ok())
+            .unwrap_or_default()
+    }
+
+    /// Keep `created` as the time `name` was first written unless it has one, or forget it
+    /// with `None`.
+    fn update_created(&self, name: &str, created: Option<u64>) {
+        let _guard = CREATED_LOCK.lock().unwrap();
+        let mut times = self.created_times();
+        let changed = match created {
+            Some(created) if !times.contains_key(name) => {
+                times.insert(name.to_string(), created);
+                true
+            }
+            Some(_) => false,
+            None => times.remove(name).is_some(),
+        };
+        if !changed {
+            return;
+        }
+        let written = serde_json::to_vec(&times)
+            .map_err(|e| e.to_string())
+            .and_then(|content| {
+                write_atomically(&self.dir.join(CREATED_FILE), &content).map_err(|e| e.to_string())
+            });
+        if let Err(e) = written {
+            log::warn!("Failed to update {:?}: {}", self.dir.join(CREATED_FILE), e);
+        }
+    }
+
+    fn write_bytes(&self, name: &str, content: &[u8]) -> Result<(), String> {
+        let path = self.dir.join(name);
+        // A save from before the launcher kept track still has its own creation time
+        let created = fs::metadata(&path)
+            .and_then(|metadata| metadata.created().or_else(|_| metadata.modified()))
+            .unwrap_or_else(|_| SystemTime::now());
+        write_atomically(&path, content).map_err(|e| e.to_string())?;
+        self.update_created(name, unix_millis(created));
+        Ok(())
+    }
+}
+
+fn unix_millis(time: SystemTime) -> Option<u64> {
+    time.duration_since(UNIX_EPOCH)
+        .ok()
+        .map(|duration| duration.as_millis() as u64)
+}
+
+impl SaveBackend for Folder {
+    fn dir(&self) -> &Path {
+        &self.dir
+    }
+
+    fn list(&self) -> Result<Vec<saves::SaveFile>, String> {
+        let Ok(entries) = fs::read_dir(&self.dir) else {
+            return Ok(Vec::new());
+        };
+
+        let created_times = self.created_times();
+        let mut saves: Vec<saves::SaveFile> = entries
+            .filter_map(|entry| entry.ok())
+            .filter_map(|entry| {
+                let name = entry.file_name().to_string_lossy().to_string();
+                let metadata = entry.metadata().ok()?;
+                if !metadata.is_file() || name.starts_with('.') {
+                    return None;
+                }
+                Some(saves::SaveFile {
+                    size: metadata.len(),
+                    created: created_times
+                        .get(&name)
+                        .copied()
+                        .or_else(|| metadata.created().ok().and_then(unix_millis)),
+                    modified: metadata.modified().ok().and_then(unix_millis),
+                    name,
+                })
+            })
+            .collect();
+        saves.sort_by(|a, b| a.name.cmp(&b.name));
+        Ok(saves)
+    }
+
+    fn read(&self, name: &str) -> Result<String, String> {
+        fs::read_to_string(self.dir.join(name)).map_err(|e| e.to_string())
+    }
+
+    fn write(&self, name: &str, data: &str) -> Result<(), String> {
+        self.write_bytes(name, data.as_bytes())
+    }
+
+    fn delete(&self, name: &str) -> Result<(), String> {
+        match fs::remove_file(self.dir.join(name)) {
+            Ok(()) => {}
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => {}
+            Err(e) => return Err(e.to_string()),
+        }
+        self.update_created(name, None);
+        Ok(())
+    }
+}
+
+/// Save files encrypted in a folder. Files written before the backend was chosen, or by its
+/// first version, are read as they are and sealed on their next write.
+struct Encrypted {
+    folder: Folder,
+    key: LessSafeKey,
+    legacy_key: [u8; 32],
+}
+
+impl Encrypted {
+    fn new(folder: Folder, passphrase: &str, game_id: &str) -> Self {
+        let mut key = [0u8; 32];
+        pbkdf2::derive(
+            pbkdf2::PBKDF2_HMAC_SHA256,
+            NonZeroU32::new(KEY_ITERATIONS).unwrap(),
+            format!("rpgmaker-launcher-saves/{}", game_id).as_bytes(),
+            passphrase.as_bytes(),
+            &mut key,
+        );
+        Self {
+            folder,
+            key: LessSafeKey::new(UnboundKey::new(&CHACHA20_POLY1305, &key).unwrap()),
+            legacy_key: pak::derive_key(passphrase),
+        }
+    }
+}
+
+impl SaveBackend for Encrypted {
+    fn dir(&self) -> &Path {
+        self.folder.dir()
+    }
+
+    fn list(&self) -> Result<Vec<saves::SaveFile>, String> {
+        self.folder.list()
+    }
+
+    fn read(&self, name: &str) -> Result<String, String> {
+        let content = fs::read(self.folder.dir.join(name)).map_err(|e| e.to_string())?;
+        if let Some(sealed) = content.strip_prefix(ENCRYPTED_MAGIC) {
+            if sealed.len() < NONCE_LEN {
+                return Err(format!("{} is truncated", name));
+            }
+            let (nonce, data) = sealed.split_at(NONCE_LEN);
+            let nonce = Nonce::try_assume_unique_for_key(nonce).map_err(|_| format!("{} is truncated", name))?;
+            let mut data = data.to_vec();
+            let opened = self
+                .key
+                .open_in_place(nonce, Aad::empty(), &mut data)
+                .map_err(|_| format!("{} is damaged or was encrypted with another key", name))?;
+            return String::from_utf8(opened.to_vec()).map_err(|e| e.to_string());
+        }
+        let Some(sealed) = content.strip_prefix(LEGACY_MAGIC) else {
+            return String::from_utf8(content).map_err(|e| e.to_string());
+        };
+        if sealed.len() < LEGACY_NONCE_LENGTH {
+            return Err(format!("{} is truncated", name));
+        }
+        let (nonce, data) = sealed.split_at(LEGACY_NONCE_LENGTH);
+        let mut data = data.to_vec();
+        let path = format!("{}/{}", String::from_utf8_lossy(nonce), name);
+        pak::apply_keystream(&self.legacy_key, &path, &mut data);
+        String::from_utf8(data).map_err(|_| format!("{} was encrypted with another key", name))
+    }
+
+    fn write(&self, name: &str, data: &str) -> Result<(), String> {
+        // A fresh nonce per write; ChaCha20 must never see the same one twice under a key
+        let mut nonce = [0u8; NONCE_LEN];
+        getrandom::getrandom(&mut nonce).map_err(|e| format!("Failed to encrypt {}: {}", name, e))?;
+        let mut sealed = data.as_bytes().to_vec();
+        self.key
+            .seal_in_place_append_tag(Nonce::assume_unique_for_key(nonce), Aad::empty(), &mut sealed)
+            .map_err(|_| format!("Failed to encrypt {}", name))?;
+        let mut content = Vec::with_capacity(ENCRYPTED_MAGIC.len() + NONCE_LEN + sealed.len());
+        content.extend_from_slice(ENCRYPTED_MAGIC);
+        content.extend_from_slice(&nonce);
+        content.extend_from_slice(&sealed);
+        self.folder.write_bytes(name, &content)
+    }
+
+    fn delete(&self, name: &str) -> Result<(), String> {
+        self.folder.delete(name)
+    }
+}
+
+/// Replace the file at `path` with `content` through a temporary file next to it, synced to
+/// disk before it is renamed over the old one. Writes to the same file wait for each other.
+pub fn write_atomically(path: &Path, content: &[u8]) -> std::io::Result<()> {
+    let lock = WRITE_LOCKS
+        .get_or_init(Default::default)
+        .lock()
+        .unwrap()
+        .entry(path.to_path_buf())
+        .or_default()
+        .clone();
+    let _guard = lock.lock().unwrap();
+
+    let (Some(dir), Some(name)) = (path.parent(), path.file_name()) else {
+        return Err(std::io::Error::new(std::io::ErrorKind::InvalidInput, "Not a file path"));
+    };
+    fs::create_dir_all(dir)?;
+    // Dot files are left out of the save list, so a leftover never shows up as a save
+    let temporary = dir.join(format!(".{}.tmp", name.to_string_lossy()));
+    let written = File::create(&temporary)
+        .and_then(|mut file| {
+            file.write_all(content)?;
+            file.sync_all()
+        })
+        .and_then(|_| fs::rename(&temporary, path));
+    if written.is_err() {
+        let _ = fs::remove_file(&temporary);
+    }
+    written?;
+    // The rename only survives a power loss once the folder itself is synced
+    #[cfg(unix)]
+    File::open(dir)?.sync_all()?;
+    Ok(())
+}
+
+/// Save files in a folder, copied to a second one. Failing to update the mirror is logged
+/// rather than failing the save.
+struct Mirrored {
+    primary: Folder,
+    mirror: Folder,
+}
+
+impl SaveBackend for Mirrored {
+    fn dir(&self) -> &Path {
+        self.primary.dir()
+    }
+
+    fn list(&self) -> Result<Vec<saves::SaveFile>, String> {
+        let mut saves = self.primary.list()?;
+        for save in self.mirror.list().unwrap_or_default() {
+            if !saves.iter().any(|s| s.name == save.name) {
+                saves.push(save);
+            }
+        }
+        saves.sort_by(|a, b| a.name.cmp(&b.name));
+        Ok(saves)
+    }
+
+    fn read(&self, name: &str) -> Result<String, String> {
+        self.primary
+            .read(name)
+            .or_else(|e| self.mirror.read(name).map_err(|_| e))
+    }
+
+    fn write(&self, name: &str, data: &str) -> Result<(), String> {
+        self.primary.write(name, data)?;
+        if let Err(e) = self.mirror.write(name, data) {
+            log::warn!("Failed to mirror {} to {:?}: {}", name, self.mirror.dir, e);
+        }
+        Ok(())
+    }
+
+    fn delete(&self, name: &str) -> Result<(), String> {
+        self.primary.delete(name)?;
+        if let Err(e) = self.mirror.delete(name) {
+            log::warn!("Failed to delete {} from {:?}: {}", name, self.mirror.dir, e);
+        }
+        Ok(())
+    }
+}
+
+/// The backend `config` describes.
+fn open<R: Runtime>(app: &AppHandle<R>, config: &SaveBackendConfig) -> Result<Arc<dyn SaveBackend>, String> {
+    let local = Folder {
+        dir: saves::save_dir(app).map_err(|e| e.to_string())?,
+    };
+    Ok(match config {
+        SaveBackendConfig::Local => Arc::new(local),
+        SaveBackendConfig::Nwjs => Arc::new(Folder {
+            dir: app.state::<GameInfo>().dir.join("save"),
+        }),
+        SaveBackendConfig::Encrypted { key } => {
+            Arc::new(Encrypted::new(local, key, &app.state::<GameInfo>().id))
+        }
+        SaveBackendConfig::Mirrored { folder } => {
+            if !folder.is_absolute() {
+                return Err(format!("The mirror folder must be an absolute path: {:?}", folder));
+            }
+            Arc::new(Mirrored {
+                primary: local,
+                mirror: Folder { dir: folder.clone() },
+            })
+        }
+    })
+}
+
+/// The save backend in use, managed as app state.
+pub struct SaveBackends {
+    active: RwLock<(SaveBackendConfig, Arc<dyn SaveBackend>)>,
+}
+
+impl SaveBackends {
+    /// The backend `config` describes, or the local one if it can't be used.
+    pub fn new<R: Runtime>(app: &AppHandle<R>, config: &SaveBackendConfig) -> tauri::Result<Self> {
+        let (config, backend) = match open(app, config) {
+            Ok(backend) => (config.clone(), backend),
+            Err(e) => {
+                log::error!("Failed to open the save backend, using the local one: {}", e);
+                let local: Arc<dyn SaveBackend> = Arc::new(Folder {
+                    dir: saves::save_dir(app)?,
+                });
+                (SaveBackendConfig::Local, local)
+            }
+        };
+        map_save_folder(app, backend.as_ref());
+        Ok(Self {
+            active: RwLock::new((config, backend)),
+        })
+    }
+
+    pub fn get(&self) -> Arc<dyn SaveBackend> {
+        self.active.read().unwrap().1.clone()
+    }
+
+    pub fn config(&self) -> SaveBackendConfig {
+        self.active.read().unwrap().0.clone()
+    }
+}
+
+/// Show the folder of `backend` under `save/` to the game's `fs` helpers, where NW.js games
+/// find their saves.
+fn map_save_folder<R: Runtime>(app: &AppHandle<R>, backend: &dyn SaveBackend) {
+    if let Some(files) = app.try_state::<GameFiles>() {
+        files.map_folder("save", backend.dir());
+    }
+}
+
+/// The save backend in use.
+pub fn backend<R: Runtime>(app: &AppHandle<R>) -> Arc<dyn SaveBackend> {
+    app.state::<SaveBackends>().get()
+}
+
+/// The backend in use, without its encryption key when the game asks.
+#[tauri::command]
+pub fn get_save_backend<R: Runtime>(webview: Webview<R>, backends: State<'_, SaveBackends>) -> SaveBackendConfig {
+    if webview.label() == MAIN_WINDOW_LABEL {
+        backends.config().redacted()
+    } else {
+        backends.config()
+    }
+}
+
+/// Switch to another save backend. With `migrate`, every save is copied from the current
+/// backend first, overwriting what the new one has; the current one is left as it was.
+/// The game is told to reload its saves with a "save-backend-changed" event.
+#[tauri::command]
+pub fn set_save_backend<R: Runtime>(
+    app: AppHandle<R>,
+    backends: State<'_, SaveBackends>,
+    config: SaveBackendConfig,
+    migrate: bool,
+) -> Result<(), String> {
+    let next = open(&app, &config)?;
+    let mut active = backends.active.write().unwrap();
+    if migrate {
+        let current = &active.1;
+        for save in current.list()? {
+            next.write(&save.name, &current.read(&save.name)?)?;
+        }
+    }
+    log::info!("Switched the save backend to {:?}", config.redacted());
+    map_save_folder(&app, next.as_ref());
+    *active = (config.clone(), next);
+    drop(active);
+    // The game page listens too, so the key stays out of the event
+    app.emit("save-backend-changed", config.redacted())
+        .map_err(|e| e.to_string())
+}
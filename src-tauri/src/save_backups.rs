@@ -0,0 +1,222 @@
+//! Rotating backups of every save.
+//!
+//! Each time the game writes a save, the file is copied to
+//! `backups/<name>.<timestamp>.<extension>` in the save backend's folder, e.g.
+//! `backups/file1.20261016-143000-250.rpgsave`, and only the newest `saveBackups` copies
+//! (5 by default, 0 turns them off) are kept. Copies are made of the file as written, so an
+//! encrypted backend's backups stay encrypted. `restore_save_backup` puts one back in place.
+
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use chrono::{NaiveDateTime, Utc};
+use serde::Serialize;
+use tauri::{AppHandle, Manager, Runtime};
+
+use crate::config::LauncherConfig;
+use crate::{save_backends, saves};
+
+const BACKUPS_DIR: &str = "backups";
+/// UTC, down to the millisecond so an autosave right after a save gets its own backup.
+const TIMESTAMP_FORMAT: &str = "%Y%m%d-%H%M%S-%3f";
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SaveBackup {
+    /// File name of the backup, to pass to `restore_save_backup`.
+    pub name: String,
+    /// Name of the save it is a copy of.
+    pub save: String,
+    pub size: u64,
+    /// When the backup was made, in milliseconds since the Unix epoch.
+    pub created: i64,
+}
+
+fn backups_dir<R: Runtime>(app: &AppHandle<R>) -> PathBuf {
+    save_backends::backend(app).dir().join(BACKUPS_DIR)
+}
+
+/// `file1.rpgsave` and a timestamp to `file1.<timestamp>.rpgsave`.
+fn backup_name(save: &str, timestamp: &str) -> String {
+    match save.rsplit_once('.') {
+        Some((stem, extension)) => format!("{}.{}.{}", stem, timestamp, extension),
+        None => format!("{}.{}", save, timestamp),
+    }
+}
+
+/// The save a backup file is a copy of, and when it was made.
+fn parse_backup_name(name: &str) -> Option<(String, i64)> {
+    let timestamp_of = |timestamp: &str| {
+        NaiveDateTime::parse_from_str(timestamp, TIMESTAMP_FORMAT)
+            .ok()
+            .map(|time| time.and_utc().timestamp_millis())
+    };
+    // With an extension, the timestamp is the part before it; without one, the last part
+    if let Some((rest, extension)) = name.rsplit_once('.') {
+        if let Some((stem, timestamp)) = rest.rsplit_once('.') {
+            if let Some(created) = timestamp_of(timestamp) {
+                return Some((format!("{}.{}", stem, extension), created));
+            }
+        }
+        if let Some(created) = timestamp_of(extension) {
+            return Some((rest.to_string(), created));
+        }
+    }
+    None
+}
+
+/// Every backup in `dir`, newest first.
+fn backups_in(dir: &Path) -> Vec<SaveBackup> {
+    let mut backups: Vec<SaveBackup> = fs::read_dir(dir)
+        .into_iter()
+        .flatten()
+        .flatten()
+        .filter_map(|entry| {
+            let name = entry.file_name().to_string_lossy().to_string();
+            let (save, created) = parse_backup_name(&name)?;
+            let metadata = entry.metadata().ok().filter(|metadata| metadata.is_file())?;
+            Some(SaveBackup {
+                name,
+                save,
+                size: metadata.len(),
+                created,
+            })
+        })
+        .collect();
+    backups.sort_by(|a, b| b.created.cmp(&a.created).then_with(|| a.name.cmp(&b.name)));
+    backups
+}
+
+/// Back up the save `name` as just written, and drop its oldest backups. Failing is logged
+/// rather than failing the save.
+pub fn backup<R: Runtime>(app: &AppHandle<R>, name: &str) {
+    let keep = app.state::<LauncherConfig>().save_backups;
+    if keep == 0 {
+        return;
+    }
+    let dir = backups_dir(app);
+    let source = save_backends::backend(app).dir().join(name);
+    let target = dir.join(backup_name(name, &Utc::now().format(TIMESTAMP_FORMAT).to_string()));
+    let copied = fs::create_dir_all(&dir).and_then(|_| fs::copy(&source, &target));
+    if let Err(e) = copied {
+        log::warn!("Failed to back up {}: {}", name, e);
+        return;
+    }
+    prune(&dir, name, keep);
+}
+
+/// Delete all but the newest `keep` backups of the save `name` in `dir`.
+fn prune(dir: &Path, name: &str, keep: usize) {
+    for old in backups_in(dir).iter().filter(|backup| backup.save == name).skip(keep) {
+        if let Err(e) = fs::remove_file(dir.join(&old.name)) {
+            log::warn!("Failed to delete the old backup {}: {}", old.name, e);
+        }
+    }
+}
+
+/// The backups of every save, or of the save `name`, newest first.
+#[tauri::command]
+pub fn list_save_backups<R: Runtime>(app: AppHandle<R>, name: Option<String>) -> Vec<SaveBackup> {
+    let mut backups = backups_in(&backups_dir(&app));
+    if let Some(name) = name {
+        backups.retain(|backup| backup.save == name);
+    }
+    backups
+}
+
+/// Put a backup back in place of its save, and tell the running game about it. The save
+/// being replaced has a backup of its own from when it was written.
+#[tauri::command]
+pub fn restore_save_backup<R: Runtime>(app: AppHandle<R>, backup: String) -> Result<(), String> {
+    let (save, _) = parse_backup_name(&backup).ok_or_else(|| format!("Not a save backup: {}", backup))?;
+    // Backup names are valid save names, with the same rules
+    saves::save_path(&app, &backup)?;
+    let path = saves::save_path(&app, &save)?;
+    let content = fs::read(backups_dir(&app).join(&backup)).map_err(|e| format!("{}: {}", backup, e))?;
+    save_backends::write_atomically(&path, &content).map_err(|e| e.to_string())?;
+    // Written again through the backend, which also updates a mirror
+    let backend = save_backends::backend(&app);
+    let data = backend.read(&save)?;
+    backend.write(&save, &data)?;
+    log::info!("Restored {} from {}", save, backup);
+    saves::notify_changed(&app, save, Some(data))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use chrono::NaiveDate;
+
+    #[test]
+    fn backup_names_round_trip() {
+        let created = NaiveDate::from_ymd_opt(2026, 10, 16)
+            .unwrap()
+            .and_hms_milli_opt(14, 30, 0, 250)
+            .unwrap()
+            .and_utc()
+            .timestamp_millis();
+        for save in ["file1.rpgsave", "global.rpgsave", "file12.rmmzsave", "my.game.rpgsave", "config"] {
+            let name = backup_name(save, "20261016-143000-250");
+            assert_eq!(parse_backup_name(&name), Some((save.to_string(), created)), "{}", name);
+        }
+        assert_eq!(
+            backup_name("file1.rpgsave", "20261016-143000-250"),
+            "file1.20261016-143000-250.rpgsave"
+        );
+
+        let now = Utc::now();
+        let name = backup_name("file1.rpgsave", &now.format(TIMESTAMP_FORMAT).to_string());
+        assert_eq!(
+            parse_backup_name(&name).map(|(_, created)| created),
+            Some(now.timestamp_millis())
+        );
+    }
+
+    #[test]
+    fn other_files_are_not_backups() {
+        for name in ["file1.rpgsave", "file1.backup.rpgsave", "file1.2026-10-16.rpgsave", "notes", ""] {
+            assert_eq!(parse_backup_name(name), None, "{}", name);
+        }
+    }
+
+    #[test]
+    fn pruning_keeps_the_newest_backups_of_the_save() {
+        let dir = std::env::temp_dir().join(format!("save-backups-{}", std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(&dir).unwrap();
+        let timestamps = [
+            "20261016-143000-250",
+            "20261016-143000-251",
+            "20261016-150000-000",
+            "20261017-090000-000",
+        ];
+        for timestamp in timestamps {
+            fs::write(dir.join(backup_name("file1.rpgsave", timestamp)), timestamp).unwrap();
+            fs::write(dir.join(backup_name("file2.rpgsave", timestamp)), timestamp).unwrap();
+        }
+        fs::write(dir.join("notes.txt"), "").unwrap();
+
+        prune(&dir, "file1.rpgsave", 2);
+        let names = |save: &str| -> Vec<String> {
+            backups_in(&dir)
+                .into_iter()
+                .filter(|backup| backup.save == save)
+                .map(|backup| backup.name)
+                .collect()
+        };
+        assert_eq!(
+            names("file1.rpgsave"),
+            vec![
+                "file1.20261017-090000-000.rpgsave".to_string(),
+                "file1.20261016-150000-000.rpgsave".to_string(),
+            ]
+        );
+        assert_eq!(names("file2.rpgsave").len(), 4);
+        assert!(dir.join("notes.txt").exists());
+
+        prune(&dir, "file2.rpgsave", 0);
+        assert!(names("file2.rpgsave").is_empty());
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}
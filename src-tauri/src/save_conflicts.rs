@@ -0,0 +1,339 @@
+//! Save conflicts between the save folder and a sync provider.
+//!
+//! Whatever syncs the saves (a cloud service, a game's own web backend, a sync plugin) reports a
+//! slot whose local and remote copies diverged with `report_save_conflict`, or
+//! [`SaveConflicts::report`] from Rust. The launcher decodes both copies, summarizes them (play
+//! time, map, party, gold, switches and variables) and emits `save-conflict`, so a frontend can
+//! ask the player to keep the local copy, the remote one, or both. `resolve_conflict` applies the
+//! choice and emits `save-conflict-resolved`, which tells the provider what to upload or drop.
+
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::Path;
+use std::sync::Mutex;
+use std::time::UNIX_EPOCH;
+
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use tauri::{AppHandle, Emitter, Manager, Runtime, State};
+
+use crate::game::GameInfo;
+use crate::save_data::{self, SaveFormat};
+use crate::saves;
+
+/// Slots the save screen shows, in MV and MZ alike.
+const MAX_SAVEFILES: usize = 20;
+/// Frames per second of the game's play time counter.
+const FRAMES_PER_SECOND: u64 = 60;
+
+/// What the player sees of one copy of a save.
+#[derive(Debug, Clone, Default, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SaveSummary {
+    /// Milliseconds since the Unix epoch.
+    pub modified: Option<i64>,
+    pub size: u64,
+    pub playtime_secs: Option<u64>,
+    pub save_count: Option<u64>,
+    pub map_id: Option<u64>,
+    pub map_name: Option<String>,
+    pub gold: Option<i64>,
+    /// Party members with their level, e.g. `Harold Lv 12`.
+    pub party: Vec<String>,
+}
+
+/// A divergent slot, as sent to the frontend.
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ConflictInfo {
+    /// File name of the slot, e.g. `file3.rpgsave`.
+    pub slot: String,
+    /// Who reported it, e.g. `steam` or the name of a sync plugin.
+    pub provider: String,
+    pub local: SaveSummary,
+    pub remote: SaveSummary,
+    /// Differences between the two copies in a few words each, e.g. `Gold: 1200 → 900`.
+    pub differences: Vec<String>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum Resolution {
+    KeepLocal,
+    KeepRemote,
+    /// Keep the local copy in its slot and put the remote one in the first free slot.
+    KeepBoth,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct ConflictResolved {
+    slot: String,
+    provider: String,
+    choice: Resolution,
+    /// Slot the remote copy went to when both were kept.
+    copy: Option<String>,
+}
+
+struct Conflict {
+    info: ConflictInfo,
+    remote_data: String,
+}
+
+/// Conflicts waiting for the player, by slot, managed as app state.
+#[derive(Default)]
+pub struct SaveConflicts(Mutex<BTreeMap<String, Conflict>>);
+
+impl SaveConflicts {
+    /// Record a conflict on `slot` with the `remote_data` a provider holds and tell the frontend.
+    pub fn report<R: Runtime>(
+        &self,
+        app: &AppHandle<R>,
+        provider: &str,
+        slot: &str,
+        remote_data: String,
+        remote_modified: Option<i64>,
+    ) -> Result<ConflictInfo, String> {
+        let format = SaveFormat::from_name(slot).ok_or_else(|| format!("Not a save file: {}", slot))?;
+        let game_dir = app.state::<GameInfo>().dir.clone();
+        let path = saves::save_path(app, slot)?;
+
+        let local_data = fs::read_to_string(&path).map_err(|e| e.to_string())?;
+        let local_save = save_data::decode(format, &local_data).ok();
+        let remote_save = save_data::decode(format, &remote_data).ok();
+
+        let mut local = local_save
+            .as_ref()
+            .map(|save| summarize(save, &game_dir))
+            .unwrap_or_default();
+        local.size = local_data.len() as u64;
+        local.modified = fs::metadata(&path)
+            .and_then(|metadata| metadata.modified())
+            .ok()
+            .and_then(|time| time.duration_since(UNIX_EPOCH).ok())
+            .map(|duration| duration.as_millis() as i64);
+        let mut remote = remote_save
+            .as_ref()
+            .map(|save| summarize(save, &game_dir))
+            .unwrap_or_default();
+        remote.size = remote_data.len() as u64;
+        remote.modified = remote_modified;
+
+        let mut differences = summary_differences(&local, &remote);
+        if let (Some(local_save), Some(remote_save)) = (&local_save, &remote_save) {
+            for (key, label) in [("switches", "Switches"), ("variables", "Variables")] {
+                let changed = count_differing(&local_save[key]["_data"], &remote_save[key]["_data"]);
+                if changed > 0 {
+                    differences.push(format!("{} differing: {}", label, changed));
+                }
+            }
+        } else {
+            differences.push("One of the copies could not be read".to_string());
+        }
+
+        let info = ConflictInfo {
+            slot: slot.to_string(),
+            provider: provider.to_string(),
+            local,
+            remote,
+            differences,
+        };
+        log::warn!("Save conflict on {} reported by {}", slot, provider);
+        self.0.lock().unwrap().insert(
+            slot.to_string(),
+            Conflict {
+                info: info.clone(),
+                remote_data,
+            },
+        );
+        app.emit("save-conflict", &info).map_err(|e| e.to_string())?;
+        Ok(info)
+    }
+}
+
+fn summarize(save: &Value, game_dir: &Path) -> SaveSummary {
+    let map_id = save_data::map_id(save);
+    let actors = &save["actors"]["_data"];
+    let party = save["party"]["_actors"]
+        .as_array()
+        .into_iter()
+        .flatten()
+        .filter_map(|id| {
+            let actor = &actors[id.as_u64()? as usize];
+            Some(format!("{} Lv {}", actor["_name"].as_str()?, actor["_level"].as_u64()?))
+        })
+        .collect();
+    SaveSummary {
+        modified: None,
+        size: 0,
+        playtime_secs: save["system"]["_framesOnSave"]
+            .as_u64()
+            .map(|frames| frames / FRAMES_PER_SECOND),
+        save_count: save["system"]["_saveCount"].as_u64(),
+        map_id,
+        map_name: map_id.and_then(|id| save_data::map_name(game_dir, id)),
+        gold: save["party"]["_gold"].as_i64(),
+        party,
+    }
+}
+
+fn format_playtime(secs: u64) -> String {
+    format!("{:02}:{:02}:{:02}", secs / 3600, secs / 60 % 60, secs % 60)
+}
+
+fn summary_differences(local: &SaveSummary, remote: &SaveSummary) -> Vec<String> {
+    let mut differences = Vec::new();
+    let mut compare = |label: &str, local: Option<String>, remote: Option<String>| {
+        if local != remote {
+            let show = |value: Option<String>| value.unwrap_or_else(|| "?".to_string());
+            differences.push(format!("{}: {} → {}", label, show(local), show(remote)));
+        }
+    };
+    compare(
+        "Play time",
+        local.playtime_secs.map(format_playtime),
+        remote.playtime_secs.map(format_playtime),
+    );
+    compare(
+        "Times saved",
+        local.save_count.map(|count| count.to_string()),
+        remote.save_count.map(|count| count.to_string()),
+    );
+    compare(
+        "Map",
+        local
+            .map_name
+            .clone()
+            .or_else(|| local.map_id.map(|id| format!("#{}", id))),
+        remote
+            .map_name
+            .clone()
+            .or_else(|| remote.map_id.map(|id| format!("#{}", id))),
+    );
+    compare(
+        "Gold",
+        local.gold.map(|gold| gold.to_string()),
+        remote.gold.map(|gold| gold.to_string()),
+    );
+    if local.party != remote.party {
+        compare("Party", Some(local.party.join(", ")), Some(remote.party.join(", ")));
+    }
+    differences
+}
+
+/// Entries that differ between two switch or variable arrays, missing ones counting as null.
+fn count_differing(local: &Value, remote: &Value) -> usize {
+    let empty = Vec::new();
+    let local = local.as_array().unwrap_or(&empty);
+    let remote = remote.as_array().unwrap_or(&empty);
+    (0..local.len().max(remote.len()))
+        .filter(|&i| {
+            let set = |value: Option<&Value>| value.filter(|v| !matches!(v, Value::Null | Value::Bool(false)));
+            set(local.get(i)) != set(remote.get(i))
+        })
+        .count()
+}
+
+/// Put `data` in the first slot the global info doesn't list, copying the slot info of `slot`
+/// with the remote play time. Returns the new slot's file name.
+fn keep_as_new_slot<R: Runtime>(app: &AppHandle<R>, slot: &str, conflict: &Conflict) -> Result<String, String> {
+    let format = SaveFormat::from_name(slot).ok_or_else(|| format!("Not a save file: {}", slot))?;
+    let global_name = format!("global.{}", format.extension());
+    let mut global = save_data::read(&saves::save_path(app, &global_name)?)?;
+    let slots = global.as_array_mut().ok_or("The global save info is not a list")?;
+
+    let free = (1..=MAX_SAVEFILES)
+        .find(|&id| !slots.get(id).is_some_and(Value::is_object))
+        .ok_or("There is no free save slot for the remote copy")?;
+    let source = save_data::slots(&saves::save_dir(app).map_err(|e| e.to_string())?)
+        .into_iter()
+        .find(|info| info.file_name == slot)
+        .map(|info| slots[info.savefile_id].clone())
+        .unwrap_or_else(|| Value::Object(Default::default()));
+
+    let mut info = source;
+    if let Some(secs) = conflict.info.remote.playtime_secs {
+        info["playtime"] = Value::from(format_playtime(secs));
+    }
+    info["timestamp"] = Value::from(
+        conflict
+            .info
+            .remote
+            .modified
+            .unwrap_or_else(|| chrono::Local::now().timestamp_millis()),
+    );
+    if slots.len() <= free {
+        slots.resize(free + 1, Value::Null);
+    }
+    slots[free] = info;
+
+    let name = format!("file{}.{}", free, format.extension());
+    saves::replace_save(app, name.clone(), conflict.remote_data.clone())?;
+    saves::replace_save(app, global_name, save_data::encode(format, &global)?)?;
+    Ok(name)
+}
+
+/// Let a sync provider running in a webview report a conflict.
+#[tauri::command]
+pub fn report_save_conflict<R: Runtime>(
+    app: AppHandle<R>,
+    conflicts: State<'_, SaveConflicts>,
+    provider: String,
+    slot: String,
+    data: String,
+    modified: Option<i64>,
+) -> Result<ConflictInfo, String> {
+    conflicts.report(&app, &provider, &slot, data, modified)
+}
+
+#[tauri::command]
+pub fn list_save_conflicts(conflicts: State<'_, SaveConflicts>) -> Vec<ConflictInfo> {
+    conflicts
+        .0
+        .lock()
+        .unwrap()
+        .values()
+        .map(|conflict| conflict.info.clone())
+        .collect()
+}
+
+#[tauri::command]
+pub fn resolve_conflict<R: Runtime>(
+    app: AppHandle<R>,
+    conflicts: State<'_, SaveConflicts>,
+    slot: String,
+    choice: Resolution,
+) -> Result<(), String> {
+    let conflict = conflicts
+        .0
+        .lock()
+        .unwrap()
+        .remove(&slot)
+        .ok_or_else(|| format!("No conflict on {}", slot))?;
+
+    let copy = match choice {
+        Resolution::KeepLocal => Ok(None),
+        Resolution::KeepRemote => saves::replace_save(&app, slot.clone(), conflict.remote_data.clone()).map(|_| None),
+        Resolution::KeepBoth => keep_as_new_slot(&app, &slot, &conflict).map(Some),
+    };
+    let copy = match copy {
+        Ok(copy) => copy,
+        Err(e) => {
+            // Still unresolved, the player can pick again
+            conflicts.0.lock().unwrap().insert(slot, conflict);
+            return Err(e);
+        }
+    };
+
+    log::info!("Save conflict on {} resolved: {:?}", slot, choice);
+    app.emit(
+        "save-conflict-resolved",
+        ConflictResolved {
+            slot,
+            provider: conflict.info.provider,
+            choice,
+            copy,
+        },
+    )
+    .map_err(|e| e.to_string())
+}
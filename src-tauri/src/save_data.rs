@@ -0,0 +1,215 @@
+//! Reading RPG Maker save files in the format NW.js writes them.
+//!
+//! MV saves are JSON compressed with LZString to Base64. MZ saves are zlib-deflated JSON
+//! kept as a "binary string" (one char per byte), written out as UTF-8.
+
+use std::fs;
+use std::io::{Read, Write};
+use std::path::Path;
+
+use flate2::read::ZlibDecoder;
+use flate2::write::ZlibEncoder;
+use flate2::Compression;
+use serde::Serialize;
+use serde_json::Value;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SaveFormat {
+    Mv,
+    Mz,
+}
+
+impl SaveFormat {
+    pub fn from_name(name: &str) -> Option<Self> {
+        if name.ends_with(".rpgsave") {
+            Some(Self::Mv)
+        } else if name.ends_with(".rmmzsave") {
+            Some(Self::Mz)
+        } else {
+            None
+        }
+    }
+
+    pub fn extension(self) -> &'static str {
+        match self {
+            Self::Mv => "rpgsave",
+            Self::Mz => "rmmzsave",
+        }
+    }
+}
+
+/// Decode the contents of a save file into its JSON.
+pub fn decode(format: SaveFormat, content: &str) -> Result<Value, String> {
+    let json = match format {
+        SaveFormat::Mv => {
+            let utf16 = lz_str::decompress_from_base64(content.trim())
+                .ok_or_else(|| "Invalid LZString data".to_string())?;
+            String::from_utf16(&utf16).map_err(|e| e.to_string())?
+        }
+        SaveFormat::Mz => {
+            let bytes: Vec<u8> = content
+                .chars()
+                .map(|c| u8::try_from(c as u32).map_err(|_| "Invalid binary string".to_string()))
+                .collect::<Result<_, _>>()?;
+            let mut json = String::new();
+            ZlibDecoder::new(&bytes[..])
+                .read_to_string(&mut json)
+                .map_err(|e| e.to_string())?;
+            json
+        }
+    };
+    serde_json::from_str(&json).map_err(|e| e.to_string())
+}
+
+/// Encode save JSON the way the game writes it, the reverse of `decode`.
+pub fn encode(format: SaveFormat, save: &Value) -> Result<String, String> {
+    let json = serde_json::to_string(save).map_err(|e| e.to_string())?;
+    match format {
+        SaveFormat::Mv => Ok(lz_str::compress_to_base64(json.as_str())),
+        SaveFormat::Mz => {
+            let mut encoder = ZlibEncoder::new(Vec::new(), Compression::fast());
+            encoder.write_all(json.as_bytes()).map_err(|e| e.to_string())?;
+            let bytes = encoder.finish().map_err(|e| e.to_string())?;
+            Ok(bytes.into_iter().map(char::from).collect())
+        }
+    }
+}
+
+pub fn read(path: &Path) -> Result<Value, String> {
+    let name = path.file_name().map(|n| n.to_string_lossy().to_string()).unwrap_or_default();
+    let format = SaveFormat::from_name(&name).ok_or_else(|| format!("Not a save file: {}", name))?;
+    let content = fs::read_to_string(path).map_err(|e| e.to_string())?;
+    decode(format, &content)
+}
+
+/// One slot from the global info file, as shown in the game's save/load screen.
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SlotInfo {
+    pub savefile_id: usize,
+    pub file_name: String,
+    pub title: String,
+    /// Play time as the game formats it, e.g. `01:23:45`.
+    pub playtime: String,
+    /// Milliseconds since the Unix epoch.
+    pub timestamp: Option<i64>,
+}
+
+/// Slots listed in `global.rpgsave` / `global.rmmzsave`.
+pub fn slots(save_dir: &Path) -> Vec<SlotInfo> {
+    for format in [SaveFormat::Mv, SaveFormat::Mz] {
+        let global = save_dir.join(format!("global.{}", format.extension()));
+        if !global.is_file() {
+            continue;
+        }
+        let info = match read(&global) {
+            Ok(info) => info,
+            Err(e) => {
+                log::warn!("Failed to read {:?}: {}", global, e);
+                return Vec::new();
+            }
+        };
+        return info
+            .as_array()
+            .into_iter()
+            .flatten()
+            .enumerate()
+            .filter(|(_, slot)| slot.is_object())
+            .map(|(savefile_id, slot)| SlotInfo {
+                savefile_id,
+                file_name: format!("file{}.{}", savefile_id, format.extension()),
+                title: slot["title"].as_str().unwrap_or_default().to_string(),
+                playtime: slot["playtime"].as_str().unwrap_or_default().to_string(),
+                timestamp: slot["timestamp"].as_i64(),
+            })
+            .collect();
+    }
+    Vec::new()
+}
+
+/// The most recently written slot.
+pub fn latest_slot(save_dir: &Path) -> Option<SlotInfo> {
+    slots(save_dir).into_iter().max_by_key(|slot| slot.timestamp.unwrap_or(0))
+}
+
+/// ID of the map the party is on in a decoded save.
+pub fn map_id(save: &Value) -> Option<u64> {
+    save["map"]["_mapId"].as_u64()
+}
+
+/// Player-facing name of a map: its display name, or its editor name if it has none.
+pub fn map_name(game_dir: &Path, map_id: u64) -> Option<String> {
+    let data_dir = game_dir.join("data");
+    let display_name = fs::read_to_string(data_dir.join(format!("Map{:03}.json", map_id)))
+        .ok()
+        .and_then(|content| serde_json::from_str::<Value>(&content).ok())
+        .and_then(|map| map["displayName"].as_str().map(str::to_string))
+        .filter(|name| !name.is_empty());
+    if display_name.is_some() {
+        return display_name;
+    }
+
+    let infos: Value = serde_json::from_str(&fs::read_to_string(data_dir.join("MapInfos.json")).ok()?).ok()?;
+    infos
+        .as_array()?
+        .iter()
+        .find(|info| info["id"].as_u64() == Some(map_id))
+        .and_then(|info| info["name"].as_str().map(str::to_string))
+}
+
+/// What a save slot holds, read from the save itself.
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SlotDetails {
+    /// Play time as the game formats it, e.g. `01:23:45`.
+    pub playtime: Option<String>,
+    pub map_name: Option<String>,
+    pub gold: Option<u64>,
+    pub party: Vec<PartyMember>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PartyMember {
+    pub actor_id: u64,
+    pub name: String,
+    pub level: Option<u64>,
+}
+
+/// The items of a saved array. JsonEx keeps arrays that carry extra properties as
+/// `{"@a": [...]}`.
+fn array(value: &Value) -> &[Value] {
+    value
+        .as_array()
+        .or_else(|| value["@a"].as_array())
+        .map(Vec::as_slice)
+        .unwrap_or_default()
+}
+
+/// Play time, map, gold and party of a decoded save.
+pub fn slot_details(game_dir: &Path, save: &Value) -> SlotDetails {
+    // Both engines count play time in frames at 60 per second
+    let playtime = save["system"]["_framesOnSave"].as_u64().map(|frames| {
+        let seconds = frames / 60;
+        format!("{:02}:{:02}:{:02}", seconds / 3600, seconds / 60 % 60, seconds % 60)
+    });
+    let actors = array(&save["actors"]["_data"]);
+    let party = array(&save["party"]["_actors"])
+        .iter()
+        .filter_map(Value::as_u64)
+        .map(|actor_id| {
+            let actor = actors.get(actor_id as usize).unwrap_or(&Value::Null);
+            PartyMember {
+                actor_id,
+                name: actor["_name"].as_str().unwrap_or_default().to_string(),
+                level: actor["_level"].as_u64(),
+            }
+        })
+        .collect();
+    SlotDetails {
+        playtime,
+        map_name: map_id(save).and_then(|map_id| map_name(game_dir, map_id)),
+        gold: save["party"]["_gold"].as_u64(),
+        party,
+    }
+}
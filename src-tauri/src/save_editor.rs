@@ -0,0 +1,67 @@
+//! Save editor window: gold, items, switches and variables of a save slot, with names from
+//! the game's data files. Only available with `"saveEditor": true` in `launcher.json`.
+//!
+//! The slot is decoded to JSON for the window and encoded back the way the game writes it.
+//! The previous version of the file is copied to `saves/backups/editor/` before every write.
+
+use std::fs;
+
+use serde_json::Value;
+use tauri::{AppHandle, Manager, Runtime};
+
+use crate::config::LauncherConfig;
+use crate::save_data::{self, SaveFormat, SlotInfo};
+use crate::saves;
+
+pub const SAVE_EDITOR_LABEL: &str = "save-editor";
+
+fn ensure_enabled<R: Runtime>(app: &AppHandle<R>) -> Result<(), String> {
+    if app.state::<LauncherConfig>().save_editor {
+        Ok(())
+    } else {
+        Err("The save editor is disabled for this game".to_string())
+    }
+}
+
+fn format_of(name: &str) -> Result<SaveFormat, String> {
+    SaveFormat::from_name(name).ok_or_else(|| format!("Not a save file: {}", name))
+}
+
+/// Slots with a save in them, as listed in the global save file.
+#[tauri::command]
+pub fn list_save_slots<R: Runtime>(app: AppHandle<R>) -> Result<Vec<SlotInfo>, String> {
+    ensure_enabled(&app)?;
+    let save_dir = saves::save_dir(&app).map_err(|e| e.to_string())?;
+    Ok(save_data::slots(&save_dir)
+        .into_iter()
+        .filter(|slot| save_dir.join(&slot.file_name).is_file())
+        .collect())
+}
+
+/// A save file decoded to its JSON.
+#[tauri::command]
+pub fn decode_save<R: Runtime>(app: AppHandle<R>, name: String) -> Result<Value, String> {
+    ensure_enabled(&app)?;
+    format_of(&name)?;
+    save_data::read(&saves::save_path(&app, &name)?)
+}
+
+/// Encode `save` and write it over the save file, after backing up the current one.
+#[tauri::command]
+pub fn write_decoded_save<R: Runtime>(app: AppHandle<R>, name: String, save: Value) -> Result<(), String> {
+    ensure_enabled(&app)?;
+    let data = save_data::encode(format_of(&name)?, &save)?;
+
+    let path = saves::save_path(&app, &name)?;
+    if path.is_file() {
+        let backup_dir = saves::save_dir(&app)
+            .map_err(|e| e.to_string())?
+            .join("backups")
+            .join("editor");
+        fs::create_dir_all(&backup_dir).map_err(|e| e.to_string())?;
+        let stamp = chrono::Local::now().format("%Y%m%d-%H%M%S");
+        fs::copy(&path, backup_dir.join(format!("{}.{}", name, stamp))).map_err(|e| e.to_string())?;
+    }
+    log::info!("Save editor: writing {}", name);
+    saves::replace_save(&app, name, data)
+}
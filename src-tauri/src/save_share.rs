@@ -0,0 +1,158 @@
+//! Sharing saves as text, so players can pass around "start at chapter 3" states.
+//!
+//! A share code is a save with its name and the game's ID, deflated and Base64-encoded
+//! behind a `RMS1-` prefix, short enough to paste in a chat. With `saveShare.pasteUrl` in
+//! `launcher.json`, the code can also be uploaded to a paste service, whose answer is the
+//! link to share. Importing takes a code, a link with the code in it, or a link the code can
+//! be downloaded from.
+
+use std::io::{Read, Write};
+
+use base64::engine::general_purpose::URL_SAFE_NO_PAD as BASE64;
+use base64::Engine;
+use flate2::read::DeflateDecoder;
+use flate2::write::DeflateEncoder;
+use flate2::Compression;
+use serde::{Deserialize, Serialize};
+use tauri::{AppHandle, Runtime, State, Webview};
+
+use crate::config::LauncherConfig;
+use crate::game::GameInfo;
+use crate::sandbox::{self, Permission};
+use crate::{net, saves};
+
+const PREFIX: &str = "RMS1-";
+/// Largest save a code may expand to, so a crafted code can't exhaust memory.
+const MAX_SAVE_SIZE: u64 = 32 * 1024 * 1024;
+
+#[derive(Serialize, Deserialize)]
+struct SharedSave {
+    game: String,
+    name: String,
+    data: String,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ShareCode {
+    pub code: String,
+    /// Link to the uploaded code, when it was uploaded.
+    pub url: Option<String>,
+}
+
+fn encode(save: &SharedSave) -> Result<String, String> {
+    let json = serde_json::to_vec(save).map_err(|e| e.to_string())?;
+    let mut encoder = DeflateEncoder::new(Vec::new(), Compression::best());
+    encoder.write_all(&json).map_err(|e| e.to_string())?;
+    let deflated = encoder.finish().map_err(|e| e.to_string())?;
+    Ok(format!("{}{}", PREFIX, BASE64.encode(deflated)))
+}
+
+fn decode(code: &str) -> Result<SharedSave, String> {
+    let invalid = || "This is not a save share code".to_string();
+    let encoded = code.trim().strip_prefix(PREFIX).ok_or_else(invalid)?;
+    let deflated = BASE64.decode(encoded).map_err(|_| invalid())?;
+    let mut json = Vec::new();
+    DeflateDecoder::new(&deflated[..])
+        .take(MAX_SAVE_SIZE + 1)
+        .read_to_end(&mut json)
+        .map_err(|_| invalid())?;
+    if json.len() as u64 > MAX_SAVE_SIZE {
+        return Err("The shared save is too large".to_string());
+    }
+    serde_json::from_slice(&json).map_err(|_| invalid())
+}
+
+/// The code in `input`, when it is the code itself or a link with the code in it.
+fn find_code(input: &str) -> Option<String> {
+    let input = input.trim();
+    let start = input.find(PREFIX)?;
+    let code = &input[start..];
+    let end = code
+        .find(|c: char| !(c.is_ascii_alphanumeric() || matches!(c, '-' | '_')))
+        .unwrap_or(code.len());
+    Some(code[..end].to_string())
+}
+
+/// Download the code from the link in `input`.
+fn download_code(input: &str) -> Result<String, String> {
+    let input = input.trim();
+    if !(input.starts_with("https://") || input.starts_with("http://")) {
+        return Err("This is not a save share code".to_string());
+    }
+    let body = net::get(input)?
+        .call()
+        .map_err(|e| e.to_string())?
+        .into_string()
+        .map_err(|e| e.to_string())?;
+    if !body.trim().starts_with(PREFIX) {
+        return Err("This is not a save share code".to_string());
+    }
+    Ok(body.trim().to_string())
+}
+
+/// Turn the save `name` into a share code, uploaded to the paste service when `upload` is set.
+#[tauri::command]
+pub async fn export_save_code<R: Runtime>(
+    app: AppHandle<R>,
+    game: State<'_, GameInfo>,
+    config: State<'_, LauncherConfig>,
+    name: String,
+    upload: bool,
+) -> Result<ShareCode, String> {
+    let data = saves::read_save(app, name.clone())?;
+    let code = encode(&SharedSave {
+        game: game.id.clone(),
+        name,
+        data,
+    })?;
+    if !upload {
+        return Ok(ShareCode { code, url: None });
+    }
+
+    let paste_url = config
+        .save_share
+        .as_ref()
+        .map(|share| share.paste_url.clone())
+        .filter(|url| !url.is_empty())
+        .ok_or_else(|| "This game has no paste service configured".to_string())?;
+    let url = net::request("POST", &paste_url)?
+        .set("Content-Type", "text/plain")
+        .send_string(&code)
+        .map_err(|e| e.to_string())?
+        .into_string()
+        .map_err(|e| e.to_string())?
+        .trim()
+        .to_string();
+    log::info!("Uploaded a share code ({} bytes)", code.len());
+    Ok(ShareCode { code, url: Some(url) })
+}
+
+/// Import a shared save from a code or link, into `name` or the slot it was shared from, and
+/// tell the running game about it. Downloading the code from a link needs the `network`
+/// permission when the game asks. Returns the name it was saved as.
+#[tauri::command]
+pub async fn import_save_code<R: Runtime>(
+    app: AppHandle<R>,
+    webview: Webview<R>,
+    game: State<'_, GameInfo>,
+    code: String,
+    name: Option<String>,
+) -> Result<String, String> {
+    let code = match find_code(&code) {
+        Some(code) => code,
+        // Fetching a link reaches whatever it points at, the local network included
+        None => {
+            sandbox::require(&webview, Permission::Network)?;
+            download_code(&code)?
+        }
+    };
+    let shared = decode(&code)?;
+    if shared.game != game.id {
+        return Err("This save was shared from another game".to_string());
+    }
+    let name = name.unwrap_or(shared.name);
+    saves::replace_save(&app, name.clone(), shared.data)?;
+    log::info!("Imported a shared save into {}", name);
+    Ok(name)
+}
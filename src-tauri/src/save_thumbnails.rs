@@ -0,0 +1,57 @@
+//! Thumbnails of the game screen taken when a slot is saved, kept as
+//! `saves/thumbs/<slot>.png` (e.g. `file3.png` for `file3.rpgsave`).
+//!
+//! The injected storage shim asks the screenshot script for a thumbnail after every slot
+//! write. The save manager shows them, and games can show them on their load screens
+//! through `launcher.getSaveThumbnail(savefileId)`.
+
+use std::fs;
+use std::path::PathBuf;
+
+use tauri::ipc::{InvokeBody, Request, Response};
+use tauri::{AppHandle, Runtime};
+
+use crate::saves;
+
+/// Path of the thumbnail for a save file or slot name (`file3.rpgsave` or `file3`).
+pub fn thumbnail_path<R: Runtime>(app: &AppHandle<R>, name: &str) -> Result<PathBuf, String> {
+    let slot = name.split('.').next().unwrap_or_default();
+    if slot.is_empty()
+        || !slot
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || matches!(c, '_' | '-'))
+    {
+        return Err(format!("Invalid save name: {}", name));
+    }
+    Ok(saves::save_dir(app)
+        .map_err(|e| e.to_string())?
+        .join("thumbs")
+        .join(format!("{}.png", slot)))
+}
+
+/// Write the PNG sent as the raw request body, for the slot in the `X-Save-Name` header.
+#[tauri::command]
+pub fn save_thumbnail<R: Runtime>(app: AppHandle<R>, request: Request<'_>) -> Result<(), String> {
+    let InvokeBody::Raw(png) = request.body() else {
+        return Err("Expected the PNG data as the request body".to_string());
+    };
+    let name = request
+        .headers()
+        .get("X-Save-Name")
+        .and_then(|value| value.to_str().ok())
+        .ok_or("Missing X-Save-Name header")?;
+
+    let path = thumbnail_path(&app, name)?;
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent).map_err(|e| e.to_string())?;
+    }
+    fs::write(&path, png).map_err(|e| e.to_string())
+}
+
+/// The thumbnail of a save as PNG bytes, or an error if it has none.
+#[tauri::command]
+pub fn get_save_thumbnail<R: Runtime>(app: AppHandle<R>, name: String) -> Result<Response, String> {
+    let path = thumbnail_path(&app, &name)?;
+    let png = fs::read(&path).map_err(|_| format!("No thumbnail for {}", name))?;
+    Ok(Response::new(png))
+}
@@ -0,0 +1,100 @@
+//! Telling saves from older builds of the game apart.
+//!
+//! The injected `save_version.js` stamps the game version into each slot's info in the
+//! global save file, and asks `check_save_version` before a slot is loaded. When the save
+//! comes from a different major or minor version, every save is copied to
+//! `saves/backups/<old version>/` first (unless `backupSavesOnUpdate` is off in launcher.json)
+//! and the player is warned that the old save might not load correctly.
+
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use tauri::{AppHandle, Manager, Runtime, State};
+use tauri_plugin_dialog::{DialogExt, MessageDialogKind};
+
+use crate::config::LauncherConfig;
+use crate::game::GameInfo;
+use crate::saves;
+
+/// The first two numbers of a version like `v1.2.3`.
+fn major_minor(version: &str) -> Option<(u64, u64)> {
+    let mut parts = version.trim().trim_start_matches(['v', 'V']).split('.');
+    let major = parts.next()?.parse().ok()?;
+    let minor = parts.next().map_or(Some(0), |minor| minor.parse().ok())?;
+    Some((major, minor))
+}
+
+/// Whether a save from `saved` may not load correctly in `current`. Patch releases don't
+/// count; versions that aren't numbers (like System.json's `versionId`) count when they differ.
+pub fn is_significant(saved: &str, current: &str) -> bool {
+    match (major_minor(saved), major_minor(current)) {
+        (Some(saved), Some(current)) => saved != current,
+        _ => saved.trim() != current.trim(),
+    }
+}
+
+/// Copy every save to `backups/<version>/`, once per version. Returns the backup folder.
+fn backup_saves(save_dir: &Path, version: &str) -> Result<PathBuf, String> {
+    let folder: String = version
+        .chars()
+        .map(|c| if c.is_ascii_alphanumeric() || c == '.' { c } else { '-' })
+        .collect();
+    let backup_dir = save_dir.join("backups").join(folder.trim_matches('.'));
+    if backup_dir.is_dir() {
+        return Ok(backup_dir);
+    }
+    fs::create_dir_all(&backup_dir).map_err(|e| e.to_string())?;
+    for entry in fs::read_dir(save_dir).map_err(|e| e.to_string())?.flatten() {
+        let name = entry.file_name().to_string_lossy().to_string();
+        if name.starts_with('.') || !entry.path().is_file() {
+            continue;
+        }
+        fs::copy(entry.path(), backup_dir.join(&name)).map_err(|e| format!("{}: {}", name, e))?;
+    }
+    Ok(backup_dir)
+}
+
+#[tauri::command]
+pub fn get_game_version(game: State<'_, GameInfo>) -> Option<String> {
+    game.version.clone()
+}
+
+/// Called before a slot is loaded. Returns whether the save's version differs significantly.
+#[tauri::command]
+pub async fn check_save_version<R: Runtime>(app: AppHandle<R>, saved_version: Option<String>) -> Result<bool, String> {
+    let game = app.state::<GameInfo>();
+    // Saves made before versions were tracked have nothing to compare
+    let (Some(saved), Some(current)) = (saved_version, game.version.clone()) else {
+        return Ok(false);
+    };
+    if !is_significant(&saved, &current) {
+        return Ok(false);
+    }
+    log::warn!("Loading a save from version {} in version {}", saved, current);
+
+    let mut message = format!(
+        "This save was made with version {} of the game, and this is version {}. \
+         Some things may not work as expected after loading it.",
+        saved, current
+    );
+    if app.state::<LauncherConfig>().backup_saves_on_update {
+        let save_dir = saves::save_dir(&app).map_err(|e| e.to_string())?;
+        match backup_saves(&save_dir, &saved) {
+            Ok(backup_dir) => {
+                log::info!("Backed up the saves to {:?}", backup_dir);
+                message.push_str(&format!(
+                    "\n\nYour saves from before the update were backed up to:\n{}",
+                    backup_dir.display()
+                ));
+            }
+            Err(e) => log::error!("Failed to back up the saves: {}", e),
+        }
+    }
+
+    app.dialog()
+        .message(message)
+        .title("Save from another version")
+        .kind(MessageDialogKind::Warning)
+        .show(|_| {});
+    Ok(true)
+}
@@ -0,0 +1,295 @@
+//! Location of the game's save data, and the lock that keeps two launchers from writing
+//! to it at the same time.
+//!
+//! Each game gets its own folder, `<app data>/saves/<game id>/`, so switching games never
+//! mixes their save slots or backups. The webview's data directory (where MV/MZ keep their web storage saves) lives inside the
+//! save directory, so everything that makes up a player's progress sits in one place.
+//!
+//! The save files themselves are kept next to it by the injected storage shim, named and
+//! encoded the way NW.js writes them (`file1.rpgsave` for MV, `file1.rmmzsave` for MZ),
+//! unless another [save backend](crate::save_backends) is configured.
+
+use std::fs::{self, File, TryLockError};
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+
+use serde::Serialize;
+use tauri::{AppHandle, Emitter, Manager, Runtime, State};
+use tauri_plugin_dialog::{DialogExt, MessageDialogButtons, MessageDialogKind};
+use tauri_plugin_opener::OpenerExt;
+
+use crate::game::GameInfo;
+use crate::save_data::{self, SaveFormat, SlotDetails};
+use crate::{save_backends, save_backups, save_thumbnails};
+
+const LOCK_FILE_NAME: &str = ".launcher.lock";
+
+pub fn save_dir<R: Runtime>(app: &AppHandle<R>) -> tauri::Result<PathBuf> {
+    let game = app.state::<GameInfo>();
+    Ok(app.path().app_data_dir()?.join("saves").join(&game.id))
+}
+
+/// Move saves from before they were kept per game, when `<app data>/saves/` itself was the
+/// save folder, into the folder of the game being launched.
+pub fn migrate_legacy_dir<R: Runtime>(app: &AppHandle<R>) -> tauri::Result<()> {
+    let saves = app.path().app_data_dir()?.join("saves");
+    // Only the old layout has the lock file at the top
+    if !saves.join(LOCK_FILE_NAME).is_file() {
+        return Ok(());
+    }
+    let target = save_dir(app)?;
+    let staging = saves.with_file_name("saves.legacy");
+    let moved = fs::rename(&saves, &staging)
+        .and_then(|_| fs::create_dir_all(&saves))
+        .and_then(|_| fs::rename(&staging, &target));
+    match moved {
+        Ok(()) => log::info!("Moved saves to {:?}", target),
+        Err(e) => log::error!("Failed to move saves from {:?} to {:?}: {}", saves, target, e),
+    }
+    Ok(())
+}
+
+pub fn open_save_folder<R: Runtime>(app: &AppHandle<R>) -> Result<(), String> {
+    let dir = save_dir(app).map_err(|e| e.to_string())?;
+    fs::create_dir_all(&dir).map_err(|e| e.to_string())?;
+    app.opener()
+        .open_path(dir.to_string_lossy(), None::<&str>)
+        .map_err(|e| e.to_string())
+}
+
+/// Data directory handed to the game's webview.
+pub fn webview_data_dir(save_dir: &Path) -> PathBuf {
+    save_dir.join("webview")
+}
+
+/// Exclusive advisory lock on a save directory, held until released or dropped.
+pub struct SaveDirLock {
+    file: Mutex<Option<File>>,
+}
+
+impl SaveDirLock {
+    /// Try to lock the directory. Returns `Ok(None)` if another process already holds it.
+    pub fn acquire(dir: &Path) -> std::io::Result<Option<Self>> {
+        fs::create_dir_all(dir)?;
+        let file = File::options()
+            .create(true)
+            .truncate(false)
+            .write(true)
+            .open(dir.join(LOCK_FILE_NAME))?;
+
+        match file.try_lock() {
+            Ok(()) => Ok(Some(Self {
+                file: Mutex::new(Some(file)),
+            })),
+            Err(TryLockError::WouldBlock) => Ok(None),
+            Err(TryLockError::Error(e)) => Err(e),
+        }
+    }
+
+    /// Give up the lock early, e.g. before handing the save folder to a new launcher process.
+    pub fn release(&self) {
+        self.file.lock().unwrap().take();
+    }
+}
+
+/// Warn that the save directory is in use. Returns `true` if the user chose to continue anyway.
+pub fn warn_locked<R: Runtime>(app: &AppHandle<R>, dir: &Path) -> bool {
+    let quit = app
+        .dialog()
+        .message(format!(
+            "The save folder is already in use by another running copy of this game:\n\n{}\n\n\
+             Playing both at once can corrupt your save files. Close the other copy first.",
+            dir.display()
+        ))
+        .title("Game already running")
+        .kind(MessageDialogKind::Warning)
+        .buttons(MessageDialogButtons::OkCancelCustom(
+            "Quit".to_string(),
+            "Continue anyway".to_string(),
+        ))
+        .blocking_show();
+    !quit
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SaveFile {
+    pub name: String,
+    pub size: u64,
+    /// When the save was first written, in milliseconds since the Unix epoch.
+    pub created: Option<u64>,
+    /// Last modification time, in milliseconds since the Unix epoch.
+    pub modified: Option<u64>,
+}
+
+/// A save with what the launcher can tell about it without loading it in the game.
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SaveDetails {
+    pub name: String,
+    pub size: u64,
+    /// When the save was first written, in milliseconds since the Unix epoch.
+    pub created: Option<u64>,
+    /// Last modification time, in milliseconds since the Unix epoch.
+    pub modified: Option<u64>,
+    /// Play time, map and party, for MV and MZ save slots (`file1.rpgsave`, ...).
+    pub slot: Option<SlotDetails>,
+}
+
+/// A save changed outside the game (imported or deleted from the save manager).
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct SaveChanged {
+    name: String,
+    /// New content, or `None` if the save was deleted.
+    data: Option<String>,
+}
+
+/// Path of a save file in the save backend's folder, refusing names that could escape it or
+/// hit the lock file.
+pub fn save_path<R: Runtime>(app: &AppHandle<R>, name: &str) -> Result<PathBuf, String> {
+    let valid = !name.is_empty()
+        && !name.starts_with('.')
+        && name
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || matches!(c, '.' | '_' | '-'));
+    if !valid {
+        return Err(format!("Invalid save name: {}", name));
+    }
+    Ok(save_backends::backend(app).dir().join(name))
+}
+
+/// Check a save name the way [`save_path`] does.
+fn check_name<R: Runtime>(app: &AppHandle<R>, name: &str) -> Result<(), String> {
+    save_path(app, name).map(|_| ())
+}
+
+#[tauri::command]
+pub fn list_saves<R: Runtime>(app: AppHandle<R>) -> Result<Vec<SaveFile>, String> {
+    save_backends::backend(&app).list()
+}
+
+/// Like `list_saves`, with the contents of the save slots, for a save manager that shows more
+/// than file names.
+#[tauri::command]
+pub fn list_saves_detailed<R: Runtime>(
+    app: AppHandle<R>,
+    game: State<'_, GameInfo>,
+) -> Result<Vec<SaveDetails>, String> {
+    let backend = save_backends::backend(&app);
+    let saves = backend.list()?;
+    Ok(saves
+        .into_iter()
+        .map(|save| {
+            let slot = SaveFormat::from_name(&save.name)
+                .filter(|_| save.name.starts_with("file"))
+                .and_then(|format| {
+                    let content = backend.read(&save.name).ok()?;
+                    match save_data::decode(format, &content) {
+                        Ok(decoded) => Some(save_data::slot_details(&game.dir, &decoded)),
+                        Err(e) => {
+                            log::warn!("Failed to decode {}: {}", save.name, e);
+                            None
+                        }
+                    }
+                });
+            SaveDetails {
+                name: save.name,
+                size: save.size,
+                created: save.created,
+                modified: save.modified,
+                slot,
+            }
+        })
+        .collect())
+}
+
+#[tauri::command]
+pub fn read_save<R: Runtime>(app: AppHandle<R>, name: String) -> Result<String, String> {
+    check_name(&app, &name)?;
+    save_backends::backend(&app).read(&name)
+}
+
+#[tauri::command]
+pub fn write_save<R: Runtime>(app: AppHandle<R>, name: String, data: String) -> Result<(), String> {
+    check_name(&app, &name)?;
+    save_backends::backend(&app).write(&name, &data)?;
+    save_backups::backup(&app, &name);
+
+    if let Some(discord) = app.try_state::<crate::discord::Discord>() {
+        discord.refresh_from_saves();
+    }
+    Ok(())
+}
+
+#[tauri::command]
+pub fn delete_save<R: Runtime>(app: AppHandle<R>, name: String) -> Result<(), String> {
+    check_name(&app, &name)?;
+    // A deleted save leaves no thumbnail behind
+    if let Ok(thumbnail) = save_thumbnails::thumbnail_path(&app, &name) {
+        let _ = fs::remove_file(thumbnail);
+    }
+    save_backends::backend(&app).delete(&name)
+}
+
+/// Delete a save from the save manager and tell the running game about it.
+#[tauri::command]
+pub fn remove_save<R: Runtime>(app: AppHandle<R>, name: String) -> Result<(), String> {
+    delete_save(app.clone(), name.clone())?;
+    notify_changed(&app, name, None)
+}
+
+/// Copy a save to a location picked by the player. Returns `false` if they cancelled.
+#[tauri::command]
+pub async fn export_save<R: Runtime>(app: AppHandle<R>, name: String) -> Result<bool, String> {
+    check_name(&app, &name)?;
+    let data = save_backends::backend(&app).read(&name)?;
+    let Some(destination) = app
+        .dialog()
+        .file()
+        .set_title("Export save")
+        .set_file_name(&name)
+        .blocking_save_file()
+    else {
+        return Ok(false);
+    };
+    let destination = destination.into_path().map_err(|e| e.to_string())?;
+    fs::write(destination, data).map_err(|e| e.to_string())?;
+    Ok(true)
+}
+
+/// Replace a save with a file picked by the player. Returns `false` if they cancelled.
+#[tauri::command]
+pub async fn import_save<R: Runtime>(app: AppHandle<R>, name: String) -> Result<bool, String> {
+    check_name(&app, &name)?;
+    let Some(source) = app
+        .dialog()
+        .file()
+        .set_title("Import save")
+        .blocking_pick_file()
+    else {
+        return Ok(false);
+    };
+    let source = source.into_path().map_err(|e| e.to_string())?;
+    let data = fs::read_to_string(source).map_err(|e| e.to_string())?;
+    replace_save(&app, name, data)?;
+    Ok(true)
+}
+
+/// Write a save from outside the game and tell the running game about it.
+pub fn replace_save<R: Runtime>(app: &AppHandle<R>, name: String, data: String) -> Result<(), String> {
+    write_save(app.clone(), name.clone(), data.clone())?;
+    notify_changed(app, name, Some(data))
+}
+
+/// Tell the running game that a save was changed from outside it, with its new content, or
+/// `None` if it was deleted.
+pub fn notify_changed<R: Runtime>(app: &AppHandle<R>, name: String, data: Option<String>) -> Result<(), String> {
+    app.emit("save-changed", SaveChanged { name, data })
+        .map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+pub fn show_save_folder<R: Runtime>(app: AppHandle<R>) -> Result<(), String> {
+    open_save_folder(&app)
+}
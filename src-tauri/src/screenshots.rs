@@ -0,0 +1,101 @@
+//! Screenshots of the game, saved as timestamped PNGs.
+//!
+//! The frame itself is rendered by the injected screenshot script (the webview has no
+//! native capture API), which sends the PNG bytes back through `save_screenshot`.
+
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use serde::Serialize;
+use tauri::ipc::{InvokeBody, Request};
+use tauri::{AppHandle, Emitter, Manager, Runtime, State, WebviewWindow};
+
+use crate::game::GameInfo;
+use crate::settings::Settings;
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct ScreenshotSaved {
+    path: PathBuf,
+    file_name: String,
+}
+
+/// Folder screenshots are written to: the player's choice, or `Pictures/<game title>`.
+pub fn screenshot_dir<R: Runtime>(
+    app: &AppHandle<R>,
+    game: &GameInfo,
+    settings: &Settings,
+) -> tauri::Result<PathBuf> {
+    if let Some(dir) = settings.get().screenshot_dir {
+        return Ok(dir);
+    }
+
+    let pictures = app
+        .path()
+        .picture_dir()
+        .or_else(|_| app.path().app_data_dir())?;
+    Ok(pictures.join(sanitize_file_name(&game.title)))
+}
+
+pub fn sanitize_file_name(name: &str) -> String {
+    name.chars()
+        .map(|c| if r#"<>:"/\|?*"#.contains(c) || c.is_control() { '_' } else { c })
+        .collect()
+}
+
+/// A `<prefix>_<date>_<time>.<extension>` path in `dir` that doesn't exist yet.
+pub fn timestamped_path(dir: &Path, prefix: &str, extension: &str) -> PathBuf {
+    let timestamp = chrono::Local::now().format("%Y-%m-%d_%H-%M-%S");
+    let mut path = dir.join(format!("{}_{}.{}", prefix, timestamp, extension));
+    let mut counter = 2;
+    while path.exists() {
+        path = dir.join(format!("{}_{}_{}.{}", prefix, timestamp, counter, extension));
+        counter += 1;
+    }
+    path
+}
+
+/// Ask the game page to render the current frame; the result arrives in `save_screenshot`.
+#[tauri::command]
+pub fn capture_screenshot<R: Runtime>(window: WebviewWindow<R>) -> Result<(), String> {
+    window
+        .eval("window.__RPGMAKER_LAUNCHER__ && window.__RPGMAKER_LAUNCHER__.captureScreenshot()")
+        .map_err(|e| e.to_string())
+}
+
+/// Write the PNG sent as the raw request body. Returns the path of the new file.
+#[tauri::command]
+pub fn save_screenshot<R: Runtime>(
+    app: AppHandle<R>,
+    game: State<'_, GameInfo>,
+    settings: State<'_, Settings>,
+    request: Request<'_>,
+) -> Result<PathBuf, String> {
+    let InvokeBody::Raw(png) = request.body() else {
+        return Err("Expected the PNG data as the request body".to_string());
+    };
+    // Any game may call this, so only let it write images
+    if !png.starts_with(b"\x89PNG\r\n\x1a\n") {
+        return Err("The screenshot is not a PNG image".to_string());
+    }
+
+    let dir = screenshot_dir(&app, &game, &settings).map_err(|e| e.to_string())?;
+    fs::create_dir_all(&dir).map_err(|e| e.to_string())?;
+
+    let path = timestamped_path(&dir, "Screenshot", "png");
+    fs::write(&path, png).map_err(|e| e.to_string())?;
+    log::info!("Screenshot saved to {:?}", path);
+
+    let file_name = path
+        .file_name()
+        .map(|n| n.to_string_lossy().to_string())
+        .unwrap_or_default();
+    let _ = app.emit(
+        "screenshot-saved",
+        ScreenshotSaved {
+            path: path.clone(),
+            file_name,
+        },
+    );
+    Ok(path)
+}
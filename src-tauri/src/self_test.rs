@@ -0,0 +1,278 @@
+//! Boot self-test for CI, run with `--self-test=<folder>`.
+//!
+//! The launcher starts the game as usual, waits for the boot probe to report the first scene,
+//! lets it settle, and captures a frame. The frame fails the test when it is a single flat
+//! color (the "boots but renders black" case), or when its perceptual hash is too far from
+//! `reference.png` in the folder. The frame is written as `first-frame.png` and the outcome
+//! as `self-test.json`, then the launcher exits with 0 on success and 1 on failure. Without
+//! a reference, the first frame becomes it.
+
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::time::Duration;
+
+use serde::Serialize;
+use tauri::image::Image;
+use tauri::ipc::{InvokeBody, Request};
+use tauri::{AppHandle, Manager, Runtime, State};
+
+use crate::display::main_window;
+use external_localhost_plugin::Diagnostics;
+
+const REFERENCE_FILE_NAME: &str = "reference.png";
+const FRAME_FILE_NAME: &str = "first-frame.png";
+const REPORT_FILE_NAME: &str = "self-test.json";
+/// How long the game has to reach its first scene.
+const BOOT_TIMEOUT: Duration = Duration::from_secs(60);
+/// Time after the first scene for fades and title screens to finish drawing.
+const SETTLE_TIME: Duration = Duration::from_secs(3);
+/// How long the page has to send the frame back.
+const CAPTURE_TIMEOUT: Duration = Duration::from_secs(15);
+/// Differing bits, out of 64, at which a frame no longer matches the reference.
+const MAX_DISTANCE: u32 = 10;
+/// Brightness spread, out of 255, below which a frame counts as a flat color.
+const MIN_CONTRAST: u8 = 8;
+
+/// Renders the frame and sends it to `self_test_frame`.
+const CAPTURE_SCRIPT: &str = r#"(function () {
+  var launcher = window.__RPGMAKER_LAUNCHER__;
+  var canvas = launcher && launcher.frameCanvas ? launcher.frameCanvas() : document.querySelector("canvas");
+  if (!canvas) {
+    launcher.invoke("self_test_frame", new Uint8Array(0));
+    return;
+  }
+  canvas.toBlob(function (blob) {
+    (blob ? blob.arrayBuffer() : Promise.resolve(new ArrayBuffer(0))).then(function (buffer) {
+      launcher.invoke("self_test_frame", new Uint8Array(buffer));
+    });
+  }, "image/png");
+})()"#;
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct SelfTestReport {
+    passed: bool,
+    /// What went wrong, if anything.
+    failure: Option<String>,
+    /// Perceptual hash of the frame, as hex.
+    hash: Option<String>,
+    /// Bits differing from the reference, when there was one.
+    distance: Option<u32>,
+    max_distance: u32,
+}
+
+/// The folder the artifacts go to, managed as app state during a self-test.
+pub struct SelfTest {
+    dir: PathBuf,
+    finished: AtomicBool,
+}
+
+impl SelfTest {
+    pub fn new(dir: PathBuf) -> Self {
+        Self {
+            dir,
+            finished: AtomicBool::new(false),
+        }
+    }
+
+    /// Write the report and exit with its outcome. Only the first outcome counts.
+    fn finish<R: Runtime>(&self, app: &AppHandle<R>, report: SelfTestReport) {
+        if self.finished.swap(true, Ordering::SeqCst) {
+            return;
+        }
+        match &report.failure {
+            None => log::info!("Self-test passed (distance {:?})", report.distance),
+            Some(failure) => log::error!("Self-test failed: {}", failure),
+        }
+        let written = fs::create_dir_all(&self.dir).and_then(|_| {
+            fs::write(
+                self.dir.join(REPORT_FILE_NAME),
+                serde_json::to_string_pretty(&report).unwrap_or_default(),
+            )
+        });
+        if let Err(e) = written {
+            log::error!("Failed to write the self-test report to {:?}: {}", self.dir, e);
+        }
+        app.exit(if report.passed { 0 } else { 1 });
+    }
+
+    fn fail<R: Runtime>(&self, app: &AppHandle<R>, failure: String) {
+        self.finish(
+            app,
+            SelfTestReport {
+                passed: false,
+                failure: Some(failure),
+                hash: None,
+                distance: None,
+                max_distance: MAX_DISTANCE,
+            },
+        );
+    }
+}
+
+/// Wait for the game to boot, then ask the page for a frame. Runs on its own thread.
+pub fn start<R: Runtime>(app: &AppHandle<R>) {
+    let app = app.clone();
+    std::thread::spawn(move || {
+        let diagnostics = app.state::<Diagnostics>().inner().clone();
+        let self_test = app.state::<SelfTest>();
+        if !wait_until(BOOT_TIMEOUT, || diagnostics.game_started()) {
+            self_test.fail(
+                &app,
+                format!("The game did not reach its first scene within {:?}", BOOT_TIMEOUT),
+            );
+            return;
+        }
+        std::thread::sleep(SETTLE_TIME);
+
+        if let Err(e) = main_window(&app).and_then(|window| window.eval(CAPTURE_SCRIPT).map_err(|e| e.to_string())) {
+            self_test.fail(&app, format!("Failed to capture a frame: {}", e));
+            return;
+        }
+        // Does nothing if `self_test_frame` already finished the test
+        std::thread::sleep(CAPTURE_TIMEOUT);
+        self_test.fail(&app, "The page did not send a frame back".to_string());
+    });
+}
+
+fn wait_until(timeout: Duration, condition: impl Fn() -> bool) -> bool {
+    let step = Duration::from_millis(250);
+    let mut waited = Duration::ZERO;
+    while !condition() {
+        if waited >= timeout {
+            return false;
+        }
+        std::thread::sleep(step);
+        waited += step;
+    }
+    true
+}
+
+/// Average brightness of each cell of a `columns` x `rows` grid over the image.
+fn brightness_grid(image: &Image<'_>, columns: u32, rows: u32) -> Vec<u8> {
+    let (width, height) = (image.width().max(1), image.height().max(1));
+    let rgba = image.rgba();
+    let mut grid = Vec::with_capacity((columns * rows) as usize);
+    for row in 0..rows {
+        for column in 0..columns {
+            let x0 = column * width / columns;
+            let x1 = ((column + 1) * width / columns).max(x0 + 1);
+            let y0 = row * height / rows;
+            let y1 = ((row + 1) * height / rows).max(y0 + 1);
+            let (mut sum, mut count) = (0u64, 0u64);
+            for y in y0..y1.min(height) {
+                for x in x0..x1.min(width) {
+                    let i = ((y * width + x) * 4) as usize;
+                    let Some(pixel) = rgba.get(i..i + 3) else {
+                        continue;
+                    };
+                    // Rec. 601 luma, in integers
+                    sum += (pixel[0] as u64 * 299 + pixel[1] as u64 * 587 + pixel[2] as u64 * 114) / 1000;
+                    count += 1;
+                }
+            }
+            grid.push((sum / count.max(1)) as u8);
+        }
+    }
+    grid
+}
+
+/// Difference hash: each bit says whether a cell of a 9x8 grid is darker than its right neighbor.
+fn difference_hash(image: &Image<'_>) -> u64 {
+    let grid = brightness_grid(image, 9, 8);
+    let mut hash = 0u64;
+    for row in 0..8 {
+        for column in 0..8 {
+            hash <<= 1;
+            if grid[row * 9 + column] < grid[row * 9 + column + 1] {
+                hash |= 1;
+            }
+        }
+    }
+    hash
+}
+
+fn is_flat(image: &Image<'_>) -> bool {
+    let grid = brightness_grid(image, 32, 32);
+    let (min, max) = grid.iter().fold((u8::MAX, u8::MIN), |(min, max), &value| {
+        (min.min(value), max.max(value))
+    });
+    max.saturating_sub(min) < MIN_CONTRAST
+}
+
+fn check(dir: &Path, png: &[u8]) -> SelfTestReport {
+    let report = |failure: Option<String>, hash: Option<u64>, distance: Option<u32>| SelfTestReport {
+        passed: failure.is_none(),
+        failure,
+        hash: hash.map(|hash| format!("{:016x}", hash)),
+        distance,
+        max_distance: MAX_DISTANCE,
+    };
+    if png.is_empty() {
+        return report(Some("The game has no canvas to capture".to_string()), None, None);
+    }
+    let frame = match Image::from_bytes(png) {
+        Ok(frame) => frame,
+        Err(e) => {
+            return report(
+                Some(format!("The captured frame is not a valid PNG: {}", e)),
+                None,
+                None,
+            )
+        }
+    };
+    if let Err(e) = fs::create_dir_all(dir).and_then(|_| fs::write(dir.join(FRAME_FILE_NAME), png)) {
+        log::warn!("Failed to write the captured frame to {:?}: {}", dir, e);
+    }
+    let hash = difference_hash(&frame);
+    if is_flat(&frame) {
+        return report(
+            Some("The first frame is a single flat color".to_string()),
+            Some(hash),
+            None,
+        );
+    }
+
+    let reference_path = dir.join(REFERENCE_FILE_NAME);
+    if !reference_path.is_file() {
+        log::info!("No reference frame yet, saving this one as {:?}", reference_path);
+        if let Err(e) = fs::write(&reference_path, png) {
+            log::warn!("Failed to save the reference frame: {}", e);
+        }
+        return report(None, Some(hash), None);
+    }
+    let reference = match Image::from_path(&reference_path) {
+        Ok(reference) => reference,
+        Err(e) => {
+            return report(
+                Some(format!("Invalid {}: {}", REFERENCE_FILE_NAME, e)),
+                Some(hash),
+                None,
+            )
+        }
+    };
+    let distance = (hash ^ difference_hash(&reference)).count_ones();
+    let failure = (distance > MAX_DISTANCE).then(|| {
+        format!(
+            "The first frame differs from {} by {} bits (at most {} allowed)",
+            REFERENCE_FILE_NAME, distance, MAX_DISTANCE
+        )
+    });
+    report(failure, Some(hash), Some(distance))
+}
+
+/// The captured frame, as the raw request body. Ends the self-test.
+#[tauri::command]
+pub fn self_test_frame<R: Runtime>(
+    app: AppHandle<R>,
+    self_test: State<'_, SelfTest>,
+    request: Request<'_>,
+) -> Result<(), String> {
+    let InvokeBody::Raw(png) = request.body() else {
+        return Err("Expected the PNG data as the request body".to_string());
+    };
+    let report = check(&self_test.dir, png);
+    self_test.finish(&app, report);
+    Ok(())
+}
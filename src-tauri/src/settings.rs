@@ -0,0 +1,127 @@
+//! Per-game settings changed by the player at runtime, persisted in the app config directory.
+
+use std::fs;
+use std::path::PathBuf;
+use std::sync::Mutex;
+
+use serde::{Deserialize, Serialize};
+use tauri::State;
+
+use crate::config::WindowMode;
+use crate::input::InputBindings;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default, rename_all = "camelCase")]
+pub struct GameSettings {
+    pub zoom: f64,
+    /// Window mode chosen by the player, overriding the one from the launcher config.
+    pub window_mode: Option<WindowMode>,
+    /// Scale the game canvas by whole multiples only, letterboxing the rest.
+    pub integer_scaling: bool,
+    /// Keep interactive resizes at the game's aspect ratio.
+    pub lock_aspect_ratio: bool,
+    /// Where screenshots are saved. Defaults to `Pictures/<game title>`.
+    pub screenshot_dir: Option<PathBuf>,
+    /// Show a toast in the game window when a screenshot is saved.
+    pub screenshot_toast: bool,
+    /// Where recordings are saved. Defaults to `Videos/<game title>`.
+    pub recording_dir: Option<PathBuf>,
+    /// Hide the mouse cursor after this many seconds without movement. `0` never hides it.
+    pub cursor_hide_delay_secs: u32,
+    /// Launcher-level master volume, from 0 to 1, on top of the game's own volume options.
+    pub master_volume: f64,
+    /// Silence the game entirely.
+    pub muted: bool,
+    /// Silence the game while its window is in the background.
+    pub mute_on_blur: bool,
+    /// Id of the audio output device the game plays on, as reported by the webview.
+    /// `None` follows the system default.
+    pub audio_device: Option<String>,
+    /// Freeze the game while its window is in the background.
+    pub pause_on_blur: bool,
+    /// Hide the window when minimized, leaving only the tray icon.
+    pub minimize_to_tray: bool,
+    /// Name of the monitor the player moved the game to.
+    pub monitor: Option<String>,
+    /// DPI scale the game renders at instead of the system's.
+    pub dpi_scale: Option<f64>,
+    /// Keep the game window above other windows.
+    pub always_on_top: bool,
+    /// Keep local play statistics (sessions, play time, crashes, time per map).
+    pub collect_stats: bool,
+    /// Key and gamepad bindings changed by the player, on top of the game's.
+    pub input_bindings: InputBindings,
+}
+
+impl Default for GameSettings {
+    fn default() -> Self {
+        Self {
+            zoom: 1.0,
+            window_mode: None,
+            integer_scaling: false,
+            lock_aspect_ratio: false,
+            screenshot_dir: None,
+            screenshot_toast: true,
+            recording_dir: None,
+            cursor_hide_delay_secs: 0,
+            master_volume: 1.0,
+            muted: false,
+            mute_on_blur: false,
+            audio_device: None,
+            pause_on_blur: false,
+            minimize_to_tray: false,
+            monitor: None,
+            dpi_scale: None,
+            always_on_top: false,
+            collect_stats: false,
+            input_bindings: InputBindings::default(),
+        }
+    }
+}
+
+/// The current game's settings, managed as app state.
+pub struct Settings {
+    path: PathBuf,
+    current: Mutex<GameSettings>,
+}
+
+impl Settings {
+    pub fn load(path: PathBuf) -> Self {
+        let current = fs::read_to_string(&path)
+            .ok()
+            .and_then(|content| match serde_json::from_str(&content) {
+                Ok(settings) => Some(settings),
+                Err(e) => {
+                    log::warn!("Invalid settings file {:?}, using defaults: {}", path, e);
+                    None
+                }
+            })
+            .unwrap_or_default();
+
+        Self {
+            path,
+            current: Mutex::new(current),
+        }
+    }
+
+    pub fn get(&self) -> GameSettings {
+        self.current.lock().unwrap().clone()
+    }
+
+    /// Change the settings and write them to disk.
+    pub fn update<F: FnOnce(&mut GameSettings)>(&self, f: F) -> Result<(), String> {
+        let mut current = self.current.lock().unwrap();
+        f(&mut current);
+
+        if let Some(parent) = self.path.parent() {
+            fs::create_dir_all(parent).map_err(|e| e.to_string())?;
+        }
+        let content = serde_json::to_string_pretty(&*current).map_err(|e| e.to_string())?;
+        fs::write(&self.path, content).map_err(|e| e.to_string())
+    }
+}
+
+#[tauri::command]
+pub fn get_settings(settings: State<'_, Settings>) -> GameSettings {
+    settings.get()
+}
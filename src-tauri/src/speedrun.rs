@@ -0,0 +1,118 @@
+//! Speedrun support, enabled with `speedrun` in `launcher.json`.
+//!
+//! The injected `speedrun.js` watches the configured milestones (switches, variables, maps)
+//! and reports start, split and reset events. They are forwarded to LiveSplit through its
+//! Server component (TCP) or named pipe, and emitted as `speedrun-event` for the timer overlay
+//! and other integrations.
+
+use std::io::Write;
+use std::net::TcpStream;
+use std::sync::Mutex;
+use std::time::Duration;
+
+use serde::{Deserialize, Serialize};
+use tauri::{AppHandle, Emitter, Runtime, State};
+
+use crate::config::SpeedrunConfig;
+
+/// LiveSplit's named pipe, which accepts the same commands as the Server component.
+#[cfg(windows)]
+const LIVESPLIT_PIPE: &str = r"\\.\pipe\LiveSplit";
+
+const CONNECT_TIMEOUT: Duration = Duration::from_millis(500);
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum SpeedrunEventKind {
+    Start,
+    Split,
+    Reset,
+}
+
+impl SpeedrunEventKind {
+    fn livesplit_command(self) -> &'static str {
+        match self {
+            Self::Start => "starttimer",
+            Self::Split => "split",
+            Self::Reset => "reset",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct SpeedrunEvent {
+    event: SpeedrunEventKind,
+    split: Option<String>,
+}
+
+pub struct Speedrun {
+    livesplit: String,
+    connection: Mutex<Option<Box<dyn Write + Send>>>,
+}
+
+impl Speedrun {
+    pub fn new(config: &SpeedrunConfig) -> Self {
+        Self {
+            livesplit: config.livesplit.trim().to_string(),
+            connection: Mutex::new(None),
+        }
+    }
+
+    fn connect(&self) -> std::io::Result<Box<dyn Write + Send>> {
+        #[cfg(windows)]
+        if self.livesplit == "pipe" {
+            let pipe = std::fs::OpenOptions::new().write(true).open(LIVESPLIT_PIPE)?;
+            return Ok(Box::new(pipe));
+        }
+
+        let address = self
+            .livesplit
+            .parse()
+            .map_err(|e| std::io::Error::new(std::io::ErrorKind::InvalidInput, e))?;
+        let stream = TcpStream::connect_timeout(&address, CONNECT_TIMEOUT)?;
+        stream.set_nodelay(true)?;
+        Ok(Box::new(stream))
+    }
+
+    /// Send a command to LiveSplit, reconnecting if it was restarted since the last one.
+    fn send(&self, command: &str) {
+        if self.livesplit.is_empty() {
+            return;
+        }
+
+        let mut connection = self.connection.lock().unwrap();
+        for _ in 0..2 {
+            if connection.is_none() {
+                match self.connect() {
+                    Ok(c) => *connection = Some(c),
+                    Err(e) => {
+                        log::debug!("LiveSplit is not available at {}: {}", self.livesplit, e);
+                        return;
+                    }
+                }
+            }
+            let written = connection
+                .as_mut()
+                .map(|c| c.write_all(format!("{}\r\n", command).as_bytes()).and_then(|_| c.flush()));
+            match written {
+                Some(Ok(())) => return,
+                _ => *connection = None,
+            }
+        }
+    }
+}
+
+/// Report a run event from the game.
+#[tauri::command]
+pub fn speedrun_event<R: Runtime>(
+    app: AppHandle<R>,
+    speedrun: State<'_, Speedrun>,
+    event: SpeedrunEventKind,
+    split: Option<String>,
+) -> Result<(), String> {
+    log::info!("Speedrun {:?} {}", event, split.as_deref().unwrap_or(""));
+    speedrun.send(event.livesplit_command());
+    app.emit("speedrun-event", SpeedrunEvent { event, split })
+        .map_err(|e| e.to_string())
+}
@@ -0,0 +1,87 @@
+//! Splash screen shown while the localhost server starts and the game loads.
+//!
+//! The main window is created hidden; the splash window is swapped for it once the game
+//! page reports its first paint, so there is no blank window in between.
+
+use std::fs;
+use std::path::Path;
+use std::time::Duration;
+
+use base64::Engine;
+use serde::Serialize;
+use tauri::{AppHandle, Manager, Runtime, State, WebviewUrl, WebviewWindow, WebviewWindowBuilder};
+
+use crate::config::LauncherConfig;
+use external_localhost_plugin::Diagnostics;
+use crate::game::GameInfo;
+
+pub const SPLASH_LABEL: &str = "splash";
+
+/// Longest the splash screen stays up if the game never reports a paint.
+const MAX_SPLASH_DURATION: Duration = Duration::from_secs(15);
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SplashInfo {
+    title: String,
+    /// The logo as a data URL, so the splash doesn't depend on the server being up.
+    image: Option<String>,
+}
+
+pub fn open<R: Runtime>(app: &AppHandle<R>, game: &GameInfo) -> tauri::Result<WebviewWindow<R>> {
+    let mut builder = WebviewWindowBuilder::new(app, SPLASH_LABEL, WebviewUrl::App("splash.html".into()))
+        .title(&game.title)
+        .inner_size(480.0, 320.0)
+        .decorations(false)
+        .resizable(false)
+        .center();
+    if let Some(icon) = crate::game::load_icon(game) {
+        builder = builder.icon(icon)?;
+    }
+    builder.build()
+}
+
+/// Show `main` and close the splash once the game painted its first frame.
+pub fn swap_when_ready<R: Runtime>(app: &AppHandle<R>, main: WebviewWindow<R>) {
+    let diagnostics = app.state::<Diagnostics>().inner().clone();
+    let splash = app.get_webview_window(SPLASH_LABEL);
+
+    std::thread::spawn(move || {
+        if !diagnostics.wait_for_first_paint(MAX_SPLASH_DURATION) {
+            log::warn!("No first paint after {:?}, showing the game window anyway", MAX_SPLASH_DURATION);
+        }
+        let _ = main.show();
+        let _ = main.set_focus();
+        if let Some(splash) = splash {
+            let _ = splash.close();
+        }
+    });
+}
+
+fn image_data_url(path: &Path) -> Option<String> {
+    let bytes = fs::read(path).ok()?;
+    let mime = match path.extension().and_then(|e| e.to_str()).map(|e| e.to_lowercase()) {
+        Some(ext) if ext == "jpg" || ext == "jpeg" => "image/jpeg",
+        Some(ext) if ext == "gif" => "image/gif",
+        Some(ext) if ext == "webp" => "image/webp",
+        _ => "image/png",
+    };
+    Some(format!(
+        "data:{};base64,{}",
+        mime,
+        base64::engine::general_purpose::STANDARD.encode(bytes)
+    ))
+}
+
+#[tauri::command]
+pub fn get_splash_info(config: State<'_, LauncherConfig>, game: State<'_, GameInfo>) -> SplashInfo {
+    let image = match &config.splash_image {
+        Some(image) => game.dir.join(image),
+        None => game.icon.clone(),
+    };
+
+    SplashInfo {
+        title: game.title.clone(),
+        image: image_data_url(&image),
+    }
+}
@@ -0,0 +1,203 @@
+//! Local play statistics, collected only when the player opts in from the settings.
+//!
+//! Nothing leaves the machine: sessions, play time, crash counts and time per map are kept
+//! in `<app data>/stats/<game id>.json`, and `export_stats` writes them to a file the player
+//! can choose to send to the developer. A session that never recorded its end is counted
+//! as a crash the next time the game starts.
+
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::PathBuf;
+use std::sync::Mutex;
+use std::time::Instant;
+
+use serde::{Deserialize, Serialize};
+use tauri::{AppHandle, Emitter, Runtime, State};
+use tauri_plugin_dialog::DialogExt;
+
+use crate::settings::Settings;
+
+/// Sessions kept in the file; older ones only count in the totals.
+const MAX_SESSIONS: usize = 100;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Session {
+    /// Start time in milliseconds since the Unix epoch.
+    pub started_at: i64,
+    pub duration_secs: f64,
+    /// Whether the session ended normally. Cleared while the session is running.
+    pub ended: bool,
+    pub launcher_version: String,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default, rename_all = "camelCase")]
+pub struct StatsData {
+    pub session_count: u64,
+    pub playtime_secs: f64,
+    pub crash_count: u64,
+    /// Seconds spent on each map, keyed by map ID or name as reported by the game.
+    pub map_time_secs: BTreeMap<String, f64>,
+    pub sessions: Vec<Session>,
+}
+
+struct Current {
+    data: StatsData,
+    /// When the running session started, if stats are enabled.
+    started: Option<Instant>,
+    /// Play time recorded before the running session.
+    previous_playtime_secs: f64,
+}
+
+pub struct Stats {
+    path: PathBuf,
+    launcher_version: String,
+    current: Mutex<Current>,
+}
+
+impl Stats {
+    pub fn load(path: PathBuf, launcher_version: String, enabled: bool) -> Self {
+        let data = fs::read_to_string(&path)
+            .ok()
+            .and_then(|content| serde_json::from_str(&content).ok())
+            .unwrap_or_default();
+        let stats = Self {
+            path,
+            launcher_version,
+            current: Mutex::new(Current {
+                data,
+                started: None,
+                previous_playtime_secs: 0.0,
+            }),
+        };
+        if enabled {
+            stats.start_session();
+        }
+        stats
+    }
+
+    fn start_session(&self) {
+        let mut current = self.current.lock().unwrap();
+        if current.started.is_some() {
+            return;
+        }
+
+        if current.data.sessions.last().is_some_and(|s| !s.ended) {
+            current.data.crash_count += 1;
+            if let Some(last) = current.data.sessions.last_mut() {
+                last.ended = true;
+            }
+        }
+        current.data.session_count += 1;
+        current.data.sessions.push(Session {
+            started_at: chrono::Utc::now().timestamp_millis(),
+            duration_secs: 0.0,
+            ended: false,
+            launcher_version: self.launcher_version.clone(),
+        });
+        if current.data.sessions.len() > MAX_SESSIONS {
+            current.data.sessions.remove(0);
+        }
+        current.started = Some(Instant::now());
+        current.previous_playtime_secs = current.data.playtime_secs;
+        self.save(&mut current, false);
+    }
+
+    /// Record the end of the running session. Called when the app exits.
+    pub fn end_session(&self) {
+        let mut current = self.current.lock().unwrap();
+        if current.started.is_some() {
+            self.save(&mut current, true);
+            current.started = None;
+        }
+    }
+
+    /// Add time spent on a map, as reported by the game.
+    fn add_map_time(&self, map: String, secs: f64) {
+        let mut current = self.current.lock().unwrap();
+        if current.started.is_none() || !secs.is_finite() || secs <= 0.0 {
+            return;
+        }
+        *current.data.map_time_secs.entry(map).or_default() += secs;
+        self.save(&mut current, false);
+    }
+
+    /// Update the running session's duration and write everything to disk.
+    fn save(&self, current: &mut Current, ended: bool) {
+        if let Some(started) = current.started {
+            let elapsed = started.elapsed().as_secs_f64();
+            current.data.playtime_secs = current.previous_playtime_secs + elapsed;
+            if let Some(session) = current.data.sessions.last_mut() {
+                session.duration_secs = elapsed;
+                session.ended = ended;
+            }
+        }
+
+        let result = self
+            .path
+            .parent()
+            .map_or(Ok(()), fs::create_dir_all)
+            .and_then(|_| {
+                let content = serde_json::to_string_pretty(&current.data).unwrap_or_default();
+                fs::write(&self.path, content)
+            });
+        if let Err(e) = result {
+            log::warn!("Failed to save stats to {:?}: {}", self.path, e);
+        }
+    }
+
+    fn snapshot(&self) -> StatsData {
+        let mut current = self.current.lock().unwrap();
+        if current.started.is_some() {
+            self.save(&mut current, false);
+        }
+        current.data.clone()
+    }
+}
+
+#[tauri::command]
+pub fn get_stats(stats: State<'_, Stats>) -> StatsData {
+    stats.snapshot()
+}
+
+#[tauri::command]
+pub fn set_collect_stats<R: Runtime>(
+    app: AppHandle<R>,
+    settings: State<'_, Settings>,
+    stats: State<'_, Stats>,
+    enabled: bool,
+) -> Result<(), String> {
+    settings.update(|s| s.collect_stats = enabled)?;
+    if enabled {
+        stats.start_session();
+    } else {
+        stats.end_session();
+    }
+    app.emit("settings-changed", settings.get()).map_err(|e| e.to_string())
+}
+
+/// Report time spent on a map. Ignored unless the player opted in.
+#[tauri::command]
+pub fn record_map_time(stats: State<'_, Stats>, map: String, secs: f64) {
+    stats.add_map_time(map, secs);
+}
+
+/// Write the stats to a file picked by the player. Returns `false` if they cancelled.
+#[tauri::command]
+pub async fn export_stats<R: Runtime>(app: AppHandle<R>, stats: State<'_, Stats>) -> Result<bool, String> {
+    let data = stats.snapshot();
+    let Some(destination) = app
+        .dialog()
+        .file()
+        .set_title("Export play statistics")
+        .set_file_name("stats.json")
+        .blocking_save_file()
+    else {
+        return Ok(false);
+    };
+    let destination = destination.into_path().map_err(|e| e.to_string())?;
+    let content = serde_json::to_string_pretty(&data).map_err(|e| e.to_string())?;
+    fs::write(destination, content).map_err(|e| e.to_string())?;
+    Ok(true)
+}
@@ -0,0 +1,214 @@
+//! Steamworks integration, built with the optional `steam` cargo feature and enabled by
+//! `steamAppId` in `launcher.json`.
+//!
+//! Achievements, stats and rich presence are exposed as commands, and the injected
+//! `greenworks.js` shim maps the greenworks API that MV/MZ Steam plugins call onto them.
+//! Builds without the feature keep the commands, which then report Steam as unavailable.
+
+use serde::Serialize;
+use tauri::State;
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SteamStatus {
+    pub available: bool,
+    pub steam_id: Option<String>,
+    pub persona_name: Option<String>,
+}
+
+/// The Steamworks client, managed as app state. Empty when Steam isn't in use.
+pub struct Steam {
+    #[cfg(feature = "steam")]
+    client: Option<steamworks::Client>,
+}
+
+#[cfg(feature = "steam")]
+impl Steam {
+    /// Restart the game through Steam if it was started directly, so the overlay and the
+    /// right user are available. Returns `true` if this process should exit.
+    pub fn restart_if_necessary(app_id: u32) -> bool {
+        steamworks::restart_app_if_necessary(steamworks::AppId(app_id))
+    }
+
+    pub fn init(app_id: Option<u32>) -> Self {
+        let Some(app_id) = app_id else {
+            return Self { client: None };
+        };
+
+        match steamworks::Client::init_app(app_id) {
+            Ok((client, single)) => {
+                log::info!("Steamworks initialized for app {}", app_id);
+                client.user_stats().request_current_stats();
+                // Steam callbacks must be pumped from the thread that owns `single`
+                std::thread::spawn(move || loop {
+                    single.run_callbacks();
+                    std::thread::sleep(std::time::Duration::from_millis(50));
+                });
+                Self { client: Some(client) }
+            }
+            Err(e) => {
+                log::warn!("Steamworks failed to initialize, is Steam running? {}", e);
+                Self { client: None }
+            }
+        }
+    }
+
+    fn client(&self) -> Result<&steamworks::Client, String> {
+        self.client.as_ref().ok_or_else(|| "Steam is not available".to_string())
+    }
+}
+
+#[cfg(not(feature = "steam"))]
+impl Steam {
+    pub fn restart_if_necessary(_app_id: u32) -> bool {
+        false
+    }
+
+    pub fn init(app_id: Option<u32>) -> Self {
+        if app_id.is_some() {
+            log::warn!("steamAppId is set but this launcher was built without the `steam` feature");
+        }
+        Self {}
+    }
+}
+
+#[tauri::command]
+pub fn steam_status(steam: State<'_, Steam>) -> SteamStatus {
+    #[cfg(feature = "steam")]
+    if let Some(client) = &steam.client {
+        return SteamStatus {
+            available: true,
+            steam_id: Some(client.user().steam_id().raw().to_string()),
+            persona_name: Some(client.friends().name()),
+        };
+    }
+
+    let _ = steam;
+    SteamStatus {
+        available: false,
+        steam_id: None,
+        persona_name: None,
+    }
+}
+
+#[cfg(feature = "steam")]
+mod commands {
+    use super::Steam;
+
+    pub fn unlock_achievement(steam: &Steam, name: &str) -> Result<(), String> {
+        let stats = steam.client()?.user_stats();
+        stats
+            .achievement(name)
+            .set()
+            .map_err(|_| format!("Unknown achievement: {}", name))?;
+        stats.store_stats().map_err(|_| "Failed to store stats".to_string())
+    }
+
+    pub fn clear_achievement(steam: &Steam, name: &str) -> Result<(), String> {
+        let stats = steam.client()?.user_stats();
+        stats
+            .achievement(name)
+            .clear()
+            .map_err(|_| format!("Unknown achievement: {}", name))?;
+        stats.store_stats().map_err(|_| "Failed to store stats".to_string())
+    }
+
+    pub fn is_achieved(steam: &Steam, name: &str) -> Result<bool, String> {
+        steam
+            .client()?
+            .user_stats()
+            .achievement(name)
+            .get()
+            .map_err(|_| format!("Unknown achievement: {}", name))
+    }
+
+    pub fn set_stat(steam: &Steam, name: &str, value: i32) -> Result<(), String> {
+        let stats = steam.client()?.user_stats();
+        stats
+            .set_stat_i32(name, value)
+            .map_err(|_| format!("Unknown stat: {}", name))?;
+        stats.store_stats().map_err(|_| "Failed to store stats".to_string())
+    }
+
+    pub fn get_stat(steam: &Steam, name: &str) -> Result<i32, String> {
+        steam
+            .client()?
+            .user_stats()
+            .get_stat_i32(name)
+            .map_err(|_| format!("Unknown stat: {}", name))
+    }
+
+    pub fn set_rich_presence(steam: &Steam, key: &str, value: Option<&str>) -> Result<(), String> {
+        if steam.client()?.friends().set_rich_presence(key, value) {
+            Ok(())
+        } else {
+            Err(format!("Steam rejected rich presence key {}", key))
+        }
+    }
+}
+
+#[cfg(not(feature = "steam"))]
+mod commands {
+    use super::Steam;
+
+    const UNAVAILABLE: &str = "This launcher was built without Steam support";
+
+    pub fn unlock_achievement(_steam: &Steam, _name: &str) -> Result<(), String> {
+        Err(UNAVAILABLE.to_string())
+    }
+
+    pub fn clear_achievement(_steam: &Steam, _name: &str) -> Result<(), String> {
+        Err(UNAVAILABLE.to_string())
+    }
+
+    pub fn is_achieved(_steam: &Steam, _name: &str) -> Result<bool, String> {
+        Err(UNAVAILABLE.to_string())
+    }
+
+    pub fn set_stat(_steam: &Steam, _name: &str, _value: i32) -> Result<(), String> {
+        Err(UNAVAILABLE.to_string())
+    }
+
+    pub fn get_stat(_steam: &Steam, _name: &str) -> Result<i32, String> {
+        Err(UNAVAILABLE.to_string())
+    }
+
+    pub fn set_rich_presence(_steam: &Steam, _key: &str, _value: Option<&str>) -> Result<(), String> {
+        Err(UNAVAILABLE.to_string())
+    }
+}
+
+#[tauri::command]
+pub fn steam_unlock_achievement(steam: State<'_, Steam>, name: String) -> Result<(), String> {
+    commands::unlock_achievement(&steam, &name)
+}
+
+#[tauri::command]
+pub fn steam_clear_achievement(steam: State<'_, Steam>, name: String) -> Result<(), String> {
+    commands::clear_achievement(&steam, &name)
+}
+
+#[tauri::command]
+pub fn steam_is_achieved(steam: State<'_, Steam>, name: String) -> Result<bool, String> {
+    commands::is_achieved(&steam, &name)
+}
+
+#[tauri::command]
+pub fn steam_set_stat(steam: State<'_, Steam>, name: String, value: i32) -> Result<(), String> {
+    commands::set_stat(&steam, &name, value)
+}
+
+#[tauri::command]
+pub fn steam_get_stat(steam: State<'_, Steam>, name: String) -> Result<i32, String> {
+    commands::get_stat(&steam, &name)
+}
+
+/// Set a rich presence key (e.g. `steam_display`), or clear it with `null`.
+#[tauri::command]
+pub fn steam_set_rich_presence(
+    steam: State<'_, Steam>,
+    key: String,
+    value: Option<String>,
+) -> Result<(), String> {
+    commands::set_rich_presence(&steam, &key, value.as_deref())
+}
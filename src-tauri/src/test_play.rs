@@ -0,0 +1,28 @@
+//! Test play from the RPG Maker editor.
+//!
+//! With the launcher set as the editor's test-play runtime, `test`, `btest` and `etest`
+//! reach the game as query parameters and `nw.App.argv`, where the engine looks for them.
+//! The launcher adds what NW.js would have: the debug console opens with the game, the
+//! developer console is enabled, and F8 opens the webview devtools.
+
+use tauri::{AppHandle, Manager, Runtime};
+
+use crate::args::TestMode;
+use crate::debug_console;
+use crate::display::main_window;
+
+pub fn start<R: Runtime>(app: &AppHandle<R>, mode: TestMode) {
+    log::info!("Test play: {:?}", mode);
+    app.manage(mode);
+    tauri::async_runtime::spawn(debug_console::toggle_debug_console(app.clone()));
+}
+
+/// F8 in test play, as in NW.js.
+#[tauri::command]
+pub fn open_devtools<R: Runtime>(app: AppHandle<R>) -> Result<(), String> {
+    if app.try_state::<TestMode>().is_none() {
+        return Err("The devtools are only available in test play".to_string());
+    }
+    main_window(&app)?.open_devtools();
+    Ok(())
+}
@@ -0,0 +1,164 @@
+//! Optional system tray icon with quick actions, enabled with `"tray": true` in `launcher.json`.
+//!
+//! With the per-game "minimize to tray" setting, minimizing hides the game window until the
+//! tray icon is clicked, for games that are left running in the background.
+
+use tauri::menu::{CheckMenuItem, Menu, MenuItem, PredefinedMenuItem};
+use tauri::tray::{MouseButton, MouseButtonState, TrayIconBuilder, TrayIconEvent};
+use tauri::{AppHandle, Emitter, Listener, Manager, Runtime, State, WebviewWindow};
+use tauri_plugin_dialog::DialogExt;
+
+use crate::args::GAME_DIR_OPTION;
+use crate::display;
+use crate::focus;
+use crate::game::GameInfo;
+use crate::pause;
+use crate::saves::{self, SaveDirLock};
+use crate::screenshots;
+use crate::settings::Settings;
+
+pub fn create<R: Runtime>(app: &AppHandle<R>, game: &GameInfo) -> tauri::Result<()> {
+    let muted = app.state::<Settings>().get().muted;
+
+    let show = MenuItem::with_id(app, "show", "Show game", true, None::<&str>)?;
+    let mute = CheckMenuItem::with_id(app, "mute", "Mute", true, muted, None::<&str>)?;
+    let pause_item = CheckMenuItem::with_id(app, "pause", "Pause", true, false, None::<&str>)?;
+    let screenshot = MenuItem::with_id(app, "screenshot", "Take screenshot", true, None::<&str>)?;
+    let open_saves = MenuItem::with_id(app, "open-saves", "Open save folder", true, None::<&str>)?;
+    let switch_game = MenuItem::with_id(app, "switch-game", "Switch game…", true, None::<&str>)?;
+    let quit = MenuItem::with_id(app, "quit", "Quit", true, None::<&str>)?;
+    let menu = Menu::with_items(
+        app,
+        &[
+            &show,
+            &PredefinedMenuItem::separator(app)?,
+            &mute,
+            &pause_item,
+            &screenshot,
+            &open_saves,
+            &PredefinedMenuItem::separator(app)?,
+            &switch_game,
+            &quit,
+        ],
+    )?;
+
+    let mut builder = TrayIconBuilder::with_id("main")
+        .tooltip(&game.title)
+        .menu(&menu)
+        .show_menu_on_left_click(false)
+        .on_menu_event(move |app, event| match event.id().as_ref() {
+            "show" => show_main_window(app),
+            "mute" => {
+                let settings = app.state::<Settings>();
+                let muted = !settings.get().muted;
+                if let Err(e) = focus::set_muted(app.clone(), settings, muted) {
+                    log::warn!("Failed to toggle mute: {}", e);
+                }
+                let _ = mute.set_checked(muted);
+            }
+            "pause" => {
+                if let Err(e) = pause::toggle(app) {
+                    log::warn!("Failed to toggle pause: {}", e);
+                }
+            }
+            "screenshot" => {
+                if let Ok(window) = display::main_window(app) {
+                    if let Err(e) = screenshots::capture_screenshot(window) {
+                        log::warn!("Failed to take a screenshot: {}", e);
+                    }
+                }
+            }
+            "open-saves" => {
+                if let Err(e) = saves::open_save_folder(app) {
+                    log::warn!("Failed to open the save folder: {}", e);
+                }
+            }
+            "switch-game" => switch_game(app),
+            "quit" => app.exit(0),
+            _ => {}
+        })
+        .on_tray_icon_event(|tray, event| {
+            if let TrayIconEvent::Click {
+                button: MouseButton::Left,
+                button_state: MouseButtonState::Up,
+                ..
+            } = event
+            {
+                show_main_window(tray.app_handle());
+            }
+        });
+
+    // Pausing can also come from the menu bar or waking from sleep
+    let checked = pause_item.clone();
+    app.listen("game-paused", move |event| {
+        let _ = checked.set_checked(event.payload() == "true");
+    });
+
+    if let Some(icon) = crate::game::load_icon(game).or_else(|| app.default_window_icon().cloned()) {
+        builder = builder.icon(icon);
+    }
+    builder.build(app)?;
+    Ok(())
+}
+
+fn show_main_window<R: Runtime>(app: &AppHandle<R>) {
+    if let Ok(window) = display::main_window(app) {
+        let _ = window.show();
+        let _ = window.unminimize();
+        let _ = window.set_focus();
+    }
+}
+
+/// Hide the window instead of leaving it minimized, if the player asked for it.
+pub fn on_resized<R: Runtime>(window: &WebviewWindow<R>) {
+    let app = window.app_handle();
+    if app.tray_by_id("main").is_none() || !app.state::<Settings>().get().minimize_to_tray {
+        return;
+    }
+    if window.is_minimized().unwrap_or(false) {
+        let _ = window.hide();
+    }
+}
+
+/// Pick another game folder and relaunch the launcher on it.
+fn switch_game<R: Runtime>(app: &AppHandle<R>) {
+    let app = app.clone();
+    app.dialog()
+        .file()
+        .set_title("Choose a game folder")
+        .pick_folder(move |folder| {
+            let Some(folder) = folder.and_then(|f| f.into_path().ok()) else {
+                return;
+            };
+
+            let exe = match std::env::current_exe() {
+                Ok(exe) => exe,
+                Err(e) => {
+                    log::error!("Failed to locate the launcher executable: {}", e);
+                    return;
+                }
+            };
+
+            // The new process must be able to take the save folder lock
+            if let Some(lock) = app.try_state::<SaveDirLock>() {
+                lock.release();
+            }
+
+            let mut game_dir_arg = std::ffi::OsString::from(GAME_DIR_OPTION);
+            game_dir_arg.push(&folder);
+            match std::process::Command::new(exe).arg(game_dir_arg).spawn() {
+                Ok(_) => app.exit(0),
+                Err(e) => log::error!("Failed to relaunch on {:?}: {}", folder, e),
+            }
+        });
+}
+
+#[tauri::command]
+pub fn set_minimize_to_tray<R: Runtime>(
+    app: AppHandle<R>,
+    settings: State<'_, Settings>,
+    enabled: bool,
+) -> Result<(), String> {
+    settings.update(|s| s.minimize_to_tray = enabled)?;
+    app.emit("settings-changed", settings.get()).map_err(|e| e.to_string())
+}
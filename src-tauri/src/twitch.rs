@@ -0,0 +1,227 @@
+//! Twitch integration, enabled with `twitch` in `launcher.json`.
+//!
+//! Chat is read over Twitch IRC and emitted as `twitch-chat` events. With an OAuth token in
+//! `TWITCH_OAUTH_TOKEN` and the application's client ID in the config, channel point
+//! redemptions are received over EventSub and emitted as `twitch-redemption`. Both
+//! connections run on their own threads and reconnect when they drop.
+
+use std::collections::hash_map::RandomState;
+use std::collections::HashMap;
+use std::hash::{BuildHasher, Hasher};
+use std::io::{BufRead, BufReader, Write};
+use std::net::TcpStream;
+use std::time::Duration;
+
+use serde::Serialize;
+use serde_json::{json, Value};
+use tauri::{AppHandle, Emitter, Runtime};
+use tungstenite::Message;
+
+use crate::config::TwitchConfig;
+use crate::net;
+
+const TOKEN_VAR: &str = "TWITCH_OAUTH_TOKEN";
+const IRC_ADDRESS: &str = "irc.chat.twitch.tv:6667";
+const EVENTSUB_URL: &str = "wss://eventsub.wss.twitch.tv/ws";
+const HELIX_URL: &str = "https://api.twitch.tv/helix";
+const REDEMPTION_EVENT: &str = "channel.channel_points_custom_reward_redemption.add";
+const RECONNECT_DELAY: Duration = Duration::from_secs(10);
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct ChatMessage {
+    user: String,
+    display_name: String,
+    user_id: Option<String>,
+    color: Option<String>,
+    message: String,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct Redemption {
+    user: String,
+    reward: String,
+    reward_id: String,
+    cost: u64,
+    /// Text the viewer entered, for rewards that ask for one.
+    input: String,
+}
+
+/// Start reading chat and, if possible, channel point redemptions.
+pub fn start<R: Runtime>(app: &AppHandle<R>, config: &TwitchConfig) {
+    let channel = config.channel.trim().trim_start_matches('#').to_lowercase();
+    if channel.is_empty() {
+        log::warn!("Twitch is enabled but no channel is configured");
+        return;
+    }
+    if let Err(e) = net::check_online(IRC_ADDRESS) {
+        log::info!("Twitch disabled: {}", e);
+        return;
+    }
+    let token = std::env::var(TOKEN_VAR)
+        .ok()
+        .map(|t| t.trim_start_matches("oauth:").to_string())
+        .filter(|t| !t.is_empty());
+
+    {
+        let app = app.clone();
+        let channel = channel.clone();
+        let token = token.clone();
+        std::thread::spawn(move || loop {
+            if let Err(e) = run_chat(&app, &channel, token.as_deref()) {
+                log::warn!("Twitch chat disconnected: {}", e);
+            }
+            std::thread::sleep(RECONNECT_DELAY);
+        });
+    }
+
+    if let (Some(token), Some(client_id)) = (token, config.client_id.clone()) {
+        let app = app.clone();
+        std::thread::spawn(move || loop {
+            if let Err(e) = run_eventsub(&app, &channel, &token, &client_id) {
+                log::warn!("Twitch EventSub disconnected: {}", e);
+            }
+            std::thread::sleep(RECONNECT_DELAY);
+        });
+    }
+}
+
+fn run_chat<R: Runtime>(app: &AppHandle<R>, channel: &str, token: Option<&str>) -> Result<(), String> {
+    let stream = TcpStream::connect(IRC_ADDRESS).map_err(|e| e.to_string())?;
+    let mut writer = stream.try_clone().map_err(|e| e.to_string())?;
+    let mut send = |line: String| writer.write_all(format!("{}\r\n", line).as_bytes());
+
+    send("CAP REQ :twitch.tv/tags".to_string()).map_err(|e| e.to_string())?;
+    match token {
+        Some(token) => {
+            send(format!("PASS oauth:{}", token)).map_err(|e| e.to_string())?;
+            send(format!("NICK {}", channel)).map_err(|e| e.to_string())?;
+        }
+        // Anonymous read-only login
+        None => {
+            let suffix = RandomState::new().build_hasher().finish() % 100_000;
+            send(format!("NICK justinfan{}", suffix)).map_err(|e| e.to_string())?;
+        }
+    }
+    send(format!("JOIN #{}", channel)).map_err(|e| e.to_string())?;
+    log::info!("Reading Twitch chat of #{}", channel);
+
+    for line in BufReader::new(stream).lines() {
+        let line = line.map_err(|e| e.to_string())?;
+        if let Some(server) = line.strip_prefix("PING ") {
+            send(format!("PONG {}", server)).map_err(|e| e.to_string())?;
+        } else if let Some(message) = parse_privmsg(&line) {
+            let _ = app.emit("twitch-chat", message);
+        } else if line.contains(" NOTICE ") && line.contains("failed") {
+            return Err(line);
+        }
+    }
+    Err("connection closed".to_string())
+}
+
+/// Parse a `PRIVMSG` line, with the tags requested through `twitch.tv/tags`.
+fn parse_privmsg(line: &str) -> Option<ChatMessage> {
+    let (tags, rest) = match line.strip_prefix('@') {
+        Some(tagged) => tagged.split_once(' ')?,
+        None => ("", line),
+    };
+    let (prefix, rest) = rest.strip_prefix(':')?.split_once(' ')?;
+    let (_, message) = rest.strip_prefix("PRIVMSG ")?.split_once(" :")?;
+    let user = prefix.split('!').next()?.to_string();
+
+    let tags: HashMap<&str, &str> = tags
+        .split(';')
+        .filter_map(|tag| tag.split_once('='))
+        .collect();
+    let tag = |name: &str| tags.get(name).filter(|v| !v.is_empty()).map(|v| v.to_string());
+
+    Some(ChatMessage {
+        display_name: tag("display-name").unwrap_or_else(|| user.clone()),
+        user,
+        user_id: tag("user-id"),
+        color: tag("color"),
+        message: message.to_string(),
+    })
+}
+
+fn helix(method: &str, path: &str, token: &str, client_id: &str) -> Result<ureq::Request, String> {
+    Ok(net::request(method, &format!("{}{}", HELIX_URL, path))?
+        .set("Authorization", &format!("Bearer {}", token))
+        .set("Client-Id", client_id))
+}
+
+fn broadcaster_id(channel: &str, token: &str, client_id: &str) -> Result<String, String> {
+    let body = helix("GET", &format!("/users?login={}", channel), token, client_id)?
+        .call()
+        .map_err(|e| e.to_string())?
+        .into_string()
+        .map_err(|e| e.to_string())?;
+    let users: Value = serde_json::from_str(&body).map_err(|e| e.to_string())?;
+    users["data"][0]["id"]
+        .as_str()
+        .map(str::to_string)
+        .ok_or_else(|| format!("Unknown Twitch channel: {}", channel))
+}
+
+fn run_eventsub<R: Runtime>(app: &AppHandle<R>, channel: &str, token: &str, client_id: &str) -> Result<(), String> {
+    let broadcaster = broadcaster_id(channel, token, client_id)?;
+    let mut url = EVENTSUB_URL.to_string();
+
+    loop {
+        let (mut socket, _) = tungstenite::connect(&url).map_err(|e| e.to_string())?;
+        loop {
+            let text = match socket.read().map_err(|e| e.to_string())? {
+                Message::Text(text) => text,
+                Message::Close(_) => return Err("connection closed".to_string()),
+                _ => continue,
+            };
+            let message: Value = serde_json::from_str(&text).map_err(|e| e.to_string())?;
+            let payload = &message["payload"];
+
+            match message["metadata"]["message_type"].as_str() {
+                Some("session_welcome") => {
+                    // A session opened through a reconnect URL keeps its subscriptions
+                    if url == EVENTSUB_URL {
+                        let session = payload["session"]["id"].as_str().unwrap_or_default();
+                        subscribe(&broadcaster, session, token, client_id)?;
+                        log::info!("Receiving Twitch channel point redemptions");
+                    }
+                }
+                Some("session_reconnect") => {
+                    if let Some(reconnect_url) = payload["session"]["reconnect_url"].as_str() {
+                        url = reconnect_url.to_string();
+                        break;
+                    }
+                }
+                Some("notification") => {
+                    let event = &payload["event"];
+                    let redemption = Redemption {
+                        user: event["user_name"].as_str().unwrap_or_default().to_string(),
+                        reward: event["reward"]["title"].as_str().unwrap_or_default().to_string(),
+                        reward_id: event["reward"]["id"].as_str().unwrap_or_default().to_string(),
+                        cost: event["reward"]["cost"].as_u64().unwrap_or(0),
+                        input: event["user_input"].as_str().unwrap_or_default().to_string(),
+                    };
+                    let _ = app.emit("twitch-redemption", redemption);
+                }
+                Some("revocation") => return Err("subscription revoked".to_string()),
+                _ => {}
+            }
+        }
+    }
+}
+
+fn subscribe(broadcaster: &str, session: &str, token: &str, client_id: &str) -> Result<(), String> {
+    let body = json!({
+        "type": REDEMPTION_EVENT,
+        "version": "1",
+        "condition": { "broadcaster_user_id": broadcaster },
+        "transport": { "method": "websocket", "session_id": session },
+    });
+    helix("POST", "/eventsub/subscriptions", token, client_id)?
+        .set("Content-Type", "application/json")
+        .send_string(&body.to_string())
+        .map_err(|e| e.to_string())?;
+    Ok(())
+}
@@ -0,0 +1,84 @@
+//! Keeps the localhost server alive for the whole session.
+//!
+//! If the server task dies or its socket stops answering, the game silently stops loading
+//! assets. A background thread pings the server every few seconds and starts it again on
+//! the same port when it misses two pings in a row. When restarting keeps failing, the
+//! game is sent `server-unavailable` and the player is told to restart the game.
+
+use std::thread;
+use std::time::Duration;
+
+use serde::Serialize;
+use tauri::{AppHandle, Emitter, Manager, Runtime};
+use tauri_plugin_dialog::{DialogExt, MessageDialogKind};
+
+use crate::display::MAIN_WINDOW_LABEL;
+use external_localhost_plugin::ServerHandle;
+
+/// How often the server is pinged.
+const HEARTBEAT_INTERVAL: Duration = Duration::from_secs(5);
+/// Missed pings in a row before the server is restarted; a busy server can miss one.
+const MISSES_BEFORE_RESTART: u32 = 2;
+/// How long a restarted server gets to answer.
+const RESTART_GRACE: Duration = Duration::from_secs(1);
+/// Failed restarts in a row before the player is told.
+const MAX_FAILED_RESTARTS: u32 = 3;
+
+#[derive(Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct ServerUnavailable {
+    failed_restarts: u32,
+}
+
+/// Start the watchdog thread.
+pub fn watch<R: Runtime>(app: &AppHandle<R>) {
+    let app = app.clone();
+    thread::spawn(move || {
+        let mut misses = 0;
+        let mut failed_restarts = 0;
+        loop {
+            thread::sleep(HEARTBEAT_INTERVAL);
+            let server = app.state::<ServerHandle>();
+            if server.is_responding() {
+                misses = 0;
+                failed_restarts = 0;
+                continue;
+            }
+            misses += 1;
+            if misses < MISSES_BEFORE_RESTART {
+                continue;
+            }
+
+            log::warn!("The localhost server missed {} pings, restarting it", misses);
+            server.ensure_running();
+            thread::sleep(RESTART_GRACE);
+            if server.is_responding() {
+                log::info!("The localhost server is back");
+                misses = 0;
+                failed_restarts = 0;
+                continue;
+            }
+            failed_restarts += 1;
+            log::error!("Restarting the localhost server failed ({} in a row)", failed_restarts);
+            if failed_restarts == MAX_FAILED_RESTARTS {
+                report_unavailable(&app, failed_restarts);
+            }
+        }
+    });
+}
+
+fn report_unavailable<R: Runtime>(app: &AppHandle<R>, failed_restarts: u32) {
+    let _ = app.emit_to(
+        MAIN_WINDOW_LABEL,
+        "server-unavailable",
+        ServerUnavailable { failed_restarts },
+    );
+    app.dialog()
+        .message(
+            "The launcher's file server stopped and could not be started again, so the game \
+             can't load its images and sounds anymore.\n\nSave if you can, then restart the game.",
+        )
+        .title("Game files unavailable")
+        .kind(MessageDialogKind::Error)
+        .show(|_| {});
+}
@@ -0,0 +1,93 @@
+const { invoke } = window.__TAURI__.core;
+const { listen } = window.__TAURI__.event;
+
+let current = "saves";
+
+function item(title, detail, className = "") {
+  const el = document.createElement("div");
+  el.className = `item ${className}`;
+  const titleEl = document.createElement("div");
+  titleEl.textContent = title;
+  el.appendChild(titleEl);
+  if (detail) {
+    const detailEl = document.createElement("div");
+    detailEl.className = "detail";
+    detailEl.textContent = detail;
+    el.appendChild(detailEl);
+  }
+  return el;
+}
+
+function empty(text) {
+  const el = document.createElement("div");
+  el.className = "empty";
+  el.textContent = text;
+  return el;
+}
+
+const renderers = {
+  async saves(section) {
+    const saves = await invoke("list_saves");
+    section.replaceChildren(
+      ...saves.map((save) =>
+        item(save.name, save.modified ? new Date(save.modified).toLocaleString() : ""),
+      ),
+    );
+    if (!saves.length) {
+      section.appendChild(empty("No saves yet."));
+    }
+  },
+
+  async achievements(section) {
+    const achievements = await invoke("get_achievements");
+    section.replaceChildren(
+      ...achievements
+        .filter((a) => a.unlockedAt || !a.hidden)
+        .map((a) => item(a.name, a.description, a.unlockedAt ? "" : "locked")),
+    );
+    if (!achievements.length) {
+      section.appendChild(empty("This game has no achievements."));
+    }
+  },
+
+  async logs(section) {
+    const entries = await invoke("get_debug_log");
+    section.replaceChildren(
+      ...entries.slice(-500).map((entry) => {
+        const line = document.createElement("div");
+        line.className = entry.level;
+        line.textContent = `${entry.time} [${entry.source}] ${entry.message}`;
+        return line;
+      }),
+    );
+    window.scrollTo(0, document.body.scrollHeight);
+  },
+};
+
+async function show(tab) {
+  current = tab;
+  document.querySelectorAll("#tabs button").forEach((button) => {
+    button.classList.toggle("active", button.dataset.tab === tab);
+  });
+  document.querySelectorAll("section").forEach((section) => {
+    section.hidden = section.id !== tab;
+  });
+  const error = document.querySelector("#error");
+  error.textContent = "";
+  try {
+    await renderers[tab](document.querySelector(`#${tab}`));
+  } catch (e) {
+    error.textContent = String(e);
+  }
+}
+
+window.addEventListener("DOMContentLoaded", () => {
+  document.querySelectorAll("#tabs button").forEach((button) => {
+    button.addEventListener("click", () => show(button.dataset.tab));
+  });
+  listen("save-changed", () => current === "saves" && show("saves"));
+  listen("achievement-unlocked", () => current === "achievements" && show("achievements"));
+  // The log has no push events for this panel, so it is refreshed while shown
+  setInterval(() => current === "logs" && show("logs"), 2000);
+  show("saves");
+});
@@ -0,0 +1,143 @@
+const { invoke } = window.__TAURI__.core;
+const { listen } = window.__TAURI__.event;
+
+const ACTIONS = [
+  ["up", "Up"],
+  ["down", "Down"],
+  ["left", "Left"],
+  ["right", "Right"],
+  ["ok", "OK"],
+  ["escape", "Cancel"],
+  ["menu", "Menu"],
+  ["shift", "Dash"],
+  ["control", "Control"],
+  ["tab", "Tab"],
+  ["pageup", "Page up"],
+  ["pagedown", "Page down"],
+  [null, "Disabled"],
+];
+
+// Names of the standard gamepad mapping's buttons
+const BUTTONS = [
+  "A", "B", "X", "Y", "LB", "RB", "LT", "RT", "Back", "Start", "L3", "R3",
+  "D-pad up", "D-pad down", "D-pad left", "D-pad right", "Home",
+];
+
+let bindingsEl;
+let promptEl;
+let errorEl;
+let cancelCapture = () => {};
+
+async function run(action) {
+  errorEl.textContent = "";
+  try {
+    await action();
+  } catch (error) {
+    errorEl.textContent = String(error);
+  }
+}
+
+function showPrompt(text) {
+  promptEl.textContent = text;
+  promptEl.hidden = !text;
+}
+
+function button(label, onClick) {
+  const el = document.createElement("button");
+  el.textContent = label;
+  el.addEventListener("click", onClick);
+  return el;
+}
+
+function chip(label, onRemove) {
+  const el = document.createElement("span");
+  el.className = "binding";
+  el.textContent = label;
+  // Removing a binding disables the input, so disabled ones have nothing to remove
+  if (onRemove) el.appendChild(button("×", onRemove));
+  return el;
+}
+
+function captureKey(action) {
+  cancelCapture();
+  showPrompt("Press a key… (Esc cancels)");
+  const onKey = (event) => {
+    event.preventDefault();
+    cancelCapture();
+    if (event.code !== "Escape") {
+      run(() => invoke("set_key_binding", { code: event.code, action }));
+    }
+  };
+  window.addEventListener("keydown", onKey, true);
+  cancelCapture = () => {
+    window.removeEventListener("keydown", onKey, true);
+    showPrompt("");
+    cancelCapture = () => {};
+  };
+}
+
+function captureButton(action) {
+  cancelCapture();
+  showPrompt("Press a gamepad button… (Esc cancels)");
+  const timer = setInterval(() => {
+    for (const pad of navigator.getGamepads()) {
+      const index = pad ? pad.buttons.findIndex((b) => b.pressed) : -1;
+      if (index >= 0) {
+        cancelCapture();
+        run(() => invoke("set_gamepad_binding", { button: index, action }));
+        return;
+      }
+    }
+  }, 50);
+  const onKey = (event) => {
+    if (event.code === "Escape") cancelCapture();
+  };
+  window.addEventListener("keydown", onKey, true);
+  cancelCapture = () => {
+    clearInterval(timer);
+    window.removeEventListener("keydown", onKey, true);
+    showPrompt("");
+    cancelCapture = () => {};
+  };
+}
+
+function render(bindings) {
+  bindingsEl.replaceChildren();
+  for (const [action, label] of ACTIONS) {
+    const row = document.createElement("tr");
+
+    const name = document.createElement("td");
+    name.textContent = label;
+
+    const keys = document.createElement("td");
+    for (const [code, bound] of Object.entries(bindings.keys)) {
+      if (bound !== action) continue;
+      const remove = action && (() => run(() => invoke("set_key_binding", { code, action: null })));
+      keys.appendChild(chip(code, remove));
+    }
+
+    const pads = document.createElement("td");
+    for (const [index, bound] of Object.entries(bindings.gamepad)) {
+      if (bound !== action) continue;
+      const label = BUTTONS[index] || `Button ${index}`;
+      const remove = action && (() => run(() => invoke("set_gamepad_binding", { button: Number(index), action: null })));
+      pads.appendChild(chip(label, remove));
+    }
+
+    const actions = document.createElement("td");
+    actions.className = "actions";
+    actions.append(button("+ Key", () => captureKey(action)), button("+ Button", () => captureButton(action)));
+
+    row.append(name, keys, pads, actions);
+    bindingsEl.appendChild(row);
+  }
+}
+
+window.addEventListener("DOMContentLoaded", async () => {
+  bindingsEl = document.querySelector("#bindings");
+  promptEl = document.querySelector("#prompt");
+  errorEl = document.querySelector("#error");
+  document.querySelector("#reset").addEventListener("click", () => run(() => invoke("reset_input_bindings")));
+  await listen("input-bindings-changed", (event) => render(event.payload));
+  run(async () => render(await invoke("get_input_bindings")));
+});
@@ -0,0 +1,57 @@
+const { invoke } = window.__TAURI__.core;
+const { listen } = window.__TAURI__.event;
+
+const MAX_ENTRIES = 5000;
+
+let entriesEl;
+let autoscrollEl;
+const hiddenSources = new Set();
+
+function addEntry(entry) {
+  const line = document.createElement("div");
+  line.className = `entry ${entry.level}`;
+  line.dataset.source = entry.source;
+  line.hidden = hiddenSources.has(entry.source);
+
+  const time = document.createElement("span");
+  time.className = "time";
+  time.textContent = `${entry.time} `;
+  const source = document.createElement("span");
+  source.className = "source";
+  source.textContent = `[${entry.source}] `;
+
+  line.append(time, source, `${entry.level} ${entry.message}`);
+  entriesEl.appendChild(line);
+
+  while (entriesEl.childElementCount > MAX_ENTRIES) {
+    entriesEl.firstElementChild.remove();
+  }
+  if (autoscrollEl.checked) {
+    window.scrollTo(0, document.body.scrollHeight);
+  }
+}
+
+window.addEventListener("DOMContentLoaded", async () => {
+  entriesEl = document.querySelector("#entries");
+  autoscrollEl = document.querySelector("#autoscroll");
+
+  document.querySelectorAll("[data-source]").forEach((checkbox) => {
+    checkbox.addEventListener("change", () => {
+      const source = checkbox.dataset.source;
+      if (checkbox.checked) {
+        hiddenSources.delete(source);
+      } else {
+        hiddenSources.add(source);
+      }
+      entriesEl.querySelectorAll(`.entry[data-source="${source}"]`).forEach((line) => {
+        line.hidden = !checkbox.checked;
+      });
+    });
+  });
+  document.querySelector("#clear").addEventListener("click", () => {
+    entriesEl.replaceChildren();
+  });
+
+  (await invoke("get_debug_log")).forEach(addEntry);
+  await listen("debug-console-entry", (event) => addEntry(event.payload));
+});
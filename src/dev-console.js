@@ -0,0 +1,74 @@
+const { invoke } = window.__TAURI__.core;
+const { listen } = window.__TAURI__.event;
+
+const pending = new Map();
+let outputEl;
+
+function addEntry(text, className) {
+  const entry = document.createElement("div");
+  entry.className = `entry ${className}`;
+  entry.textContent = text;
+  outputEl.appendChild(entry);
+  outputEl.scrollTop = outputEl.scrollHeight;
+}
+
+async function run(code, label) {
+  addEntry(`> ${label || code}`, "input");
+  try {
+    const id = await invoke("dev_eval", { code });
+    pending.set(id, true);
+  } catch (error) {
+    addEntry(String(error), "error");
+  }
+}
+
+// JavaScript for the quick actions, run in the game page.
+const ACTIONS = {
+  switch: (f) => `$gameSwitches.setValue(${Number(f.id)}, ${f.value === "true"}); $gameSwitches.value(${Number(f.id)})`,
+  variable: (f) => {
+    const value = f.value.trim() !== "" && !isNaN(Number(f.value)) ? Number(f.value) : JSON.stringify(f.value);
+    return `$gameVariables.setValue(${Number(f.id)}, ${value}); $gameVariables.value(${Number(f.id)})`;
+  },
+  teleport: (f) =>
+    `$gamePlayer.reserveTransfer(${Number(f.map)}, ${Number(f.x)}, ${Number(f.y)}, 0, 0); ` +
+    `if (!(SceneManager._scene instanceof Scene_Map)) SceneManager.goto(Scene_Map); "Transferring"`,
+  item: (f) => {
+    const amount = Number(f.amount) || 1;
+    if (f.kind === "gold") {
+      return `$gameParty.gainGold(${amount}); $gameParty.gold()`;
+    }
+    const table = { item: "$dataItems", weapon: "$dataWeapons", armor: "$dataArmors" }[f.kind];
+    return `$gameParty.gainItem(${table}[${Number(f.id)}], ${amount}); $gameParty.numItems(${table}[${Number(f.id)}])`;
+  },
+};
+
+window.addEventListener("DOMContentLoaded", async () => {
+  outputEl = document.querySelector("#output");
+  const codeEl = document.querySelector("#code");
+
+  codeEl.addEventListener("keydown", (event) => {
+    if (event.key === "Enter" && !event.shiftKey) {
+      event.preventDefault();
+      const code = codeEl.value.trim();
+      if (code) {
+        codeEl.value = "";
+        run(code);
+      }
+    }
+  });
+
+  document.querySelectorAll("#tools form").forEach((form) => {
+    form.addEventListener("submit", (event) => {
+      event.preventDefault();
+      const fields = Object.fromEntries(new FormData(form));
+      run(ACTIONS[form.dataset.action](fields));
+    });
+  });
+
+  await listen("dev-console-result", (event) => {
+    const { id, ok, output } = event.payload;
+    if (pending.delete(id)) {
+      addEntry(output, ok ? "result" : "error");
+    }
+  });
+});
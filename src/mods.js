@@ -0,0 +1,79 @@
+const { invoke } = window.__TAURI__.core;
+
+let modsEl;
+let errorEl;
+
+async function run(action) {
+  errorEl.textContent = "";
+  try {
+    await action();
+  } catch (error) {
+    errorEl.textContent = String(error);
+  }
+  await refresh();
+}
+
+function button(label, onClick, disabled) {
+  const el = document.createElement("button");
+  el.textContent = label;
+  el.disabled = disabled;
+  el.addEventListener("click", onClick);
+  return el;
+}
+
+function move(mods, index, offset) {
+  const order = mods.map((mod) => mod.id);
+  const [id] = order.splice(index, 1);
+  order.splice(index + offset, 0, id);
+  return invoke("set_mod_order", { order });
+}
+
+async function refresh() {
+  const mods = await invoke("list_mods");
+  modsEl.replaceChildren();
+  document.querySelector("#empty").hidden = mods.length > 0;
+
+  mods.forEach((mod, index) => {
+    const row = document.createElement("tr");
+
+    const enabled = document.createElement("td");
+    const checkbox = document.createElement("input");
+    checkbox.type = "checkbox";
+    checkbox.checked = mod.enabled;
+    checkbox.addEventListener("change", () =>
+      run(() => invoke("set_mod_enabled", { id: mod.id, enabled: checkbox.checked }))
+    );
+    enabled.appendChild(checkbox);
+
+    const name = document.createElement("td");
+    name.textContent = mod.name;
+    if (mod.description) {
+      const description = document.createElement("div");
+      description.className = "description";
+      description.textContent = mod.description;
+      name.appendChild(description);
+    }
+    const version = document.createElement("td");
+    version.textContent = mod.version;
+    const author = document.createElement("td");
+    author.textContent = mod.author;
+
+    const actions = document.createElement("td");
+    actions.className = "actions";
+    actions.append(
+      button("▲", () => run(() => move(mods, index, -1)), index === 0),
+      button("▼", () => run(() => move(mods, index, 1)), index === mods.length - 1)
+    );
+
+    row.append(enabled, name, version, author, actions);
+    modsEl.appendChild(row);
+  });
+}
+
+window.addEventListener("DOMContentLoaded", () => {
+  modsEl = document.querySelector("#mods");
+  errorEl = document.querySelector("#error");
+  document.querySelector("#refresh").addEventListener("click", () => run(async () => {}));
+  document.querySelector("#open-folder").addEventListener("click", () => run(() => invoke("open_mods_folder")));
+  run(async () => {});
+});
@@ -0,0 +1,59 @@
+const { invoke } = window.__TAURI__.core;
+const { getCurrentWindow } = window.__TAURI__.window;
+
+window.addEventListener("DOMContentLoaded", async () => {
+  const errorEl = document.querySelector("#error");
+  const preview = await invoke("get_report_preview");
+
+  if (preview.crash) {
+    document.querySelector("#heading").textContent = "The game crashed";
+    document.querySelector("#intro").textContent =
+      "The game closed unexpectedly last time. Sending this report helps the developer fix it.";
+    const crashEl = document.querySelector("#crash");
+    crashEl.textContent = `${preview.crash.message}\n${preview.crash.location ?? ""}`;
+    crashEl.hidden = false;
+  }
+
+  const system = preview.system;
+  const contents = [
+    "Your description",
+    `System information: ${system.os} ${system.arch}, webview ${system.webview ?? "unknown"}, launcher ${system.launcherVersion}`,
+    `The launcher log (${preview.crash ? preview.crash.logs.length : preview.logLines} lines)`,
+  ];
+  if (preview.crash) {
+    contents.push("The crash details shown above");
+  }
+  document.querySelector("#contents").replaceChildren(
+    ...contents.map((text) => {
+      const item = document.createElement("li");
+      item.textContent = text;
+      return item;
+    })
+  );
+
+  if (preview.latestSave) {
+    document.querySelector("#save-name").textContent = preview.latestSave;
+    document.querySelector("#save-option").hidden = false;
+  }
+
+  document.querySelector("#discard").addEventListener("click", async () => {
+    await invoke("discard_report");
+    await getCurrentWindow().close();
+  });
+
+  document.querySelector("#send").addEventListener("click", async (event) => {
+    errorEl.textContent = "";
+    event.target.disabled = true;
+    try {
+      await invoke("send_report", {
+        description: document.querySelector("#description").value,
+        includeSave: document.querySelector("#include-save").checked,
+      });
+      document.querySelector("#sent").hidden = false;
+      setTimeout(() => getCurrentWindow().close(), 1500);
+    } catch (error) {
+      errorEl.textContent = String(error);
+      event.target.disabled = false;
+    }
+  });
+});
@@ -0,0 +1,197 @@
+const { invoke } = window.__TAURI__.core;
+
+let slotEl;
+let entriesEl;
+let filterEl;
+let statusEl;
+let errorEl;
+let saveButton;
+
+// Names from the database, loaded once
+const names = { items: [], weapons: [], armors: [], switches: [], variables: [] };
+let current = null;
+let tab = "items";
+
+async function run(action) {
+  errorEl.textContent = "";
+  statusEl.textContent = "";
+  try {
+    await action();
+  } catch (error) {
+    errorEl.textContent = String(error);
+  }
+}
+
+// MV's JsonEx writes arrays that are referenced more than once as { "@a": [...] }
+function array(value) {
+  if (Array.isArray(value)) return value;
+  return value && Array.isArray(value["@a"]) ? value["@a"] : [];
+}
+
+function numberInput(value, onChange) {
+  const el = document.createElement("input");
+  el.type = "number";
+  el.value = value;
+  el.addEventListener("change", () => {
+    const number = Number(el.value);
+    if (Number.isFinite(number)) {
+      onChange(number);
+      saveButton.disabled = false;
+    }
+  });
+  return el;
+}
+
+function row(id, name, control) {
+  const tr = document.createElement("tr");
+  const idCell = document.createElement("td");
+  idCell.className = "id";
+  idCell.textContent = id;
+  const nameCell = document.createElement("td");
+  nameCell.textContent = name;
+  const controlCell = document.createElement("td");
+  controlCell.appendChild(control);
+  tr.append(idCell, nameCell, controlCell);
+  return tr;
+}
+
+function matches(id, name) {
+  const filter = filterEl.value.trim().toLowerCase();
+  return !filter || String(id) === filter || name.toLowerCase().includes(filter);
+}
+
+function renderInventory(key) {
+  const party = current.party;
+  const owned = party[`_${key}`];
+  for (const entry of names[key]) {
+    if (!entry || !entry.name || !matches(entry.id, entry.name)) continue;
+    const count = owned[entry.id] || 0;
+    entriesEl.appendChild(
+      row(entry.id, entry.name, numberInput(count, (value) => {
+        if (value > 0) {
+          owned[entry.id] = Math.floor(value);
+        } else {
+          delete owned[entry.id];
+        }
+      }))
+    );
+  }
+}
+
+function renderSwitches() {
+  const values = array(current.switches._data);
+  names.switches.forEach((name, id) => {
+    if (id === 0 || (!name && !values[id]) || !matches(id, name)) return;
+    const el = document.createElement("input");
+    el.type = "checkbox";
+    el.checked = !!values[id];
+    el.addEventListener("change", () => {
+      values[id] = el.checked;
+      saveButton.disabled = false;
+    });
+    entriesEl.appendChild(row(id, name, el));
+  });
+}
+
+function renderVariables() {
+  const values = array(current.variables._data);
+  names.variables.forEach((name, id) => {
+    if (id === 0 || (!name && !values[id]) || !matches(id, name)) return;
+    const value = values[id] ?? 0;
+    // Plugins sometimes keep strings or objects in variables, which are shown but not edited
+    const control =
+      typeof value === "number"
+        ? numberInput(value, (number) => {
+            values[id] = number;
+          })
+        : Object.assign(document.createElement("code"), { textContent: JSON.stringify(value) });
+    entriesEl.appendChild(row(id, name, control));
+  });
+}
+
+function render() {
+  entriesEl.replaceChildren();
+  document.querySelectorAll("#tabs button").forEach((el) => {
+    el.classList.toggle("active", el.dataset.tab === tab);
+  });
+  document.querySelector("#gold-row").hidden = !current;
+  if (!current) return;
+
+  document.querySelector("#gold").value = current.party._gold;
+  if (tab === "switches") {
+    renderSwitches();
+  } else if (tab === "variables") {
+    renderVariables();
+  } else {
+    renderInventory(tab);
+  }
+}
+
+async function loadNames() {
+  const [system, items, weapons, armors] = await Promise.all(
+    ["System", "Items", "Weapons", "Armors"].map((name) => invoke("read_data_file", { name }))
+  );
+  names.switches = system.switches || [];
+  names.variables = system.variables || [];
+  names.items = items;
+  names.weapons = weapons;
+  names.armors = armors;
+}
+
+async function loadSlots() {
+  const slots = await invoke("list_save_slots");
+  slotEl.replaceChildren(
+    ...slots.map((slot) => {
+      const option = document.createElement("option");
+      option.value = slot.fileName;
+      option.textContent = `Slot ${slot.savefileId}: ${slot.title} (${slot.playtime})`;
+      return option;
+    })
+  );
+  if (slots.length === 0) {
+    statusEl.textContent = "No saves yet.";
+  }
+}
+
+async function loadSave() {
+  current = slotEl.value ? await invoke("decode_save", { name: slotEl.value }) : null;
+  saveButton.disabled = true;
+  render();
+}
+
+window.addEventListener("DOMContentLoaded", () => {
+  slotEl = document.querySelector("#slot");
+  entriesEl = document.querySelector("#entries");
+  filterEl = document.querySelector("#filter");
+  statusEl = document.querySelector("#status");
+  errorEl = document.querySelector("#error");
+  saveButton = document.querySelector("#save");
+
+  slotEl.addEventListener("change", () => run(loadSave));
+  filterEl.addEventListener("input", render);
+  document.querySelectorAll("#tabs button").forEach((el) => {
+    el.addEventListener("click", () => {
+      tab = el.dataset.tab;
+      render();
+    });
+  });
+  document.querySelector("#gold").addEventListener("change", (event) => {
+    const gold = Number(event.target.value);
+    if (current && Number.isFinite(gold) && gold >= 0) {
+      current.party._gold = Math.floor(gold);
+      saveButton.disabled = false;
+    }
+  });
+  saveButton.addEventListener("click", () =>
+    run(async () => {
+      await invoke("write_decoded_save", { name: slotEl.value, save: current });
+      saveButton.disabled = true;
+      statusEl.textContent = "Saved.";
+    })
+  );
+
+  run(async () => {
+    await Promise.all([loadNames(), loadSlots()]);
+    await loadSave();
+  });
+});
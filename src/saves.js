@@ -0,0 +1,128 @@
+const { invoke } = window.__TAURI__.core;
+
+let savesEl;
+let errorEl;
+
+function formatSize(bytes) {
+  return bytes < 1024 ? `${bytes} B` : `${(bytes / 1024).toFixed(1)} KB`;
+}
+
+async function run(action) {
+  errorEl.textContent = "";
+  try {
+    await action();
+  } catch (error) {
+    errorEl.textContent = String(error);
+  }
+  await refresh();
+}
+
+// Thumbnails are taken when a slot is saved; older saves and the global file have none
+async function thumbnail(name) {
+  const cell = document.createElement("td");
+  cell.className = "thumbnail";
+  try {
+    const png = await invoke("get_save_thumbnail", { name });
+    const img = document.createElement("img");
+    img.src = URL.createObjectURL(new Blob([png], { type: "image/png" }));
+    cell.appendChild(img);
+  } catch {
+    // No thumbnail
+  }
+  return cell;
+}
+
+function button(label, onClick) {
+  const el = document.createElement("button");
+  el.textContent = label;
+  el.addEventListener("click", onClick);
+  return el;
+}
+
+async function refresh() {
+  const saves = await invoke("list_saves");
+  savesEl.replaceChildren();
+  document.querySelector("#empty").hidden = saves.length > 0;
+
+  for (const save of saves) {
+    const row = document.createElement("tr");
+    const name = document.createElement("td");
+    name.textContent = save.name;
+    const size = document.createElement("td");
+    size.textContent = formatSize(save.size);
+    const modified = document.createElement("td");
+    modified.textContent = save.modified ? new Date(save.modified).toLocaleString() : "";
+
+    const actions = document.createElement("td");
+    actions.className = "actions";
+    actions.append(
+      button("Export…", () => run(() => invoke("export_save", { name: save.name }))),
+      button("Import…", () => run(() => invoke("import_save", { name: save.name }))),
+      button("Delete", () => {
+        if (confirm(`Delete ${save.name}? This can't be undone.`)) {
+          run(() => invoke("remove_save", { name: save.name }));
+        }
+      })
+    );
+
+    row.append(await thumbnail(save.name), name, size, modified, actions);
+    savesEl.appendChild(row);
+  }
+}
+
+// Saves from the game's web version, exported from the browser console as JSON
+async function importFromBrowser() {
+  const statusEl = document.querySelector("#status");
+  statusEl.textContent = "";
+  const overwrite = confirm("Replace saves that already exist with the ones from the browser?");
+  const summary = await invoke("import_browser_saves", { overwrite });
+  if (!summary) {
+    return;
+  }
+  const parts = [`Imported ${summary.imported.length} file(s)`];
+  if (summary.skipped.length) {
+    parts.push(`kept ${summary.skipped.length} existing`);
+  }
+  if (summary.invalid.length) {
+    parts.push(`${summary.invalid.length} unreadable: ${summary.invalid.join(", ")}`);
+  }
+  statusEl.textContent = parts.join(", ") + ".";
+}
+
+// Switching copies the saves over and keeps the choice for the next launches
+async function switchBackend(type) {
+  const config = { type };
+  if (type === "encrypted") {
+    config.key = prompt("Passphrase to encrypt the saves with:");
+    if (!config.key) {
+      return;
+    }
+  } else if (type === "mirrored") {
+    config.folder = prompt("Full path of the folder to mirror the saves to:");
+    if (!config.folder) {
+      return;
+    }
+  }
+  const migrate = confirm("Copy the current saves over? Saves with the same name are replaced.");
+  await invoke("set_save_backend", { config, migrate });
+  // A merge patch, so the fields of the previous type are removed explicitly
+  await invoke("set_config", { patch: { saveBackend: { key: null, folder: null, ...config } } });
+  document.querySelector("#status").textContent = "Save storage changed.";
+}
+
+async function showBackend() {
+  const config = await invoke("get_save_backend");
+  document.querySelector("#backend").value = config.type;
+}
+
+window.addEventListener("DOMContentLoaded", () => {
+  savesEl = document.querySelector("#saves");
+  errorEl = document.querySelector("#error");
+  document.querySelector("#refresh").addEventListener("click", () => run(async () => {}));
+  document.querySelector("#open-folder").addEventListener("click", () => run(() => invoke("show_save_folder")));
+  document.querySelector("#import-browser").addEventListener("click", () => run(importFromBrowser));
+  document.querySelector("#backend").addEventListener("change", (event) =>
+    run(() => switchBackend(event.target.value)).then(showBackend)
+  );
+  run(showBackend);
+});
@@ -0,0 +1,77 @@
+const { invoke } = window.__TAURI__.core;
+const { listen } = window.__TAURI__.event;
+
+// How each setting is applied, by its key in the game settings.
+const SETTERS = {
+  windowMode: (value) => invoke("set_window_mode", { mode: value }),
+  zoom: (value) => invoke("set_zoom", { factor: Number(value) }),
+  integerScaling: (value) => invoke("set_integer_scaling", { enabled: value }),
+  lockAspectRatio: (value) => invoke("set_aspect_ratio_lock", { enabled: value }),
+  dpiScale: (value) => invoke("set_dpi_scale", { scale: value === "" ? null : Number(value) }),
+  alwaysOnTop: (value) => invoke("set_always_on_top", { enabled: value }),
+  cursorHideDelaySecs: (value) => invoke("set_cursor_autohide", { delaySecs: Math.max(0, Math.round(Number(value))) }),
+  masterVolume: (value) => invoke("set_master_volume", { volume: Number(value) }),
+  audioDevice: (value) => invoke("set_audio_device", { id: value === "" ? null : value }),
+  muted: (value) => invoke("set_muted", { muted: value }),
+  muteOnBlur: (value) => invoke("set_mute_on_blur", { enabled: value }),
+  pauseOnBlur: (value) => invoke("set_pause_on_blur", { enabled: value }),
+  minimizeToTray: (value) => invoke("set_minimize_to_tray", { enabled: value }),
+  collectStats: (value) => invoke("set_collect_stats", { enabled: value }),
+};
+
+function show(settings, windowMode) {
+  document.querySelectorAll("[data-setting]").forEach((input) => {
+    const value = input.dataset.setting === "windowMode" ? windowMode : settings[input.dataset.setting];
+    if (input.type === "checkbox") {
+      input.checked = !!value;
+    } else {
+      input.value = value ?? "";
+    }
+  });
+}
+
+// Devices are only known once the game page has reported them.
+function showAudioDevices(outputs) {
+  const select = document.querySelector("#audio-device");
+  select.replaceChildren(new Option("System default", ""));
+  outputs.devices.forEach((device, index) => {
+    select.add(new Option(device.label || `Device ${index + 1}`, device.id));
+  });
+  select.value = outputs.selected ?? "";
+  select.disabled = !outputs.canSelect;
+}
+
+async function reload() {
+  showAudioDevices(await invoke("list_audio_devices"));
+  show(await invoke("get_settings"), await invoke("get_window_mode"));
+}
+
+window.addEventListener("DOMContentLoaded", async () => {
+  const errorEl = document.querySelector("#error");
+
+  document.querySelectorAll("[data-setting]").forEach((input) => {
+    input.addEventListener("change", async () => {
+      errorEl.textContent = "";
+      const value = input.type === "checkbox" ? input.checked : input.value;
+      try {
+        await SETTERS[input.dataset.setting](value);
+      } catch (error) {
+        errorEl.textContent = String(error);
+      }
+      await reload();
+    });
+  });
+
+  document.querySelector("#export-stats").addEventListener("click", async () => {
+    errorEl.textContent = "";
+    try {
+      await invoke("export_stats");
+    } catch (error) {
+      errorEl.textContent = String(error);
+    }
+  });
+
+  await reload();
+  await listen("settings-changed", reload);
+  await listen("audio-devices-changed", reload);
+});
@@ -0,0 +1,12 @@
+const { invoke } = window.__TAURI__.core;
+
+window.addEventListener("DOMContentLoaded", async () => {
+  const info = await invoke("get_splash_info");
+  document.querySelector("#title").textContent = info.title;
+
+  if (info.image) {
+    const logo = document.querySelector("#logo");
+    logo.src = info.image;
+    logo.hidden = false;
+  }
+});